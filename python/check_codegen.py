@@ -0,0 +1,79 @@
+"""Checks that the unrolled integer kernels (micro_rotations_16, _24 and
+_32 in src/integer.rs) compile to straight-line shift-and-add code.
+
+    python3 python/check_codegen.py
+
+builds the library in release mode with --emit asm and, for each kernel,
+counts its instructions and fails if any of them is a multiply, a call
+(a bounds check panics through one) or a jump (a loop that didn't unroll,
+or a branch on the direction). The instruction patterns cover x86_64 and
+aarch64, whichever the host is.
+"""
+
+import glob
+import os
+import re
+import subprocess
+import sys
+
+_ROOT = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+_KERNEL = re.compile(r"^(_ZN9cordic_rs7integer\d+micro_rotations_(\d+)17h\w+E):")
+_MULTIPLY = re.compile(r"^\s+(i?mul|madd|msub|smulh|umulh)\w*\s")
+_CALL = re.compile(r"^\s+(call|bl|blr)\w*\s")
+_JUMP = re.compile(r"^\s+(j\w+|b|b\.\w+|cbn?z|tbn?z)\s")
+
+
+def emit_asm():
+    out = os.path.join(_ROOT, "target", "codegen", "cordic_rs.s")
+    os.makedirs(os.path.dirname(out), exist_ok=True)
+    for stale in glob.glob(os.path.join(os.path.dirname(out), "*.s")):
+        os.remove(stale)
+    subprocess.run(
+        ["cargo", "rustc", "--quiet", "--release", "--lib", "--crate-type", "rlib",
+         "--", "--emit", "asm", "-o", out],
+        cwd=_ROOT, check=True, stderr=subprocess.DEVNULL,
+    )
+    # rustc adds a hash to the name when it's given -o
+    return glob.glob(os.path.join(os.path.dirname(out), "*.s"))[0]
+
+
+def kernels(lines):
+    """Yields (iterations, instructions) for each kernel in the listing."""
+    i = 0
+    while i < len(lines):
+        match = _KERNEL.match(lines[i])
+        i += 1
+        if not match:
+            continue
+        body = []
+        while not lines[i].startswith(".Lfunc_end"):
+            line = lines[i]
+            if line.startswith("\t") and not line.startswith("\t."):
+                body.append(line)
+            i += 1
+        yield int(match.group(2)), body
+
+
+def main():
+    with open(emit_asm()) as listing:
+        lines = listing.read().split("\n")
+
+    failed = False
+    seen = []
+    for iterations, body in sorted(kernels(lines)):
+        seen.append(iterations)
+        problems = [line.strip() for line in body
+                    if _MULTIPLY.match(line) or _CALL.match(line) or _JUMP.match(line)]
+        print("micro_rotations_{}: {} instructions, {:.1f} per iteration{}".format(
+            iterations, len(body), len(body) / iterations,
+            "" if not problems else ", unexpected: " + "; ".join(problems)))
+        failed |= bool(problems)
+
+    if seen != [16, 24, 32]:
+        print("expected kernels for 16, 24 and 32 iterations, found {}".format(seen))
+        failed = True
+    sys.exit(1 if failed else 0)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,617 @@
+use crate::cordic::{reduce, Backend, CordicConfig, GainCompensation, MAX_ITERS};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::integer::{from_q60, to_q60, Q60};
+use crate::lambert::lambert;
+use crate::power::toggles;
+use crate::qformat::QFormat;
+use crate::redundant::{sincos_sigma, SigmaCordic};
+use crate::reference::Reference;
+use crate::sintable::{generate_table, Interpolation, Lut};
+use crate::taylor::taylor;
+
+// Anything that approximates cosine and sine. CORDIC and the Taylor
+// series implement it here, and implementing it for your own method lets
+// it go through the same accuracy comparison (see Registry) as they do
+pub trait TrigAlgorithm {
+    // Short name used to label results, e.g. "cordic-integer-24"
+    fn name(&self) -> String;
+
+    // [cos theta, sin theta]
+    fn sincos(&self, theta: FixedPoint) -> Result<[FixedPoint; 2], CordicError>;
+
+    // What one call costs, if there's a model for it. Algorithms without
+    // one still get their accuracy measured but are left out of pareto()
+    fn cost(&self) -> Option<Cost> {
+        None
+    }
+}
+
+// Operation counts for one sincos call, as a rough model of what it
+// would take in hardware or on a core without an FPU. Comparisons (e.g.
+// in angle reduction) count as adds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Cost {
+    pub adds: usize,
+    pub shifts: usize,
+    pub multiplies: usize,
+    pub divides: usize,
+    // Constants that have to be stored, e.g. the angle table
+    pub table_entries: usize,
+    // Average register bit toggles per call, as a proxy for dynamic
+    // power, for the algorithms with an integer datapath to count them
+    // on (see src/power.rs)
+    pub toggles: Option<usize>,
+}
+
+impl Cost {
+    // Weighted total, assuming single cycle adds and shifts, 4 cycle
+    // multiplies and 20 cycle divides like a small integer core. Table
+    // entries cost memory rather than time, so they aren't counted
+    pub fn cycles(&self) -> usize {
+        self.adds + self.shifts + 4 * self.multiplies + 20 * self.divides
+    }
+}
+
+// Reducing theta into [-pi, pi] and folding it into [-pi/2, pi/2], see
+// reduce() and fold_quadrant()
+pub(crate) const REDUCTION_ADDS: usize = 4;
+
+// Angles the toggle count in Cost is averaged over
+const TOGGLE_POINTS: usize = 256;
+
+impl TrigAlgorithm for CordicConfig {
+    fn name(&self) -> String {
+        let backend = match self.backend {
+            Backend::Integer => "integer",
+            Backend::Float => "float",
+        };
+        // e.g. "-q31" for Q31 registers, "-q31x2" for Q31 in and out
+        // with double width registers
+        let width = match self.io_bits {
+            Some(bits) if self.double_word => format!("-q{}x2", bits),
+            Some(bits) => format!("-q{}", bits),
+            None => String::new(),
+        };
+        let width = if self.correctly_rounded {
+            width + "-correctly-rounded"
+        } else if self.monotone {
+            width + "-monotone"
+        } else {
+            width
+        };
+        // A custom schedule in place of the count, e.g. "schedule-0.1.1.2"
+        let steps = match &self.shifts {
+            Some(Ok(shifts)) => {
+                let shifts = shifts.as_slice().iter().map(|shift| shift.to_string());
+                format!("schedule-{}", shifts.collect::<Vec<_>>().join("."))
+            }
+            Some(Err(_)) => "schedule-invalid".to_string(),
+            None => self.iters.to_string(),
+        };
+        match self.compensation {
+            GainCompensation::Post => format!("cordic-{}{}-{}", backend, width, steps),
+            GainCompensation::Prescale => {
+                format!("cordic-{}{}-prescale-{}", backend, width, steps)
+            }
+            GainCompensation::Csd => format!("cordic-{}{}-csd-{}", backend, width, steps),
+            GainCompensation::None => format!("cordic-{}{}-raw-{}", backend, width, steps),
+        }
+    }
+
+    fn sincos(&self, theta: FixedPoint) -> Result<[FixedPoint; 2], CordicError> {
+        self.rotate(theta).map(|ret| [ret.x, ret.y])
+    }
+
+    // Each iteration is an add for each of x, y and z and a shift for
+    // each of x and y. Post compensation adds a multiply for each of x
+    // and y, prescaling just loads K as the starting x, and CSD
+    // compensation swaps each multiply for K's shifts and adds
+    fn cost(&self) -> Option<Cost> {
+        // How long correct rounding takes depends on the angle, so there's
+        // no one count to give
+        if self.correctly_rounded {
+            return None;
+        }
+        let iters = match &self.shifts {
+            Some(Ok(shifts)) => shifts.as_slice().len(),
+            _ => self.iters.min(MAX_ITERS),
+        };
+        let (multiplies, gain_adds, gain_shifts) = match self.compensation {
+            GainCompensation::Post => (2, 0, 0),
+            GainCompensation::Csd => self
+                .gain_multiplier()
+                .map_or((0, 0, 0), |k| (0, 2 * k.adders(), 2 * k.shifts())),
+            GainCompensation::Prescale | GainCompensation::None => (0, 0, 0),
+        };
+        // Monotone mode rotates twice and interpolates between the two,
+        // a multiply to find the grid point and one for each of x and y
+        // (see src/monotone.rs)
+        let (rotations, interpolation) = if self.monotone { (2, 3) } else { (1, 0) };
+        Some(Cost {
+            adds: REDUCTION_ADDS + rotations * (3 * iters + gain_adds) + 2 * interpolation,
+            shifts: rotations * (2 * iters + gain_shifts),
+            multiplies: rotations * multiplies + interpolation,
+            divides: 0,
+            table_entries: iters + 1,
+            toggles: toggles(self, TOGGLE_POINTS)
+                .ok()
+                .map(|toggles| toggles.total().round() as usize),
+        })
+    }
+}
+
+impl TrigAlgorithm for SigmaCordic {
+    fn name(&self) -> String {
+        format!("cordic-sigma-{}-{}", self.strategy.name(), self.iters)
+    }
+
+    fn sincos(&self, theta: FixedPoint) -> Result<[FixedPoint; 2], CordicError> {
+        let theta = Q60::from_bits(to_q60(reduce(theta)));
+        let ret = sincos_sigma(theta, self.iters, self.strategy.as_ref())?;
+        Ok(ret.sincos.map(|x| from_q60(x.to_bits())))
+    }
+}
+
+// The Taylor series with the given number of terms, see src/taylor.rs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Taylor(pub usize);
+
+impl TrigAlgorithm for Taylor {
+    fn name(&self) -> String {
+        format!("taylor-{}", self.0)
+    }
+
+    fn sincos(&self, theta: FixedPoint) -> Result<[FixedPoint; 2], CordicError> {
+        Ok(taylor(theta, self.0))
+    }
+
+    // Each term is two adds into the sums, and for each series the next
+    // term is the previous one times x twice, divided by a constant
+    fn cost(&self) -> Option<Cost> {
+        Some(Cost {
+            adds: REDUCTION_ADDS + 2 * self.0,
+            shifts: 0,
+            multiplies: 4 * self.0,
+            divides: 2 * self.0,
+            table_entries: 0,
+            toggles: None,
+        })
+    }
+}
+
+// How far one algorithm strayed from f64::cos and f64::sin (or another
+// Reference) over a set of angles
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accuracy {
+    pub name: String,
+    pub max_cos_error: f64,
+    pub max_sin_error: f64,
+    pub rms_cos_error: f64,
+    pub rms_sin_error: f64,
+    pub cost: Option<Cost>,
+}
+
+impl Accuracy {
+    // Worse of the cosine and sine errors
+    pub fn max_error(&self) -> f64 {
+        self.max_cos_error.max(self.max_sin_error)
+    }
+
+    // Correct fractional bits, i.e. -log2 of max_error()
+    pub fn bits(&self) -> f64 {
+        -self.max_error().log2()
+    }
+}
+
+// What pareto() weighs against each other: a cost and the worst error
+// bought with it. Accuracy and the workbench's FunctionAccuracy (see
+// src/workbench.rs) both have them
+pub trait Tradeoff {
+    fn cost(&self) -> Option<Cost>;
+    fn max_error(&self) -> f64;
+}
+
+impl Tradeoff for Accuracy {
+    fn cost(&self) -> Option<Cost> {
+        self.cost
+    }
+
+    fn max_error(&self) -> f64 {
+        Accuracy::max_error(self)
+    }
+}
+
+// Lambert's continued fraction for the half angle's tangent with the
+// given number of terms, see src/lambert.rs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lambert(pub usize);
+
+impl TrigAlgorithm for Lambert {
+    fn name(&self) -> String {
+        format!("lambert-{}", self.0)
+    }
+
+    fn sincos(&self, theta: FixedPoint) -> Result<[FixedPoint; 2], CordicError> {
+        lambert(theta, self.0)
+    }
+
+    // Squaring the half angle, an add and a divide for each term below
+    // the first and a divide into x. Turning t into cos and sin is two
+    // adds, a multiply for t^2, one divide for 1 / (1 + t^2) and two
+    // multiplies by it, with the halving and doubling as shifts
+    fn cost(&self) -> Option<Cost> {
+        let terms = self.0.clamp(1, MAX_ITERS);
+        Some(Cost {
+            adds: REDUCTION_ADDS + (terms - 1) + 2,
+            shifts: 2,
+            multiplies: 1 + 3,
+            divides: terms + 1,
+            table_entries: 0,
+            toggles: None,
+        })
+    }
+}
+
+// A list of algorithms to compare against each other. Downstream crates
+// register their own next to (or instead of) the built in ones:
+//
+//   let mut registry = Registry::with_builtins(24);
+//   registry.register(MyLookupTable::new(256));
+//   for result in registry.accuracy(&thetas)? { ... }
+#[derive(Default)]
+pub struct Registry {
+    algorithms: Vec<Box<dyn TrigAlgorithm>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Both CORDIC backends at `iters` iterations plus a Taylor series
+    // with the same number of terms
+    pub fn with_builtins(iters: usize) -> Self {
+        let mut registry = Self::new();
+        registry
+            .register(CordicConfig::new(iters).backend(Backend::Integer))
+            .register(CordicConfig::new(iters).backend(Backend::Float))
+            .register(Taylor(iters));
+        registry
+    }
+
+    // Everything explore mode sweeps: both backends with post and
+    // prescaled gain compensation at every iteration count, Taylor series
+    // up to 30 terms, continued fractions up to 12 terms, and Q31 sine
+    // tables of 16 to 4096 entries read each way
+    pub fn exploration() -> Self {
+        let mut registry = Self::new();
+        for backend in [Backend::Integer, Backend::Float] {
+            for compensation in [GainCompensation::Post, GainCompensation::Prescale] {
+                for iters in 1..=MAX_ITERS {
+                    registry.register(
+                        CordicConfig::new(iters)
+                            .backend(backend)
+                            .gain_compensation(compensation),
+                    );
+                }
+            }
+        }
+        for terms in 1..=30 {
+            registry.register(Taylor(terms));
+        }
+        for terms in 1..=12 {
+            registry.register(Lambert(terms));
+        }
+        for log2 in 4..=12 {
+            for interpolation in [
+                Interpolation::Nearest,
+                Interpolation::Linear,
+                Interpolation::Quadratic,
+            ] {
+                // Neither argument can be out of range
+                let table = generate_table(1 << log2, QFormat::Q31).unwrap();
+                registry.register(Lut {
+                    table,
+                    interpolation,
+                });
+            }
+        }
+        registry
+    }
+
+    pub fn register<A: TrigAlgorithm + 'static>(&mut self, algorithm: A) -> &mut Self {
+        self.algorithms.push(Box::new(algorithm));
+        self
+    }
+
+    pub fn algorithms(&self) -> impl Iterator<Item = &dyn TrigAlgorithm> {
+        self.algorithms.iter().map(|algorithm| algorithm.as_ref())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn TrigAlgorithm> {
+        self.algorithms().find(|algorithm| algorithm.name() == name)
+    }
+
+    // Runs every registered algorithm over `thetas`, in registration
+    // order. The first error any of them returns is passed on
+    pub fn accuracy(&self, thetas: &[f64]) -> Result<Vec<Accuracy>, CordicError> {
+        self.accuracy_against(thetas, Reference::Std)
+    }
+
+    pub fn accuracy_against(
+        &self,
+        thetas: &[f64],
+        reference: Reference,
+    ) -> Result<Vec<Accuracy>, CordicError> {
+        self.algorithms()
+            .map(|algorithm| accuracy_against(algorithm, thetas, reference))
+            .collect()
+    }
+}
+
+pub fn accuracy(algorithm: &dyn TrigAlgorithm, thetas: &[f64]) -> Result<Accuracy, CordicError> {
+    accuracy_against(algorithm, thetas, Reference::Std)
+}
+
+// The same against `reference`. Reference::DoubleDouble measures errors
+// well below an ulp of f64, which std can't
+pub fn accuracy_against(
+    algorithm: &dyn TrigAlgorithm,
+    thetas: &[f64],
+    reference: Reference,
+) -> Result<Accuracy, CordicError> {
+    let mut max = [0.0f64; 2];
+    let mut sum_squares = [0.0f64; 2];
+    for &theta in thetas {
+        let ret = algorithm.sincos(FixedPoint::new(theta))?;
+        let exact = reference.sincos(theta);
+        let errors = [
+            Reference::error(ret[0].to_f64(), exact[0]),
+            Reference::error(ret[1].to_f64(), exact[1]),
+        ];
+        for i in 0..2 {
+            max[i] = max[i].max(errors[i]);
+            sum_squares[i] += errors[i] * errors[i];
+        }
+    }
+    let count = thetas.len().max(1) as f64;
+    Ok(Accuracy {
+        name: algorithm.name(),
+        max_cos_error: max[0],
+        max_sin_error: max[1],
+        rms_cos_error: (sum_squares[0] / count).sqrt(),
+        rms_sin_error: (sum_squares[1] / count).sqrt(),
+        cost: algorithm.cost(),
+    })
+}
+
+// How many f64s apart two values are, counting across zero (so the
+// distance between -0.0 and 0.0 is 0 and between the smallest subnormals
+// either side of it is 2). Next to a reference of 0 even a tiny error is
+// an enormous count, since the f64s crowd together there. NaN is as far
+// from everything as it gets
+pub fn ulp_distance(a: f64, b: f64) -> u64 {
+    if a.is_nan() || b.is_nan() {
+        return u64::MAX;
+    }
+    // Maps the bits to an integer that orders the same way as the values
+    let ordered = |x: f64| {
+        let bits = x.to_bits() as i64;
+        if bits < 0 {
+            i64::MIN - bits
+        } else {
+            bits
+        }
+    };
+    ordered(a).abs_diff(ordered(b))
+}
+
+// |value - reference| / |reference|, or None when the reference is
+// exactly zero and there's nothing to be relative to
+pub fn relative_error(value: f64, reference: f64) -> Option<f64> {
+    if reference == 0.0 {
+        None
+    } else {
+        Some((value - reference).abs() / reference.abs())
+    }
+}
+
+// The results nothing else beats on both cycles and worst case error,
+// i.e. the cheapest way to get each level of accuracy. They come out
+// cheapest (and so least accurate) first. Results without a cost are
+// left out
+pub fn pareto<T: Tradeoff + Clone>(results: &[T]) -> Vec<T> {
+    let mut costed = results
+        .iter()
+        .filter(|result| result.cost().is_some())
+        .collect::<Vec<&T>>();
+    costed.sort_by(|a, b| {
+        let cycles = |result: &T| result.cost().map_or(0, |cost| cost.cycles());
+        cycles(a)
+            .cmp(&cycles(b))
+            .then(a.max_error().total_cmp(&b.max_error()))
+    });
+
+    // Sorted by cost, a result is on the frontier exactly when it's more
+    // accurate than everything cheaper
+    let mut frontier: Vec<T> = Vec::new();
+    for result in costed {
+        if frontier
+            .last()
+            .is_none_or(|best| result.max_error() < best.max_error())
+        {
+            frontier.push(result.clone());
+        }
+    }
+    frontier
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Always answers (1, 0), so its error is exactly |cos - 1| and |sin|
+    struct Constant;
+
+    impl TrigAlgorithm for Constant {
+        fn name(&self) -> String {
+            "constant".to_string()
+        }
+
+        fn sincos(&self, _theta: FixedPoint) -> Result<[FixedPoint; 2], CordicError> {
+            Ok([FixedPoint::new(1.0), FixedPoint::new(0.0)])
+        }
+    }
+
+    #[test]
+    fn error_measures() {
+        assert_eq![ulp_distance(1.0, 1.0), 0];
+        assert_eq![ulp_distance(1.0, 1.0 + f64::EPSILON), 1];
+        assert_eq![ulp_distance(-0.0, 0.0), 0];
+        let tiny = f64::from_bits(1);
+        assert_eq![ulp_distance(-tiny, tiny), 2];
+        assert_eq![ulp_distance(-1.0, 1.0), 2 * 1.0f64.to_bits()];
+        assert_eq![ulp_distance(f64::NAN, 1.0), u64::MAX];
+
+        assert_eq![relative_error(1.5, 1.0), Some(0.5)];
+        assert_eq![relative_error(-1.5, -1.0), Some(0.5)];
+        assert_eq![relative_error(1e-300, 0.0), None];
+        assert_eq![relative_error(0.0, -0.0), None];
+    }
+
+    #[test]
+    fn builtins() {
+        let registry = Registry::with_builtins(40);
+        let names = registry
+            .algorithms()
+            .map(|a| a.name())
+            .collect::<Vec<String>>();
+        assert_eq![names, ["cordic-integer-40", "cordic-float-40", "taylor-40"]];
+
+        let thetas = (-628..=628).map(|i| i as f64 / 100.0).collect::<Vec<f64>>();
+        for result in registry.accuracy(&thetas).unwrap() {
+            assert![result.max_cos_error < 1e-9 && result.max_sin_error < 1e-9];
+            assert![result.rms_cos_error <= result.max_cos_error];
+        }
+    }
+
+    #[test]
+    fn external() {
+        let mut registry = Registry::new();
+        registry.register(Constant);
+        assert![registry.get("constant").is_some()];
+        assert![registry.get("taylor-40").is_none()];
+
+        let result = registry.accuracy(&[0.0, std::f64::consts::PI]).unwrap();
+        assert_eq![result[0].name, "constant"];
+        assert_eq![result[0].max_cos_error, 2.0];
+        assert![result[0].max_sin_error < 1e-15];
+        assert![(result[0].rms_cos_error - 2.0f64.sqrt()).abs() < 1e-15];
+    }
+
+    // f64::cos and f64::sin themselves
+    struct Std;
+
+    impl TrigAlgorithm for Std {
+        fn name(&self) -> String {
+            "std".to_string()
+        }
+
+        fn sincos(&self, theta: FixedPoint) -> Result<[FixedPoint; 2], CordicError> {
+            let theta = theta.to_f64();
+            Ok([FixedPoint::new(theta.cos()), FixedPoint::new(theta.sin())])
+        }
+    }
+
+    #[test]
+    fn references() {
+        let thetas = (-628..=628).map(|i| i as f64 / 100.0).collect::<Vec<f64>>();
+        // std is perfect against itself, and only an extended precision
+        // reference sees its rounding
+        let against = |reference| accuracy_against(&Std, &thetas, reference).unwrap();
+        assert_eq![against(Reference::Std).max_error(), 0.0];
+        let exact = against(Reference::DoubleDouble);
+        assert![exact.max_error() > 0.0 && exact.max_error() <= f64::EPSILON / 2.0];
+        assert![exact.rms_cos_error < exact.max_cos_error];
+
+        // Against std a full length CORDIC sweep gets std's errors mixed
+        // in; against double-double it doesn't, and no reference should
+        // make an error disappear that's bigger than std's own
+        let config = CordicConfig::new(MAX_ITERS).backend(Backend::Float);
+        let std = accuracy(&config, &thetas).unwrap();
+        let dd = accuracy_against(&config, &thetas, Reference::DoubleDouble).unwrap();
+        assert_ne![std, dd];
+        assert![(std.max_error() - dd.max_error()).abs() <= exact.max_error()];
+        let registry = Registry::with_builtins(24);
+        for (f64_result, dd_result) in registry.accuracy(&thetas).unwrap().iter().zip(
+            registry
+                .accuracy_against(&thetas, Reference::DoubleDouble)
+                .unwrap(),
+        ) {
+            assert_eq![f64_result.name, dd_result.name];
+            assert![(f64_result.max_error() - dd_result.max_error()).abs() < 1e-15];
+        }
+    }
+
+    #[test]
+    fn frontier() {
+        let thetas = (-314..=314).map(|i| i as f64 / 50.0).collect::<Vec<f64>>();
+        let results = Registry::exploration().accuracy(&thetas).unwrap();
+        let frontier = pareto(&results);
+
+        // Strictly more expensive and strictly more accurate all the way
+        // along, and nothing off the frontier beats any point on it
+        for pair in frontier.windows(2) {
+            assert![pair[0].cost.unwrap().cycles() < pair[1].cost.unwrap().cycles()];
+            assert![pair[0].max_error() > pair[1].max_error()];
+        }
+        for result in &results {
+            let cycles = result.cost.unwrap().cycles();
+            assert![!frontier.iter().any(|best| {
+                cycles < best.cost.unwrap().cycles() && result.max_error() < best.max_error()
+            })];
+        }
+
+        // Prescaling saves the final multiplies for the same accuracy
+        assert![frontier
+            .iter()
+            .any(|result| result.name.contains("prescale"))];
+    }
+
+    #[test]
+    fn scheduled_names() {
+        let config = CordicConfig::new(24).schedule(&[0, 1, 1, 2]);
+        assert_eq![config.name(), "cordic-integer-schedule-0.1.1.2"];
+        assert_eq![config.cost().unwrap().shifts, 2 * 4];
+        let invalid = config.schedule(&[MAX_ITERS]);
+        assert_eq![invalid.name(), "cordic-integer-schedule-invalid"];
+    }
+
+    #[test]
+    fn toggles_on_integer_datapaths() {
+        let toggles = |config: CordicConfig| config.cost().unwrap().toggles;
+        let full = toggles(CordicConfig::new(24)).unwrap();
+        let q31 = toggles(CordicConfig::new(24).io_width(31)).unwrap();
+        assert![q31 < full];
+        assert_eq![toggles(CordicConfig::new(24).backend(Backend::Float)), None];
+        assert_eq![Taylor(10).cost().unwrap().toggles, None];
+    }
+
+    #[test]
+    fn costless_left_out() {
+        let mut registry = Registry::new();
+        registry.register(Constant).register(Taylor(10));
+        let results = registry.accuracy(&[0.0, 1.0]).unwrap();
+        let frontier = pareto(&results);
+        assert_eq![frontier.len(), 1];
+        assert_eq![frontier[0].name, "taylor-10"];
+    }
+
+    #[test]
+    fn errors_pass_through() {
+        let mut registry = Registry::new();
+        registry.register(CordicConfig::new(0));
+        assert![registry.accuracy(&[0.0]).is_err()];
+    }
+}
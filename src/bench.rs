@@ -0,0 +1,221 @@
+use crate::algorithm::{relative_error, ulp_distance};
+use crate::cordic::CordicConfig;
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+
+// The bench sweep as data rather than CSV, for tooling that runs its own
+// campaigns: sweep() rotates each angle and keeps what CORDIC, std and
+// the libm crate each gave, and SweepResult boils that down
+// to ErrorStats. The bench mode prints exactly this
+//
+//   let result = sweep(&CordicConfig::new(24), &sweep_angles())?;
+//   println!("{}", result.cos_stats().max);
+
+// The angles the bench and most other sweeps run over: -6.28 to 6.28 in
+// steps of 0.01, so negative angles and both wrap-arounds get exercised
+pub fn sweep_angles() -> Vec<f64> {
+    (-628..=628).map(|i| i as f64 / 100.0).collect()
+}
+
+// One angle of a sweep
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepPoint {
+    pub theta: f64,
+    pub cos: f64,
+    pub sin: f64,
+    // The platform's libm, through std
+    pub std_cos: f64,
+    pub std_sin: f64,
+    // The libm crate (musl's ported to Rust), the same everywhere
+    pub libm_cos: f64,
+    pub libm_sin: f64,
+}
+
+impl SweepPoint {
+    // |CORDIC - std|
+    pub fn cos_error(&self) -> f64 {
+        (self.cos - self.std_cos).abs()
+    }
+
+    pub fn sin_error(&self) -> f64 {
+        (self.sin - self.std_sin).abs()
+    }
+}
+
+// Summary of one output's errors against std over a sweep
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErrorStats {
+    pub max: f64,
+    pub mean: f64,
+    pub rms: f64,
+    // The largest relative error, over the angles where std isn't exactly
+    // zero (see relative_error())
+    pub max_relative: f64,
+    pub max_ulps: u64,
+    // Where the largest error was, the first such angle on a tie
+    pub worst_theta: f64,
+}
+
+impl ErrorStats {
+    // Over (theta, value, reference) triples. Nothing to summarize is all
+    // zeros, with NaN for worst_theta
+    pub fn new(samples: impl IntoIterator<Item = (f64, f64, f64)>) -> Self {
+        let mut stats = ErrorStats {
+            max: 0.0,
+            mean: 0.0,
+            rms: 0.0,
+            max_relative: 0.0,
+            max_ulps: 0,
+            worst_theta: f64::NAN,
+        };
+        let mut count = 0;
+        for (theta, value, reference) in samples {
+            let error = (value - reference).abs();
+            // NaN is the worst error there is
+            let worse = error > stats.max || (error.is_nan() && !stats.max.is_nan());
+            if worse || count == 0 {
+                stats.max = error;
+                stats.worst_theta = theta;
+            }
+            stats.mean += error;
+            stats.rms += error * error;
+            if let Some(relative) = relative_error(value, reference) {
+                stats.max_relative = stats.max_relative.max(relative);
+            }
+            stats.max_ulps = stats.max_ulps.max(ulp_distance(value, reference));
+            count += 1;
+        }
+        if count > 0 {
+            stats.mean /= count as f64;
+            stats.rms = (stats.rms / count as f64).sqrt();
+        }
+        stats
+    }
+
+    // Correct fractional bits, i.e. -log2 of max
+    pub fn bits(&self) -> f64 {
+        -self.max.log2()
+    }
+}
+
+// Everything one sweep produced, in the order of the angles given
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepResult {
+    pub config: CordicConfig,
+    pub points: Vec<SweepPoint>,
+}
+
+impl SweepResult {
+    pub fn thetas(&self) -> Vec<f64> {
+        self.points.iter().map(|point| point.theta).collect()
+    }
+
+    pub fn cos_stats(&self) -> ErrorStats {
+        ErrorStats::new(self.points.iter().map(|p| (p.theta, p.cos, p.std_cos)))
+    }
+
+    pub fn sin_stats(&self) -> ErrorStats {
+        ErrorStats::new(self.points.iter().map(|p| (p.theta, p.sin, p.std_sin)))
+    }
+
+    // The same against the libm crate, which doesn't change from platform
+    // to platform the way std can
+    pub fn libm_cos_stats(&self) -> ErrorStats {
+        ErrorStats::new(self.points.iter().map(|p| (p.theta, p.cos, p.libm_cos)))
+    }
+
+    pub fn libm_sin_stats(&self) -> ErrorStats {
+        ErrorStats::new(self.points.iter().map(|p| (p.theta, p.sin, p.libm_sin)))
+    }
+}
+
+// Rotates every angle in `thetas` with `config`. The first error
+// rotate() returns is passed on
+pub fn sweep(config: &CordicConfig, thetas: &[f64]) -> Result<SweepResult, CordicError> {
+    let points = thetas
+        .iter()
+        .map(|&theta| {
+            let ret = config.rotate(FixedPoint::new(theta))?;
+            Ok(SweepPoint {
+                theta,
+                cos: ret.x.to_f64(),
+                sin: ret.y.to_f64(),
+                std_cos: theta.cos(),
+                std_sin: theta.sin(),
+                libm_cos: libm::cos(theta),
+                libm_sin: libm::sin(theta),
+            })
+        })
+        .collect::<Result<Vec<_>, CordicError>>()?;
+    Ok(SweepResult {
+        config: *config,
+        points,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{accuracy, TrigAlgorithm};
+    use crate::cordic::{Backend, MAX_ITERS};
+
+    #[test]
+    fn matches_accuracy() {
+        let thetas = sweep_angles();
+        assert_eq![thetas.len(), 1257];
+        assert_eq![
+            (thetas[0], thetas[628], thetas[1256]),
+            (-628.0 / 100.0, 0.0, 628.0 / 100.0)
+        ];
+        for backend in [Backend::Integer, Backend::Float] {
+            let config = CordicConfig::new(24).backend(backend);
+            let result = sweep(&config, &thetas).unwrap();
+            assert_eq![result.thetas(), thetas];
+            assert_eq![result.config, config];
+
+            // The same numbers the registry's comparison gets
+            let summary = accuracy(&config as &dyn TrigAlgorithm, &thetas).unwrap();
+            let (cos, sin) = (result.cos_stats(), result.sin_stats());
+            assert_eq![cos.max, summary.max_cos_error];
+            assert_eq![sin.max, summary.max_sin_error];
+            assert![(cos.rms - summary.rms_cos_error).abs() <= 1e-12 * cos.rms];
+            assert![cos.mean <= cos.rms && cos.rms <= cos.max];
+            let worst = result
+                .points
+                .iter()
+                .find(|p| p.theta == cos.worst_theta)
+                .unwrap();
+            assert_eq![worst.cos_error(), cos.max];
+            assert![cos.bits() > 20.0 && cos.bits() < 26.0];
+            assert![cos.max_ulps > 1 << 20];
+            // std and the port agree to within an ulp or so
+            let libm = result.libm_cos_stats();
+            assert![(libm.max - cos.max).abs() < 1e-15];
+        }
+
+        let full = sweep(&CordicConfig::new(MAX_ITERS), &thetas).unwrap();
+        assert![full.sin_stats().max < 1e-15];
+        assert_eq![
+            sweep(&CordicConfig::new(0), &thetas),
+            Err(CordicError::IterationOutOfRange(0))
+        ];
+    }
+
+    #[test]
+    fn stats() {
+        let stats = ErrorStats::new([(1.0, 1.5, 1.0), (2.0, 0.0, 1.0), (3.0, 1.0, 0.0)]);
+        assert_eq![stats.max, 1.0];
+        assert_eq![stats.worst_theta, 2.0];
+        assert_eq![stats.mean, 2.5 / 3.0];
+        assert_eq![stats.rms, (2.25f64 / 3.0).sqrt()];
+        // 1.0 against 0 has no relative error
+        assert_eq![stats.max_relative, 1.0];
+
+        let empty = ErrorStats::new([]);
+        assert_eq![(empty.max, empty.mean, empty.max_ulps), (0.0, 0.0, 0)];
+        assert![empty.worst_theta.is_nan()];
+        let nan = ErrorStats::new([(1.0, 1.0, 1.0), (2.0, f64::NAN, 1.0), (3.0, 5.0, 1.0)]);
+        assert![nan.max.is_nan()];
+        assert_eq![nan.worst_theta, 2.0];
+    }
+}
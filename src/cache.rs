@@ -0,0 +1,194 @@
+// Angle tables, built once per program and shared from then on instead
+// of being recomputed on every call.
+//
+// The cache is keyed by (backend, iteration count): each backend has a
+// slot per count from 1 to MAX_ITERS, filled the first time anything asks
+// for that count (growing whatever the backend already has, see
+// AngleCache::get) and handed out as a &'static slice after that. The float
+// backend's entries are atan(2^-i) from math::atan, the integer backend's
+// are its Q60 constants (see src/integer.rs) as FixedPoint. The integer
+// kernels themselves run straight off the constants and never need the
+// cache, which is also what's left on targets without std or floats.
+//
+// Building a table allocates it once, and nothing after that allocates
+// at all. Anything that can't afford the build on its first call (an
+// interrupt handler, a timed loop) can call warm_tables() up front
+
+use crate::cordic::{checked_iters, Backend, MAX_ITERS};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::hyperbolic::{schedule, SCHEDULE_LEN};
+use crate::integer::{from_q60, ANGLES};
+use crate::math;
+use std::sync::OnceLock;
+
+// The tables of one backend, tables[n] holding the one for n iterations
+pub(crate) struct AngleCache {
+    tables: [OnceLock<&'static [FixedPoint]>; MAX_ITERS + 1],
+    // atan(2^-i) as the backend has it
+    entry: fn(usize) -> FixedPoint,
+}
+
+impl AngleCache {
+    pub(crate) const fn new(entry: fn(usize) -> FixedPoint) -> Self {
+        Self {
+            tables: [const { OnceLock::new() }; MAX_ITERS + 1],
+            entry,
+        }
+    }
+
+    // The table for `iters` iterations (at most MAX_ITERS), building it
+    // if this is the first time it's been asked for. A table is grown
+    // from what's already there rather than built from scratch: a longer
+    // one is borrowed as is, since a shorter table is just its prefix,
+    // and otherwise the longest shorter one is copied and only the
+    // entries past its end are computed. So an engine built for 24
+    // iterations and later asked for 48 computes the other 24 once
+    pub(crate) fn get(&self, iters: usize) -> &'static [FixedPoint] {
+        let iters = iters.min(MAX_ITERS);
+        self.tables[iters].get_or_init(|| {
+            if let Some(longer) = self.tables[iters..].iter().find_map(OnceLock::get) {
+                return &longer[..iters];
+            }
+            let built = self.tables[..iters].iter().rev().find_map(OnceLock::get);
+            let mut table = Vec::with_capacity(iters);
+            table.extend_from_slice(built.copied().unwrap_or_default());
+            table.extend((table.len()..iters).map(self.entry));
+            Box::leak(table.into_boxed_slice())
+        })
+    }
+
+    // Whether the table for `iters` iterations has been built
+    #[cfg(test)]
+    fn built(&self, iters: usize) -> bool {
+        self.tables[iters].get().is_some()
+    }
+}
+
+fn circular_angle(i: usize) -> FixedPoint {
+    FixedPoint::new(math::atan(math::powi(2.0, -(i as i32))))
+}
+
+fn integer_angle(i: usize) -> FixedPoint {
+    from_q60(ANGLES[i])
+}
+
+static FLOAT: AngleCache = AngleCache::new(circular_angle);
+static INTEGER: AngleCache = AngleCache::new(integer_angle);
+
+// (2^-shift, atanh(2^-shift)) for each step of the hyperbolic schedule,
+// which is the same length whatever the iteration count
+static HYPERBOLIC: OnceLock<[(FixedPoint, FixedPoint); SCHEDULE_LEN]> = OnceLock::new();
+
+// atan(2^-i) for i in 0..iters (at most MAX_ITERS) as `backend` has them
+pub(crate) fn angles(backend: Backend, iters: usize) -> &'static [FixedPoint] {
+    match backend {
+        Backend::Float => FLOAT.get(iters),
+        Backend::Integer => INTEGER.get(iters),
+    }
+}
+
+pub(crate) fn hyperbolic_steps() -> &'static [(FixedPoint, FixedPoint)] {
+    HYPERBOLIC.get_or_init(|| {
+        let shifts = schedule();
+        std::array::from_fn(|i| {
+            let poweroftwo = math::powi(2.0, -shifts[i]);
+            (
+                FixedPoint::new(poweroftwo),
+                FixedPoint::new(math::atanh(poweroftwo)),
+            )
+        })
+    })
+}
+
+// Builds the table `backend` uses for `iters` iterations now rather than
+// on first use, with the same check on `iters` as everything else. The
+// hyperbolic table is always built since exp, ln and friends run in
+// floating point whichever backend is picked for rotations
+pub fn warm_tables(backend: Backend, iters: usize) -> Result<(), CordicError> {
+    angles(backend, checked_iters(iters)?);
+    hyperbolic_steps();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn built_once() {
+        warm_tables(Backend::Float, 24).unwrap();
+        assert_eq![angles(Backend::Float, 24).as_ptr(), angles(Backend::Float, 24).as_ptr()];
+        assert_eq![hyperbolic_steps().as_ptr(), hyperbolic_steps().as_ptr()];
+        assert_eq![warm_tables(Backend::Integer, 0), Err(CordicError::IterationOutOfRange(0))];
+    }
+
+    #[test]
+    fn keyed_by_backend_and_iters() {
+        // Each (backend, iters) gets its own slot, and asking for one
+        // doesn't build any other
+        let cache = AngleCache::new(circular_angle);
+        assert_eq![cache.get(10).len(), 10];
+        assert![cache.built(10) && !cache.built(9) && !cache.built(11)];
+        assert_eq![cache.get(MAX_ITERS + 5).len(), MAX_ITERS];
+        assert![cache.built(MAX_ITERS)];
+
+        for iters in [1, 16, MAX_ITERS] {
+            let float = angles(Backend::Float, iters);
+            let integer = angles(Backend::Integer, iters);
+            assert_eq![(float.len(), integer.len()), (iters, iters)];
+            for i in 0..iters {
+                assert_eq![float[i], circular_angle(i)];
+                assert_eq![integer[i], from_q60(ANGLES[i])];
+                assert![(float[i].to_f64() - integer[i].to_f64()).abs() < 1e-16];
+            }
+        }
+    }
+
+    // Tables grow from what's built rather than starting over, counting
+    // the entries computed on each backend's cache
+    #[test]
+    fn grows_on_demand() {
+        static FLOAT_ENTRIES: AtomicUsize = AtomicUsize::new(0);
+        static INTEGER_ENTRIES: AtomicUsize = AtomicUsize::new(0);
+        fn float_entry(i: usize) -> FixedPoint {
+            FLOAT_ENTRIES.fetch_add(1, Ordering::Relaxed);
+            circular_angle(i)
+        }
+        fn integer_entry(i: usize) -> FixedPoint {
+            INTEGER_ENTRIES.fetch_add(1, Ordering::Relaxed);
+            integer_angle(i)
+        }
+
+        for (backend, entry, count) in [
+            (Backend::Float, float_entry as fn(usize) -> FixedPoint, &FLOAT_ENTRIES),
+            (Backend::Integer, integer_entry, &INTEGER_ENTRIES),
+        ] {
+            let cache = AngleCache::new(entry);
+            let computed = || count.load(Ordering::Relaxed);
+            assert_eq![cache.get(24).len(), 24];
+            assert_eq![computed(), 24];
+            // 48 extends the 24 by the other 24
+            let grown = cache.get(48);
+            assert_eq![computed(), 48];
+            assert_eq![grown[..24], cache.get(24)[..]];
+            // Anything shorter than what's built is a prefix of it, with
+            // nothing computed and the same memory
+            assert_eq![cache.get(30).as_ptr(), grown.as_ptr()];
+            assert_eq![cache.get(48).as_ptr(), grown.as_ptr()];
+            assert_eq![computed(), 48];
+            // And past the end it grows again, from the longest table
+            assert_eq![cache.get(MAX_ITERS).len(), MAX_ITERS];
+            assert_eq![computed(), MAX_ITERS];
+            assert_eq![cache.get(MAX_ITERS)[..], angles(backend, MAX_ITERS)[..]];
+        }
+    }
+
+    #[test]
+    fn prefixes() {
+        assert_eq![angles(Backend::Float, 10)[..], angles(Backend::Float, MAX_ITERS)[..10]];
+        assert_eq![angles(Backend::Float, 1)[0], FixedPoint::new(std::f64::consts::FRAC_PI_4)];
+        assert_eq![hyperbolic_steps().len(), schedule().len()];
+    }
+}
@@ -0,0 +1,138 @@
+// Chebyshev series, the usual way to build a polynomial approximation
+// that's close to minimax on an interval without running Remez:
+//
+//   f(x) ~= sum c[k] T_k(x) for x in [-1, 1]
+//
+// where T_k(cos t) = cos(k t). fit() gets the coefficients by sampling f
+// at the Chebyshev nodes, and eval() sums a series with the Clenshaw
+// recurrence
+//   b_k = c[k] + 2 x b_(k+1) - b_(k+2),  f(x) = c[0] + x b_1 - b_2
+// which needs no powers of x and keeps the rounding error to a few units
+// whatever the length. Functions on another interval [a, b] map x to
+// (2x - a - b) / (b - a) first
+//
+// c[0] is used as is, not halved as some texts write the series. The
+// Q60 version needs no floating point, so it's there without `float` too
+
+#[cfg(feature = "float")]
+use crate::cordic::sincos;
+#[cfg(feature = "float")]
+use crate::fixed::FixedPoint;
+use crate::integer::Q60;
+
+// sum coeffs[k] T_k(x). An empty series is 0
+#[cfg(feature = "float")]
+pub fn eval(coeffs: &[FixedPoint], x: FixedPoint) -> FixedPoint {
+    let zero = FixedPoint::new(0.0);
+    let Some((&first, rest)) = coeffs.split_first() else {
+        return zero;
+    };
+    let twice_x = x + x;
+    let (mut b1, mut b2) = (zero, zero);
+    for &c in rest.iter().rev() {
+        let b0 = c + twice_x * b1 - b2;
+        b2 = b1;
+        b1 = b0;
+    }
+    first + x * b1 - b2
+}
+
+// eval() on Q60, multiplying in 128 bits. Q60 only reaches [-8, 8), so
+// the partial sums saturate there rather than wrap, the way a DSP's
+// saturating MAC would
+pub fn eval_q60(coeffs: &[Q60], x: Q60) -> Q60 {
+    let saturate = |v: i128| v.clamp(i64::MIN as i128, i64::MAX as i128);
+    let times_x = |v: i128| (x.to_bits() as i128 * v) >> Q60::FRAC_BITS;
+    let Some((first, rest)) = coeffs.split_first() else {
+        return Q60::from_bits(0);
+    };
+    let (mut b1, mut b2) = (0i128, 0i128);
+    for c in rest.iter().rev() {
+        let b0 = saturate(c.to_bits() as i128 + 2 * times_x(b1) - b2);
+        b2 = b1;
+        b1 = b0;
+    }
+    Q60::from_bits(saturate(first.to_bits() as i128 + times_x(b1) - b2) as i64)
+}
+
+// The n coefficients of the degree n - 1 series that matches `f` at the
+// n Chebyshev nodes cos(pi (k + 1/2) / n), with the cosines from the
+// CORDIC engine. For a smooth f the coefficients fall off quickly and
+// the last one kept is about the worst error
+#[cfg(feature = "float")]
+pub fn fit(f: impl Fn(FixedPoint) -> FixedPoint, n: usize) -> Vec<FixedPoint> {
+    let pi = std::f64::consts::PI;
+    let node = |k: usize| FixedPoint::new(pi * (k as f64 + 0.5) / n as f64);
+    let values = (0..n).map(|k| f(sincos(node(k))[0])).collect::<Vec<_>>();
+    let scale = FixedPoint::new(2.0 / n as f64);
+    (0..n)
+        .map(|j| {
+            let sum = values
+                .iter()
+                .enumerate()
+                .map(|(k, &value)| value * sincos(node(k) * FixedPoint::new(j as f64))[0])
+                .sum::<FixedPoint>();
+            if j == 0 {
+                sum * scale * FixedPoint::new(0.5)
+            } else {
+                sum * scale
+            }
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polynomials() {
+        let c = |values: &[f64]| {
+            values
+                .iter()
+                .map(|&v| FixedPoint::new(v))
+                .collect::<Vec<_>>()
+        };
+        assert_eq![eval(&[], FixedPoint::new(0.5)), FixedPoint::new(0.0)];
+        for i in -10..=10 {
+            let x = i as f64 / 10.0;
+            // T_0 = 1, T_1 = x, T_2 = 2x^2 - 1, T_3 = 4x^3 - 3x
+            let series = eval(&c(&[1.0, 2.0, 3.0, 4.0]), FixedPoint::new(x)).to_f64();
+            let expected =
+                1.0 + 2.0 * x + 3.0 * (2.0 * x * x - 1.0) + 4.0 * (4.0 * x * x * x - 3.0 * x);
+            assert![(series - expected).abs() < 1e-14];
+        }
+    }
+
+    #[test]
+    fn fits_exp() {
+        let coeffs = fit(|x| FixedPoint::new(x.to_f64().exp()), 14);
+        // c[k] = 2 I_k(1), about 2 / (2^k k!)
+        assert![(coeffs[1].to_f64() - 1.130_318_207_984_97).abs() < 1e-15];
+        assert![coeffs[13].abs() < FixedPoint::new(1e-13)];
+        let q60 = coeffs
+            .iter()
+            .map(|&c| Q60::from_bits((c.to_f64() * 2f64.powi(60)).round() as i64))
+            .collect::<Vec<_>>();
+        for i in -100..=100 {
+            let x = i as f64 / 100.0;
+            let series = eval(&coeffs, FixedPoint::new(x)).to_f64();
+            assert![(series - x.exp()).abs() < 1e-14];
+
+            let x_q60 = Q60::from_bits((x * 2f64.powi(60)).round() as i64);
+            let series = eval_q60(&q60, x_q60).to_bits() as f64 / 2f64.powi(60);
+            assert![(series - x.exp()).abs() < 1e-14];
+        }
+    }
+
+    #[test]
+    fn q60_saturates() {
+        let max = Q60::from_bits(i64::MAX);
+        assert_eq![eval_q60(&[], max), Q60::from_bits(0)];
+        assert_eq![eval_q60(&[max; 8], max), max];
+        assert_eq![
+            eval_q60(&[Q60::from_bits(i64::MIN); 8], max),
+            Q60::from_bits(i64::MIN)
+        ];
+    }
+}
@@ -0,0 +1,229 @@
+// Drop-in replacements for the CMSIS-DSP fast math trig functions, with
+// the same symbol names, signatures and Q formats. Linking against these
+// instead of CMSIS-DSP swaps the implementation without touching any
+// call sites, which makes A/B accuracy comparisons on the same firmware
+// straightforward. Since the symbols clash with CMSIS-DSP itself, this
+// is behind its own feature.
+//
+// Angle conventions follow CMSIS-DSP, which isn't consistent between
+// functions:
+//   - arm_sin_cos_*: Q31 in [-1, 1) for [-180, 180) degrees, f32 in
+//     degrees
+//   - arm_sin_* / arm_cos_*: Q31/Q15 in [0, 1) for [0, 2 pi), f32 in
+//     radians
+//   - arm_atan2_q31: result in Q2.29 radians
+//
+// Each unsafe function says what it needs from its pointers under
+// "Safety:" in its comment
+
+#![allow(non_camel_case_types, clippy::missing_safety_doc)]
+
+use crate::cordic::{atan2, sincos};
+use crate::fixed::FixedPoint;
+use crate::hyperbolic::sqrt;
+use std::f64::consts::PI;
+
+pub type q15_t = i16;
+pub type q31_t = i32;
+pub type float32_t = f32;
+
+// Status codes, matching the values of CMSIS-DSP's `arm_status`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum arm_status {
+    ARM_MATH_SUCCESS = 0,
+    ARM_MATH_ARGUMENT_ERROR = -1,
+}
+
+// [0, 1) fractions of a full turn, as used by arm_sin_q31 & co. Negative
+// inputs are a full turn off, which makes no difference
+fn turns_to_radians(turns: FixedPoint) -> FixedPoint {
+    turns * FixedPoint::new(2.0 * PI)
+}
+
+// Sine and cosine of `theta`, a Q31 fraction of 180 degrees
+//
+// Safety: `pSinVal` and `pCosVal` must be valid for writing one
+// `q31_t` each
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn arm_sin_cos_q31(theta: q31_t, pSinVal: *mut q31_t, pCosVal: *mut q31_t) {
+    let [cos, sin] = sincos(FixedPoint::from_q31(theta) * FixedPoint::new(PI));
+    *pSinVal = sin.to_q31();
+    *pCosVal = cos.to_q31();
+}
+
+// Sine and cosine of `theta`, in degrees
+//
+// Safety: `pSinVal` and `pCosVal` must be valid for writing one
+// `float32_t` each
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn arm_sin_cos_f32(
+    theta: float32_t,
+    pSinVal: *mut float32_t,
+    pCosVal: *mut float32_t,
+) {
+    let [cos, sin] = sincos(FixedPoint::new(theta as f64 * PI / 180.0));
+    *pSinVal = sin.to_f64() as f32;
+    *pCosVal = cos.to_f64() as f32;
+}
+
+// Sine of `x`, a Q31 fraction of a full turn
+#[no_mangle]
+pub extern "C" fn arm_sin_q31(x: q31_t) -> q31_t {
+    sincos(turns_to_radians(FixedPoint::from_q31(x)))[1].to_q31()
+}
+
+// Cosine of `x`, a Q31 fraction of a full turn
+#[no_mangle]
+pub extern "C" fn arm_cos_q31(x: q31_t) -> q31_t {
+    sincos(turns_to_radians(FixedPoint::from_q31(x)))[0].to_q31()
+}
+
+// Sine of `x`, a Q15 fraction of a full turn
+#[no_mangle]
+pub extern "C" fn arm_sin_q15(x: q15_t) -> q15_t {
+    sincos(turns_to_radians(FixedPoint::from_q15(x)))[1].to_q15()
+}
+
+// Cosine of `x`, a Q15 fraction of a full turn
+#[no_mangle]
+pub extern "C" fn arm_cos_q15(x: q15_t) -> q15_t {
+    sincos(turns_to_radians(FixedPoint::from_q15(x)))[0].to_q15()
+}
+
+// Sine of `x` radians
+#[no_mangle]
+pub extern "C" fn arm_sin_f32(x: float32_t) -> float32_t {
+    sincos(FixedPoint::new(x as f64))[1].to_f64() as f32
+}
+
+// Cosine of `x` radians
+#[no_mangle]
+pub extern "C" fn arm_cos_f32(x: float32_t) -> float32_t {
+    sincos(FixedPoint::new(x as f64))[0].to_f64() as f32
+}
+
+// Angle of (`x`, `y`) in Q2.29 radians
+//
+// Safety: `result` must be valid for writing one `q31_t`
+#[no_mangle]
+pub unsafe extern "C" fn arm_atan2_q31(y: q31_t, x: q31_t, result: *mut q31_t) -> arm_status {
+    let angle = atan2(FixedPoint::from_q31(y), FixedPoint::from_q31(x));
+    // Q2.29 is Q31 shifted right by two
+    *result = (angle * FixedPoint::new(0.25)).to_q31();
+    arm_status::ARM_MATH_SUCCESS
+}
+
+// Angle of (`x`, `y`) in radians
+//
+// Safety: `result` must be valid for writing one `float32_t`
+#[no_mangle]
+pub unsafe extern "C" fn arm_atan2_f32(
+    y: float32_t,
+    x: float32_t,
+    result: *mut float32_t,
+) -> arm_status {
+    let angle = atan2(FixedPoint::new(y as f64), FixedPoint::new(x as f64));
+    *result = angle.to_f64() as f32;
+    arm_status::ARM_MATH_SUCCESS
+}
+
+// Square root of a Q31 value. Negative inputs write 0 and return
+// `ARM_MATH_ARGUMENT_ERROR`, like CMSIS-DSP
+//
+// Safety: `pOut` must be valid for writing one `q31_t`
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn arm_sqrt_q31(in_: q31_t, pOut: *mut q31_t) -> arm_status {
+    match sqrt(FixedPoint::from_q31(in_)) {
+        Ok(root) => {
+            *pOut = root.to_q31();
+            arm_status::ARM_MATH_SUCCESS
+        }
+        Err(_) => {
+            *pOut = 0;
+            arm_status::ARM_MATH_ARGUMENT_ERROR
+        }
+    }
+}
+
+// Square root of a Q15 value, same as `arm_sqrt_q31`
+//
+// Safety: `pOut` must be valid for writing one `q15_t`
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn arm_sqrt_q15(in_: q15_t, pOut: *mut q15_t) -> arm_status {
+    match sqrt(FixedPoint::from_q15(in_)) {
+        Ok(root) => {
+            *pOut = root.to_q15();
+            arm_status::ARM_MATH_SUCCESS
+        }
+        Err(_) => {
+            *pOut = 0;
+            arm_status::ARM_MATH_ARGUMENT_ERROR
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sin_cos() {
+        let (mut sin, mut cos) = (0, 0);
+        // 90 degrees
+        unsafe { arm_sin_cos_q31(0x4000_0000, &mut sin, &mut cos) };
+        assert![(sin - i32::MAX).abs() <= 1 && cos.abs() <= 1];
+
+        let (mut sin, mut cos) = (0.0, 0.0);
+        unsafe { arm_sin_cos_f32(-30.0, &mut sin, &mut cos) };
+        assert![(sin + 0.5).abs() < 1e-6];
+        assert![(cos - 0.75_f32.sqrt()).abs() < 1e-6];
+    }
+
+    #[test]
+    fn turns() {
+        // A quarter turn
+        assert![(arm_sin_q31(0x2000_0000) - i32::MAX).abs() <= 1];
+        assert![arm_cos_q31(0x2000_0000).abs() <= 1];
+        assert![(arm_sin_q15(0x2000) - i16::MAX).abs() <= 1];
+        assert![arm_cos_q15(0x2000).abs() <= 1];
+        // Negative inputs are the same angle a turn later
+        assert![(arm_sin_q31(-0x6000_0000) - i32::MAX).abs() <= 1];
+
+        assert![(arm_sin_f32(1.0) - 1.0_f32.sin()).abs() < 1e-6];
+        assert![(arm_cos_f32(1.0) - 1.0_f32.cos()).abs() < 1e-6];
+    }
+
+    #[test]
+    fn atan2_q2_29() {
+        let mut angle = 0;
+        let status = unsafe { arm_atan2_q31(0, -0x4000_0000, &mut angle) };
+        assert_eq![status, arm_status::ARM_MATH_SUCCESS];
+        assert![(angle as f64 / 2_f64.powi(29) - PI).abs() < 1e-8];
+
+        let mut angle = 0.0;
+        unsafe { arm_atan2_f32(1.0, 1.0, &mut angle) };
+        assert![(angle - std::f32::consts::FRAC_PI_4).abs() < 1e-6];
+    }
+
+    #[test]
+    fn square_roots() {
+        let mut out = 0;
+        let status = unsafe { arm_sqrt_q31(0x1000_0000, &mut out) };
+        assert_eq![status, arm_status::ARM_MATH_SUCCESS];
+        assert![(out - 0x2D41_3CCD).abs() <= 1];
+
+        let status = unsafe { arm_sqrt_q31(-1, &mut out) };
+        assert_eq![status, arm_status::ARM_MATH_ARGUMENT_ERROR];
+        assert_eq![out, 0];
+
+        let mut out = 0;
+        let status = unsafe { arm_sqrt_q15(0x2000, &mut out) };
+        assert_eq![status, arm_status::ARM_MATH_SUCCESS];
+        assert![(out - 0x4000).abs() <= 1];
+    }
+}
@@ -0,0 +1,208 @@
+use crate::cordic::{polar, rotate_vector, sincos};
+use crate::fixed::FixedPoint;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+// A complex number re + i * im. The arithmetic works for anything that
+// can add and multiply, but the polar conversions are only implemented
+// for FixedPoint, where they go through the CORDIC engine instead of
+// sin/cos/atan2/sqrt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T> Complex<T> {
+    pub fn new(re: T, im: T) -> Self {
+        Self { re, im }
+    }
+}
+
+impl<T: Add<Output = T>> Add for Complex<T> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            re: self.re + other.re,
+            im: self.im + other.im,
+        }
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Complex<T> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self {
+            re: self.re - other.re,
+            im: self.im - other.im,
+        }
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Mul for Complex<T> {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        // (a + bi)(c + di) = (ac - bd) + (ad + bc)i
+        Self {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Complex<T> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            re: -self.re,
+            im: -self.im,
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Complex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} + {}i", self.re, self.im)
+    }
+}
+
+impl Complex<FixedPoint> {
+    // r * (cos theta + i sin theta), computed by rotating (r, 0) rather
+    // than computing sin and cos and multiplying
+    pub fn from_polar(r: FixedPoint, theta: FixedPoint) -> Self {
+        let v = rotate_vector([r, FixedPoint::new(0.0)], theta);
+        Self::new(v[0], v[1])
+    }
+
+    // (|z|, arg z) from a single vectoring pass
+    pub fn to_polar(self) -> (FixedPoint, FixedPoint) {
+        polar([self.re, self.im])
+    }
+
+    pub fn abs(self) -> FixedPoint {
+        self.to_polar().0
+    }
+
+    // Angle in [-pi, pi], following the same conventions as atan2
+    pub fn arg(self) -> FixedPoint {
+        self.to_polar().1
+    }
+
+    pub fn conj(self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+
+    // z * e^(i theta), i.e. multiplication by a unit complex number,
+    // without ever computing the unit complex number itself. This is the
+    // operation CORDIC natively performs
+    pub fn rotate(self, theta: FixedPoint) -> Self {
+        let v = rotate_vector([self.re, self.im], theta);
+        Self::new(v[0], v[1])
+    }
+}
+
+// cos theta + i sin theta, from a single CORDIC rotation. Both parts
+// come out of the same pass, so there's no reason to ever compute them
+// separately
+pub fn cis(theta: FixedPoint) -> Complex<FixedPoint> {
+    let [cos, sin] = sincos(theta);
+    Complex::new(cos, sin)
+}
+
+// e^(i theta), which is the same thing as cis(theta) written the way
+// phasor-heavy code usually reads
+pub fn expi(theta: FixedPoint) -> Complex<FixedPoint> {
+    cis(theta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_enough(a: FixedPoint, b: FixedPoint) -> bool {
+        let diff = a - b;
+        diff < FixedPoint::new(0.01) && -diff < FixedPoint::new(0.01)
+    }
+
+    #[test]
+    fn polar_round_trip() {
+        for i in -314..=314 {
+            let theta = FixedPoint::new(i as f64 / 100.0);
+            for r in [0.25, 1.0, 4.0] {
+                let z = Complex::from_polar(FixedPoint::new(r), theta);
+                assert![close_enough(
+                    z.re,
+                    FixedPoint::new(r * (i as f64 / 100.0).cos())
+                )];
+                assert![close_enough(
+                    z.im,
+                    FixedPoint::new(r * (i as f64 / 100.0).sin())
+                )];
+
+                let (abs, arg) = z.to_polar();
+                assert![close_enough(abs, FixedPoint::new(r))];
+                assert![close_enough(arg, theta)];
+            }
+        }
+    }
+
+    #[test]
+    fn rotate_matches_multiply() {
+        let z = Complex::new(FixedPoint::new(1.5), FixedPoint::new(-0.5));
+        for i in -628..=628 {
+            let theta = FixedPoint::new(i as f64 / 100.0);
+            let rotated = z.rotate(theta);
+            let multiplied = z * cis(theta);
+
+            assert![close_enough(rotated.re, multiplied.re)];
+            assert![close_enough(rotated.im, multiplied.im)];
+        }
+    }
+
+    #[test]
+    fn cis_is_cos_plus_i_sin() {
+        for i in -628..=628 {
+            let theta = i as f64 / 100.0;
+            let z = cis(FixedPoint::new(theta));
+
+            assert![close_enough(z.re, FixedPoint::new(theta.cos()))];
+            assert![close_enough(z.im, FixedPoint::new(theta.sin()))];
+            assert![z == expi(FixedPoint::new(theta))];
+        }
+    }
+
+    #[test]
+    fn cis_adds_angles() {
+        // cis(a) * cis(b) == cis(a + b)
+        let a = FixedPoint::new(0.7);
+        let b = FixedPoint::new(-2.1);
+        let product = cis(a) * cis(b);
+        let sum = cis(a + b);
+
+        assert![close_enough(product.re, sum.re)];
+        assert![close_enough(product.im, sum.im)];
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Complex::new(FixedPoint::new(1.0), FixedPoint::new(2.0));
+        let b = Complex::new(FixedPoint::new(3.0), FixedPoint::new(-1.0));
+
+        assert_eq![
+            a + b,
+            Complex::new(FixedPoint::new(4.0), FixedPoint::new(1.0))
+        ];
+        assert_eq![
+            a - b,
+            Complex::new(FixedPoint::new(-2.0), FixedPoint::new(3.0))
+        ];
+        assert_eq![
+            a * b,
+            Complex::new(FixedPoint::new(5.0), FixedPoint::new(5.0))
+        ];
+        assert_eq![
+            a * a.conj(),
+            Complex::new(FixedPoint::new(5.0), FixedPoint::new(0.0))
+        ];
+    }
+}
@@ -0,0 +1,1876 @@
+use crate::cache;
+use crate::csd::{gain_csd, CsdMultiplier};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::integer;
+use crate::log::{log, LogLevel};
+use crate::math;
+#[cfg(feature = "checks")]
+use crate::monotone;
+use crate::schedule::{check_shifts, schedule_gain};
+use std::ops::Rem;
+
+// Reduces theta into the symmetric range [-pi, pi]
+//
+// NOTE: `%` in Rust keeps the sign of the dividend, so (-0.5) % (2 * pi)
+// is -0.5 and not 2 * pi - 0.5. That is fine for us as long as we then
+// fold the result into [-pi, pi] instead of assuming it's positive
+pub fn reduce(theta: FixedPoint) -> FixedPoint {
+    let pi = FixedPoint::new(std::f64::consts::PI);
+    let two_pi = FixedPoint::new(2.0 * std::f64::consts::PI);
+
+    let theta = theta.rem(two_pi);
+    if theta > pi {
+        theta - two_pi
+    } else if theta < -pi {
+        theta + two_pi
+    } else {
+        theta
+    }
+}
+
+// Folds a reduced theta in [-pi, pi] into [-pi/2, pi/2], returning
+// whether the outputs need to be negated afterwards.
+//
+// CORDIC only converges for |theta| <= sum(atan(2^-i)) ~= 1.74, so
+// anything in the left half-plane gets rotated by pi first. Rotating
+// by pi just flips the sign of both the cosine and the sine
+pub fn fold_quadrant(theta: FixedPoint) -> (FixedPoint, bool) {
+    let pi = FixedPoint::new(std::f64::consts::PI);
+    let half_pi = FixedPoint::new(std::f64::consts::FRAC_PI_2);
+
+    if theta > half_pi {
+        (theta - pi, true)
+    } else if theta < -half_pi {
+        (theta + pi, true)
+    } else {
+        (theta, false)
+    }
+}
+
+// Folds a reduced theta in [-pi, pi] into [-pi/4, pi/4] by taking out
+// the nearest multiple k of pi/2, and rotates `v` by that multiple
+// instead, which is exact: a quarter turn just swaps x and y and negates
+// one of them. What's left is an octant's worth of angle, so the
+// micro-rotations never have to get anywhere near +-pi/2. Subtracting
+// k pi/2 is exact too, since theta is within a factor of two of it
+pub fn fold_octant(theta: FixedPoint, v: [FixedPoint; 2]) -> (FixedPoint, [FixedPoint; 2]) {
+    let half_pi = std::f64::consts::FRAC_PI_2;
+    let k = math::round(theta.to_f64() / half_pi);
+    let theta = theta - FixedPoint::new(k * half_pi);
+    let [x, y] = v;
+    let v = match (k as i64).rem_euclid(4) {
+        0 => [x, y],
+        1 => [-y, x],
+        2 => [-x, -y],
+        _ => [y, -x],
+    };
+    (theta, v)
+}
+
+// Past this many iterations the shift amount is beyond the precision
+// of FixedPoint: atan(2^-i) == 2^-i exactly and the micro-rotation no
+// longer changes the vector, so the extra iterations are wasted work
+pub const MAX_ITERS: usize = FixedPoint::PRECISION_BITS;
+
+// The most steps a custom schedule (CordicConfig::schedule()) can have,
+// room for every shift twice over
+pub const MAX_SCHEDULE: usize = 2 * MAX_ITERS;
+
+// A custom schedule (see schedule::Schedule, whose checks and gain it
+// shares), with the shift amounts kept inline so CordicConfig stays Copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Shifts {
+    len: usize,
+    shifts: [u8; MAX_SCHEDULE],
+}
+
+impl Shifts {
+    fn new(shifts: &[usize]) -> Result<Self, CordicError> {
+        if shifts.is_empty() || shifts.len() > MAX_SCHEDULE {
+            return Err(CordicError::IterationOutOfRange(shifts.len()));
+        }
+        check_shifts(shifts)?;
+        let mut ret = Shifts {
+            len: shifts.len(),
+            shifts: [0; MAX_SCHEDULE],
+        };
+        for (slot, &shift) in ret.shifts.iter_mut().zip(shifts) {
+            *slot = shift as u8;
+        }
+        Ok(ret)
+    }
+
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        &self.shifts[..self.len]
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.as_slice().iter().map(|&shift| shift as usize)
+    }
+
+    pub(crate) fn gain(&self) -> FixedPoint {
+        schedule_gain(self.iter())
+    }
+}
+
+// Validates an iteration count, clamping anything past MAX_ITERS down
+// to MAX_ITERS. Callers that want to warn about clamping can compare the
+// returned count against what they asked for
+pub fn checked_iters(iters: usize) -> Result<usize, CordicError> {
+    if iters == 0 {
+        Err(CordicError::IterationOutOfRange(iters))
+    } else {
+        Ok(iters.min(MAX_ITERS))
+    }
+}
+
+// GAINS[n] = cumprod(1 / sqrt(1 + 2^-2y)) for y in 0..n, i.e. the scale
+// factor that undoes the magnification of n micro-rotations. Each
+// rotation by atan(2^-y) stretches the vector by sqrt(1 + 2^-2y), and
+// there is one of those for every iteration, starting at y = 0
+//
+// NOTE: This used to be a fold() over 0..(iters - 1) on every call, which
+// both wasted work and was missing the last rotation's factor. Since it
+// only depends on the iteration count, it's pre-computed here (with
+// 60 digits of precision, then rounded to f64). GAINS[0] is the gain of
+// doing nothing at all and is only there so the index is the iteration
+// count
+#[rustfmt::skip]
+const GAINS: [f64; MAX_ITERS + 1] = [
+    1.0, std::f64::consts::FRAC_1_SQRT_2, 0.6324555320336759, 0.6135719910778964,
+    0.6088339125177524, 0.6076482562561683, 0.6073517701412959, 0.607277644093526,
+    0.6072591122988927, 0.6072544793325624, 0.6072533210898752, 0.6072530315291343,
+    0.6072529591389448, 0.6072529410413972, 0.6072529365170103, 0.6072529353859135,
+    0.6072529351031393, 0.6072529350324458, 0.6072529350147724, 0.607252935010354,
+    0.6072529350092495, 0.6072529350089733, 0.6072529350089043, 0.607252935008887,
+    0.6072529350088827, 0.6072529350088817, 0.6072529350088813, 0.6072529350088813,
+    0.6072529350088812, 0.6072529350088812, 0.6072529350088812, 0.6072529350088812,
+    0.6072529350088812, 0.6072529350088812, 0.6072529350088812, 0.6072529350088812,
+    0.6072529350088812, 0.6072529350088812, 0.6072529350088812, 0.6072529350088812,
+    0.6072529350088812, 0.6072529350088812, 0.6072529350088812, 0.6072529350088812,
+    0.6072529350088812, 0.6072529350088812, 0.6072529350088812, 0.6072529350088812,
+    0.6072529350088812, 0.6072529350088812, 0.6072529350088812, 0.6072529350088812,
+    0.6072529350088812, 0.6072529350088812,
+];
+
+// The limit of GAINS as the iteration count goes to infinity
+pub const GAIN_LIMIT: f64 = 0.607_252_935_008_881_2;
+
+// Scale factor that undoes the magnification of `iters` CORDIC
+// rotations. Past MAX_ITERS it is indistinguishable from GAIN_LIMIT
+pub fn gain(iters: usize) -> Result<FixedPoint, CordicError> {
+    let iters = checked_iters(iters)?;
+    Ok(FixedPoint::new(GAINS[iters]))
+}
+
+// atan(2^-i) for i in 0..iters, the micro-angles the float backend
+// rotates by, exactly as it uses them (built from f64::atan, or libm's
+// with the `libm` feature). Backend::angle_table() gives either
+// backend's
+pub fn angle_table(iters: usize) -> Result<&'static [FixedPoint], CordicError> {
+    Backend::Float.angle_table(iters)
+}
+
+// Options for a CORDIC rotation. The defaults (MAX_ITERS iterations,
+// gain compensated) give the most accurate cosine and sine, and the
+// builder methods below change one thing at a time, e.g.
+//
+//   CordicConfig::new(24).compensate_gain(false).rotate(theta)
+//
+// A config is plain data and never changes once built, so one can be
+// shared by reference across any number of threads (it's Send and Sync,
+// see the tests). The only state behind it is process wide: the float
+// backend's angle cache, which is built under a lock and read without
+// one (see src/cache.rs), and the logger. sincos_parallel() below is
+// the batch version that does the sharing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CordicConfig {
+    pub(crate) iters: usize,
+    pub(crate) compensation: GainCompensation,
+    pub(crate) backend: Backend,
+    pub(crate) io_bits: Option<u32>,
+    pub(crate) double_word: bool,
+    pub(crate) small_angle: SmallAngle,
+    pub(crate) special_angles: bool,
+    pub(crate) octant: bool,
+    pub(crate) monotone: bool,
+    pub(crate) correctly_rounded: bool,
+    // A custom schedule, or why the one asked for was rejected
+    pub(crate) shifts: Option<Result<Shifts, CordicError>>,
+}
+
+// Whether rotate() skips the iterations for tiny angles, and below what
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SmallAngle {
+    Off,
+    // Below 2^-Backend::small_angle_bits()
+    Backend,
+    // Below 2^-bits
+    Below(u32),
+}
+
+// How (or whether) the 1/K magnification of the micro-rotations gets
+// undone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GainCompensation {
+    // Multiply the rotated vector by K once all the iterations are done
+    Post,
+    // Start from (K, 0) instead of (1, 0). The magnification then brings
+    // the vector up to exactly unit length, so there's no multiply at
+    // all. This is the standard trick in multiplier-less designs, since
+    // K is a constant that can be loaded instead of computed
+    Prescale,
+    // Multiply by K afterwards like Post, but as a fixed sequence of
+    // shifts and adds from K's canonical signed digit form at the
+    // backend's precision (see src/csd.rs), so it still takes no
+    // multiplier, only a few more adders
+    Csd,
+    // Leave the vector 1/K too long
+    None,
+}
+
+// Which arithmetic the micro-rotations run in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    // i64 with 60 fractional bits and a constant angle table (see
+    // src/integer.rs). Gives the same bits on every platform, so this is
+    // the default
+    Integer,
+    // FixedPoint (i.e. f64) throughout, with the angle table built from
+    // f64::atan. Its last bit or so can differ between platforms' libms
+    Float,
+}
+
+impl Backend {
+    // Below 2^-bits radians, (1, theta) is already cos and sin to within
+    // half a unit in the last place of the backend's precision p (53 bits
+    // relative for f64, 60 fractional bits for Q60). The error in cos is
+    // theta^2 / 2, which needs theta <= 2^-(p + 1) / 2, and the error in
+    // sin, theta^3 / 6, is smaller still, so bits = ceil((p + 1) / 2)
+    pub fn small_angle_bits(self) -> u32 {
+        (self.precision() + 2) / 2
+    }
+
+    // Fractional bits the backend carries (counting f64's significand
+    // as if it were all fraction, which it is near 1)
+    pub(crate) fn precision(self) -> u32 {
+        match self {
+            Backend::Integer => integer::Q60::FRAC_BITS,
+            Backend::Float => FixedPoint::PRECISION_BITS as u32,
+        }
+    }
+
+    // atan(2^-i) for i in 0..iters as this backend has them, from the
+    // table cache (see src/cache.rs). The integer backend's are its Q60
+    // constants, angle_table_q60(), rounded to FixedPoint
+    pub fn angle_table(self, iters: usize) -> Result<&'static [FixedPoint], CordicError> {
+        Ok(cache::angles(self, checked_iters(iters)?))
+    }
+}
+
+// cos(k pi / 12) for the k where it's one of the special values, i.e.
+// multiples of pi/6 and pi/4, rounded to nearest
+fn special_cos(k: i64) -> Option<f64> {
+    let half_sqrt_3 = 0.8660254037844386;
+    let half_sqrt_2 = std::f64::consts::FRAC_1_SQRT_2;
+    match k.rem_euclid(24) {
+        0 => Some(1.0),
+        2 | 22 => Some(half_sqrt_3),
+        3 | 21 => Some(half_sqrt_2),
+        4 | 20 => Some(0.5),
+        6 | 18 => Some(0.0),
+        8 | 16 => Some(-0.5),
+        9 | 15 => Some(-half_sqrt_2),
+        10 | 14 => Some(-half_sqrt_3),
+        12 => Some(-1.0),
+        _ => None,
+    }
+}
+
+impl Default for CordicConfig {
+    fn default() -> Self {
+        Self {
+            iters: MAX_ITERS,
+            compensation: GainCompensation::Post,
+            backend: Backend::Integer,
+            io_bits: None,
+            double_word: false,
+            small_angle: SmallAngle::Off,
+            special_angles: false,
+            octant: false,
+            monotone: false,
+            correctly_rounded: false,
+            shifts: None,
+        }
+    }
+}
+
+// The result of a rotation: the vector (x, y) along with the gain K for
+// the iteration count used.
+//
+// With gain compensation on, (x, y) is (cos theta, sin theta) and K has
+// already been applied. With it off, (x, y) is the raw rotated vector,
+// which is 1/K too long, and it's up to the caller to multiply by K
+// somewhere (or fold it into their own coefficients)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rotation {
+    pub x: FixedPoint,
+    pub y: FixedPoint,
+    pub gain: FixedPoint,
+}
+
+// The result of vectoring: the magnitude and angle of the input vector,
+// along with the gain K for the iteration count used. Like Rotation, the
+// magnitude is 1/K too long when gain compensation is off
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vectoring {
+    pub magnitude: FixedPoint,
+    pub angle: FixedPoint,
+    pub gain: FixedPoint,
+}
+
+impl CordicConfig {
+    pub fn new(iters: usize) -> Self {
+        Self::default().iters(iters)
+    }
+
+    pub fn iters(mut self, iters: usize) -> Self {
+        self.iters = iters;
+        self
+    }
+
+    // Whether to multiply the output by K. Hardware pipelines often skip
+    // this multiply entirely and fold K into whatever comes next
+    pub fn compensate_gain(self, compensate: bool) -> Self {
+        if compensate {
+            self.gain_compensation(GainCompensation::Post)
+        } else {
+            self.gain_compensation(GainCompensation::None)
+        }
+    }
+
+    pub fn gain_compensation(mut self, compensation: GainCompensation) -> Self {
+        self.compensation = compensation;
+        self
+    }
+
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    // Models rotate() on a datapath of `frac_bits` fractional bits (at
+    // most 60), e.g. 31 for Q31 in and out: theta comes in rounded to
+    // that width and cos and sin come out at it. This runs on the integer
+    // backend's narrow kernel, with the gain compensated by prescaling,
+    // so it needs Backend::Integer and some gain compensation
+    pub fn io_width(mut self, frac_bits: u32) -> Self {
+        self.io_bits = Some(frac_bits);
+        self
+    }
+
+    // With io_width, whether the registers inside are twice as wide as
+    // the I/O (up to 60 bits) with a single rounding at the end, which is
+    // how accurate hardware does it, or the same width, so every shift
+    // truncates at the output's precision
+    pub fn double_word(mut self, double_word: bool) -> Self {
+        self.double_word = double_word;
+        self
+    }
+
+    // Lets rotate() skip the iterations entirely for angles below the
+    // backend's small angle threshold (Backend::small_angle_bits()),
+    // returning (1, theta) since that's already as close as the backend
+    // can represent cos and sin there. Off by default so results stay bit
+    // for bit what the iterations give
+    pub fn small_angle(mut self, enabled: bool) -> Self {
+        self.small_angle = if enabled {
+            SmallAngle::Backend
+        } else {
+            SmallAngle::Off
+        };
+        self
+    }
+
+    // small_angle() with the threshold at 2^-bits instead. Lower bits
+    // widen the fast path at the cost of accuracy: the error is up to
+    // 2^-(2 * bits + 1) in cos and 2^-(3 * bits) / 6 in sin
+    pub fn small_angle_below(mut self, bits: u32) -> Self {
+        self.small_angle = SmallAngle::Below(bits);
+        self
+    }
+
+    // Has rotate() return exact table values at the angles lookup table
+    // implementations get exactly right, every multiple of pi/6 and pi/4
+    // (0, pi/6, pi/4, pi/3, pi/2, ..., pi and on around), so checks like
+    // cos(pi/2) == 0 or sin(pi/6) == 0.5 hold exactly in code ported from
+    // one. An angle within a few ulps of one of them counts, since pi/6
+    // written as PI / 6.0 or as FRAC_PI_6 can round differently
+    pub fn special_angles(mut self, enabled: bool) -> Self {
+        self.special_angles = enabled;
+        self
+    }
+
+    // Reduces every angle into [-pi/4, pi/4] (see fold_octant) rather
+    // than [-pi/2, pi/2] before the iterations, taking the quarter turns
+    // out exactly by swapping and negating, the way libm's sin and cos
+    // do. The iterations' error doesn't grow with the angle, so this
+    // matches those implementations' folding more than it helps accuracy
+    pub fn octant_reduction(mut self, octant: bool) -> Self {
+        self.octant = octant;
+        self
+    }
+
+    // Makes rotate()'s sin rise and cos fall over [0, pi/2] (and mirror
+    // that in the other quadrants) without ever stepping back, even by
+    // one LSB, at the cost of a second rotation and some accuracy (see
+    // src/monotone.rs). Needs gain compensation and no schedule, or
+    // rotate() returns DomainError("monotone") or DomainError("schedule")
+    pub fn monotone(mut self, monotone: bool) -> Self {
+        self.monotone = monotone;
+        self
+    }
+
+    // Has rotate() return cos and sin rounded to nearest in the output
+    // format (io_width's, up to 52 bits, or f64 without it), working at
+    // more and more precision until the rounding is certain (see
+    // src/correctly_rounded.rs). Slow, and the iteration count, backend
+    // and register widths make no difference; it's there to check fast
+    // configs against. Rounding never unsorts, so it's monotone too
+    pub fn correctly_rounded(mut self, correctly_rounded: bool) -> Self {
+        self.correctly_rounded = correctly_rounded;
+        self
+    }
+
+    // Runs the micro-rotations with these shift amounts, in order, in
+    // place of the usual 0, 1, ..., iters - 1, for trying out schedules
+    // that repeat or skip steps, e.g. schedule(&[0, 1, 2, 2, 3, 4]). The
+    // gain becomes the product over the steps actually run and the
+    // iteration count is ignored. Shifts must be below MAX_ITERS, with at
+    // least one and at most MAX_SCHEDULE of them, or rotate() returns
+    // IterationOutOfRange. Only rotations take a schedule: vector() and
+    // io_width() with one return DomainError("schedule")
+    pub fn schedule(mut self, shifts: &[usize]) -> Self {
+        self.shifts = Some(Shifts::new(shifts));
+        self
+    }
+
+    // The custom schedule, if there's one and it's valid
+    pub(crate) fn custom_shifts(&self) -> Result<Option<&Shifts>, CordicError> {
+        match &self.shifts {
+            None => Ok(None),
+            Some(Ok(shifts)) => Ok(Some(shifts)),
+            Some(Err(e)) => Err(*e),
+        }
+    }
+
+    // K as shifts and adds for GainCompensation::Csd, rounded to the
+    // backend's precision
+    pub(crate) fn gain_multiplier(&self) -> Result<CsdMultiplier, CordicError> {
+        let bits = self.backend.precision();
+        match self.custom_shifts()? {
+            Some(shifts) => {
+                let k = shifts.gain().to_f64() * math::powi(2.0, bits as i32);
+                CsdMultiplier::new(math::round(k) as i64, bits)
+            }
+            None => gain_csd(checked_iters(self.iters)?, bits),
+        }
+    }
+
+    // How many micro-rotations rotate() runs and the gain K they need
+    fn steps(&self) -> Result<(usize, FixedPoint), CordicError> {
+        match self.custom_shifts()? {
+            Some(shifts) => Ok((shifts.len, shifts.gain())),
+            None => {
+                let iters = checked_iters(self.iters)?;
+                Ok((iters, gain(iters)?))
+            }
+        }
+    }
+
+    pub fn rotate(&self, theta: FixedPoint) -> Result<Rotation, CordicError> {
+        let ret = self.dispatch(theta)?;
+        // Here rather than in any one path, so every path gets checked
+        #[cfg(feature = "checks")]
+        if self.compensation != GainCompensation::None {
+            let mirror = self.dispatch(-theta)?;
+            check_rotation(self, theta, &ret, &mirror);
+        }
+        Ok(ret)
+    }
+
+    // Whichever of the paths through rotate() the config takes
+    fn dispatch(&self, theta: FixedPoint) -> Result<Rotation, CordicError> {
+        if self.correctly_rounded {
+            return self.rotate_correctly_rounded(theta);
+        }
+        if self.monotone {
+            return self.rotate_monotone(theta);
+        }
+        if let Some(io_bits) = self.io_bits {
+            return self.rotate_narrow(theta, io_bits);
+        }
+        if let Some(ret) = self.rotate_special(theta)? {
+            return Ok(ret);
+        }
+        if let Some(ret) = self.rotate_small(theta)? {
+            return Ok(ret);
+        }
+        self.compose([FixedPoint::new(1.0), FixedPoint::new(0.0)], &[theta])
+    }
+
+    // The special angle table, when it's on and theta is one of them. Only
+    // with gain compensation, like rotate_small()
+    fn rotate_special(&self, theta: FixedPoint) -> Result<Option<Rotation>, CordicError> {
+        if !self.special_angles || self.compensation == GainCompensation::None {
+            return Ok(None);
+        }
+        let (_, kvalue) = self.steps()?;
+        let theta = theta.to_f64();
+        let twelfths = math::round(theta * 12.0 / std::f64::consts::PI);
+        let error = (theta - twelfths * std::f64::consts::PI / 12.0).abs();
+        // NaN and infinities fail this, and go on to the iterations
+        let near = error <= 4.0 * f64::EPSILON * theta.abs();
+        if !near || twelfths.abs() > 1e15 {
+            return Ok(None);
+        }
+        let k = twelfths as i64;
+        let (cos, sin) = match (special_cos(k), special_cos(k - 6)) {
+            (Some(cos), Some(sin)) => (cos, sin),
+            _ => return Ok(None),
+        };
+        // theta is +-0 exactly for k == 0, and sin keeps its sign
+        let sin = if k == 0 { theta } else { sin };
+        Ok(Some(Rotation {
+            x: FixedPoint::new(cos),
+            y: FixedPoint::new(sin),
+            gain: kvalue,
+        }))
+    }
+
+    // The small angle fast path, when it's on and applies. Only with gain
+    // compensation, since without it the iterations' 1/K would be missing
+    fn rotate_small(&self, theta: FixedPoint) -> Result<Option<Rotation>, CordicError> {
+        let bits = match self.small_angle {
+            SmallAngle::Off => return Ok(None),
+            SmallAngle::Backend => self.backend.small_angle_bits(),
+            SmallAngle::Below(bits) => bits,
+        };
+        let (_, kvalue) = self.steps()?;
+        let limit = FixedPoint::new(1.0 / (1u64 << bits.min(63)) as f64);
+        // NaN compares false, so it goes through the iterations
+        let small = theta.abs() < limit;
+        if self.compensation == GainCompensation::None || !small {
+            return Ok(None);
+        }
+        Ok(Some(Rotation {
+            x: FixedPoint::new(1.0),
+            y: theta,
+            gain: kvalue,
+        }))
+    }
+
+    fn rotate_narrow(&self, theta: FixedPoint, io_bits: u32) -> Result<Rotation, CordicError> {
+        let iters = checked_iters(self.iters)?;
+        if self.shifts.is_some() {
+            return Err(CordicError::DomainError("schedule"));
+        }
+        if io_bits == 0
+            || io_bits > integer::Q60::FRAC_BITS
+            || self.backend != Backend::Integer
+            || self.compensation == GainCompensation::None
+        {
+            return Err(CordicError::DomainError("io_width"));
+        }
+        // A Q format has nowhere to put NaN or infinity, and to_q60()
+        // would quietly turn them into 0
+        if !theta.to_f64().is_finite() {
+            return Err(CordicError::InvalidAngle("rotate"));
+        }
+        let internal = if self.double_word {
+            (2 * io_bits).min(integer::Q60::FRAC_BITS)
+        } else {
+            io_bits
+        };
+
+        // Round theta to the input width, run the kernel at the internal
+        // one and round to nearest on the way out
+        let drop = integer::Q60::FRAC_BITS - io_bits;
+        let round = |x: i64| {
+            if drop == 0 {
+                x
+            } else {
+                (((x >> (drop - 1)) + 1) >> 1) << drop
+            }
+        };
+        let z = integer::Q60::from_bits(round(integer::to_q60(reduce(theta))));
+        let [x, y] = integer::sincos_narrow(z, iters, internal, internal, internal);
+        Ok(Rotation {
+            x: integer::from_q60(round(x.to_bits())),
+            y: integer::from_q60(round(y.to_bits())),
+            gain: gain(iters)?,
+        })
+    }
+
+    // Rotates `v` by each angle in `thetas` in turn, without undoing the
+    // gain in between. Every rotation stretches the vector by another
+    // 1/K, so the returned gain is K^n for n angles and is compensated
+    // for once at the end (or once up front when prescaling).
+    //
+    // The angle each rotation leaves over (the residual z once the
+    // iterations run out) is carried into the next one, so rounding the
+    // angles to what `iters` micro-rotations can reach doesn't add up
+    // over long chains of small rotations
+    pub fn compose(
+        &self,
+        v: [FixedPoint; 2],
+        thetas: &[FixedPoint],
+    ) -> Result<Rotation, CordicError> {
+        let (iters, kvalue) = self.steps()?;
+        let shifts = self.custom_shifts()?;
+        let total_gain = thetas
+            .iter()
+            .fold(FixedPoint::new(1.0), |total, _| total * kvalue);
+
+        let mut v = match self.compensation {
+            GainCompensation::Prescale => [v[0] * total_gain, v[1] * total_gain],
+            GainCompensation::Post | GainCompensation::Csd | GainCompensation::None => v,
+        };
+        log(
+            LogLevel::Info,
+            format_args!(
+                "rotate: {} angle(s), {} iterations, {:?} backend, {:?} compensation, gain {}",
+                thetas.len(),
+                iters,
+                self.backend,
+                self.compensation,
+                total_gain
+            ),
+        );
+        let mut residual = FixedPoint::new(0.0);
+        for &theta in thetas {
+            let (theta, folded) = if self.octant {
+                fold_octant(reduce(theta + residual), v)
+            } else {
+                (theta + residual, v)
+            };
+            let (rotated, left_over) = match (self.backend, shifts) {
+                (Backend::Integer, None) => integer::rotate(theta, folded, iters),
+                (Backend::Float, None) => rotate_raw(theta, folded, iters),
+                (Backend::Integer, Some(shifts)) => {
+                    integer::rotate_scheduled(theta, folded, shifts.as_slice())
+                }
+                (Backend::Float, Some(shifts)) => rotate_scheduled(theta, folded, shifts.iter()),
+            };
+            v = rotated;
+            residual = left_over;
+        }
+
+        // Scale vector back such that magnitude is 1
+        // NOTE: This can be done either by keeping track of the
+        // initial values or performing a square root. If the machine
+        // is slow enough that CORDIC is practical (i.e. expensive
+        // hardware multiplication), then it is too slow for square roots
+        // and divisions
+        let [x, y] = match self.compensation {
+            GainCompensation::Post => [v[0] * total_gain, v[1] * total_gain],
+            // Once per rotation, since each one added its own 1/K
+            GainCompensation::Csd => {
+                let k = self.gain_multiplier()?;
+                thetas.iter().fold(v, |v, _| [k.apply(v[0]), k.apply(v[1])])
+            }
+            GainCompensation::Prescale | GainCompensation::None => v,
+        };
+
+        Ok(Rotation {
+            x,
+            y,
+            gain: total_gain,
+        })
+    }
+
+    // sin and cos of every angle in `thetas`, written to the same index
+    // of `out_sin` and `out_cos`, for batches going straight into
+    // buffers someone else owns (a DMA descriptor, a ring). Nothing is
+    // allocated, and the lengths are checked before anything is written
+    pub fn sincos_into(
+        &self,
+        thetas: &[FixedPoint],
+        out_sin: &mut [FixedPoint],
+        out_cos: &mut [FixedPoint],
+    ) -> Result<(), CordicError> {
+        if thetas.len() != out_sin.len() || thetas.len() != out_cos.len() {
+            return Err(CordicError::DomainError("sincos_into"));
+        }
+        for ((&theta, sin), cos) in thetas.iter().zip(out_sin).zip(out_cos) {
+            let ret = self.rotate(theta)?;
+            *cos = ret.x;
+            *sin = ret.y;
+        }
+        Ok(())
+    }
+
+    // sincos_into() split across `threads` scoped worker threads, all
+    // sharing this config. Each gets a contiguous chunk of `thetas` and
+    // the matching chunks of the outputs, so the results are the same as
+    // sincos_into()'s bit for bit
+    pub fn sincos_parallel(
+        &self,
+        thetas: &[FixedPoint],
+        out_sin: &mut [FixedPoint],
+        out_cos: &mut [FixedPoint],
+        threads: usize,
+    ) -> Result<(), CordicError> {
+        if threads == 0 || thetas.len() != out_sin.len() || thetas.len() != out_cos.len() {
+            return Err(CordicError::DomainError("sincos_parallel"));
+        }
+        let chunk = thetas.len().div_ceil(threads).max(1);
+        std::thread::scope(|scope| {
+            let workers = thetas
+                .chunks(chunk)
+                .zip(out_sin.chunks_mut(chunk))
+                .zip(out_cos.chunks_mut(chunk))
+                .map(|((thetas, sin), cos)| scope.spawn(move || self.sincos_into(thetas, sin, cos)))
+                .collect::<Vec<_>>();
+            // sincos_into() doesn't panic, so a worker only can with the
+            // `checks` feature on. The panic has already been printed, and
+            // comes back as an error rather than taking this thread down too
+            workers.into_iter().try_for_each(|worker| {
+                worker
+                    .join()
+                    .unwrap_or(Err(CordicError::WorkerPanicked("sincos_parallel")))
+            })
+        })
+    }
+
+    // Vectoring mode, the inverse of rotate(): finds the magnitude and
+    // angle of `v` (i.e. converts to polar) using the same shift-and-add
+    // micro-rotations
+    pub fn vector(&self, v: [FixedPoint; 2]) -> Result<Vectoring, CordicError> {
+        let iters = checked_iters(self.iters)?;
+        if self.shifts.is_some() {
+            return Err(CordicError::DomainError("schedule"));
+        }
+        let kvalue = gain(iters)?;
+        log(
+            LogLevel::Info,
+            format_args!(
+                "vector: ({}, {}), {} iterations, {:?} backend, {:?} compensation",
+                v[0], v[1], iters, self.backend, self.compensation
+            ),
+        );
+
+        let v = match self.compensation {
+            GainCompensation::Prescale => [v[0] * kvalue, v[1] * kvalue],
+            GainCompensation::Post | GainCompensation::Csd | GainCompensation::None => v,
+        };
+        // A NaN coordinate has no length or angle, but the iterations
+        // would make one up (comparisons with NaN are always false)
+        let (magnitude, angle) = if v[0].to_f64().is_nan() || v[1].to_f64().is_nan() {
+            let nan = FixedPoint::new(f64::NAN);
+            (nan, nan)
+        } else {
+            match self.backend {
+                Backend::Integer => integer::vector(v, iters),
+                Backend::Float => vector_raw(v, iters),
+            }
+        };
+        let magnitude = match self.compensation {
+            GainCompensation::Post => magnitude * kvalue,
+            GainCompensation::Csd => gain_csd(iters, self.backend.precision())?.apply(magnitude),
+            GainCompensation::Prescale | GainCompensation::None => magnitude,
+        };
+
+        Ok(Vectoring {
+            magnitude,
+            angle,
+            gain: kvalue,
+        })
+    }
+}
+
+// With the `checks` feature, every gain compensated rotate() is checked
+// against properties any correct backend has, panicking with everything
+// needed to reproduce it if one doesn't hold:
+//   |cos| and |sin| are at most 1, give or take rounding
+//   cos^2 + sin^2 is 1, since micro-rotations don't change the length
+//     and the gain is undone
+//   rotating by -theta gives (cos, -sin), to within the error of the
+//     iterations (the two don't take exactly mirrored paths when z
+//     lands on 0 partway)
+// `mirror` is the rotation by -theta
+#[cfg(feature = "checks")]
+fn check_rotation(config: &CordicConfig, theta: FixedPoint, ret: &Rotation, mirror: &Rotation) {
+    let [cos, sin] = [ret.x.to_f64(), ret.y.to_f64()];
+    if !(theta.to_f64().is_finite() && cos.is_finite() && sin.is_finite()) {
+        return;
+    }
+    // Each result is within the last angle, atan(2^-(iters - 1)) <
+    // 2^-(iters - 1), of the true one, so the two can be twice that apart
+    // A custom schedule can skip steps and converge to anything, so
+    // only its length and norm are checked
+    let iterations = if config.shifts.is_some() {
+        f64::INFINITY
+    } else {
+        4.0 / (1u64 << config.iters.min(MAX_ITERS)) as f64
+    };
+    // What each path adds on top of f64 rounding, doubled for the norm:
+    // io_width's narrow datapath rounds theta in and cos and sin out and
+    // truncates every step, the small angle path's (1, theta) is long by
+    // up to theta^2, and monotone's chords sag by up to 4 times the
+    // plain error inside the arc (see src/monotone.rs)
+    let mut rounding = 1e-9;
+    if let Some(io_bits) = config.io_bits {
+        let steps = (config.iters.min(MAX_ITERS) + 2) as f64;
+        rounding += 2.0 * (1.0 + 4.0 * steps) * math::powi(2.0, -(io_bits as i32));
+    }
+    let small_bits = match config.small_angle {
+        SmallAngle::Off => None,
+        SmallAngle::Backend => Some(config.backend.small_angle_bits()),
+        SmallAngle::Below(bits) => Some(bits),
+    };
+    if let Some(bits) = small_bits {
+        rounding += math::powi(2.0, -2 * bits.min(512) as i32);
+    }
+    if config.monotone {
+        rounding += 8.0 * monotone::error_bound(config).unwrap_or(f64::INFINITY);
+    }
+
+    let fail = |property: &str| -> ! {
+        panic!(
+            "CORDIC post-condition failed: {}\n  config: {:?}\n  theta: {}\n  rotate(theta): ({}, {})\n  rotate(-theta): ({}, {})",
+            property, config, theta, ret.x, ret.y, mirror.x, mirror.y
+        )
+    };
+    if cos.abs() > 1.0 + rounding || sin.abs() > 1.0 + rounding {
+        fail("|cos| <= 1 and |sin| <= 1");
+    }
+    if (cos * cos + sin * sin - 1.0).abs() > rounding {
+        fail("cos^2 + sin^2 == 1");
+    }
+    if (mirror.x.to_f64() - cos).abs() > iterations + rounding
+        || (mirror.y.to_f64() + sin).abs() > iterations + rounding
+    {
+        fail("rotate(-theta) == (cos, -sin)");
+    }
+}
+
+pub fn cordic(theta: FixedPoint, iters: usize) -> Result<[FixedPoint; 2], CordicError> {
+    let ret = CordicConfig::new(iters).rotate(theta)?;
+    Ok([ret.x, ret.y])
+}
+
+// What the rest of the library (cis, sinc, atan2, the windows, ...)
+// rotates with: MAX_ITERS iterations on the default backend, so those
+// are as deterministic across platforms as rotate() itself. Neither
+// rotate() nor vector() can fail on this config, only on what they're
+// given, and an angle or vector that isn't finite gives NaN here the
+// way f64's functions do, so callers don't go through a Result
+fn library_config() -> CordicConfig {
+    CordicConfig::new(MAX_ITERS)
+}
+
+pub(crate) fn sincos(theta: FixedPoint) -> [FixedPoint; 2] {
+    rotate_vector([FixedPoint::new(1.0), FixedPoint::new(0.0)], theta)
+}
+
+pub(crate) fn rotate_vector(v: [FixedPoint; 2], theta: FixedPoint) -> [FixedPoint; 2] {
+    match library_config().compose(v, &[theta]) {
+        Ok(ret) => [ret.x, ret.y],
+        Err(_) => [FixedPoint::new(f64::NAN); 2],
+    }
+}
+
+pub(crate) fn polar(v: [FixedPoint; 2]) -> (FixedPoint, FixedPoint) {
+    let [x, y] = v;
+    let zero = FixedPoint::new(0.0);
+    let pi = FixedPoint::new(std::f64::consts::PI);
+    let half_pi = FixedPoint::new(std::f64::consts::FRAC_PI_2);
+
+    // NaN has no angle, and the iterations would make one up. With an
+    // infinity the length is infinite and the angle is the one f64::atan2
+    // gives: a diagonal when both are, otherwise the axis along the
+    // infinite one, with the zeros' rules for which way
+    let [fx, fy] = [x.to_f64(), y.to_f64()];
+    if fx.is_nan() || fy.is_nan() {
+        let magnitude = if fx.is_infinite() || fy.is_infinite() {
+            f64::INFINITY
+        } else {
+            f64::NAN
+        };
+        return (FixedPoint::new(magnitude), FixedPoint::new(f64::NAN));
+    }
+    if fx.is_infinite() || fy.is_infinite() {
+        let angle = match (fx.is_infinite(), fy.is_infinite()) {
+            (true, true) if fx > 0.0 => std::f64::consts::FRAC_PI_4,
+            (true, true) => 3.0 * std::f64::consts::FRAC_PI_4,
+            (false, true) => std::f64::consts::FRAC_PI_2,
+            _ if fx > 0.0 => 0.0,
+            _ => std::f64::consts::PI,
+        };
+        let angle = if fy.is_sign_negative() { -angle } else { angle };
+        return (FixedPoint::new(f64::INFINITY), FixedPoint::new(angle));
+    }
+
+    // Points on an axis have an exact angle, so there's no point running
+    // the iterations (which would only get close to it). (0, 0) doesn't
+    // have an angle at all, and like f64::atan2 the sign bits of the
+    // zeros pick one of 0, -0, pi or -pi
+    if y == zero {
+        let angle = if x.is_sign_negative() { pi } else { zero };
+        let angle = if y.is_sign_negative() { -angle } else { angle };
+        return (x.abs(), angle);
+    }
+    if x == zero {
+        let angle = if y < zero { -half_pi } else { half_pi };
+        return (y.abs(), angle);
+    }
+
+    match library_config().vector(v) {
+        Ok(ret) => (ret.magnitude, ret.angle),
+        Err(_) => (FixedPoint::new(f64::NAN), FixedPoint::new(f64::NAN)),
+    }
+}
+
+// The angle of (x, y) in [-pi, pi], with the same conventions as
+// f64::atan2 for zeros, points on the axes, infinities and NaN
+pub fn atan2(y: FixedPoint, x: FixedPoint) -> FixedPoint {
+    polar([x, y]).1
+}
+
+// CORDIC (for trig functions, at least) does require some
+// compile time constants. However, this is far more space
+// efficient than naively storing sine itself. These are
+// included here to allow for larger iteration countes, but
+// any reasonable implementation would pre-compute a certain
+// amount and keep them in a global array, which is what
+// src/cache.rs does
+// atan(2^-x) for x in 0..iters. The loops below iterate over this slice
+// rather than indexing it, so they can't ask for an angle past its end
+pub(crate) fn angles(iters: usize) -> &'static [FixedPoint] {
+    cache::angles(Backend::Float, iters)
+}
+
+// Runs `iters` (already validated) micro-rotations on `v`, returning the
+// rotated vector *without* undoing the gain, along with the residual
+// angle the micro-rotations didn't get to
+fn rotate_raw(
+    theta: FixedPoint,
+    v: [FixedPoint; 2],
+    iters: usize,
+) -> ([FixedPoint; 2], FixedPoint) {
+    // CORDIC *can* calculate theta even if theta wraps around, but
+    // at the expense of accuracy. We make sure that theta is in
+    // its lowest valid representation (the symmetric range [-pi, pi]),
+    // and then fold it into the right half-plane where the
+    // iterations actually converge. Another issue with floating point
+    // numbers is buildup of error across iterations. Fixed point
+    // arithmetic can fix this, but I haven't gotten around to
+    // implementing that.
+    if let Some(ret) = no_angle(theta) {
+        return ret;
+    }
+    let (mut theta, negate) = fold_quadrant(reduce(theta));
+    let angles = angles(iters);
+
+    let mut poweroftwo = FixedPoint::new(1.0);
+    let fixed_point_zero = FixedPoint::new(0.0);
+    let fixed_point_pos_two = FixedPoint::new(2.0);
+
+    // Rotating by pi (the quadrant fold) just negates the vector
+    let mut v = if negate { [-v[0], -v[1]] } else { v };
+    for (i, &angle) in angles.iter().enumerate() {
+        let sigma_is_neg = theta < fixed_point_zero;
+
+        // v = R * v
+        // NOTE: Matrix is always of the form
+        // [ 1.0, -factor; factor, 1.0 ]
+        //
+        // You can imagine `v` as a vector whose cosine is
+        // one and sine is zero. The following matrix is a
+        // rotation matrix, and it is normally of the form
+        // [ cos theta, -sin theta; sin theta, cos theta ].
+        // However, this also magnfiies the vector by some
+        // set amount, and we account for all these magnitude
+        // increases in one multiplication at the end of the
+        // calculation
+        //
+        // The following simplifies down to a rotation of
+        // tan^-1(2^-i) and a increase in magnitude of
+        // (1 + 2^(-2j))^(1/2)
+
+/*
+        let factor = if sigma_is_neg {
+            // NOTE: Almost all compilers will optimize multiplication by
+            // -1 to be flipping a single bit, so for performance reasons
+            // this isn't a normal multiplication
+            //
+            // HOWEVER, if -1 were stored in a variable, that optimization
+            // isn't as obvious, so the compiler might not catch it. We store
+            // sigma_is_neg instead of sigma = -1 or sigma = 1 because of this
+            FixedPoint::new(-1.0) * poweroftwo
+        } else {
+            poweroftwo
+        };
+
+        let matrix = [
+            [fixed_point_pos_one, fixed_point_neg_one * factor],
+            [factor, fixed_point_pos_one],
+        ];
+
+        */
+
+        // NOTE: v = matrix * v using the variables as defined above, but the
+        // following is much faster for a few reasons
+        //   1. Multiplication by constants has optimizations done at compile time
+        //     a. -1 * x simplifies to flipping a single bit
+        //     b. Two constants multiplied by each other are pre-multiplied
+
+        v = if sigma_is_neg {
+            [
+                v[0] + poweroftwo * v[1],
+                -(poweroftwo * v[0]) + v[1]
+            ]
+        } else {
+            [
+                v[0] - poweroftwo * v[1],
+                poweroftwo * v[0] + v[1]
+            ]
+        };
+
+        // sigma
+        theta = if sigma_is_neg {
+            theta + angle
+        } else {
+            theta - angle
+        };
+        log_iteration(i, !sigma_is_neg, theta, v);
+        poweroftwo /= fixed_point_pos_two;
+    }
+
+    (v, theta)
+}
+
+// An infinite or NaN angle has no sine or cosine (f64::sin gives NaN),
+// but the iterations would still pick a direction for it, since every
+// comparison with NaN is false. Both coordinates and the residual come
+// back NaN instead, the same as the integer backend's
+fn no_angle(theta: FixedPoint) -> Option<([FixedPoint; 2], FixedPoint)> {
+    if theta.to_f64().is_finite() {
+        return None;
+    }
+    let nan = FixedPoint::new(f64::NAN);
+    Some(([nan, nan], nan))
+}
+
+// rotate_raw() with the given shifts (already validated) in place of
+// 0..iters, for both CordicConfig::schedule() and Schedule
+pub(crate) fn rotate_scheduled(
+    theta: FixedPoint,
+    v: [FixedPoint; 2],
+    shifts: impl IntoIterator<Item = usize>,
+) -> ([FixedPoint; 2], FixedPoint) {
+    if let Some(ret) = no_angle(theta) {
+        return ret;
+    }
+    let (mut theta, negate) = fold_quadrant(reduce(theta));
+    let angles = angles(MAX_ITERS);
+    let zero = FixedPoint::new(0.0);
+
+    let mut v = if negate { [-v[0], -v[1]] } else { v };
+    for (shift, i) in shifts.into_iter().zip(0..) {
+        let Some(&angle) = angles.get(shift) else {
+            continue;
+        };
+        let poweroftwo = FixedPoint::new(math::powi(2.0, -(shift as i32)));
+        let sigma_is_neg = theta < zero;
+        v = if sigma_is_neg {
+            theta += angle;
+            [v[0] + poweroftwo * v[1], v[1] - poweroftwo * v[0]]
+        } else {
+            theta -= angle;
+            [v[0] - poweroftwo * v[1], v[1] + poweroftwo * v[0]]
+        };
+        log_iteration(i, !sigma_is_neg, theta, v);
+    }
+    (v, theta)
+}
+
+// Vectoring mode: runs `iters` (already validated) micro-rotations that
+// drive y to zero instead of theta, adding up the angles it took to get
+// there. What's left is the (gain-magnified) magnitude in x and the
+// angle of the original vector in z
+fn vector_raw(v: [FixedPoint; 2], iters: usize) -> (FixedPoint, FixedPoint) {
+    let angles = angles(iters);
+    let fixed_point_zero = FixedPoint::new(0.0);
+    let pi = FixedPoint::new(std::f64::consts::PI);
+
+    // Same convergence problem as rotation mode, vectors in the left
+    // half-plane get rotated by pi first (which is just negation), and
+    // the angle starts out at +-pi to account for it. Which one depends
+    // on the side of the x axis (-0.0 counting as below)
+    let (mut v, mut z) = if v[0] < fixed_point_zero {
+        if v[1].is_sign_negative() {
+            ([-v[0], -v[1]], -pi)
+        } else {
+            ([-v[0], -v[1]], pi)
+        }
+    } else {
+        (v, fixed_point_zero)
+    };
+
+    let mut poweroftwo = FixedPoint::new(1.0);
+    for (i, angle) in angles.iter().enumerate() {
+        // Rotate towards the x axis: clockwise when y is above it,
+        // counterclockwise when below
+        let sigma_is_neg = v[1] < fixed_point_zero;
+        if sigma_is_neg {
+            v = [v[0] - poweroftwo * v[1], poweroftwo * v[0] + v[1]];
+            z -= *angle;
+        } else {
+            v = [v[0] + poweroftwo * v[1], -(poweroftwo * v[0]) + v[1]];
+            z += *angle;
+        }
+        log_iteration(i, sigma_is_neg, z, v);
+        poweroftwo /= FixedPoint::new(2.0);
+    }
+
+    (v[0], z)
+}
+
+// The Debug level trace of one micro-rotation, after it's been applied.
+// sigma is +1 for counterclockwise, whichever mode decided it
+fn log_iteration(i: usize, counterclockwise: bool, z: FixedPoint, v: [FixedPoint; 2]) {
+    log(
+        LogLevel::Debug,
+        format_args!(
+            "  iteration {}: sigma {}, z {}, v ({}, {})",
+            i,
+            if counterclockwise { "+1" } else { "-1" },
+            z,
+            v[0],
+            v[1]
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_enough(a: FixedPoint, b: FixedPoint) -> bool {
+        let diff = a - b;
+        diff < FixedPoint::new(0.01) && -diff < FixedPoint::new(0.01)
+    }
+
+    #[test]
+    fn basic() {
+        for i in -628..=628 {
+            // -2pi to 2pi
+            let ret = cordic(FixedPoint::new(i as f64 / 100.0), 1000).unwrap();
+            let cos = FixedPoint::new((i as f64 / 100.0).cos());
+            let sin = FixedPoint::new((i as f64 / 100.0).sin());
+
+            println!(
+                "Theta == {}\t{} vs {}\t{} vs {}",
+                (i as f64 / 100.0),
+                ret[0],
+                cos,
+                ret[1],
+                sin
+            );
+
+            assert![close_enough(ret[0], cos)];
+            assert![close_enough(ret[1], sin)];
+        }
+    }
+
+    #[test]
+    fn negative_symmetry() {
+        for i in 0..=628 {
+            let theta = i as f64 / 100.0;
+            let pos = cordic(FixedPoint::new(theta), 100).unwrap();
+            let neg = cordic(FixedPoint::new(-theta), 100).unwrap();
+
+            assert![close_enough(pos[0], neg[0])];
+            assert![close_enough(pos[1], -neg[1])];
+        }
+    }
+
+    #[test]
+    fn reduce_is_symmetric() {
+        let pi = FixedPoint::new(std::f64::consts::PI);
+        for i in -2000..=2000 {
+            let theta = reduce(FixedPoint::new(i as f64 / 100.0));
+            assert![theta <= pi && theta >= -pi];
+        }
+        assert![close_enough(
+            reduce(FixedPoint::new(-0.5)),
+            FixedPoint::new(-0.5)
+        )];
+    }
+
+    #[test]
+    fn iteration_range() {
+        assert_eq![checked_iters(0), Err(CordicError::IterationOutOfRange(0))];
+        assert_eq![checked_iters(1), Ok(1)];
+        assert_eq![checked_iters(MAX_ITERS), Ok(MAX_ITERS)];
+        assert_eq![checked_iters(usize::MAX), Ok(MAX_ITERS)];
+
+        assert![cordic(FixedPoint::new(1.0), 0).is_err()];
+        assert![cordic(FixedPoint::new(1.0), 1).is_ok()];
+    }
+
+    #[test]
+    fn gain_table_matches_product() {
+        // Recompute the product the slow way, with one factor per
+        // rotation actually performed
+        for iters in 1..=MAX_ITERS {
+            let expected = (0..iters as i32)
+                .map(|y| 1.0_f64 / (1.0_f64 + 2_f64.powi(-2 * y)).sqrt())
+                .product::<f64>();
+            let diff = gain(iters).unwrap().to_f64() - expected;
+
+            assert![diff.abs() < 1e-15, "gain({}) is off by {}", iters, diff];
+        }
+    }
+
+    #[test]
+    fn gain_converges_to_limit() {
+        // Regression test: the old fold skipped the last rotation, so one
+        // iteration was left unscaled (gain 1 instead of 1/sqrt(2))
+        assert![gain(1).unwrap() == FixedPoint::new(std::f64::consts::FRAC_1_SQRT_2)];
+        assert![gain(MAX_ITERS).unwrap() == FixedPoint::new(GAIN_LIMIT)];
+        assert![gain(1_000_000).unwrap() == FixedPoint::new(GAIN_LIMIT)];
+        assert![gain(0).is_err()];
+
+        // The gain shrinks towards the limit with every iteration
+        for iters in 1..MAX_ITERS {
+            assert![gain(iters).unwrap() >= gain(iters + 1).unwrap()];
+        }
+    }
+
+    #[test]
+    fn single_rotation_has_unit_magnitude() {
+        // One rotation lands on +-45 degrees, which with the right gain
+        // is exactly on the unit circle
+        let ret = cordic(FixedPoint::new(0.1), 1).unwrap();
+        let magnitude = ret[0] * ret[0] + ret[1] * ret[1];
+        assert![close_enough(magnitude, FixedPoint::new(1.0))];
+        assert![close_enough(ret[0], ret[1])];
+    }
+
+    #[test]
+    fn uncompensated_gain() {
+        for i in -628..=628 {
+            let theta = FixedPoint::new(i as f64 / 100.0);
+            let scaled = CordicConfig::new(32).rotate(theta).unwrap();
+            let raw = CordicConfig::new(32)
+                .compensate_gain(false)
+                .rotate(theta)
+                .unwrap();
+
+            assert![scaled.gain == raw.gain];
+            assert![raw.x * raw.gain == scaled.x];
+            assert![raw.y * raw.gain == scaled.y];
+
+            // The raw vector is 1/K long, so ~1.647 instead of 1
+            let magnitude = raw.x * raw.x + raw.y * raw.y;
+            let expected = FixedPoint::new(1.0) / (raw.gain * raw.gain);
+            assert![close_enough(magnitude, expected)];
+        }
+    }
+
+    #[test]
+    fn prescaled_gain_matches_post_scaled() {
+        // Both paths compute the same thing, the only difference is where
+        // the rounding happens. Each iteration can round differently by
+        // about an ULP, so the bound grows with the iteration count
+        for iters in [1, 8, 16, 32, MAX_ITERS] {
+            let bound = FixedPoint::new(iters as f64 * f64::EPSILON);
+            for i in -628..=628 {
+                let theta = FixedPoint::new(i as f64 / 100.0);
+                let post = CordicConfig::new(iters).rotate(theta).unwrap();
+                let pre = CordicConfig::new(iters)
+                    .gain_compensation(GainCompensation::Prescale)
+                    .rotate(theta)
+                    .unwrap();
+
+                let dx = post.x - pre.x;
+                let dy = post.y - pre.y;
+                assert![dx <= bound && -dx <= bound, "cos {} off by {}", theta, dx];
+                assert![dy <= bound && -dy <= bound, "sin {} off by {}", theta, dy];
+            }
+        }
+    }
+
+    #[test]
+    fn composed_rotations() {
+        let config = CordicConfig::new(32);
+        let start = [FixedPoint::new(1.0), FixedPoint::new(0.0)];
+        for i in -20..=20 {
+            let step = FixedPoint::new(i as f64 / 100.0);
+            let steps = [step; 10];
+            let composed = config.compose(start, &steps).unwrap();
+            let total = config.rotate(FixedPoint::new(i as f64 / 10.0)).unwrap();
+
+            assert![close_enough(composed.x, total.x)];
+            assert![close_enough(composed.y, total.y)];
+
+            // One K per rotation
+            let expected_gain = steps
+                .iter()
+                .fold(FixedPoint::new(1.0), |g, _| g * total.gain);
+            assert![composed.gain == expected_gain];
+        }
+    }
+
+    #[test]
+    fn composed_rotations_carry_residual() {
+        // 1000 rotations of 0.001 with only 8 iterations each: every
+        // rotation individually misses by up to atan(2^-7), but carrying
+        // the residual along keeps the total on target
+        let config = CordicConfig::new(8);
+        let start = [FixedPoint::new(1.0), FixedPoint::new(0.0)];
+        let steps = [FixedPoint::new(0.001); 1000];
+        let composed = config.compose(start, &steps).unwrap();
+
+        assert![close_enough(composed.x, FixedPoint::new(1.0_f64.cos()))];
+        assert![close_enough(composed.y, FixedPoint::new(1.0_f64.sin()))];
+    }
+
+    #[test]
+    fn composed_rotation_of_vector() {
+        // Rotating (0, 2) by pi/2 twice ends up at (0, -2)
+        let quarter = FixedPoint::new(std::f64::consts::FRAC_PI_2);
+        let start = [FixedPoint::new(0.0), FixedPoint::new(2.0)];
+        for compensation in [
+            GainCompensation::Post,
+            GainCompensation::Prescale,
+            GainCompensation::Csd,
+        ] {
+            let ret = CordicConfig::default()
+                .gain_compensation(compensation)
+                .compose(start, &[quarter, quarter])
+                .unwrap();
+
+            assert![close_enough(ret.x, FixedPoint::new(0.0))];
+            assert![close_enough(ret.y, FixedPoint::new(-2.0))];
+        }
+    }
+
+    #[test]
+    fn csd_compensation() {
+        // K as shifts and adds at the backend's precision lands within a
+        // few ulps of multiplying by it
+        for backend in [Backend::Integer, Backend::Float] {
+            for iters in [8, 24, MAX_ITERS] {
+                let config = CordicConfig::new(iters).backend(backend);
+                let csd = config.gain_compensation(GainCompensation::Csd);
+                for i in -30..=30 {
+                    let theta = FixedPoint::new(i as f64 / 10.0);
+                    let post = config.rotate(theta).unwrap();
+                    let ret = csd.rotate(theta).unwrap();
+                    assert![(ret.x - post.x).abs() <= FixedPoint::new(1e-15)];
+                    assert![(ret.y - post.y).abs() <= FixedPoint::new(1e-15)];
+                }
+            }
+        }
+        let schedule = CordicConfig::new(24)
+            .schedule(&[0, 1, 2, 2, 3, 4, 5, 6, 7, 8, 9, 10])
+            .gain_compensation(GainCompensation::Csd);
+        let ret = schedule.rotate(FixedPoint::new(0.5)).unwrap();
+        assert![(ret.x.to_f64() - 0.5f64.cos()).abs() < 1e-3];
+        assert![(ret.y.to_f64() - 0.5f64.sin()).abs() < 1e-3];
+    }
+
+    #[test]
+    fn vectoring() {
+        for i in -314..=314 {
+            let theta = i as f64 / 100.0;
+            for r in [0.5, 1.0, 3.0] {
+                let v = [
+                    FixedPoint::new(r * theta.cos()),
+                    FixedPoint::new(r * theta.sin()),
+                ];
+                for compensation in [
+                    GainCompensation::Post,
+                    GainCompensation::Prescale,
+                    GainCompensation::Csd,
+                ] {
+                    let ret = CordicConfig::default()
+                        .gain_compensation(compensation)
+                        .vector(v)
+                        .unwrap();
+
+                    assert![close_enough(ret.magnitude, FixedPoint::new(r))];
+                    assert![close_enough(ret.angle, FixedPoint::new(theta))];
+                }
+
+                let raw = CordicConfig::default()
+                    .compensate_gain(false)
+                    .vector(v)
+                    .unwrap();
+                assert![close_enough(raw.magnitude * raw.gain, FixedPoint::new(r))];
+            }
+        }
+    }
+
+    #[test]
+    fn atan2_matches_f64() {
+        // Every combination of signs, zeros and magnitudes, which covers
+        // all four quadrants, both sides of each axis and the origin
+        let values = [-3.0, -1.0, -0.25, -0.0, 0.0, 0.25, 1.0, 3.0];
+        for &y in values.iter() {
+            for &x in values.iter() {
+                let ret = atan2(FixedPoint::new(y), FixedPoint::new(x));
+                let expected = y.atan2(x);
+
+                if x == 0.0 || y == 0.0 {
+                    // On an axis, so it has to be exact, sign included
+                    assert![ret == FixedPoint::new(expected), "atan2({}, {})", y, x];
+                    assert_eq![ret.is_sign_negative(), expected.is_sign_negative()];
+                } else {
+                    let diff = (ret.to_f64() - expected).abs();
+                    assert![diff < 1e-14, "atan2({}, {}) off by {}", y, x, diff];
+                }
+            }
+        }
+
+        // Infinities against everything, and NaN anywhere, exactly as
+        // f64 has them
+        let (inf, nan) = (f64::INFINITY, f64::NAN);
+        let values = [-inf, -3.0, -0.0, 0.0, 1.0, inf, nan];
+        for &y in values.iter() {
+            for &x in values.iter() {
+                if y.is_finite() && x.is_finite() {
+                    continue;
+                }
+                let ret = atan2(FixedPoint::new(y), FixedPoint::new(x)).to_f64();
+                let expected = y.atan2(x);
+                if expected.is_nan() {
+                    assert![ret.is_nan(), "atan2({}, {}) = {}", y, x, ret];
+                } else {
+                    assert_eq![ret.to_bits(), expected.to_bits(), "atan2({}, {})", y, x];
+                }
+            }
+        }
+        let (magnitude, _) = polar([FixedPoint::new(nan), FixedPoint::new(-inf)]);
+        assert_eq![magnitude.to_f64(), inf];
+    }
+
+    #[test]
+    fn atan2_sweep() {
+        for i in -628..=628 {
+            let theta = i as f64 / 200.0 * 1.99;
+            let ret = atan2(FixedPoint::new(theta.sin()), FixedPoint::new(theta.cos()));
+            let expected = theta.sin().atan2(theta.cos());
+
+            assert![(ret.to_f64() - expected).abs() < 1e-14];
+        }
+    }
+
+    #[test]
+    fn library_uses_default_backend() {
+        // What cis, sinc, atan2 and the rest build on gives exactly what
+        // the integer backend does, so they're as deterministic as it is
+        let integer = CordicConfig::new(MAX_ITERS).backend(Backend::Integer);
+        for i in -50..=50 {
+            let theta = FixedPoint::new(i as f64 * 0.13);
+            let ret = integer.rotate(theta).unwrap();
+            assert_eq![sincos(theta), [ret.x, ret.y]];
+
+            let v = [FixedPoint::new(3.0), FixedPoint::new(i as f64 + 0.5)];
+            let ret = integer.compose(v, &[theta]).unwrap();
+            assert_eq![rotate_vector(v, theta), [ret.x, ret.y]];
+            let ret = integer.vector(v).unwrap();
+            assert_eq![polar(v), (ret.magnitude, ret.angle)];
+        }
+        assert![sincos(FixedPoint::new(f64::NAN))[0].to_f64().is_nan()];
+    }
+
+    #[test]
+    fn clamped_iters_match_max() {
+        // Anything past MAX_ITERS is clamped, so the results must be
+        // identical rather than just close
+        for i in -314..=314 {
+            let theta = FixedPoint::new(i as f64 / 100.0);
+            let max = cordic(theta, MAX_ITERS).unwrap();
+            let huge = cordic(theta, 1_000_000).unwrap();
+
+            assert![max[0] == huge[0] && max[1] == huge[1]];
+        }
+    }
+
+    #[test]
+    fn special_angles() {
+        use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, FRAC_PI_3, FRAC_PI_4, FRAC_PI_6, PI};
+        for &backend in &[Backend::Integer, Backend::Float] {
+            let config = CordicConfig::new(MAX_ITERS)
+                .backend(backend)
+                .special_angles(true);
+            let sincos = |theta: f64| {
+                let ret = config.rotate(FixedPoint::new(theta)).unwrap();
+                (ret.x.to_f64(), ret.y.to_f64())
+            };
+            assert_eq![sincos(FRAC_PI_2), (0.0, 1.0)];
+            assert_eq![sincos(PI / 2.0), (0.0, 1.0)];
+            assert_eq![sincos(FRAC_PI_6).1, 0.5];
+            assert_eq![sincos(PI / 6.0).1, 0.5];
+            assert_eq![sincos(FRAC_PI_3).0, 0.5];
+            assert_eq![sincos(FRAC_PI_4), (FRAC_1_SQRT_2, FRAC_1_SQRT_2)];
+            assert_eq![sincos(3.0 * FRAC_PI_4), (-FRAC_1_SQRT_2, FRAC_1_SQRT_2)];
+            assert_eq![sincos(PI), (-1.0, 0.0)];
+            assert_eq![sincos(-PI), (-1.0, 0.0)];
+            assert_eq![sincos(-2.0 * PI / 3.0), (-0.5, -(3.0f64.sqrt() / 2.0))];
+            assert_eq![sincos(2.0 * PI), (1.0, 0.0)];
+            assert_eq![sincos(-0.0).1.to_bits(), (-0.0f64).to_bits()];
+
+            // Anything else, pi/12 included, goes through the iterations
+            let plain = CordicConfig::new(MAX_ITERS).backend(backend);
+            for &theta in &[0.1, PI / 12.0, FRAC_PI_2 + 1e-9, 1e-300] {
+                let theta = FixedPoint::new(theta);
+                assert_eq![config.rotate(theta), plain.rotate(theta)];
+            }
+            assert_ne![
+                plain.rotate(FixedPoint::new(FRAC_PI_2)).unwrap().x.to_f64(),
+                0.0
+            ];
+        }
+    }
+
+    #[test]
+    fn small_angle() {
+        assert_eq![Backend::Float.small_angle_bits(), 27];
+        assert_eq![Backend::Integer.small_angle_bits(), 31];
+
+        // Within half an ulp of cos and sin right up to the threshold,
+        // on both backends
+        for &backend in &[Backend::Float, Backend::Integer] {
+            let config = CordicConfig::new(40).backend(backend).small_angle(true);
+            let limit = 0.5f64.powi(backend.small_angle_bits() as i32);
+            for &theta in &[0.0, 1e-12, -limit * 0.999, limit * 0.5] {
+                let ret = config.rotate(FixedPoint::new(theta)).unwrap();
+                assert_eq![
+                    (ret.x, ret.y),
+                    (FixedPoint::new(1.0), FixedPoint::new(theta))
+                ];
+                assert_eq![ret.x.to_f64(), theta.cos()];
+                assert_eq![ret.y.to_f64(), theta.sin()];
+                assert_eq![ret.gain, gain(40).unwrap()];
+            }
+
+            // At the threshold and past it the iterations run as usual
+            let plain = CordicConfig::new(40).backend(backend);
+            let theta = FixedPoint::new(limit);
+            assert_eq![config.rotate(theta), plain.rotate(theta)];
+            let unscaled = config.compensate_gain(false);
+            assert_eq![
+                unscaled.rotate(FixedPoint::new(1e-12)),
+                plain.compensate_gain(false).rotate(FixedPoint::new(1e-12))
+            ];
+        }
+
+        // A configurable threshold, and still checking the iterations
+        let wide = CordicConfig::new(40).small_angle_below(10);
+        let ret = wide.rotate(FixedPoint::new(5e-4)).unwrap();
+        assert_eq![ret.y, FixedPoint::new(5e-4)];
+        assert![CordicConfig::new(0)
+            .small_angle(true)
+            .rotate(FixedPoint::new(0.0))
+            .is_err()];
+    }
+
+    #[test]
+    fn custom_schedule() {
+        let theta = FixedPoint::new(0.7);
+        for &backend in &[Backend::Integer, Backend::Float] {
+            // The usual schedule written out gives the usual result, up
+            // to the rounding of the recomputed gain
+            let usual = CordicConfig::new(24).backend(backend);
+            let written = usual.schedule(&(0..24).collect::<Vec<_>>());
+            let [a, b] = [usual.rotate(theta).unwrap(), written.rotate(theta).unwrap()];
+            assert![(a.gain - b.gain).abs() < FixedPoint::new(1e-15)];
+            assert![(a.x - b.x).abs() < FixedPoint::new(1e-15)];
+            assert![(a.y - b.y).abs() < FixedPoint::new(1e-15)];
+
+            // Repeating a shift is one more step and one more stretch,
+            // and still converges
+            let repeated = usual.schedule(&[0, 1, 2, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+            let ret = repeated.rotate(theta).unwrap();
+            assert![(ret.gain - a.gain).abs() > FixedPoint::new(1e-6)];
+            assert![(ret.x.to_f64() - 0.7f64.cos()).abs() < 1e-3];
+            assert![(ret.y.to_f64() - 0.7f64.sin()).abs() < 1e-3];
+        }
+
+        let config = CordicConfig::new(24);
+        for (shifts, error) in [
+            (vec![], CordicError::IterationOutOfRange(0)),
+            (
+                vec![0, MAX_ITERS],
+                CordicError::IterationOutOfRange(MAX_ITERS),
+            ),
+            (
+                vec![1; MAX_SCHEDULE + 1],
+                CordicError::IterationOutOfRange(MAX_SCHEDULE + 1),
+            ),
+        ] {
+            assert_eq![config.schedule(&shifts).rotate(theta), Err(error)];
+        }
+        let scheduled = config.schedule(&[0, 1, 2]);
+        let unit = [FixedPoint::new(1.0), FixedPoint::new(0.0)];
+        assert_eq![
+            scheduled.vector(unit),
+            Err(CordicError::DomainError("schedule"))
+        ];
+        assert_eq![
+            scheduled.io_width(31).rotate(theta),
+            Err(CordicError::DomainError("schedule"))
+        ];
+    }
+
+    #[test]
+    fn octant() {
+        use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+        let unit = [FixedPoint::new(1.0), FixedPoint::new(0.0)];
+        for i in -400..=400 {
+            let theta = i as f64 * PI / 400.0;
+            let (left, [x, y]) = fold_octant(FixedPoint::new(theta), unit);
+            assert![left.to_f64().abs() <= FRAC_PI_4 + 1e-15];
+            // The quarter turns taken out are exact
+            let quarter = ((theta - left.to_f64()) / FRAC_PI_2).round() * FRAC_PI_2;
+            assert_eq![
+                (x.to_f64(), y.to_f64()),
+                (quarter.cos().round(), quarter.sin().round())
+            ];
+        }
+
+        for &backend in &[Backend::Integer, Backend::Float] {
+            let plain = CordicConfig::new(MAX_ITERS).backend(backend);
+            let config = plain.octant_reduction(true);
+            let worst = |config: CordicConfig, near: f64| {
+                (-1000..=1000)
+                    .map(|i| near + i as f64 * 1e-4)
+                    .map(|theta| {
+                        let ret = config.rotate(FixedPoint::new(theta)).unwrap();
+                        let cos = (ret.x.to_f64() - theta.cos()).abs();
+                        cos.max((ret.y.to_f64() - theta.sin()).abs())
+                    })
+                    .fold(0.0, f64::max)
+            };
+            for &near in &[0.0, FRAC_PI_4, FRAC_PI_2, -FRAC_PI_2, 3.0] {
+                assert![worst(config, near) < 8.0 * f64::EPSILON];
+                assert![worst(config, near) <= worst(plain, near) * 1.5];
+            }
+
+            // Arbitrary vectors and chains of angles fold the same way
+            let v = [FixedPoint::new(0.3), FixedPoint::new(-0.8)];
+            let thetas = [FixedPoint::new(2.0), FixedPoint::new(-2.9)];
+            let folded = config.compose(v, &thetas).unwrap();
+            let straight = plain.compose(v, &thetas).unwrap();
+            assert![(folded.x.to_f64() - straight.x.to_f64()).abs() < 1e-14];
+            assert![(folded.y.to_f64() - straight.y.to_f64()).abs() < 1e-14];
+        }
+    }
+
+    #[test]
+    fn sincos_into() {
+        let config = CordicConfig::new(40);
+        let thetas = (0..16)
+            .map(|i| FixedPoint::new(i as f64 * 0.4 - 3.0))
+            .collect::<Vec<FixedPoint>>();
+        let mut sin = [FixedPoint::new(0.0); 16];
+        let mut cos = [FixedPoint::new(0.0); 16];
+        config.sincos_into(&thetas, &mut sin, &mut cos).unwrap();
+        for (i, &theta) in thetas.iter().enumerate() {
+            let ret = config.rotate(theta).unwrap();
+            assert_eq![(cos[i], sin[i]), (ret.x, ret.y)];
+        }
+
+        // A short buffer is caught before any of them is touched
+        let mut short = [FixedPoint::new(9.0); 15];
+        assert_eq![
+            config.sincos_into(&thetas, &mut sin, &mut short),
+            Err(CordicError::DomainError("sincos_into"))
+        ];
+        assert![short.iter().all(|&x| x == FixedPoint::new(9.0))];
+        assert![CordicConfig::new(0)
+            .sincos_into(&thetas, &mut sin, &mut cos)
+            .is_err()];
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        fn send_sync<T: Send + Sync>() {}
+        send_sync::<CordicConfig>();
+        send_sync::<Rotation>();
+        send_sync::<Vectoring>();
+        send_sync::<crate::table::Tables>();
+
+        let config = CordicConfig::new(40).backend(Backend::Float);
+        let thetas = (0..1000)
+            .map(|i| FixedPoint::new(i as f64 * 0.0125 - 6.0))
+            .collect::<Vec<FixedPoint>>();
+        let mut sin = vec![FixedPoint::new(0.0); 1000];
+        let mut cos = vec![FixedPoint::new(0.0); 1000];
+        config.sincos_into(&thetas, &mut sin, &mut cos).unwrap();
+        for threads in [1, 3, 8, 2000] {
+            let mut par_sin = vec![FixedPoint::new(9.0); 1000];
+            let mut par_cos = vec![FixedPoint::new(9.0); 1000];
+            config
+                .sincos_parallel(&thetas, &mut par_sin, &mut par_cos, threads)
+                .unwrap();
+            assert_eq![(&par_sin, &par_cos), (&sin, &cos)];
+        }
+
+        assert_eq![
+            config.sincos_parallel(&thetas, &mut sin, &mut cos, 0),
+            Err(CordicError::DomainError("sincos_parallel"))
+        ];
+        assert![config
+            .sincos_parallel(&thetas, &mut sin[1..], &mut cos, 4)
+            .is_err()];
+        assert![CordicConfig::new(0)
+            .sincos_parallel(&thetas, &mut sin, &mut cos, 4)
+            .is_err()];
+        assert_eq![config.sincos_parallel(&[], &mut [], &mut [], 4), Ok(())];
+    }
+
+    #[test]
+    fn double_word() {
+        let worst = |config: CordicConfig| {
+            (-314..=314)
+                .map(|i| {
+                    let theta = i as f64 / 100.0;
+                    let ret = config.rotate(FixedPoint::new(theta)).unwrap();
+                    let cos = (ret.x.to_f64() - theta.cos()).abs();
+                    cos.max((ret.y.to_f64() - theta.sin()).abs())
+                })
+                .fold(0.0, f64::max)
+        };
+        let same = worst(CordicConfig::new(32).io_width(31));
+        let double = worst(CordicConfig::new(32).io_width(31).double_word(true));
+
+        // Same width loses a few bits to truncation over 32 iterations,
+        // double width only the final rounding and the last angle
+        let lsb = 0.5f64.powi(31);
+        assert![same > 4.0 * lsb];
+        assert![double < 2.0 * lsb];
+
+        // Outputs land on the Q31 grid
+        let ret = CordicConfig::new(32)
+            .io_width(31)
+            .rotate(FixedPoint::new(0.3))
+            .unwrap();
+        assert_eq![
+            ret.x.to_f64() * 2f64.powi(31),
+            (ret.x.to_f64() * 2f64.powi(31)).round()
+        ];
+
+        assert_eq![
+            CordicConfig::new(32)
+                .io_width(31)
+                .rotate(FixedPoint::new(f64::NAN)),
+            Err(CordicError::InvalidAngle("rotate"))
+        ];
+
+        let float = CordicConfig::new(32).backend(Backend::Float).io_width(31);
+        assert![float.rotate(FixedPoint::new(0.3)).is_err()];
+        assert![CordicConfig::new(32)
+            .io_width(61)
+            .rotate(FixedPoint::new(0.3))
+            .is_err()];
+    }
+
+    #[test]
+    fn non_finite_angles() {
+        for backend in [Backend::Integer, Backend::Float] {
+            let config = CordicConfig::new(32).backend(backend);
+            for config in [config, config.schedule(&[0, 1, 2, 3])] {
+                for theta in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+                    let ret = config.rotate(FixedPoint::new(theta)).unwrap();
+                    assert![ret.x.to_f64().is_nan() && ret.y.to_f64().is_nan()];
+                }
+            }
+            let nan = FixedPoint::new(f64::NAN);
+            let ret = config.vector([nan, FixedPoint::new(1.0)]).unwrap();
+            assert![ret.magnitude.to_f64().is_nan() && ret.angle.to_f64().is_nan()];
+        }
+        let [cos, sin] = sincos(FixedPoint::new(f64::NAN));
+        assert![cos.to_f64().is_nan() && sin.to_f64().is_nan()];
+    }
+
+    #[cfg(feature = "checks")]
+    #[test]
+    fn checks_pass() {
+        for backend in [Backend::Integer, Backend::Float] {
+            for iters in [1, 8, 24, MAX_ITERS] {
+                let config = CordicConfig::new(iters).backend(backend);
+                for i in -628..=628 {
+                    config.rotate(FixedPoint::new(i as f64 / 100.0)).unwrap();
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "checks")]
+    #[test]
+    fn checks_every_path() {
+        // Each path rotate() can take, which all go through the checks
+        let base = CordicConfig::new(24);
+        let configs = [
+            base.special_angles(true),
+            base.small_angle(true),
+            base.small_angle_below(6),
+            base.io_width(12),
+            base.io_width(16).double_word(true),
+            base.monotone(true),
+            base.iters(4).monotone(true),
+            base.io_width(12).monotone(true),
+            base.correctly_rounded(true),
+            base.io_width(20).correctly_rounded(true),
+        ];
+        for config in &configs {
+            for i in -628..=628 {
+                let theta = FixedPoint::new(i as f64 / 100.0);
+                config.rotate(theta).unwrap();
+                config.rotate(theta * FixedPoint::new(1e-3)).unwrap();
+            }
+        }
+    }
+
+    #[cfg(feature = "checks")]
+    #[test]
+    #[should_panic(expected = "cos^2 + sin^2 == 1")]
+    fn checks_catch_length() {
+        let short = Rotation {
+            x: FixedPoint::new(0.9),
+            y: FixedPoint::new(0.0),
+            gain: FixedPoint::new(1.0),
+        };
+        check_rotation(
+            &CordicConfig::default(),
+            FixedPoint::new(0.0),
+            &short,
+            &short,
+        );
+    }
+
+    #[cfg(feature = "checks")]
+    #[test]
+    #[should_panic(expected = "rotate(-theta) == (cos, -sin)")]
+    fn checks_catch_asymmetry() {
+        let ret = Rotation {
+            x: FixedPoint::new(0.6),
+            y: FixedPoint::new(0.8),
+            gain: FixedPoint::new(1.0),
+        };
+        check_rotation(
+            &CordicConfig::default(),
+            FixedPoint::new(0.9273),
+            &ret,
+            &ret,
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use crate::cordic::{checked_iters, gain, reduce, CordicConfig, GainCompensation, Rotation};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::integer::{self, Q60};
+use crate::log::{log, LogLevel};
+use crate::math;
+use crate::reference::{self, DoubleDouble};
+
+// CordicConfig::correctly_rounded(). cos and sin rounded to nearest in
+// the output format, i.e. what an infinitely precise implementation
+// would give, as a yardstick for the fast configs. Ziv's strategy: work
+// cos and sin out at some precision along with a bound e on the error,
+// and if everything within e of the result rounds the same way, that's
+// the answer. Otherwise the true value is too close to halfway between
+// two outputs to tell, so go again with more bits:
+//
+//   the narrow integer kernel with registers 16 bits wider than the
+//     output, then the full 60 bits (see integer::sincos_narrow)
+//   the double-double reference, about 104 bits (see src/reference.rs)
+//
+// Nearly every input settles at the first. cos and sin of a nonzero
+// binary fraction are never exactly halfway, but they can come closer
+// to it than even 104 bits can tell, in which case it gives up with
+// DomainError("correctly_rounded") rather than guess
+
+// The most fractional bits the output can have: beyond this a Q value
+// near 1 doesn't fit in an f64 and would be rounded again on the way out
+const MAX_FRAC_BITS: u32 = f64::MANTISSA_DIGITS - 1;
+
+// [cos theta, sin theta] rounded to `frac_bits` fractional bits, or to
+// f64 for None, and the precision that settled them
+pub(crate) fn sincos(theta: f64, frac_bits: Option<u32>) -> Result<([f64; 2], u32), CordicError> {
+    // Up to 2 pi short of theta in f64 per turn taken off by reduce()
+    let turns = math::ceil(theta.abs() / (2.0 * std::f64::consts::PI));
+    let reduction = turns * 2.5e-16;
+    let z = Q60::from_bits(integer::to_q60(reduce(FixedPoint::new(theta))));
+    let mut widths = vec![frac_bits.map_or(Q60::FRAC_BITS, |bits| bits + 16)];
+    widths.push(Q60::FRAC_BITS);
+    widths.dedup();
+    for bits in widths.into_iter().filter(|&bits| bits <= Q60::FRAC_BITS) {
+        let iters = (bits as usize + 2).min(integer::ANGLES.len());
+        // The angle left over, and up to an LSB of truncation per step in
+        // each register, grown by the gain, plus the angle table's rounding
+        let error = math::powi(2.0, 1 - iters as i32)
+            + 4.0 * (iters + 2) as f64 * math::powi(2.0, -(bits as i32))
+            + reduction;
+        // Past about 2^58 radians reduce() has lost every bit of the
+        // angle, and a bound of 1 or more can't settle anything anyway
+        // (it would also overflow v +- error below)
+        if error >= 1.0 {
+            continue;
+        }
+        let error = math::ceil(error * math::powi(2.0, Q60::FRAC_BITS as i32)) as i64;
+        let v = integer::sincos_narrow(z, iters, bits, bits, bits);
+        let settled = v.map(|v| {
+            let v = v.to_bits();
+            let (low, high) = (
+                round_q60(v - error, frac_bits),
+                round_q60(v + error, frac_bits),
+            );
+            (low == high).then_some(low)
+        });
+        if let [Some(cos), Some(sin)] = settled {
+            return Ok(([cos, sin], bits));
+        }
+    }
+
+    // The series are good to about 2^-104 relative, and taking multiples
+    // of pi/2 off theta loses about 2^-104 of theta
+    let reduction = if theta.abs() < std::f64::consts::FRAC_PI_4 {
+        0.0
+    } else {
+        math::powi(2.0, -100) * theta.abs()
+    };
+    let settled = reference::sincos(theta).map(|v| {
+        let error = math::powi(2.0, -96) * v.hi.abs() + reduction;
+        let low = round_double_double(v - DoubleDouble::new(error), frac_bits);
+        let high = round_double_double(v + DoubleDouble::new(error), frac_bits);
+        (low == high).then_some(low)
+    });
+    match settled {
+        [Some(cos), Some(sin)] => Ok(([cos, sin], 104)),
+        _ => Err(CordicError::DomainError("correctly_rounded")),
+    }
+}
+
+// A Q60 value rounded to nearest with `frac_bits` fractional bits, or to
+// f64 (which i64 to f64 conversion already does)
+fn round_q60(v: i64, frac_bits: Option<u32>) -> f64 {
+    let scaled = match frac_bits {
+        Some(bits) => {
+            let drop = Q60::FRAC_BITS - bits;
+            let half = if drop == 0 { 0 } else { 1 << (drop - 1) };
+            ((v + half) >> drop) << drop
+        }
+        None => v,
+    };
+    scaled as f64 * math::powi(2.0, -(Q60::FRAC_BITS as i32))
+}
+
+fn round_double_double(v: DoubleDouble, frac_bits: Option<u32>) -> f64 {
+    let bits = match frac_bits {
+        Some(bits) => bits as i32,
+        None => return v.to_f64(),
+    };
+    // Scaling by a power of two is exact, and so is what's left once the
+    // nearest integer to hi comes off
+    let scaled = v * DoubleDouble::new(math::powi(2.0, bits));
+    let n = math::round(scaled.hi);
+    let rest = (scaled - DoubleDouble::new(n)).to_f64();
+    let n = if rest >= 0.5 {
+        n + 1.0
+    } else if rest < -0.5 {
+        n - 1.0
+    } else {
+        n
+    };
+    n * math::powi(2.0, -bits)
+}
+
+impl CordicConfig {
+    pub(crate) fn rotate_correctly_rounded(
+        &self,
+        theta: FixedPoint,
+    ) -> Result<Rotation, CordicError> {
+        let kvalue = gain(checked_iters(self.iters)?)?;
+        // Like monotone(), only meaningful for the plain iterations with
+        // the gain taken out
+        if self.shifts.is_some() {
+            return Err(CordicError::DomainError("schedule"));
+        }
+        if self.compensation == GainCompensation::None {
+            return Err(CordicError::DomainError("correctly_rounded"));
+        }
+        let theta = theta.to_f64();
+        let theta = match self.io_bits {
+            Some(bits) if bits == 0 || bits > MAX_FRAC_BITS => {
+                return Err(CordicError::DomainError("correctly_rounded"));
+            }
+            Some(_) if !theta.is_finite() => return Err(CordicError::InvalidAngle("rotate")),
+            // In on the same grid as out
+            Some(bits) => {
+                math::round(theta * math::powi(2.0, bits as i32)) * math::powi(2.0, -(bits as i32))
+            }
+            None if !theta.is_finite() => {
+                return Ok(Rotation {
+                    x: FixedPoint::new(f64::NAN),
+                    y: FixedPoint::new(f64::NAN),
+                    gain: kvalue,
+                });
+            }
+            None => theta,
+        };
+        let ([x, y], bits) = sincos(theta, self.io_bits)?;
+        log(
+            LogLevel::Debug,
+            format_args!(
+                "correctly rounded: theta {} settled at {} bits",
+                theta, bits
+            ),
+        );
+        Ok(Rotation {
+            x: FixedPoint::new(x),
+            y: FixedPoint::new(y),
+            gain: kvalue,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cordic::Backend;
+
+    // Whether `got` is `exact` rounded to nearest on a 2^-bits grid
+    fn is_nearest(got: f64, exact: DoubleDouble, bits: u32) -> bool {
+        let lsb = 2f64.powi(-(bits as i32));
+        let on_grid = (got / lsb).fract() == 0.0;
+        on_grid && (DoubleDouble::new(got) - exact).to_f64().abs() <= lsb / 2.0
+    }
+
+    #[test]
+    fn small_formats() {
+        // Every Q8 to Q14 input in [-4, 4], against the double-double
+        // reference, and plain CORDIC at the same width gets some wrong
+        for bits in [8, 10, 12, 14] {
+            let config = CordicConfig::new(bits as usize + 2).io_width(bits);
+            let exact = config.correctly_rounded(true);
+            let mut misrounded = 0;
+            let scale = 2f64.powi(bits as i32);
+            for i in -(4 * scale as i64)..=4 * scale as i64 {
+                let theta = i as f64 / scale;
+                let want = reference::sincos(theta);
+                let got = exact.rotate(FixedPoint::new(theta)).unwrap();
+                assert![is_nearest(got.x.to_f64(), want[0], bits), "cos {}", theta];
+                assert![is_nearest(got.y.to_f64(), want[1], bits), "sin {}", theta];
+                let fast = config.rotate(FixedPoint::new(theta)).unwrap();
+                misrounded += (fast != got) as usize;
+            }
+            assert![misrounded > 0, "Q{}", bits];
+        }
+    }
+
+    #[test]
+    fn escalates() {
+        // Q30 needs more than the first pass now and then, and
+        // whatever settles it, the answer is the nearest
+        let mut widths = Vec::new();
+        for i in 0..20000 {
+            let theta = 1.0 + i as f64 * 2f64.powi(-30) * 997.0;
+            let (got, bits) = sincos(theta, Some(30)).unwrap();
+            let want = reference::sincos(theta);
+            assert![is_nearest(got[0], want[0], 30) && is_nearest(got[1], want[1], 30)];
+            widths.push(bits);
+        }
+        assert![widths.iter().all(|&bits| [46, 60, 104].contains(&bits))];
+        assert![widths.iter().filter(|&&bits| bits == 46).count() > 19000];
+        assert![widths.iter().any(|&bits| bits > 46)];
+
+        // To f64 it's the double-double rounded, which std nearly always
+        // agrees with
+        let mut agree = 0;
+        for i in 1..=2000 {
+            let theta = i as f64 / 300.0;
+            let (got, _) = sincos(theta, None).unwrap();
+            let want = reference::sincos(theta);
+            assert_eq![got, [want[0].to_f64(), want[1].to_f64()]];
+            agree += (got == [theta.cos(), theta.sin()]) as usize;
+        }
+        assert![agree > 1900];
+    }
+
+    #[test]
+    fn config() {
+        // The iteration count, backend and width of the registers don't
+        // matter, only the output format
+        let theta = FixedPoint::new(0.7);
+        let exact = CordicConfig::new(12).io_width(20).correctly_rounded(true);
+        let other = CordicConfig::new(40)
+            .backend(Backend::Float)
+            .io_width(20)
+            .double_word(true)
+            .monotone(true)
+            .correctly_rounded(true);
+        let sincos = |config: CordicConfig| config.rotate(theta).map(|ret| [ret.x, ret.y]);
+        assert_eq![sincos(exact), sincos(other)];
+        let ret = exact.rotate(theta).unwrap();
+        assert_eq![
+            ret.x.to_f64(),
+            (0.7f64.cos() * 1048576.0).round() / 1048576.0
+        ];
+        // Input rounds onto the same grid
+        assert_eq![
+            exact.rotate(FixedPoint::new(0.7 + 1e-9)),
+            exact.rotate(theta)
+        ];
+
+        assert_eq![
+            exact.io_width(53).rotate(theta),
+            Err(CordicError::DomainError("correctly_rounded"))
+        ];
+        assert_eq![
+            exact.iters(0).rotate(theta),
+            Err(CordicError::IterationOutOfRange(0))
+        ];
+        // Neither a schedule nor leaving the gain in means anything here
+        assert_eq![
+            exact.schedule(&[0, 1, 2]).rotate(theta),
+            Err(CordicError::DomainError("schedule"))
+        ];
+        assert_eq![
+            exact.compensate_gain(false).rotate(theta),
+            Err(CordicError::DomainError("correctly_rounded"))
+        ];
+        // Huge angles go straight to the double-double pass
+        for theta in [1e300, -1e300, f64::MAX, f64::MIN] {
+            let _ = CordicConfig::new(32).correctly_rounded(true).rotate(FixedPoint::new(theta));
+        }
+        assert_eq![
+            exact.rotate(FixedPoint::new(f64::NAN)),
+            Err(CordicError::InvalidAngle("rotate"))
+        ];
+        let full = CordicConfig::new(24).correctly_rounded(true);
+        assert![full
+            .rotate(FixedPoint::new(f64::INFINITY))
+            .unwrap()
+            .x
+            .to_f64()
+            .is_nan()];
+        let ret = full.rotate(FixedPoint::new(0.0)).unwrap();
+        assert_eq![(ret.x.to_f64(), ret.y.to_f64()), (1.0, 0.0)];
+    }
+}
@@ -0,0 +1,347 @@
+use crate::error::CordicError;
+use crate::integer::{ANGLES, GAINS, Q60};
+
+#[cfg(feature = "float")]
+use crate::fixed::FixedPoint;
+
+// Canonical signed digit form: a number as a sum of +-2^j with no two
+// neighbouring digits both nonzero. That's the fewest nonzero digits of
+// any signed binary form, about a third of the bits on average against a
+// half for plain binary, so multiplying by a constant written this way
+// takes the fewest shifted copies of the input, and so the fewest adders.
+// It's the usual way to multiply by a constant without a multiplier,
+// e.g. K for gain compensation or the taps of a fixed filter
+
+// `value`'s digits, each -1, 0 or 1, least significant first, with no
+// trailing zeros (so 0 has none). Negative values work too
+pub fn csd(value: i64) -> Vec<i8> {
+    let mut n = value as i128;
+    let mut digits = Vec::new();
+    while n != 0 {
+        // On a run of ones, 2^k - 1 = 2^k - 2^0: take -1 and carry, so
+        // the next digit is always 0
+        let digit = match n.rem_euclid(4) {
+            1 => 1,
+            3 => -1,
+            _ => 0,
+        };
+        digits.push(digit);
+        n = (n - digit as i128) >> 1;
+    }
+    digits
+}
+
+// A multiply by a fixed point constant as one shift and add (or
+// subtract) per nonzero digit of its CSD form, the way it would be wired
+// in hardware
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsdMultiplier {
+    // The constant's fractional bits, digit j is worth 2^(j - frac_bits)
+    pub frac_bits: u32,
+    pub digits: Vec<i8>,
+}
+
+impl CsdMultiplier {
+    // `constant` has `frac_bits` fractional bits (at most 62), e.g.
+    // CsdMultiplier::new(0x9b75, 16) for K ~= 0.6073 in Q16
+    pub fn new(constant: i64, frac_bits: u32) -> Result<Self, CordicError> {
+        if frac_bits > 62 {
+            return Err(CordicError::DomainError("CsdMultiplier::new"));
+        }
+        Ok(CsdMultiplier {
+            frac_bits,
+            digits: csd(constant),
+        })
+    }
+
+    // The constant back, with `frac_bits` fractional bits
+    pub fn constant(&self) -> i64 {
+        self.terms()
+            .map(|(j, digit)| digit as i128 * (1i128 << j))
+            .sum::<i128>() as i64
+    }
+
+    // Each nonzero digit's position and sign
+    fn terms(&self) -> impl Iterator<Item = (u32, i8)> + '_ {
+        self.digits
+            .iter()
+            .enumerate()
+            .filter(|(_, &digit)| digit != 0)
+            .map(|(j, &digit)| (j as u32, digit))
+    }
+
+    // Shifted copies of the input that get summed
+    pub fn shifts(&self) -> usize {
+        self.terms().count()
+    }
+
+    // Two input adders it takes to sum them
+    pub fn adders(&self) -> usize {
+        self.shifts().saturating_sub(1)
+    }
+
+    // x times the constant, in x's own format. Each copy is an arithmetic
+    // shift, so bits shifted off the bottom are floored away the same as
+    // in the CORDIC iterations, and the sum wraps like a register would
+    pub fn multiply(&self, x: i64) -> i64 {
+        self.terms().fold(0i64, |sum, (j, digit)| {
+            let copy = if j >= self.frac_bits {
+                x.wrapping_shl(j - self.frac_bits)
+            } else {
+                x >> (self.frac_bits - j).min(63)
+            };
+            if digit > 0 {
+                sum.wrapping_add(copy)
+            } else {
+                sum.wrapping_sub(copy)
+            }
+        })
+    }
+
+    // The copies, most significant first, as `term(shift)` with a sign
+    // in front, where shift is how far right (negative: left) x moves
+    fn expression(&self, term: impl Fn(i64) -> String) -> String {
+        let mut terms = self.terms().collect::<Vec<_>>();
+        terms.reverse();
+        if terms.is_empty() {
+            return "0".to_string();
+        }
+        terms
+            .iter()
+            .enumerate()
+            .map(|(i, &(j, digit))| {
+                let copy = term(self.frac_bits as i64 - j as i64);
+                match (i, digit > 0) {
+                    (0, true) => copy,
+                    (0, false) => format!("-{}", copy),
+                    (_, true) => format!(" + {}", copy),
+                    (_, false) => format!(" - {}", copy),
+                }
+            })
+            .collect()
+    }
+
+    fn describe(&self) -> String {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        format!(
+            "x * {} / 2^{} with {} shifted cop{} and {} adder{}, generated by cordic-rs",
+            self.constant(),
+            self.frac_bits,
+            self.shifts(),
+            if self.shifts() == 1 { "y" } else { "ies" },
+            self.adders(),
+            plural(self.adders())
+        )
+    }
+
+    // `pub fn name(x: i64) -> i64`, computing what multiply() does (the
+    // copies are floored the same way) but without wrapping, so it
+    // panics on overflow in debug builds like any other Rust arithmetic
+    pub fn to_rust(&self, name: &str) -> String {
+        let body = self.expression(|shift| match shift {
+            0 => "x".to_string(),
+            s if s > 0 => format!("(x >> {})", s),
+            s => format!("(x << {})", -s),
+        });
+        format!(
+            "// {}
+pub fn {}(x: i64) -> i64 {{
+    {}
+}}
+",
+            self.describe(),
+            name,
+            body
+        )
+    }
+
+    // `static inline int64_t name(int64_t x)`. C leaves right shifts of
+    // negative numbers to the compiler, every mainstream one shifts
+    // arithmetically as this assumes
+    pub fn to_c(&self, name: &str) -> String {
+        let body = self.expression(|shift| match shift {
+            0 => "x".to_string(),
+            s if s > 0 => format!("(x >> {})", s),
+            s => format!("(int64_t)((uint64_t)x << {})", -s),
+        });
+        format!(
+            "#include <stdint.h>
+
+/* {} */
+static inline int64_t {}(int64_t x) {{
+    return {};
+}}
+",
+            self.describe(),
+            name,
+            body
+        )
+    }
+
+    // A combinational Verilog module with a signed WIDTH bit input and
+    // output (`width` by default), one adder per term past the first
+    pub fn to_verilog(&self, name: &str, width: u32) -> String {
+        let body = self.expression(|shift| match shift {
+            0 => "x".to_string(),
+            s if s > 0 => format!("(x >>> {})", s),
+            s => format!("(x <<< {})", -s),
+        });
+        format!(
+            "// {}
+module {} #(parameter WIDTH = {}) (
+    input  wire signed [WIDTH-1:0] x,
+    output wire signed [WIDTH-1:0] y
+);
+    assign y = {};
+endmodule
+",
+            self.describe(),
+            name,
+            width,
+            body
+        )
+    }
+
+    // multiply() for FixedPoint, where the copies are exact scalings by
+    // powers of two and only the sums round
+    #[cfg(feature = "float")]
+    pub(crate) fn apply(&self, x: FixedPoint) -> FixedPoint {
+        self.terms().fold(FixedPoint::new(0.0), |sum, (j, digit)| {
+            let scale = crate::math::powi(2.0, j as i32 - self.frac_bits as i32);
+            let copy = FixedPoint::new(x.to_f64() * scale);
+            if digit > 0 {
+                sum + copy
+            } else {
+                sum - copy
+            }
+        })
+    }
+}
+
+// Any real constant rounded to nearest at `frac_bits` fractional bits
+// (at most 62), e.g. recode(std::f64::consts::FRAC_1_SQRT_2, 16) for
+// the 1/sqrt(2) of a radix-4 butterfly
+#[cfg(feature = "float")]
+pub fn recode(constant: f64, frac_bits: u32) -> Result<CsdMultiplier, CordicError> {
+    if !constant.is_finite() {
+        return Err(CordicError::DomainError("recode"));
+    }
+    if frac_bits > 62 {
+        return Err(CordicError::DomainError("CsdMultiplier::new"));
+    }
+    let scaled = crate::math::round(constant * crate::math::powi(2.0, frac_bits as i32));
+    // Past 2^63 the constant's integer bits don't fit next to the
+    // fraction
+    if scaled.abs() >= crate::math::powi(2.0, 63) {
+        return Err(CordicError::Overflow("recode"));
+    }
+    CsdMultiplier::new(scaled as i64, frac_bits)
+}
+
+// The CORDIC gain K at `iters` iterations rounded to nearest at
+// `frac_bits` fractional bits (1 to 60), as a CsdMultiplier. This is the
+// multiply GainCompensation::Csd does after the iterations
+pub fn gain_csd(iters: usize, frac_bits: u32) -> Result<CsdMultiplier, CordicError> {
+    if iters == 0 {
+        return Err(CordicError::IterationOutOfRange(iters));
+    }
+    if frac_bits == 0 || frac_bits > Q60::FRAC_BITS {
+        return Err(CordicError::DomainError("gain_csd"));
+    }
+    let k = GAINS[iters.min(ANGLES.len())];
+    let drop = Q60::FRAC_BITS - frac_bits;
+    let k = if drop == 0 {
+        k
+    } else {
+        (k + (1 << (drop - 1))) >> drop
+    };
+    CsdMultiplier::new(k, frac_bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recodes() {
+        assert_eq![csd(0), Vec::<i8>::new()];
+        // 7 = 8 - 1, 15 = 16 - 1, -3 = -4 + 1
+        assert_eq![csd(7), vec![-1, 0, 0, 1]];
+        assert_eq![csd(15), vec![-1, 0, 0, 0, 1]];
+        assert_eq![csd(-3), vec![1, 0, -1]];
+        for value in (-5000..5000).chain([i64::MAX, i64::MIN, i64::MIN + 1]) {
+            let digits = csd(value);
+            let sum = digits
+                .iter()
+                .enumerate()
+                .map(|(j, &digit)| digit as i128 * (1i128 << j))
+                .sum::<i128>();
+            assert_eq![sum, value as i128];
+            assert![digits.windows(2).all(|pair| pair[0] == 0 || pair[1] == 0)];
+            // Never more nonzero digits than plain binary has ones
+            let nonzero = digits.iter().filter(|&&digit| digit != 0).count() as u32;
+            assert![nonzero <= value.unsigned_abs().count_ones()];
+        }
+    }
+
+    #[test]
+    fn multiplies_by_k() {
+        assert_eq![gain_csd(0, 16), Err(CordicError::IterationOutOfRange(0))];
+        assert_eq![gain_csd(16, 0), Err(CordicError::DomainError("gain_csd"))];
+        assert_eq![
+            CsdMultiplier::new(1, 63),
+            Err(CordicError::DomainError("CsdMultiplier::new"))
+        ];
+
+        // K in Q16 is 0x9b75 = 0b1001_1011_0111_0101: ten ones, but seven
+        // CSD digits
+        let k = gain_csd(24, 16).unwrap();
+        assert_eq![k.constant(), 0x9b75];
+        assert_eq![k.shifts(), 7];
+        assert_eq![k.adders(), 6];
+        // Each floored copy is off by under a unit
+        for x in [0, 1 << 16, -(1 << 16), 12345, -99999, 1 << 40] {
+            let exact = (x as i128 * 0x9b75) >> 16;
+            let product = k.multiply(x) as i128;
+            assert![(product - exact).abs() <= k.shifts() as i128, "{}", x];
+        }
+        // More bits of K need more adders
+        let q60 = gain_csd(53, 60).unwrap();
+        assert![q60.adders() > k.adders()];
+        // One times K has nothing to floor
+        assert_eq![q60.multiply(1 << 60), q60.constant()];
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn generates_code() {
+        assert_eq![recode(0.607252935, 16), gain_csd(24, 16)];
+        assert_eq![recode(-1.5, 4).unwrap().constant(), -24];
+        assert_eq![
+            recode(f64::NAN, 16),
+            Err(CordicError::DomainError("recode"))
+        ];
+        assert_eq![recode(4.0, 61), Err(CordicError::Overflow("recode"))];
+
+        // 1.75 = 2 - 1/4: a left shift and a right one
+        let k = recode(1.75, 2).unwrap();
+        assert_eq![k.multiply(100), 175];
+        assert_eq![
+            k.to_rust("times_1_75"),
+            "// x * 7 / 2^2 with 2 shifted copies and 1 adder, generated by cordic-rs\n\
+             pub fn times_1_75(x: i64) -> i64 {\n    (x << 1) - (x >> 2)\n}\n"
+        ];
+        assert![k
+            .to_c("times_1_75")
+            .contains("return (int64_t)((uint64_t)x << 1) - (x >> 2);")];
+        let verilog = k.to_verilog("times_1_75", 18);
+        assert![verilog.contains("module times_1_75 #(parameter WIDTH = 18) (")];
+        assert![verilog.contains("    assign y = (x <<< 1) - (x >>> 2);\nendmodule")];
+
+        assert![recode(0.0, 8).unwrap().to_rust("zero").contains("    0\n")];
+        assert![recode(-1.0, 1)
+            .unwrap()
+            .to_rust("negate")
+            .contains("    -x\n")];
+    }
+}
@@ -0,0 +1,114 @@
+// The inputs that are numerically nasty for a CORDIC front end, and a
+// check of how one configuration copes with each. The micro-rotations
+// themselves only ever see [-pi/2, pi/2]; everything that goes wrong
+// goes wrong before that, in reduce() and the quadrant fold:
+//   just below a multiple of 2pi or pi/2, where rounding can push the
+//     reduced angle over the edge into the wrong quadrant
+//   tiny and subnormal angles, where theta - 2pi or theta + pi/2 rounds
+//     the angle itself away
+//   huge angles, where 2pi in f64 is off by about 2.4e-16 and so the
+//     remainder is off by that times theta / 2pi. Past about 1e15 there's
+//     no right answer left in the reduced angle at all
+//   the exact multiples of pi/2, whose results should be 0 and +-1
+// "Handled" means within the error the iteration count and output width
+// allow of f64::cos and f64::sin, which reduce exactly
+
+use crate::cordic::CordicConfig;
+use crate::fixed::FixedPoint;
+
+// One input run through one configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeCase {
+    pub name: &'static str,
+    pub theta: f64,
+    // NaN if the rotation returned an error
+    pub cos_error: f64,
+    pub sin_error: f64,
+    pub handled: bool,
+}
+
+// The inputs edge_cases() tries, named
+pub fn edge_inputs() -> Vec<(&'static str, f64)> {
+    use std::f64::consts::{FRAC_PI_2, PI};
+    let below = |x: f64| f64::from_bits(x.to_bits() - 1);
+    let two_pi = 2.0 * PI;
+    vec![
+        ("zero", 0.0),
+        ("negative zero", -0.0),
+        ("-epsilon", -f64::EPSILON),
+        ("smallest normal", f64::MIN_POSITIVE),
+        ("smallest subnormal", f64::from_bits(1)),
+        ("negative subnormal", -f64::from_bits(1)),
+        ("pi/2", FRAC_PI_2),
+        ("just below pi/2", below(FRAC_PI_2)),
+        ("pi", PI),
+        ("-pi", -PI),
+        ("3pi/2", 3.0 * FRAC_PI_2),
+        ("2pi - epsilon", below(two_pi)),
+        ("-2pi + epsilon", -below(two_pi)),
+        ("2pi", two_pi),
+        ("1000pi/2", 1000.0 * FRAC_PI_2),
+        ("1e6", 1e6),
+        ("1e15", 1e15),
+        ("1e300", 1e300),
+        ("MAX", f64::MAX),
+        ("-MAX", -f64::MAX),
+    ]
+}
+
+// Runs every edge_inputs() value through `config`. The allowance is the
+// last iteration's angle twice over plus the output's rounding, on top
+// of a few units of f64 rounding in the reduction and gain. Registers
+// only as wide as the output can also lose up to a unit to truncation
+// in every iteration
+pub fn edge_cases(config: &CordicConfig) -> Vec<EdgeCase> {
+    let iterations = 4.0 / (1u64 << config.iters.min(63)) as f64;
+    let unit = config.io_bits.map_or(0.0, |bits| 0.5f64.powi(bits as i32));
+    let registers = if config.double_word {
+        0.0
+    } else {
+        config.iters as f64 * unit
+    };
+    let allowance = iterations + unit + registers + 8.0 * f64::EPSILON;
+
+    edge_inputs()
+        .into_iter()
+        .map(|(name, theta)| {
+            let (cos_error, sin_error) = match config.rotate(FixedPoint::new(theta)) {
+                Ok(ret) => (
+                    (ret.x.to_f64() - theta.cos()).abs(),
+                    (ret.y.to_f64() - theta.sin()).abs(),
+                ),
+                Err(_) => (f64::NAN, f64::NAN),
+            };
+            EdgeCase {
+                name,
+                theta,
+                cos_error,
+                sin_error,
+                // NaN compares false, so errors aren't handled
+                handled: cos_error <= allowance && sin_error <= allowance,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cordic::Backend;
+
+    #[test]
+    fn reports_edges() {
+        for backend in [Backend::Integer, Backend::Float] {
+            let cases = edge_cases(&CordicConfig::new(53).backend(backend));
+            assert_eq![cases.len(), edge_inputs().len()];
+            // Everything up to a turn either way is fine. From a few
+            // hundred turns the error in 2pi already shows
+            for case in &cases {
+                let small = case.theta.abs() < 100.0;
+                assert_eq![case.handled, small, "{:?} {:?}", backend, case];
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(not(feature = "float"))]
+#[allow(unused_imports)]
+use crate::float_guard::{f32, f64};
+use std::error::Error;
+use std::fmt;
+
+// Everything that can go wrong when asking the library for a result.
+// Callers that just want a number can `unwrap()`, but the CLI (and
+// anything else taking user input) should report these properly
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CordicError {
+    // Zero iterations never rotates the vector at all, so the "result"
+    // would just be the initial (1, 0) regardless of theta. Also a shift
+    // amount past the precision, or a custom schedule that's empty or
+    // longer than MAX_SCHEDULE (with its length)
+    IterationOutOfRange(usize),
+    // The named function was given an angle that isn't a number at all
+    // (NaN or an infinity) where it has no NaN to give back, e.g. a Q31
+    // rotation or a sweep being analyzed
+    InvalidAngle(&'static str),
+    // The argument is outside the domain of the named function, e.g.
+    // ln(0) or acosh(0.5)
+    DomainError(&'static str),
+    // A result didn't fit the fixed point format the named function was
+    // asked to produce, and the caller asked for an error over saturating
+    // or wrapping
+    Overflow(&'static str),
+    // A binary table couldn't be generated or loaded, and why
+    InvalidTable(&'static str),
+    // An image for the rotation demo couldn't be read, and why
+    InvalidImage(&'static str),
+    // Text that was supposed to be a number wasn't, and why
+    ParseError(&'static str),
+    // A worker thread of the named function panicked, which only the
+    // `checks` feature does on purpose
+    WorkerPanicked(&'static str),
+}
+
+impl fmt::Display for CordicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CordicError::IterationOutOfRange(iters) => {
+                write!(
+                    f,
+                    "iteration count, shift or schedule length {} is out of range",
+                    iters
+                )
+            }
+            CordicError::InvalidAngle(function) => {
+                write!(f, "angle passed to {} is not finite", function)
+            }
+            CordicError::DomainError(function) => {
+                write!(f, "argument is outside the domain of {}", function)
+            }
+            CordicError::Overflow(function) => {
+                write!(f, "result of {} overflows its fixed point format", function)
+            }
+            CordicError::InvalidTable(reason) => write!(f, "invalid table: {}", reason),
+            CordicError::InvalidImage(reason) => write!(f, "invalid image: {}", reason),
+            CordicError::ParseError(reason) => write!(f, "can't parse number: {}", reason),
+            CordicError::WorkerPanicked(function) => {
+                write!(f, "a worker thread of {} panicked", function)
+            }
+        }
+    }
+}
+
+impl Error for CordicError {}
@@ -0,0 +1,229 @@
+use crate::table::crc32;
+
+// Writers for getting results into other tools. Each sweep is a set of
+// named columns of equal length, e.g. theta, the CORDIC cosine and the
+// reference cosine
+
+// Renders the columns as MATLAB/Octave row vectors, one assignment per
+// column. Values use Rust's shortest round-trip formatting so nothing
+// is lost on the way in
+pub fn matlab_arrays(columns: &[(&str, &[f64])]) -> String {
+    let mut out = String::new();
+    for (name, values) in columns {
+        out.push_str(name);
+        out.push_str(" = [");
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                // Keep lines a sane length for editors
+                out.push_str(if i % 8 == 0 { " ...\n    " } else { " " });
+            }
+            out.push_str(&matlab_number(*value));
+        }
+        out.push_str("];\n");
+    }
+    out
+}
+
+// Serializes one column as a version 1.0 NPY file of little-endian f64,
+// which `np.load` reads straight back into a 1-D array
+pub fn npy(values: &[f64]) -> Vec<u8> {
+    npy_shaped(values, &format!("({},)", values.len()))
+}
+
+// A rows x columns NPY array, `values` row after row. None if there
+// aren't exactly that many values
+pub fn npy_grid(values: &[f64], rows: usize, columns: usize) -> Option<Vec<u8>> {
+    if rows.checked_mul(columns) != Some(values.len()) {
+        return None;
+    }
+    Some(npy_shaped(values, &format!("({}, {})", rows, columns)))
+}
+
+fn npy_shaped(values: &[f64], shape: &str) -> Vec<u8> {
+    let mut header = format!(
+        "{{'descr': '<f8', 'fortran_order': False, 'shape': {}, }}",
+        shape
+    );
+    // The format wants the data to start on a 64 byte boundary, with
+    // the header padded by spaces and ended with a newline
+    let unpadded = NPY_MAGIC.len() + 4 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    let mut bytes = NPY_MAGIC.to_vec();
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    for value in values {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+// Bundles the columns into an NPZ archive, which is just a zip of one
+// `<name>.npy` per column. Entries are stored uncompressed so this only
+// needs the CRC from the table writer, `np.load` hands back a dict-like
+// object keyed by column name
+pub fn npz(columns: &[(&str, &[f64])]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut directory = Vec::new();
+    for (name, values) in columns {
+        let file_name = format!("{}.npy", name);
+        let data = npy(values);
+        let offset = bytes.len() as u32;
+
+        let mut entry = Vec::new();
+        entry.extend_from_slice(&20_u16.to_le_bytes()); // version needed
+        entry.extend_from_slice(&0_u16.to_le_bytes()); // flags
+        entry.extend_from_slice(&0_u16.to_le_bytes()); // method: stored
+        entry.extend_from_slice(&0_u16.to_le_bytes()); // time
+        entry.extend_from_slice(&ZIP_DATE.to_le_bytes());
+        entry.extend_from_slice(&crc32(&data).to_le_bytes());
+        entry.extend_from_slice(&(data.len() as u32).to_le_bytes()); // compressed
+        entry.extend_from_slice(&(data.len() as u32).to_le_bytes()); // uncompressed
+        entry.extend_from_slice(&(file_name.len() as u16).to_le_bytes());
+        entry.extend_from_slice(&0_u16.to_le_bytes()); // extra length
+
+        bytes.extend_from_slice(&0x0403_4b50_u32.to_le_bytes());
+        bytes.extend_from_slice(&entry);
+        bytes.extend_from_slice(file_name.as_bytes());
+        bytes.extend_from_slice(&data);
+
+        directory.extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+        directory.extend_from_slice(&20_u16.to_le_bytes()); // version made by
+        directory.extend_from_slice(&entry);
+        directory.extend_from_slice(&0_u16.to_le_bytes()); // comment length
+        directory.extend_from_slice(&0_u16.to_le_bytes()); // disk number
+        directory.extend_from_slice(&0_u16.to_le_bytes()); // internal attributes
+        directory.extend_from_slice(&0_u32.to_le_bytes()); // external attributes
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(file_name.as_bytes());
+    }
+
+    let directory_offset = bytes.len() as u32;
+    bytes.extend_from_slice(&directory);
+    bytes.extend_from_slice(&0x0605_4b50_u32.to_le_bytes());
+    bytes.extend_from_slice(&0_u16.to_le_bytes()); // this disk
+    bytes.extend_from_slice(&0_u16.to_le_bytes()); // directory disk
+    bytes.extend_from_slice(&(columns.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&(columns.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&directory_offset.to_le_bytes());
+    bytes.extend_from_slice(&0_u16.to_le_bytes()); // comment length
+    bytes
+}
+
+// A mono 16 bit PCM WAV file, the simplest thing every audio player
+// understands
+pub fn wav_pcm16(samples: &[i16], rate: u32) -> Vec<u8> {
+    // The size fields are 32 bits, so a longer file (or a rate of over
+    // two billion) has them saturate, as most writers do
+    let data_len = (2 * samples.len() as u64).min(u32::MAX as u64) as u32;
+    let mut bytes = b"RIFF".to_vec();
+    bytes.extend_from_slice(&data_len.saturating_add(36).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16_u32.to_le_bytes()); // fmt chunk length
+    bytes.extend_from_slice(&1_u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1_u16.to_le_bytes()); // channels
+    bytes.extend_from_slice(&rate.to_le_bytes());
+    bytes.extend_from_slice(&rate.saturating_mul(2).to_le_bytes()); // bytes per second
+    bytes.extend_from_slice(&2_u16.to_le_bytes()); // bytes per frame
+    bytes.extend_from_slice(&16_u16.to_le_bytes()); // bits per sample
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
+
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+// 1980-01-01 in DOS format, the earliest date a zip entry can carry
+const ZIP_DATE: u16 = (1 << 5) | 1;
+
+fn matlab_number(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "Inf" } else { "-Inf" }.to_string()
+    } else {
+        format!("{:?}", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matlab_layout() {
+        let script = matlab_arrays(&[("theta", &[0.0, -1.5]), ("err", &[f64::NAN, f64::INFINITY])]);
+        assert_eq![script, "theta = [0.0 -1.5];\nerr = [NaN Inf];\n"];
+    }
+
+    #[test]
+    fn matlab_wraps_long_rows() {
+        let values = [1.0; 9];
+        let script = matlab_arrays(&[("x", &values)]);
+        assert![script.contains(" ...\n    1.0];")];
+    }
+
+    #[test]
+    fn npy_layout() {
+        let bytes = npy(&[1.0, -2.5]);
+        assert_eq![&bytes[..8], b"\x93NUMPY\x01\x00"];
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq![(10 + header_len) % 64, 0];
+        let header = std::str::from_utf8(&bytes[10..10 + header_len]).unwrap();
+        assert![header.contains("'shape': (2,)")];
+        assert![header.ends_with('\n')];
+        assert_eq![
+            &bytes[10 + header_len..10 + header_len + 8],
+            &1.0_f64.to_le_bytes()
+        ];
+        assert_eq![bytes.len(), 10 + header_len + 16];
+    }
+
+    #[test]
+    fn npy_grid_shape() {
+        assert_eq![npy_grid(&[1.0; 5], 2, 3), None];
+        assert_eq![npy_grid(&[1.0; 6], usize::MAX, 2), None];
+        let bytes = npy_grid(&[1.0; 6], 2, 3).unwrap();
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq![(10 + header_len) % 64, 0];
+        let header = std::str::from_utf8(&bytes[10..10 + header_len]).unwrap();
+        assert![header.contains("'shape': (2, 3)")];
+        assert_eq![bytes.len(), 10 + header_len + 48];
+    }
+
+    #[test]
+    fn npz_directory() {
+        let bytes = npz(&[("theta", &[0.0]), ("cos", &[1.0, 0.5])]);
+        let end = bytes.len() - 22;
+        assert_eq![&bytes[end..end + 4], &0x0605_4b50_u32.to_le_bytes()];
+        assert_eq![u16::from_le_bytes([bytes[end + 10], bytes[end + 11]]), 2];
+
+        // The first entry starts the file and holds theta.npy verbatim
+        let name_len = u16::from_le_bytes([bytes[26], bytes[27]]) as usize;
+        assert_eq![&bytes[30..30 + name_len], b"theta.npy"];
+        let data = npy(&[0.0]);
+        assert_eq![&bytes[30 + name_len..30 + name_len + data.len()], &data[..]];
+    }
+
+    #[test]
+    fn wav_header() {
+        let bytes = wav_pcm16(&[0, -1, 32767], 48000);
+        assert_eq![bytes.len(), 44 + 6];
+        assert_eq![&bytes[0..4], b"RIFF"];
+        assert_eq![
+            u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            42
+        ];
+        assert_eq![
+            u32::from_le_bytes([bytes[24], bytes[25], bytes[26], bytes[27]]),
+            48000
+        ];
+        assert_eq![&bytes[44..], &[0, 0, 0xFF, 0xFF, 0xFF, 0x7F]];
+    }
+}
@@ -0,0 +1,223 @@
+use crate::cordic::{reduce, MAX_ITERS};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::integer::{fold, to_q60, ANGLES, GAINS, Q60};
+
+// Fault injection on the integer datapath, for seeing how CORDIC
+// hardware responds to a transient fault (a particle strike, a timing
+// glitch) that flips one bit of one register partway through. Runs the
+// same prescaled Q60 iterations as sincos_q60(), with the flip applied
+// to x, y or z just before the chosen iteration, and compares against
+// the fault free result.
+//
+// What to expect: the iterations after a flip in z steer towards
+// whatever angle z now says is left, so the output lands off by the
+// flipped bit's weight, as long as enough iterations remain to get
+// there. Late in the run they can't, and the damage is capped by the few
+// small angles left. A flip in x or y is carried straight through to the
+// output (rotated, and scaled by the rest of the gain). The registers
+// wrap rather than saturate, so flipping the top bits gives nonsense,
+// the same as the hardware would
+
+// Which register of the datapath a fault hits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    X,
+    Y,
+    Z,
+}
+
+// One bit flip: `bit` (0 is the least significant of the 64) of
+// `register`, just before `iteration` (0 is before the first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fault {
+    pub iteration: usize,
+    pub register: Register,
+    pub bit: u32,
+}
+
+// A rotation with a fault in it, against the same rotation without
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaultEffect {
+    pub fault: Fault,
+    pub theta: f64,
+    pub cos: f64,
+    pub sin: f64,
+    // The larger of the two outputs' distance from the fault free ones
+    pub deviation: f64,
+}
+
+// cos and sin of theta at `iters` iterations (clamped to MAX_ITERS) with
+// `fault` injected. The fault has to land within the iterations and the
+// 64 bits of a register
+pub fn inject(theta: FixedPoint, iters: usize, fault: Fault) -> Result<FaultEffect, CordicError> {
+    if iters == 0 {
+        return Err(CordicError::IterationOutOfRange(iters));
+    }
+    let iters = iters.min(MAX_ITERS);
+    if fault.iteration >= iters {
+        return Err(CordicError::IterationOutOfRange(fault.iteration));
+    }
+    if fault.bit >= 64 {
+        return Err(CordicError::DomainError("inject"));
+    }
+    if !theta.to_f64().is_finite() {
+        return Err(CordicError::InvalidAngle("inject"));
+    }
+    let z = to_q60(reduce(theta));
+    let clean = faulty_rotations(z, iters, None);
+    let faulty = faulty_rotations(z, iters, Some(fault));
+    // Differenced in Q60 before converting, so a flip in the low bits
+    // isn't lost to f64 rounding
+    let to_f64 = |x: i128| x as f64 / (1u64 << Q60::FRAC_BITS) as f64;
+    let deviation = (0..2)
+        .map(|k| to_f64((faulty[k] as i128 - clean[k] as i128).abs()))
+        .fold(0.0, f64::max);
+    Ok(FaultEffect {
+        fault,
+        theta: theta.to_f64(),
+        cos: to_f64(faulty[0] as i128),
+        sin: to_f64(faulty[1] as i128),
+        deviation,
+    })
+}
+
+// `count` faults drawn uniformly over every iteration, register and bit,
+// each on an angle drawn uniformly from [-pi, pi]. The same seed always
+// gives the same campaign
+pub fn random_faults(
+    seed: u64,
+    count: usize,
+    iters: usize,
+) -> Result<Vec<FaultEffect>, CordicError> {
+    if iters == 0 {
+        return Err(CordicError::IterationOutOfRange(iters));
+    }
+    let iters = iters.min(MAX_ITERS);
+    let mut rng = SplitMix64(seed);
+    (0..count)
+        .map(|_| {
+            let fault = Fault {
+                iteration: rng.below(iters as u64) as usize,
+                register: [Register::X, Register::Y, Register::Z][rng.below(3) as usize],
+                bit: rng.below(64) as u32,
+            };
+            let theta = std::f64::consts::PI * (2.0 * rng.unit() - 1.0);
+            inject(FixedPoint::new(theta), iters, fault)
+        })
+        .collect()
+}
+
+// sincos_q60()'s iterations with wrapping arithmetic, so a flipped top
+// bit can't overflow, and the fault (if any) applied on the way
+fn faulty_rotations(z: i64, iters: usize, fault: Option<Fault>) -> [i64; 2] {
+    let (mut z, negate) = fold(z);
+    let k = GAINS[iters];
+    let mut v = if negate { [-k, 0] } else { [k, 0] };
+    for (i, &angle) in ANGLES.iter().enumerate().take(iters) {
+        if let Some(fault) = fault.filter(|fault| fault.iteration == i) {
+            let mask = 1i64 << fault.bit;
+            match fault.register {
+                Register::X => v[0] ^= mask,
+                Register::Y => v[1] ^= mask,
+                Register::Z => z ^= mask,
+            }
+        }
+        if z >= 0 {
+            v = [v[0].wrapping_sub(v[1] >> i), v[1].wrapping_add(v[0] >> i)];
+            z = z.wrapping_sub(angle);
+        } else {
+            v = [v[0].wrapping_add(v[1] >> i), v[1].wrapping_sub(v[0] >> i)];
+            z = z.wrapping_add(angle);
+        }
+    }
+    v
+}
+
+// Sebastiano Vigna's SplitMix64, small and good enough for picking
+// faults, and the same sequence everywhere
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in 0..n, n > 0, with the (tiny) modulo bias ignored
+    pub(crate) fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    // Uniform in [0, 1)
+    pub(crate) fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integer::sincos_q60;
+
+    #[test]
+    fn flips() {
+        let theta = FixedPoint::new(0.6);
+        let fault = |iteration, register, bit| Fault {
+            iteration,
+            register,
+            bit,
+        };
+        // Without a fault the kernel is sincos_q60()
+        let clean = sincos_q60(Q60::from_bits(to_q60(theta)), 40).unwrap();
+        assert_eq![
+            faulty_rotations(to_q60(theta), 40, None),
+            clean.map(Q60::to_bits)
+        ];
+
+        // A low bit of x early on stays a low bit's worth of error
+        let effect = inject(theta, 40, fault(3, Register::X, 2)).unwrap();
+        assert![effect.deviation > 0.0 && effect.deviation < 1e-16];
+        // An early flip of 2^-10 in z turns the output by about 2^-10, a
+        // late one can only change the last few micro-rotations
+        let early = inject(theta, 40, fault(1, Register::Z, 50)).unwrap();
+        let late = inject(theta, 40, fault(36, Register::Z, 50)).unwrap();
+        assert![early.deviation > 2e-4 && early.deviation < 2e-3];
+        assert![late.deviation < 1e-10];
+        // The sign bit of y wraps around into garbage, without a panic
+        let sign = inject(theta, 40, fault(10, Register::Y, 63)).unwrap();
+        assert![sign.deviation > 1.0];
+
+        assert_eq![
+            inject(theta, 40, fault(40, Register::X, 0)),
+            Err(CordicError::IterationOutOfRange(40))
+        ];
+        assert_eq![
+            inject(theta, 40, fault(0, Register::X, 64)),
+            Err(CordicError::DomainError("inject"))
+        ];
+        assert_eq![
+            inject(FixedPoint::new(f64::NAN), 40, fault(0, Register::X, 0)),
+            Err(CordicError::InvalidAngle("inject"))
+        ];
+    }
+
+    #[test]
+    fn seeded_campaign() {
+        let campaign = random_faults(7, 200, 32).unwrap();
+        assert_eq![campaign.len(), 200];
+        assert_eq![random_faults(7, 200, 32).unwrap(), campaign];
+        assert_ne![random_faults(8, 200, 32).unwrap(), campaign];
+        for effect in &campaign {
+            assert![effect.fault.iteration < 32 && effect.fault.bit < 64];
+            assert![effect.theta.abs() <= std::f64::consts::PI];
+        }
+        assert_eq![
+            random_faults(7, 1, 0),
+            Err(CordicError::IterationOutOfRange(0))
+        ];
+    }
+}
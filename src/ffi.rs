@@ -0,0 +1,337 @@
+// C interface, for calling the Rust model from existing C firmware
+// (e.g. to cross-validate a C implementation against it while
+// migrating). Everything here only uses C-compatible types, and the
+// header can be generated with
+//
+//   cbindgen --config cbindgen.toml --output cordic.h
+//
+// Angles in the Q31 functions are fractions of pi, i.e. the full Q31
+// range [-1, 1) maps onto [-pi, pi), the same convention most fixed
+// point DSP libraries use. Each unsafe function says what it needs
+// from its pointers under "Safety:" in its comment
+
+#![allow(clippy::missing_safety_doc)]
+
+use crate::cordic::{atan2, cordic, CordicConfig};
+use crate::fixed::FixedPoint;
+use crate::iterations::Step;
+
+// Result of every call. Anything other than `CORDIC_OK` means the
+// outputs were left untouched
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CordicStatus {
+    CordicOk = 0,
+    CordicNullPointer = 1,
+    CordicInvalidIterations = 2,
+    CordicInvalidAngle = 3,
+}
+
+// One micro-rotation of `cordic_trace_f64`, see `Step` (src/iterations.rs)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CordicStep {
+    pub iteration: u32,
+    // The micro-rotation was by atan(2^-shift)
+    pub shift: u32,
+    // 1 for counterclockwise, -1 for clockwise
+    pub sigma: i32,
+    // The cosine and sine estimates so far, gain undone
+    pub cos: f64,
+    pub sin: f64,
+    // The angle still left to rotate by
+    pub z: f64,
+}
+
+fn q31_angle_to_radians(theta: i32) -> FixedPoint {
+    FixedPoint::from_q31(theta) * FixedPoint::new(std::f64::consts::PI)
+}
+
+// Computes the sine and cosine of `theta` (Q31, fraction of pi) as Q31.
+// Uses the default engine, `CordicConfig::default()`: `MAX_ITERS`
+// iterations on the Q60 integer backend
+//
+// Safety: `sin` and `cos` must each be either null or valid for
+// writing one `int32_t`
+#[no_mangle]
+pub unsafe extern "C" fn cordic_sincos_q31(
+    theta: i32,
+    sin: *mut i32,
+    cos: *mut i32,
+) -> CordicStatus {
+    if sin.is_null() || cos.is_null() {
+        return CordicStatus::CordicNullPointer;
+    }
+
+    match CordicConfig::default().rotate(q31_angle_to_radians(theta)) {
+        Ok(ret) => {
+            *cos = ret.x.to_q31();
+            *sin = ret.y.to_q31();
+            CordicStatus::CordicOk
+        }
+        // Never happens with the default's iteration count
+        Err(_) => CordicStatus::CordicInvalidIterations,
+    }
+}
+
+// Computes the sine and cosine of `theta` (radians) with `iters`
+// iterations
+//
+// Safety: `sin` and `cos` must each be either null or valid for
+// writing one `double`
+#[no_mangle]
+pub unsafe extern "C" fn cordic_sincos_f64(
+    theta: f64,
+    iters: u32,
+    sin: *mut f64,
+    cos: *mut f64,
+) -> CordicStatus {
+    if sin.is_null() || cos.is_null() {
+        return CordicStatus::CordicNullPointer;
+    }
+
+    match cordic(FixedPoint::new(theta), iters as usize) {
+        Ok(ret) => {
+            *cos = ret[0].to_f64();
+            *sin = ret[1].to_f64();
+            CordicStatus::CordicOk
+        }
+        Err(_) => CordicStatus::CordicInvalidIterations,
+    }
+}
+
+// Computes the sine and cosine of `n` angles (radians) with `iters`
+// iterations, for evaluating whole arrays in one call. The status covers
+// the arguments: once they're accepted every element is written, and an
+// angle the engine rejects gets NaN for both its sine and cosine rather
+// than failing the rest of the batch
+//
+// Safety: `thetas` must be valid for reading `n` `double`s, and `sin`
+// and `cos` must each be valid for writing `n` `double`s. Any of them
+// may be null, in which case nothing is read or written
+#[no_mangle]
+pub unsafe extern "C" fn cordic_sincos_batch_f64(
+    thetas: *const f64,
+    n: usize,
+    iters: u32,
+    sin: *mut f64,
+    cos: *mut f64,
+) -> CordicStatus {
+    if thetas.is_null() || sin.is_null() || cos.is_null() {
+        return CordicStatus::CordicNullPointer;
+    }
+    if crate::cordic::checked_iters(iters as usize).is_err() {
+        return CordicStatus::CordicInvalidIterations;
+    }
+
+    let config = CordicConfig::new(iters as usize);
+    let thetas = std::slice::from_raw_parts(thetas, n);
+    let sin = std::slice::from_raw_parts_mut(sin, n);
+    let cos = std::slice::from_raw_parts_mut(cos, n);
+    for i in 0..n {
+        let ret = config.rotate(FixedPoint::new(thetas[i]));
+        let [c, s] = ret.map_or([f64::NAN; 2], |ret| [ret.x.to_f64(), ret.y.to_f64()]);
+        cos[i] = c;
+        sin[i] = s;
+    }
+    CordicStatus::CordicOk
+}
+
+// Records the first `iters` micro-rotations of `theta` (radians), for
+// plotting convergence. `iters` runs from 1 to `MAX_ITERS`, since there
+// are no more steps than that
+//
+// Safety: `steps` must be either null or valid for writing `iters`
+// `CordicStep`s
+#[no_mangle]
+pub unsafe extern "C" fn cordic_trace_f64(
+    theta: f64,
+    iters: u32,
+    steps: *mut CordicStep,
+) -> CordicStatus {
+    if steps.is_null() {
+        return CordicStatus::CordicNullPointer;
+    }
+    if crate::cordic::checked_iters(iters as usize) != Ok(iters as usize) {
+        return CordicStatus::CordicInvalidIterations;
+    }
+    let trace = match CordicConfig::new(iters as usize).iterations(FixedPoint::new(theta)) {
+        Ok(trace) => trace,
+        Err(_) => return CordicStatus::CordicInvalidAngle,
+    };
+
+    let steps = std::slice::from_raw_parts_mut(steps, iters as usize);
+    for (out, step) in steps.iter_mut().zip(trace) {
+        *out = CordicStep::from(step);
+    }
+    CordicStatus::CordicOk
+}
+
+impl From<Step> for CordicStep {
+    fn from(step: Step) -> Self {
+        Self {
+            iteration: step.iteration as u32,
+            shift: step.shift as u32,
+            sigma: if step.counterclockwise { 1 } else { -1 },
+            cos: (step.x * step.gain).to_f64(),
+            sin: (step.y * step.gain).to_f64(),
+            z: step.z.to_f64(),
+        }
+    }
+}
+
+// Computes the angle of (`x`, `y`) as a Q31 fraction of pi. An angle of
+// exactly pi saturates to the largest Q31 value
+//
+// Safety: `angle` must be either null or valid for writing one `int32_t`
+#[no_mangle]
+pub unsafe extern "C" fn cordic_atan2_q31(y: i32, x: i32, angle: *mut i32) -> CordicStatus {
+    if angle.is_null() {
+        return CordicStatus::CordicNullPointer;
+    }
+
+    let ret = atan2(FixedPoint::from_q31(y), FixedPoint::from_q31(x));
+    *angle = (ret / FixedPoint::new(std::f64::consts::PI)).to_q31();
+    CordicStatus::CordicOk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sincos_q31() {
+        let (mut sin, mut cos) = (0, 0);
+
+        // pi/2 is 0.5 in Q31
+        let status = unsafe { cordic_sincos_q31(0x4000_0000, &mut sin, &mut cos) };
+        assert_eq![status, CordicStatus::CordicOk];
+        assert![(sin - i32::MAX).abs() <= 1];
+        assert![cos.abs() <= 1];
+
+        // -pi/6, where sin is exactly -0.5
+        let theta = FixedPoint::new(-1.0 / 6.0).to_q31();
+        unsafe { cordic_sincos_q31(theta, &mut sin, &mut cos) };
+        assert![(sin - i32::MIN / 2).abs() <= 1];
+        assert![(cos - FixedPoint::new(0.75_f64.sqrt()).to_q31()).abs() <= 1];
+    }
+
+    #[test]
+    fn sincos_f64() {
+        let (mut sin, mut cos) = (0.0, 0.0);
+        let status = unsafe { cordic_sincos_f64(1.0, 40, &mut sin, &mut cos) };
+        assert_eq![status, CordicStatus::CordicOk];
+        assert![(sin - 1.0_f64.sin()).abs() < 1e-9];
+        assert![(cos - 1.0_f64.cos()).abs() < 1e-9];
+
+        let status = unsafe { cordic_sincos_f64(1.0, 0, &mut sin, &mut cos) };
+        assert_eq![status, CordicStatus::CordicInvalidIterations];
+    }
+
+    #[test]
+    fn sincos_batch_f64() {
+        let thetas = [-2.0, -0.5, 0.0, 0.5, 2.0];
+        let mut sin = [0.0; 5];
+        let mut cos = [0.0; 5];
+        let status = unsafe {
+            cordic_sincos_batch_f64(thetas.as_ptr(), 5, 40, sin.as_mut_ptr(), cos.as_mut_ptr())
+        };
+
+        assert_eq![status, CordicStatus::CordicOk];
+        for i in 0..5 {
+            assert![(sin[i] - thetas[i].sin()).abs() < 1e-9];
+            assert![(cos[i] - thetas[i].cos()).abs() < 1e-9];
+        }
+
+        // Every element is written, with NaN where there's no answer
+        let thetas = [0.5, f64::NAN, f64::INFINITY, -0.5];
+        let (mut sin, mut cos) = ([7.0; 4], [7.0; 4]);
+        let status = unsafe {
+            cordic_sincos_batch_f64(thetas.as_ptr(), 4, 40, sin.as_mut_ptr(), cos.as_mut_ptr())
+        };
+        assert_eq![status, CordicStatus::CordicOk];
+        assert![sin[1].is_nan() && cos[1].is_nan() && sin[2].is_nan() && cos[2].is_nan()];
+        assert![(sin[3] + 0.5f64.sin()).abs() < 1e-9];
+
+        // Nothing is written when the arguments are rejected
+        let (mut sin, mut cos) = ([7.0; 4], [7.0; 4]);
+        let status = unsafe {
+            cordic_sincos_batch_f64(thetas.as_ptr(), 4, 0, sin.as_mut_ptr(), cos.as_mut_ptr())
+        };
+        assert_eq![status, CordicStatus::CordicInvalidIterations];
+        assert_eq![(sin, cos), ([7.0; 4], [7.0; 4])];
+    }
+
+    #[test]
+    fn trace_f64() {
+        let mut steps = [CordicStep {
+            iteration: 0,
+            shift: 0,
+            sigma: 0,
+            cos: 0.0,
+            sin: 0.0,
+            z: 0.0,
+        }; 24];
+        let status = unsafe { cordic_trace_f64(1.0, 24, steps.as_mut_ptr()) };
+        assert_eq![status, CordicStatus::CordicOk];
+        // The same steps as the library's, ending on what rotate() gives
+        let config = CordicConfig::new(24);
+        let want = config.iterations(FixedPoint::new(1.0)).unwrap();
+        assert![steps
+            .iter()
+            .zip(want)
+            .all(|(&got, step)| got == step.into())];
+        assert_eq![(steps[0].iteration, steps[0].sigma), (0, 1)];
+        let last = steps[23];
+        let ret = config.rotate(FixedPoint::new(1.0)).unwrap();
+        assert![(last.cos - ret.x.to_f64()).abs() < 1e-15];
+        assert![(last.sin - ret.y.to_f64()).abs() < 1e-15];
+
+        unsafe {
+            assert_eq![
+                cordic_trace_f64(f64::NAN, 24, steps.as_mut_ptr()),
+                CordicStatus::CordicInvalidAngle
+            ];
+            assert_eq![
+                cordic_trace_f64(1.0, 0, steps.as_mut_ptr()),
+                CordicStatus::CordicInvalidIterations
+            ];
+            assert_eq![
+                cordic_trace_f64(1.0, 24, std::ptr::null_mut()),
+                CordicStatus::CordicNullPointer
+            ];
+        }
+    }
+
+    #[test]
+    fn atan2_q31() {
+        let mut angle = 0;
+        let status = unsafe { cordic_atan2_q31(0x1000_0000, 0x1000_0000, &mut angle) };
+        assert_eq![status, CordicStatus::CordicOk];
+        // pi/4 is 0.25 in Q31
+        assert![(angle - 0x2000_0000).abs() <= 1];
+
+        unsafe { cordic_atan2_q31(0, -1, &mut angle) };
+        assert_eq![angle, i32::MAX];
+    }
+
+    #[test]
+    fn null_pointers() {
+        let mut out = 0;
+        let null = std::ptr::null_mut();
+        unsafe {
+            assert_eq![
+                cordic_sincos_q31(0, null, &mut out),
+                CordicStatus::CordicNullPointer
+            ];
+            assert_eq![
+                cordic_sincos_q31(0, &mut out, null),
+                CordicStatus::CordicNullPointer
+            ];
+            assert_eq![
+                cordic_atan2_q31(0, 1, null),
+                CordicStatus::CordicNullPointer
+            ];
+        }
+    }
+}
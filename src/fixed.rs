@@ -0,0 +1,364 @@
+use crate::math;
+use std::cmp::{Ordering, PartialEq, PartialOrd};
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+
+// FixedPoint actually wraps floating point numbers, so currently there
+// isn't a difference, but this is an opportunity to come up with your own
+// real number representation. External libraries do exist that can
+// handle this
+//
+// The arithmetic is marked #[inline]: each operation is a single
+// instruction, and without the hint a caller in another crate (the CLI,
+// or anyone using the library without LTO) pays a function call for it
+pub struct FixedPoint {
+    val: f64,
+}
+
+impl FixedPoint {
+    // Number of bits of precision the representation carries. Once a
+    // CORDIC iteration shifts by more than this, the micro-rotation is
+    // below the resolution of the number and has no effect
+    pub const PRECISION_BITS: usize = f64::MANTISSA_DIGITS as usize;
+
+    #[inline]
+    pub const fn new(val: f64) -> Self {
+        Self { val }
+    }
+
+    #[inline]
+    pub fn to_f64(self) -> f64 {
+        self.val
+    }
+
+    // Q31 is the usual 32 bit DSP format: a sign bit and 31 fractional
+    // bits, covering [-1, 1). Converting to it rounds to nearest and
+    // saturates, so 1.0 comes out as 0x7FFFFFFF
+    pub fn from_q31(bits: i32) -> Self {
+        Self {
+            val: bits as f64 / math::powi(2.0, 31),
+        }
+    }
+
+    pub fn to_q31(self) -> i32 {
+        math::round(self.val * math::powi(2.0, 31)) as i32
+    }
+
+    // Q15 is the 16 bit version of Q31, with the same rounding and
+    // saturation
+    pub fn from_q15(bits: i16) -> Self {
+        Self {
+            val: bits as f64 / math::powi(2.0, 15),
+        }
+    }
+
+    pub fn to_q15(self) -> i16 {
+        math::round(self.val * math::powi(2.0, 15)) as i16
+    }
+
+    // True for negative numbers, including -0.0
+    #[inline]
+    pub fn is_sign_negative(self) -> bool {
+        self.val.is_sign_negative()
+    }
+
+    pub fn abs(self) -> Self {
+        Self {
+            val: self.val.abs(),
+        }
+    }
+
+    // The rest of this block and the trait impls below follow what
+    // num-traits and std expect of a number type (Zero, One, Signed,
+    // FromPrimitive, ToPrimitive, the assigning operators, Sum and
+    // Product), so FixedPoint drops into generic numeric code. The
+    // num-traits impls themselves are in src/num_interop.rs, behind
+    // `--features num-traits`
+    pub const ZERO: FixedPoint = FixedPoint { val: 0.0 };
+    pub const ONE: FixedPoint = FixedPoint { val: 1.0 };
+
+    pub fn is_zero(self) -> bool {
+        self.val == 0.0
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.val > 0.0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.val < 0.0
+    }
+
+    // -1, 0 or 1, with 0 for both zeros (unlike f64::signum)
+    pub fn signum(self) -> Self {
+        if self.is_positive() {
+            Self::ONE
+        } else if self.is_negative() {
+            -Self::ONE
+        } else {
+            Self::ZERO
+        }
+    }
+
+    // None if `n` can't be held exactly
+    pub fn from_i64(n: i64) -> Option<Self> {
+        let val = n as f64;
+        if val as i128 == n as i128 {
+            Some(Self { val })
+        } else {
+            None
+        }
+    }
+
+    // Rounds toward zero like `as`, but None for NaN, infinities and
+    // anything out of range instead of saturating
+    pub fn to_i64(self) -> Option<i64> {
+        let truncated = math::trunc(self.val);
+        if (-9_223_372_036_854_775_808.0..9_223_372_036_854_775_808.0).contains(&truncated) {
+            Some(truncated as i64)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for FixedPoint {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl From<f64> for FixedPoint {
+    fn from(val: f64) -> Self {
+        Self::new(val)
+    }
+}
+
+// Every i32 fits exactly
+impl From<i32> for FixedPoint {
+    fn from(n: i32) -> Self {
+        Self::new(n as f64)
+    }
+}
+
+impl Copy for FixedPoint {}
+
+impl Clone for FixedPoint {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Add for FixedPoint {
+    type Output = Self;
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        Self {
+            val: self.val + other.val,
+        }
+    }
+}
+
+impl Sub for FixedPoint {
+    type Output = Self;
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        Self {
+            val: self.val - other.val,
+        }
+    }
+}
+
+impl Mul for FixedPoint {
+    type Output = Self;
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        Self {
+            val: self.val * other.val,
+        }
+    }
+}
+
+impl Div for FixedPoint {
+    type Output = Self;
+    #[inline]
+    fn div(self, other: Self) -> Self {
+        Self {
+            val: self.val / other.val,
+        }
+    }
+}
+
+impl Neg for FixedPoint {
+    type Output = Self;
+    #[inline]
+    fn neg(self) -> Self {
+        Self { val: -self.val }
+    }
+}
+
+impl Rem for FixedPoint {
+    type Output = Self;
+    fn rem(self, modulus: FixedPoint) -> Self {
+        Self {
+            val: self.val.rem(modulus.val),
+        }
+    }
+}
+
+impl AddAssign for FixedPoint {
+    #[inline]
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for FixedPoint {
+    #[inline]
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for FixedPoint {
+    #[inline]
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl DivAssign for FixedPoint {
+    #[inline]
+    fn div_assign(&mut self, other: Self) {
+        *self = *self / other;
+    }
+}
+
+impl RemAssign for FixedPoint {
+    fn rem_assign(&mut self, other: Self) {
+        *self = *self % other;
+    }
+}
+
+impl Sum for FixedPoint {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |total, x| total + x)
+    }
+}
+
+impl Product for FixedPoint {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |total, x| total * x)
+    }
+}
+
+impl PartialOrd for FixedPoint {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.val.partial_cmp(&other.val)
+    }
+}
+
+impl PartialEq for FixedPoint {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.val == other.val
+    }
+}
+
+impl fmt::Debug for FixedPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FixedPoint({})", self.val)
+    }
+}
+
+impl fmt::Display for FixedPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn q31_round_trip() {
+        for bits in [i32::MIN, -12345678, -1, 0, 1, 0x4000_0000, i32::MAX] {
+            assert_eq![FixedPoint::from_q31(bits).to_q31(), bits];
+        }
+        assert_eq![FixedPoint::new(0.5).to_q31(), 0x4000_0000];
+        assert_eq![FixedPoint::new(-1.0).to_q31(), i32::MIN];
+
+        // Saturates instead of wrapping around
+        assert_eq![FixedPoint::new(1.0).to_q31(), i32::MAX];
+        assert_eq![FixedPoint::new(-3.0).to_q31(), i32::MIN];
+    }
+
+    #[test]
+    fn q15_round_trip() {
+        for bits in [i16::MIN, -1234, -1, 0, 1, 0x4000, i16::MAX] {
+            assert_eq![FixedPoint::from_q15(bits).to_q15(), bits];
+        }
+        assert_eq![FixedPoint::new(1.0).to_q15(), i16::MAX];
+        assert_eq![FixedPoint::new(-1.0).to_q15(), i16::MIN];
+    }
+
+    // Written against std traits only, the way generic DSP code would be
+    fn dot<T>(a: &[T], b: &[T]) -> T
+    where
+        T: Copy + Default + AddAssign + Mul<Output = T>,
+    {
+        let mut total = T::default();
+        for (&x, &y) in a.iter().zip(b) {
+            total += x * y;
+        }
+        total
+    }
+
+    #[test]
+    fn generic() {
+        let a = [1, 2, 3].map(FixedPoint::from);
+        let b = [0.5, -1.0, 2.0].map(FixedPoint::from);
+        assert_eq![dot(&a, &b), FixedPoint::new(4.5)];
+        assert_eq![a.iter().copied().sum::<FixedPoint>(), FixedPoint::new(6.0)];
+        assert_eq![
+            a.iter().copied().product::<FixedPoint>(),
+            FixedPoint::new(6.0)
+        ];
+
+        let mut x = FixedPoint::ONE;
+        x -= FixedPoint::new(3.0);
+        x *= FixedPoint::new(2.0);
+        x /= FixedPoint::new(8.0);
+        assert_eq![x, FixedPoint::new(-0.5)];
+        x %= FixedPoint::new(0.3);
+        assert![(x - FixedPoint::new(-0.2)).abs() < FixedPoint::new(1e-15)];
+    }
+
+    #[test]
+    fn number_traits() {
+        assert![FixedPoint::default().is_zero()];
+        assert![FixedPoint::new(-0.0).is_zero()];
+        assert_eq![FixedPoint::new(-3.5).signum(), -FixedPoint::ONE];
+        assert_eq![FixedPoint::new(-0.0).signum(), FixedPoint::ZERO];
+        assert![FixedPoint::new(2.0).is_positive() && !FixedPoint::ZERO.is_positive()];
+
+        assert_eq![
+            FixedPoint::from_i64(1 << 53),
+            Some(FixedPoint::new(9007199254740992.0))
+        ];
+        assert_eq![FixedPoint::from_i64((1 << 53) + 1), None];
+        assert_eq![FixedPoint::new(-2.9).to_i64(), Some(-2)];
+        assert_eq![
+            FixedPoint::new(-9223372036854775808.0).to_i64(),
+            Some(i64::MIN)
+        ];
+        assert_eq![FixedPoint::new(9223372036854775808.0).to_i64(), None];
+        assert_eq![FixedPoint::new(f64::NAN).to_i64(), None];
+    }
+}
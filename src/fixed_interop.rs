@@ -0,0 +1,180 @@
+use crate::cordic::{atan2, CordicConfig};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::trig::FixedTrig;
+use fixed::types::extra::{LeEqU128, LeEqU16, LeEqU32, LeEqU64, LeEqU8};
+use fixed::{FixedI128, FixedI16, FixedI32, FixedI64, FixedI8};
+use std::convert::TryFrom;
+
+// Interop with the `fixed` crate, behind `--features fixed`, for
+// projects that already keep their numbers in I16F16 and friends:
+//
+//   let theta = I16F16::from_num(0.5);
+//   let x = FixedPoint::from(theta);
+//   let (sin, cos) = theta.sin_cos();
+//   let y = I16F16::try_from(x.abs())?;
+//
+// Into FixedPoint is exact for anything up to 53 bits wide, i.e. every
+// FixedI8, FixedI16 and FixedI32, and rounds to nearest beyond that. Back
+// the other way rounds to nearest, ties to even, and is a TryFrom: NaN
+// or a value out of the type's range is CordicError::Overflow("fixed").
+// With the same number of fractional bits on both sides a value survives
+// the round trip bit for bit. QFormat (src/qformat.rs) does the same for
+// raw bits, without the feature
+//
+// FixedTrig runs the engine on the fixed types directly: the angle goes
+// in through FixedPoint and the results come back in the same type. The
+// _cordic methods report results the type can't hold (tan near pi/2, or
+// a type with fewer than two integer bits holding cos 0 = 1) as
+// CordicError::Overflow("fixed"), and the plain ones saturate
+
+// Clamped to the type's range. Nothing here gives NaN for a finite
+// angle, but it would come out as 0 rather than panicking in
+// saturating_from_num
+trait Saturate {
+    fn saturate(x: FixedPoint) -> Self;
+}
+
+macro_rules! fixed_interop {
+    ($($fixed:ident: $frac:ident),*) => {
+        $(
+            impl<Frac: $frac> From<$fixed<Frac>> for FixedPoint {
+                fn from(x: $fixed<Frac>) -> Self {
+                    FixedPoint::new(x.to_num::<f64>())
+                }
+            }
+
+            impl<Frac: $frac> TryFrom<FixedPoint> for $fixed<Frac> {
+                type Error = CordicError;
+
+                fn try_from(x: FixedPoint) -> Result<Self, CordicError> {
+                    $fixed::<Frac>::checked_from_num(x.to_f64())
+                        .ok_or(CordicError::Overflow("fixed"))
+                }
+            }
+
+            impl<Frac: $frac> FixedTrig for $fixed<Frac> {
+                fn sin_cos_cordic(self, iters: usize) -> Result<(Self, Self), CordicError> {
+                    let ret = CordicConfig::new(iters).rotate(self.into())?;
+                    Ok((Self::try_from(ret.y)?, Self::try_from(ret.x)?))
+                }
+
+                fn tan_cordic(self, iters: usize) -> Result<Self, CordicError> {
+                    let (sin, cos) = FixedPoint::from(self).sin_cos_cordic(iters)?;
+                    Self::try_from(sin / cos)
+                }
+
+                fn sin_cos(self) -> (Self, Self) {
+                    let (sin, cos) = FixedPoint::from(self).sin_cos();
+                    (Self::saturate(sin), Self::saturate(cos))
+                }
+
+                fn tan(self) -> Self {
+                    Self::saturate(FixedPoint::from(self).tan())
+                }
+
+                fn atan2(self, other: Self) -> Self {
+                    Self::saturate(atan2(self.into(), other.into()))
+                }
+            }
+
+            impl<Frac: $frac> Saturate for $fixed<Frac> {
+                fn saturate(x: FixedPoint) -> Self {
+                    match x.to_f64() {
+                        x if x.is_nan() => Self::ZERO,
+                        x => Self::saturating_from_num(x),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+fixed_interop!(
+    FixedI8: LeEqU8,
+    FixedI16: LeEqU16,
+    FixedI32: LeEqU32,
+    FixedI64: LeEqU64,
+    FixedI128: LeEqU128
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cordic::MAX_ITERS;
+    use fixed::types::{I16F16, I1F15, I32F32, I64F0};
+
+    #[test]
+    fn conversions() {
+        // Bit for bit through FixedPoint and back
+        for bits in [i32::MIN, -0x1_8000, -1, 0, 1, 0x1_8000, i32::MAX] {
+            let x = I16F16::from_bits(bits);
+            assert_eq![I16F16::try_from(FixedPoint::from(x)), Ok(x)];
+        }
+        assert_eq![
+            FixedPoint::from(I16F16::from_num(1.5)),
+            FixedPoint::new(1.5)
+        ];
+        assert_eq![
+            FixedPoint::from(I32F32::from_bits(i64::MIN)),
+            FixedPoint::new(-2147483648.0)
+        ];
+        // Past 53 bits it rounds
+        let big = I64F0::from_bits((1 << 60) + 1);
+        assert_eq![FixedPoint::from(big), FixedPoint::new(2f64.powi(60))];
+
+        // Nearest, ties to even, on the way back
+        let lsb = 2f64.powi(-16);
+        let back = |x: f64| I16F16::try_from(FixedPoint::new(x)).map(I16F16::to_bits);
+        assert_eq![back(lsb / 2.0), Ok(0)];
+        assert_eq![back(1.5 * lsb), Ok(2)];
+        assert_eq![back(0.6 * lsb), Ok(1)];
+        assert_eq![back(-1.4 * lsb), Ok(-1)];
+
+        assert_eq![
+            I1F15::try_from(FixedPoint::new(1.0)),
+            Err(CordicError::Overflow("fixed"))
+        ];
+        assert_eq![
+            I16F16::try_from(FixedPoint::new(f64::NAN)),
+            Err(CordicError::Overflow("fixed"))
+        ];
+        assert_eq![
+            I1F15::try_from(FixedPoint::new(-1.0)),
+            Ok(I1F15::from_num(-1))
+        ];
+    }
+
+    #[test]
+    fn trig() {
+        let lsb = 2f64.powi(-16);
+        for i in -40..=40 {
+            let theta = I16F16::from_num(i as f64 / 10.0);
+            let exact = theta.to_num::<f64>();
+            let (sin, cos) = theta.sin_cos();
+            assert![(sin.to_num::<f64>() - exact.sin()).abs() <= lsb / 2.0];
+            assert![(cos.to_num::<f64>() - exact.cos()).abs() <= lsb / 2.0];
+            assert_eq![theta.sin_cos_cordic(MAX_ITERS), Ok((sin, cos))];
+            assert_eq![theta.sin(), sin];
+        }
+        let one = I16F16::from_num(1);
+        assert![(one.atan2(one).to_num::<f64>() - std::f64::consts::FRAC_PI_4).abs() <= lsb];
+        assert![(one.tan().to_num::<f64>() - 1f64.tan()).abs() <= lsb];
+        assert_eq![
+            one.sin_cos_cordic(0),
+            Err(CordicError::IterationOutOfRange(0))
+        ];
+
+        // cos 0 = 1 doesn't fit in I1F15, which saturates or reports it
+        let zero = I1F15::ZERO;
+        assert_eq![zero.cos(), I1F15::MAX];
+        assert_eq![zero.cos_cordic(24), Err(CordicError::Overflow("fixed"))];
+        // Neither does tan at the I16F16 just past pi/2
+        let quarter_turn = I16F16::from_num(std::f64::consts::FRAC_PI_2);
+        assert_eq![quarter_turn.tan(), I16F16::MIN];
+        assert_eq![
+            quarter_turn.tan_cordic(MAX_ITERS),
+            Err(CordicError::Overflow("fixed"))
+        ];
+    }
+}
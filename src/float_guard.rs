@@ -0,0 +1,13 @@
+// Compile-time check for builds without the `float` feature. The
+// modules that are still built then import these in place of the primitive f32 and
+// f64, so naming a float type in them (`as f64`, `f64::NAN`, a float
+// parameter...) stops the build right there, on any machine, instead of
+// waiting for someone to link for a soft-float target and notice.
+// Float literals and arithmetic are caught by the crate-wide
+// clippy::float_arithmetic deny in lib.rs
+
+#[allow(dead_code, non_camel_case_types)]
+pub(crate) enum f32 {}
+
+#[allow(dead_code, non_camel_case_types)]
+pub(crate) enum f64 {}
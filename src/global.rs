@@ -0,0 +1,92 @@
+// A ready made configuration for code that just wants a sine and a
+// cosine, without choosing an iteration count, a backend or a width:
+//
+//   let [cos, sin] = cordic_rs::sincos(1.0);
+//
+// The default is 32 iterations of the integer backend with Q31 in and
+// out, which is what a typical fixed point DSP would run, with the
+// registers at double width so the only rounding is the final one (see
+// CordicConfig::double_word). Plain Q31 registers are off by a couple of
+// bits even at zero, which is a poor first impression. Anything more
+// specific goes through CordicConfig directly, as before.
+//
+// The default is built on first use and fixed from then on. A program
+// that wants a different one calls set_default() before anything asks
+// for it
+
+use crate::cordic::CordicConfig;
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use std::sync::OnceLock;
+
+static DEFAULT: OnceLock<CordicConfig> = OnceLock::new();
+
+// The configuration sincos() uses, building the default if nothing has
+// set one yet
+pub fn default_config() -> &'static CordicConfig {
+    DEFAULT.get_or_init(|| CordicConfig::new(32).io_width(31).double_word(true))
+}
+
+// Replaces the default before its first use. The config is tried first
+// on 0, which some configs answer without iterating, and on 1, which
+// goes through the iterations, so one that can't rotate is turned away
+// here rather than failing inside sincos() later. Too late (sincos() or
+// default_config() already ran, or this was already called) is a
+// DomainError
+pub fn set_default(config: CordicConfig) -> Result<(), CordicError> {
+    for theta in [0.0, 1.0] {
+        config.rotate(FixedPoint::new(theta))?;
+    }
+    DEFAULT
+        .set(config)
+        .map_err(|_| CordicError::DomainError("set_default"))
+}
+
+// [cos theta, sin theta] with the default configuration. Like f64::sin
+// and f64::cos, NaN and infinities give NaN, and so would an error from
+// the config, though set_default() only takes one that rotates
+pub fn sincos(theta: f64) -> [f64; 2] {
+    if !theta.is_finite() {
+        return [f64::NAN; 2];
+    }
+    match default_config().rotate(FixedPoint::new(theta)) {
+        Ok(ret) => [ret.x.to_f64(), ret.y.to_f64()],
+        Err(_) => [f64::NAN; 2],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The default is process wide, so everything touching it is in the
+    // one test to keep the order fixed
+    #[test]
+    fn default_engine() {
+        assert_eq![
+            set_default(CordicConfig::new(0)),
+            Err(CordicError::IterationOutOfRange(0))
+        ];
+        assert![set_default(CordicConfig::new(32).schedule(&[])).is_err()];
+
+        assert_eq![sincos(0.0), [1.0, 0.0]];
+        for i in -100..=100 {
+            let theta = i as f64 * 0.07;
+            let [cos, sin] = sincos(theta);
+            assert![(cos - theta.cos()).abs() < 2.0 * 0.5f64.powi(31)];
+            assert![(sin - theta.sin()).abs() < 2.0 * 0.5f64.powi(31)];
+        }
+        assert![sincos(f64::NAN).iter().all(|x| x.is_nan())];
+        assert![sincos(f64::NEG_INFINITY).iter().all(|x| x.is_nan())];
+
+        // Fixed once it's been used
+        assert_eq![
+            *default_config(),
+            CordicConfig::new(32).io_width(31).double_word(true)
+        ];
+        assert_eq![
+            set_default(CordicConfig::new(16)),
+            Err(CordicError::DomainError("set_default"))
+        ];
+    }
+}
@@ -0,0 +1,186 @@
+use crate::cordic::{CordicConfig, MAX_ITERS};
+use crate::error::CordicError;
+use crate::export::npy_grid;
+use crate::fixed::FixedPoint;
+
+// Error as a function of both the angle and the iteration count, the
+// picture behind "CORDIC gets a bit per iteration". Each row is one
+// iteration count and each column one angle across a turn, so reading
+// down a column shows the error halving (roughly) at each step, and
+// reading along a row shows which angles the last micro-rotation happens
+// to land close to. The value is the worse of the cosine and sine errors
+// against f64
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    // The columns, evenly spaced over [-pi, pi]
+    pub thetas: Vec<f64>,
+    // The rows, 1 to the iteration count asked for
+    pub iters: Vec<usize>,
+    // Row after row, so errors[row * thetas.len() + column]
+    pub errors: Vec<f64>,
+}
+
+// Runs `config` at every iteration count from 1 to `max_iters` (clamped
+// to MAX_ITERS) over `points` angles. Everything else about `config`,
+// the backend, widths and so on, is kept
+pub fn heatmap(
+    config: &CordicConfig,
+    points: usize,
+    max_iters: usize,
+) -> Result<Heatmap, CordicError> {
+    if points < 2 {
+        return Err(CordicError::DomainError("heatmap"));
+    }
+    let pi = std::f64::consts::PI;
+    let thetas = (0..points)
+        .map(|i| -pi + 2.0 * pi * i as f64 / (points - 1) as f64)
+        .collect::<Vec<_>>();
+    let iters = (1..=max_iters.min(MAX_ITERS)).collect::<Vec<_>>();
+    if iters.is_empty() {
+        return Err(CordicError::IterationOutOfRange(max_iters));
+    }
+    let mut errors = Vec::with_capacity(iters.len() * points);
+    for &n in &iters {
+        let config = CordicConfig {
+            iters: n,
+            ..*config
+        };
+        for &theta in &thetas {
+            let ret = config.rotate(FixedPoint::new(theta))?;
+            let cos_error = (ret.x.to_f64() - theta.cos()).abs();
+            let sin_error = (ret.y.to_f64() - theta.sin()).abs();
+            errors.push(cos_error.max(sin_error));
+        }
+    }
+    Ok(Heatmap {
+        thetas,
+        iters,
+        errors,
+    })
+}
+
+impl Heatmap {
+    fn row(&self, row: usize) -> &[f64] {
+        let width = self.thetas.len();
+        &self.errors[row * width..(row + 1) * width]
+    }
+
+    // One line per iteration count, headed by the angles
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("Iterations");
+        for theta in &self.thetas {
+            out.push_str(&format!(",{}", theta));
+        }
+        out.push('\n');
+        for (row, n) in self.iters.iter().enumerate() {
+            out.push_str(&n.to_string());
+            for error in self.row(row) {
+                out.push_str(&format!(",{:e}", error));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    // The errors as an iterations x angles array, e.g. for
+    // plt.imshow(np.log2(np.load("heatmap.npy"))). The axes are the
+    // ones above, so they aren't stored
+    pub fn to_npy(&self) -> Vec<u8> {
+        // The sizes always match, heatmap() builds them that way
+        npy_grid(&self.errors, self.iters.len(), self.thetas.len()).unwrap_or_default()
+    }
+
+    // A standalone SVG image, one cell per value with iterations going
+    // down. Colour is the number of correct bits, -log2(error), from dark
+    // blue at none to yellow at 53 or more, so a bit per iteration shows
+    // up as an even ramp from top to bottom
+    pub fn to_svg(&self) -> String {
+        const LEFT: usize = 40;
+        const TOP: usize = 20;
+        const CELL_HEIGHT: usize = 8;
+        let cell_width = (800 / self.thetas.len()).max(1);
+        let width = LEFT + cell_width * self.thetas.len() + 10;
+        let height = TOP + CELL_HEIGHT * self.iters.len() + 30;
+
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\" font-size=\"10\">\n",
+            width, height
+        );
+        out.push_str(&format!(
+            "<text x=\"{}\" y=\"12\">CORDIC error in bits against f64, theta across, iterations down</text>\n",
+            LEFT
+        ));
+        for (row, n) in self.iters.iter().enumerate() {
+            let y = TOP + row * CELL_HEIGHT;
+            for (column, error) in self.row(row).iter().enumerate() {
+                let bits = if *error > 0.0 {
+                    (-error.log2()).clamp(0.0, 53.0)
+                } else {
+                    53.0
+                };
+                out.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"hsl({:.0},80%,{:.0}%)\"/>\n",
+                    LEFT + column * cell_width,
+                    y,
+                    cell_width,
+                    CELL_HEIGHT,
+                    240.0 - 180.0 * bits / 53.0,
+                    25.0 + 30.0 * bits / 53.0
+                ));
+            }
+            if n % 8 == 0 || *n == 1 {
+                out.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n",
+                    LEFT - 4,
+                    y + CELL_HEIGHT,
+                    n
+                ));
+            }
+        }
+        let bottom = TOP + CELL_HEIGHT * self.iters.len() + 14;
+        let right = LEFT + cell_width * self.thetas.len();
+        for (x, label) in [(LEFT, "-pi"), ((LEFT + right) / 2, "0"), (right, "pi")] {
+            out.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>\n",
+                x, bottom, label
+            ));
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converges_down_the_rows() {
+        assert_eq![
+            heatmap(&CordicConfig::new(24), 1, 24),
+            Err(CordicError::DomainError("heatmap"))
+        ];
+        assert_eq![
+            heatmap(&CordicConfig::new(24), 16, 0),
+            Err(CordicError::IterationOutOfRange(0))
+        ];
+
+        let map = heatmap(&CordicConfig::new(24), 65, 40).unwrap();
+        assert_eq![map.thetas.len(), 65];
+        assert_eq![map.iters, (1..=40).collect::<Vec<_>>()];
+        assert_eq![map.errors.len(), 65 * 40];
+        // The worst error in each row is within the last micro-rotation's
+        // angle, about 2^-(n - 1), all the way down
+        for (row, &n) in map.iters.iter().enumerate() {
+            let worst = map.row(row).iter().cloned().fold(0.0, f64::max);
+            assert![worst < 2.0 * 0.5f64.powi(n as i32 - 1), "{} {}", n, worst];
+        }
+
+        let csv = map.to_csv();
+        assert_eq![csv.lines().count(), 41];
+        assert![csv.starts_with("Iterations,-3.14159")];
+        let svg = map.to_svg();
+        assert_eq![svg.matches("<rect").count(), 65 * 40];
+        assert![map.to_npy().len() > 65 * 40 * 8];
+    }
+}
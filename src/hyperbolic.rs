@@ -0,0 +1,658 @@
+use crate::cache::hyperbolic_steps;
+use crate::cordic::MAX_ITERS;
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::math;
+
+// Hyperbolic CORDIC works exactly like the circular version, except the
+// micro-rotations are by atanh(2^-i) along a hyperbola instead of
+// atan(2^-i) around a circle:
+//
+//   x' = x + sigma * 2^-i * y
+//   y' = y + sigma * 2^-i * x
+//   z' = z - sigma * atanh(2^-i)
+//
+// Two things are different though:
+//   1. atanh(2^0) is infinite, so the shifts start at 1 instead of 0
+//   2. sum(atanh(2^-j), j > i) < atanh(2^-i) for some i, which means
+//      it can't always make up the difference. Repeating shifts 4, 13,
+//      40, ... (i.e. 3i + 1) fixes that
+//
+// Each micro-rotation *shrinks* the vector by sqrt(1 - 2^-2i), and
+// HYPERBOLIC_GAIN undoes all of them for the MAX_ITERS shifts (plus
+// repeats) used here
+const HYPERBOLIC_GAIN: f64 = 1.207497067763072;
+
+// Iterations only converge for |z| (or |atanh(y / x)| in vectoring
+// mode) up to sum(atanh(2^-i)) over the schedule, ~1.118
+const CONVERGENCE_LIMIT: f64 = 1.1;
+
+// Shift amounts: 1, 2, 3, 4, 4, 5, ..., 13, 13, 14, ...
+pub(crate) const fn schedule() -> [i32; SCHEDULE_LEN] {
+    let mut shifts = [0; SCHEDULE_LEN];
+    let (mut n, mut repeat) = (0, 4);
+    let mut i = 1;
+    while i <= MAX_ITERS as i32 {
+        shifts[n] = i;
+        n += 1;
+        if i == repeat {
+            shifts[n] = i;
+            n += 1;
+            repeat = 3 * repeat + 1;
+        }
+        i += 1;
+    }
+    shifts
+}
+
+// How many steps schedule() has, fixed so the table is an array
+pub(crate) const SCHEDULE_LEN: usize = {
+    let (mut len, mut repeat) = (MAX_ITERS, 4);
+    while repeat <= MAX_ITERS {
+        len += 1;
+        repeat = 3 * repeat + 1;
+    }
+    len
+};
+
+// Rotation mode: drives z to zero, starting from (x, y). Starting at
+// (1, 0), this lands on (cosh z, sinh z) once the gain is undone
+fn rotate(mut v: [FixedPoint; 2], mut z: FixedPoint) -> [FixedPoint; 2] {
+    let fixed_point_zero = FixedPoint::new(0.0);
+    for &(poweroftwo, angle) in hyperbolic_steps() {
+        if z < fixed_point_zero {
+            v = [v[0] - poweroftwo * v[1], v[1] - poweroftwo * v[0]];
+            z += angle;
+        } else {
+            v = [v[0] + poweroftwo * v[1], v[1] + poweroftwo * v[0]];
+            z -= angle;
+        }
+    }
+
+    let kvalue = FixedPoint::new(HYPERBOLIC_GAIN);
+    [v[0] * kvalue, v[1] * kvalue]
+}
+
+// Vectoring mode: drives y to zero, starting from (x, y) with x > |y|.
+// What's left is sqrt(x^2 - y^2) in x and atanh(y / x) in z
+fn vector(mut v: [FixedPoint; 2]) -> (FixedPoint, FixedPoint) {
+    let fixed_point_zero = FixedPoint::new(0.0);
+    let mut z = fixed_point_zero;
+    for &(poweroftwo, angle) in hyperbolic_steps() {
+        if v[1] < fixed_point_zero {
+            v = [v[0] + poweroftwo * v[1], v[1] + poweroftwo * v[0]];
+            z -= angle;
+        } else {
+            v = [v[0] - poweroftwo * v[1], v[1] - poweroftwo * v[0]];
+            z += angle;
+        }
+    }
+
+    (v[0] * FixedPoint::new(HYPERBOLIC_GAIN), z)
+}
+
+// Splits x into k * ln(2) + r with |r| <= ln(2) / 2, so that
+// e^x = 2^k * e^r and e^r is well within the convergence limit
+fn split_ln2(x: FixedPoint) -> (i32, FixedPoint) {
+    let ln2 = std::f64::consts::LN_2;
+    let k = math::round(x.to_f64() / ln2) as i32;
+    (k, x - FixedPoint::new(k as f64 * ln2))
+}
+
+// For NaN and the infinities, what IEEE gives: NaN stays NaN and the
+// infinities go to the function's limits there. The iterations can't be
+// trusted with them, since every comparison with NaN is false and inf -
+// inf is NaN, so the functions below return this before iterating
+pub(crate) fn limit(x: FixedPoint, at_neg_inf: f64, at_pos_inf: f64) -> Option<FixedPoint> {
+    let x = x.to_f64();
+    if x.is_nan() {
+        Some(FixedPoint::new(f64::NAN))
+    } else if x == f64::INFINITY {
+        Some(FixedPoint::new(at_pos_inf))
+    } else if x == f64::NEG_INFINITY {
+        Some(FixedPoint::new(at_neg_inf))
+    } else {
+        None
+    }
+}
+
+// Multiplies by 2^k, which on a real fixed point number is just a shift.
+// It's done as two halves so that scaling a subnormal back up (k past
+// 1023) doesn't go through an infinite 2^k. Both are powers of two, so
+// it's still exact
+pub(crate) fn scale_pow2(x: FixedPoint, k: i32) -> FixedPoint {
+    let half = k / 2;
+    x * FixedPoint::new(math::powi(2.0, half)) * FixedPoint::new(math::powi(2.0, k - half))
+}
+
+// Splits a positive x into m * 2^e with m in [0.5, 1) by repeated
+// halving/doubling (shifts, again). Zero and infinity would halve or
+// double forever, so anything that isn't positive and finite comes back
+// as (x, 0): callers deal with those first (see limit())
+pub(crate) fn split_pow2(x: FixedPoint) -> (FixedPoint, i32) {
+    let half = FixedPoint::new(0.5);
+    let one = FixedPoint::new(1.0);
+    let two = FixedPoint::new(2.0);
+    let finite = x.to_f64().is_finite();
+    if !finite || x <= FixedPoint::new(0.0) {
+        return (x, 0);
+    }
+
+    let mut m = x;
+    let mut e = 0;
+    while m >= one {
+        m *= half;
+        e += 1;
+    }
+    while m < half {
+        m *= two;
+        e -= 1;
+    }
+    (m, e)
+}
+
+// [cosh x, sinh x]
+fn cosh_sinh(x: FixedPoint) -> [FixedPoint; 2] {
+    let one = FixedPoint::new(1.0);
+    let zero = FixedPoint::new(0.0);
+    if let Some(sinh) = limit(x, f64::NEG_INFINITY, f64::INFINITY) {
+        return [sinh.abs(), sinh];
+    }
+    if x.abs() <= FixedPoint::new(CONVERGENCE_LIMIT) {
+        return rotate([one, zero], x);
+    }
+
+    // Out of range, so go through e^x and e^-x instead:
+    //   e^r = cosh r + sinh r, e^-r = cosh r - sinh r
+    let (k, r) = split_ln2(x);
+    let [cosh, sinh] = rotate([one, zero], r);
+    let exp_pos = scale_pow2(cosh + sinh, k);
+    let exp_neg = scale_pow2(cosh - sinh, k.saturating_neg());
+    let half = FixedPoint::new(0.5);
+    [(exp_pos + exp_neg) * half, (exp_pos - exp_neg) * half]
+}
+
+pub fn cosh(x: FixedPoint) -> FixedPoint {
+    cosh_sinh(x)[0]
+}
+
+pub fn sinh(x: FixedPoint) -> FixedPoint {
+    cosh_sinh(x)[1]
+}
+
+// Past |x| = 20, tanh x is within 2^-55 of +-1, so it rounds to that in
+// f64. It also keeps sinh / cosh from turning into inf / inf = NaN once
+// both overflow, around |x| = 710
+const TANH_SATURATES: f64 = 20.0;
+
+pub fn tanh(x: FixedPoint) -> FixedPoint {
+    if let Some(ret) = limit(x, -1.0, 1.0) {
+        return ret;
+    }
+    if x.abs() > FixedPoint::new(TANH_SATURATES) {
+        return FixedPoint::new(if x.is_sign_negative() { -1.0 } else { 1.0 });
+    }
+    let [cosh, sinh] = cosh_sinh(x);
+    sinh / cosh
+}
+
+pub fn exp(x: FixedPoint) -> FixedPoint {
+    // Rotating (1, 1) gives (cosh r + sinh r, sinh r + cosh r) = (e^r, e^r)
+    let one = FixedPoint::new(1.0);
+    if let Some(ret) = limit(x, 0.0, f64::INFINITY) {
+        return ret;
+    }
+    let (k, r) = split_ln2(x);
+    let v = rotate([one, one], r);
+    scale_pow2(v[0], k)
+}
+
+// ln(x) = 2 * atanh((x - 1) / (x + 1)), and vectoring (x + 1, x - 1)
+// computes that atanh without a division
+pub fn ln(x: FixedPoint) -> Result<FixedPoint, CordicError> {
+    if x <= FixedPoint::new(0.0) {
+        return Err(CordicError::DomainError("ln"));
+    }
+    if let Some(ret) = limit(x, f64::NAN, f64::INFINITY) {
+        return Ok(ret);
+    }
+    // Otherwise ln(0.5) + ln(2) has to cancel to the last bit, which
+    // depends on how the angle table happened to round
+    if x == FixedPoint::new(1.0) {
+        return Ok(FixedPoint::new(0.0));
+    }
+
+    // ln(m * 2^e) = ln(m) + e * ln(2)
+    let (m, e) = split_pow2(x);
+    Ok(ln_mantissa(m) + FixedPoint::new(e as f64 * std::f64::consts::LN_2))
+}
+
+// ln(m) for m in [0.5, 1), which keeps the atanh argument within
+// [-1/3, 0)
+fn ln_mantissa(m: FixedPoint) -> FixedPoint {
+    let one = FixedPoint::new(1.0);
+    let (_, z) = vector([m + one, m - one]);
+    FixedPoint::new(2.0) * z
+}
+
+// log2(x) = e + log2(m) for x = m * 2^e. The exponent comes straight from
+// the shifts, so it's exact, and exact powers of two (m == 0.5) never
+// touch the CORDIC iterations at all
+pub fn log2(x: FixedPoint) -> Result<FixedPoint, CordicError> {
+    if x <= FixedPoint::new(0.0) {
+        return Err(CordicError::DomainError("log2"));
+    }
+    if let Some(ret) = limit(x, f64::NAN, f64::INFINITY) {
+        return Ok(ret);
+    }
+
+    let (m, e) = split_pow2(x);
+    if m == FixedPoint::new(0.5) {
+        return Ok(FixedPoint::new((e - 1) as f64));
+    }
+    Ok(FixedPoint::new(e as f64) + ln_mantissa(m) * FixedPoint::new(std::f64::consts::LOG2_E))
+}
+
+// 2^x = 2^k * e^(f * ln(2)) for x = k + f with k an integer. 2^k is a
+// shift, and |f * ln(2)| <= ln(2) / 2 is well within the convergence
+// limit. Integer x is exact
+pub fn exp2(x: FixedPoint) -> FixedPoint {
+    if let Some(ret) = limit(x, 0.0, f64::INFINITY) {
+        return ret;
+    }
+    let k = math::round(x.to_f64()) as i32;
+    let f = x - FixedPoint::new(k as f64);
+    let one = FixedPoint::new(1.0);
+    if f == FixedPoint::new(0.0) {
+        return scale_pow2(one, k);
+    }
+
+    let v = rotate([one, one], f * FixedPoint::new(std::f64::consts::LN_2));
+    scale_pow2(v[0], k)
+}
+
+// Logarithm in an arbitrary base, ln(x) / ln(base)
+pub fn log(x: FixedPoint, base: FixedPoint) -> Result<FixedPoint, CordicError> {
+    let zero = FixedPoint::new(0.0);
+    if x <= zero || base <= zero || base == FixedPoint::new(1.0) {
+        return Err(CordicError::DomainError("log"));
+    }
+
+    Ok(ln(x)? / ln(base)?)
+}
+
+// x^y = e^(y * ln(x)), with the cases ln() can't handle sorted out first:
+//   - x^0 is 1 for every x (including 0^0, like f64::powf)
+//   - 0^y is 0 for y > 0, and a division by zero for y < 0
+//   - a negative x only has a real power when y is an integer, and then
+//     it's |x|^y with the sign flipped for odd y
+//   - NaN and the infinities go by f64::powf: 1^y is 1 even for a NaN y,
+//     any other NaN gives NaN, and an infinite x or y gives 0 or +-inf
+//     depending on which side of 1 |x| is
+//
+// NOTE: Any error in ln(x) gets multiplied by y before going through
+// exp(), so the relative error of the result grows with |y * ln(x)|
+pub fn powf(x: FixedPoint, y: FixedPoint) -> Result<FixedPoint, CordicError> {
+    let zero = FixedPoint::new(0.0);
+    let one = FixedPoint::new(1.0);
+    if y == zero || x == one {
+        return Ok(one);
+    }
+
+    let (fx, fy) = (x.to_f64(), y.to_f64());
+    if fx.is_nan() || fy.is_nan() {
+        return Ok(FixedPoint::new(f64::NAN));
+    }
+    if fy.is_infinite() {
+        let ax = fx.abs();
+        let ret = if ax == 1.0 {
+            1.0
+        } else if (ax > 1.0) == (fy > 0.0) {
+            f64::INFINITY
+        } else {
+            0.0
+        };
+        return Ok(FixedPoint::new(ret));
+    }
+    if fx.is_infinite() {
+        let ret = if fy > 0.0 { f64::INFINITY } else { 0.0 };
+        let odd = math::trunc(fy) == fy && fy % 2.0 != 0.0;
+        return Ok(FixedPoint::new(if fx < 0.0 && odd { -ret } else { ret }));
+    }
+
+    if x == zero {
+        if y > zero {
+            return Ok(zero);
+        }
+        return Err(CordicError::DomainError("powf"));
+    }
+
+    if x < zero {
+        let n = y.to_f64();
+        if math::trunc(n) != n {
+            return Err(CordicError::DomainError("powf"));
+        }
+        let ret = exp(y * ln(-x)?);
+        if n % 2.0 == 0.0 {
+            return Ok(ret);
+        }
+        return Ok(-ret);
+    }
+
+    Ok(exp(y * ln(x)?))
+}
+
+// sqrt(x) = sqrt((x + 1/4)^2 - (x - 1/4)^2), which is exactly what
+// vectoring leaves in x
+pub fn sqrt(x: FixedPoint) -> Result<FixedPoint, CordicError> {
+    let zero = FixedPoint::new(0.0);
+    if x < zero {
+        return Err(CordicError::DomainError("sqrt"));
+    } else if x == zero {
+        return Ok(zero);
+    }
+    if let Some(ret) = limit(x, f64::NAN, f64::INFINITY) {
+        return Ok(ret);
+    }
+
+    // sqrt(m * 4^e) = sqrt(m) * 2^e, with m in [0.25, 1)
+    let (m, e) = split_pow2(x);
+    let (m, e) = if e % 2 == 0 {
+        (m, e)
+    } else {
+        (m * FixedPoint::new(0.5), e + 1)
+    };
+    let quarter = FixedPoint::new(0.25);
+    let (root, _) = vector([m + quarter, m - quarter]);
+    Ok(scale_pow2(root, e / 2))
+}
+
+// atanh(x) for |x| < 1. Vectoring (1, x) gives it directly as long as
+// it's within the convergence limit, which covers |x| up to ~0.8. Past
+// that it's 0.5 * ln((1 + x) / (1 - x)), which ln() range-reduces
+pub fn atanh(x: FixedPoint) -> Result<FixedPoint, CordicError> {
+    let one = FixedPoint::new(1.0);
+    if x.abs() >= one {
+        return Err(CordicError::DomainError("atanh"));
+    }
+    if let Some(ret) = limit(x, f64::NAN, f64::NAN) {
+        return Ok(ret);
+    }
+
+    if x.abs() <= FixedPoint::new(0.8) {
+        let (_, z) = vector([one, x]);
+        Ok(z)
+    } else {
+        Ok(FixedPoint::new(0.5) * ln((one + x) / (one - x))?)
+    }
+}
+
+// Past this x^2 + 1 and x^2 - 1 round to x^2 in f64, so sqrt() gives
+// back x and ln(x + x) = ln(x) + ln(2) is exact. It also keeps x^2 from
+// overflowing, which for |x| > ~1e154 would make the result infinite
+const HUGE: f64 = (1u64 << 28) as f64;
+
+// asinh(x) = ln(x + sqrt(x^2 + 1)). For negative x that subtracts two
+// nearly equal numbers, so use asinh(-x) == -asinh(x) instead
+pub fn asinh(x: FixedPoint) -> Result<FixedPoint, CordicError> {
+    let one = FixedPoint::new(1.0);
+    if let Some(ret) = limit(x, f64::NEG_INFINITY, f64::INFINITY) {
+        return Ok(ret);
+    }
+    let ax = x.abs();
+    let ret = if ax > FixedPoint::new(HUGE) {
+        ln(ax)? + FixedPoint::new(std::f64::consts::LN_2)
+    } else {
+        ln(ax + sqrt(ax * ax + one)?)?
+    };
+    if x < FixedPoint::new(0.0) {
+        Ok(-ret)
+    } else {
+        Ok(ret)
+    }
+}
+
+// acosh(x) = ln(x + sqrt(x^2 - 1)) for x >= 1
+pub fn acosh(x: FixedPoint) -> Result<FixedPoint, CordicError> {
+    let one = FixedPoint::new(1.0);
+    if x < one {
+        return Err(CordicError::DomainError("acosh"));
+    }
+    if let Some(ret) = limit(x, f64::NAN, f64::INFINITY) {
+        return Ok(ret);
+    }
+    if x > FixedPoint::new(HUGE) {
+        return Ok(ln(x)? + FixedPoint::new(std::f64::consts::LN_2));
+    }
+
+    ln(x + sqrt(x * x - one)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_enough(a: FixedPoint, b: FixedPoint) -> bool {
+        let diff = a - b;
+        diff < FixedPoint::new(0.01) && -diff < FixedPoint::new(0.01)
+    }
+
+    // Relative version, for functions that get big
+    fn relatively_close(a: FixedPoint, b: f64) -> bool {
+        ((a.to_f64() - b) / b).abs() < 1e-12
+    }
+
+    #[test]
+    fn cosh_sinh_exp() {
+        for i in -500..=500 {
+            let x = i as f64 / 50.0;
+            let fx = FixedPoint::new(x);
+
+            assert![relatively_close(cosh(fx), x.cosh()), "cosh {}", x];
+            assert![relatively_close(exp(fx), x.exp()), "exp {}", x];
+            assert![close_enough(sinh(fx), FixedPoint::new(x.sinh()))];
+            assert![close_enough(tanh(fx), FixedPoint::new(x.tanh()))];
+        }
+    }
+
+    #[test]
+    fn ln_sqrt() {
+        for x in [1e-9, 0.001, 0.1, 0.5, 1.0, 2.0, 3.0, 10.0, 12345.678, 1e12] {
+            let fx = FixedPoint::new(x);
+            assert![close_enough(ln(fx).unwrap(), FixedPoint::new(x.ln()))];
+            assert![relatively_close(sqrt(fx).unwrap(), x.sqrt()), "sqrt {}", x];
+        }
+        assert![sqrt(FixedPoint::new(0.0)).unwrap() == FixedPoint::new(0.0)];
+    }
+
+    #[test]
+    fn inverse_hyperbolic() {
+        for i in -999..=999 {
+            let x = i as f64 / 1000.0;
+            let ret = atanh(FixedPoint::new(x)).unwrap();
+            assert![close_enough(ret, FixedPoint::new(x.atanh())), "atanh {}", x];
+        }
+
+        for i in -1000..=1000 {
+            let x = i as f64 / 10.0;
+            let ret = asinh(FixedPoint::new(x)).unwrap();
+            assert![close_enough(ret, FixedPoint::new(x.asinh())), "asinh {}", x];
+        }
+
+        for i in 10..=1000 {
+            let x = i as f64 / 10.0;
+            let ret = acosh(FixedPoint::new(x)).unwrap();
+            assert![close_enough(ret, FixedPoint::new(x.acosh())), "acosh {}", x];
+        }
+
+        // Where x^2 overflows, and either side of where ln(2x) takes over
+        for x in [1e200, 1e300, 2f64.powi(28), 2f64.powi(28) * 1.5, 1e9] {
+            let ret = asinh(FixedPoint::new(x)).unwrap();
+            assert![relatively_close(ret, x.asinh()), "asinh {}", x];
+            assert_eq![asinh(FixedPoint::new(-x)).unwrap(), -ret];
+            let ret = acosh(FixedPoint::new(x)).unwrap();
+            assert![relatively_close(ret, x.acosh()), "acosh {}", x];
+        }
+        // Finite all the way up, where even std's come out infinite
+        let top = f64::MAX.ln() + std::f64::consts::LN_2;
+        assert![relatively_close(
+            asinh(FixedPoint::new(f64::MAX)).unwrap(),
+            top
+        )];
+        assert![relatively_close(
+            acosh(FixedPoint::new(f64::MAX)).unwrap(),
+            top
+        )];
+    }
+
+    #[test]
+    fn binary_logs() {
+        for x in [1e-9, 0.001, 0.1, 0.3, 0.75, 1.5, 3.0, 10.0, 12345.678, 1e12] {
+            let ret = log2(FixedPoint::new(x)).unwrap();
+            assert![close_enough(ret, FixedPoint::new(x.log2())), "log2 {}", x];
+        }
+        for i in -600..=600 {
+            let x = i as f64 / 20.0;
+            assert![
+                relatively_close(exp2(FixedPoint::new(x)), x.exp2()),
+                "exp2 {}",
+                x
+            ];
+        }
+    }
+
+    // Same answer as f64, bit for bit, with NaN matching NaN
+    fn same(a: FixedPoint, b: f64) -> bool {
+        let a = a.to_f64();
+        (a.is_nan() && b.is_nan()) || a.to_bits() == b.to_bits()
+    }
+
+    #[test]
+    fn non_finite() {
+        for x in [f64::NEG_INFINITY, f64::INFINITY, f64::NAN] {
+            let fx = FixedPoint::new(x);
+            assert![same(cosh(fx), x.cosh()), "cosh {}", x];
+            assert![same(sinh(fx), x.sinh()), "sinh {}", x];
+            assert![same(tanh(fx), x.tanh()), "tanh {}", x];
+            assert![same(exp(fx), x.exp()), "exp {}", x];
+            assert![same(exp2(fx), x.exp2()), "exp2 {}", x];
+            assert![same(asinh(fx).unwrap(), x.asinh()), "asinh {}", x];
+        }
+        for x in [f64::INFINITY, f64::NAN] {
+            let fx = FixedPoint::new(x);
+            assert![same(ln(fx).unwrap(), x.ln()), "ln {}", x];
+            assert![same(log2(fx).unwrap(), x.log2()), "log2 {}", x];
+            assert![same(sqrt(fx).unwrap(), x.sqrt()), "sqrt {}", x];
+            assert![same(acosh(fx).unwrap(), x.acosh()), "acosh {}", x];
+        }
+        let nan = FixedPoint::new(f64::NAN);
+        assert![atanh(nan).unwrap().to_f64().is_nan()];
+        assert![ln(FixedPoint::new(f64::NEG_INFINITY)).is_err()];
+        assert![atanh(FixedPoint::new(f64::INFINITY)).is_err()];
+    }
+
+    #[test]
+    fn tanh_saturates() {
+        for x in [20.5, 25.0, 100.0, 709.0, 711.0, 1000.0, 1e300] {
+            assert![tanh(FixedPoint::new(x)).to_f64() == 1.0, "tanh {}", x];
+            assert![tanh(FixedPoint::new(-x)).to_f64() == -1.0, "tanh {}", -x];
+        }
+        for x in [19.0, 20.0] {
+            assert![
+                relatively_close(tanh(FixedPoint::new(x)), x.tanh()),
+                "tanh {}",
+                x
+            ];
+        }
+    }
+
+    #[test]
+    fn powers_of_two_are_exact() {
+        for k in -40..=40 {
+            let x = FixedPoint::new(2_f64.powi(k));
+            assert![log2(x).unwrap() == FixedPoint::new(k as f64)];
+            assert![exp2(FixedPoint::new(k as f64)) == x];
+        }
+    }
+
+    #[test]
+    fn arbitrary_base() {
+        for x in [0.01, 0.5, 2.0, 7.0, 1000.0] {
+            for base in [0.5, 2.0, 10.0, std::f64::consts::E] {
+                let ret = log(FixedPoint::new(x), FixedPoint::new(base)).unwrap();
+                assert![close_enough(ret, FixedPoint::new(x.log(base)))];
+            }
+        }
+    }
+
+    #[test]
+    fn powers() {
+        // Relative error characterization: y * ln(x) spans roughly
+        // [-50, 50] here, and the error stays within ~50 ULPs of the
+        // result throughout
+        let mut worst = 0.0_f64;
+        for x in [1e-6, 0.01, 0.5, 0.999, 1.0, 1.001, 2.0, 10.0, 1e6] {
+            for i in -40..=40 {
+                let y = i as f64 / 4.0;
+                let ret = powf(FixedPoint::new(x), FixedPoint::new(y)).unwrap();
+                let expected = x.powf(y);
+                worst = worst.max(((ret.to_f64() - expected) / expected).abs());
+            }
+        }
+        assert![worst < 1e-12, "worst relative error {}", worst];
+
+        // Negative bases with integer exponents
+        for x in [-0.5, -2.0, -3.0] {
+            for n in -5..=5 {
+                let ret = powf(FixedPoint::new(x), FixedPoint::new(n as f64)).unwrap();
+                assert![relatively_close(ret, x.powi(n)), "{}^{}", x, n];
+            }
+        }
+    }
+
+    #[test]
+    fn non_finite_powers() {
+        let (inf, nan) = (f64::INFINITY, f64::NAN);
+        let values = [-inf, -3.0, -2.0, -1.0, -0.5, 0.5, 1.0, 2.0, 3.0, inf, nan];
+        for x in values.iter().chain([0.0].iter()) {
+            for y in values.iter().chain([0.0].iter()) {
+                if x.is_finite() && y.is_finite() && *x != 1.0 && *y != 0.0 {
+                    continue;
+                }
+                if *x == 0.0 && *y < 0.0 {
+                    continue;
+                }
+                let ret = powf(FixedPoint::new(*x), FixedPoint::new(*y)).unwrap();
+                assert![same(ret, x.powf(*y)), "{}^{}", x, y];
+            }
+        }
+    }
+
+    #[test]
+    fn domains() {
+        let err = |name| Err(CordicError::DomainError(name));
+
+        assert_eq![ln(FixedPoint::new(0.0)), err("ln")];
+        assert_eq![ln(FixedPoint::new(-1.0)), err("ln")];
+        assert_eq![sqrt(FixedPoint::new(-1e-9)), err("sqrt")];
+        assert_eq![atanh(FixedPoint::new(1.0)), err("atanh")];
+        assert_eq![atanh(FixedPoint::new(-1.5)), err("atanh")];
+        assert_eq![acosh(FixedPoint::new(0.999)), err("acosh")];
+        assert_eq![acosh(FixedPoint::new(1.0)), Ok(FixedPoint::new(0.0))];
+        assert_eq![log2(FixedPoint::new(0.0)), err("log2")];
+        assert_eq![log(FixedPoint::new(-2.0), FixedPoint::new(2.0)), err("log")];
+        assert_eq![log(FixedPoint::new(2.0), FixedPoint::new(1.0)), err("log")];
+        assert_eq![log(FixedPoint::new(2.0), FixedPoint::new(0.0)), err("log")];
+
+        let zero = FixedPoint::new(0.0);
+        let one = FixedPoint::new(1.0);
+        assert_eq![powf(zero, zero), Ok(one)];
+        assert_eq![powf(zero, FixedPoint::new(2.5)), Ok(zero)];
+        assert_eq![powf(zero, FixedPoint::new(-1.0)), err("powf")];
+        assert_eq![
+            powf(FixedPoint::new(-2.0), FixedPoint::new(0.5)),
+            err("powf")
+        ];
+        assert_eq![powf(FixedPoint::new(-2.0), zero), Ok(one)];
+    }
+}
@@ -0,0 +1,142 @@
+use crate::cordic::sincos;
+use crate::fixed::FixedPoint;
+
+// Angle sum, difference, double and half angle identities on [cos, sin]
+// pairs, for code that needs several related angles per sample. Once a
+// CORDIC pass has given the pair for a and for b, the pairs for a + b,
+// a - b and 2a are a few multiplies and adds each, far cheaper than
+// another pass of micro-rotations:
+//
+//   let (a, b) = (cordic(a, 32)?, cordic(b, 32)?);
+//   let [cos, sin] = sin_cos_sum(a, b);
+//
+// Each combination adds about an ulp of rounding to its inputs' error,
+// so chaining a handful is fine but thousands would drift (see
+// sin_cos_multiples)
+
+// [cos (a + b), sin (a + b)] from [cos a, sin a] and [cos b, sin b]
+pub fn sin_cos_sum(a: [FixedPoint; 2], b: [FixedPoint; 2]) -> [FixedPoint; 2] {
+    let [cos_a, sin_a] = a;
+    let [cos_b, sin_b] = b;
+    [cos_a * cos_b - sin_a * sin_b, sin_a * cos_b + cos_a * sin_b]
+}
+
+// [cos (a - b), sin (a - b)] from [cos a, sin a] and [cos b, sin b]
+pub fn sin_cos_difference(a: [FixedPoint; 2], b: [FixedPoint; 2]) -> [FixedPoint; 2] {
+    let [cos_b, sin_b] = b;
+    sin_cos_sum(a, [cos_b, -sin_b])
+}
+
+// [cos 2a, sin 2a] from [cos a, sin a]
+pub fn sin_cos_double(a: [FixedPoint; 2]) -> [FixedPoint; 2] {
+    sin_cos_sum(a, a)
+}
+
+// The half angle and the full angle, each as [cos, sin]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HalfAngle {
+    pub half: [FixedPoint; 2],
+    pub full: [FixedPoint; 2],
+}
+
+// cos and sin of both theta / 2 and theta from one CORDIC pass. Getting
+// the half angle from the full one needs a square root, which costs as
+// much as the pass itself, so this goes the other way: rotate by theta /
+// 2 and double it
+pub fn sin_cos_half(theta: FixedPoint) -> HalfAngle {
+    let half = sincos(theta / FixedPoint::new(2.0));
+    HalfAngle {
+        half,
+        full: sin_cos_double(half),
+    }
+}
+
+// [cos k theta, sin k theta] for k in 1..=n from one CORDIC pass, each
+// harmonic the previous one plus theta. The kth carries k times the
+// pass's error (plus an ulp or so of rounding per step), so the 100th is
+// still good to around 1e-13
+pub fn sin_cos_multiples(theta: FixedPoint, n: usize) -> Vec<[FixedPoint; 2]> {
+    let first = sincos(theta);
+    let mut harmonics = Vec::with_capacity(n);
+    let mut current = first;
+    for _ in 0..n {
+        harmonics.push(current);
+        current = sin_cos_sum(current, first);
+    }
+    harmonics
+}
+
+// sin k theta for k in 1..=n by the Chebyshev recurrence
+//   sin (k + 1) theta = 2 cos theta sin k theta - sin (k - 1) theta
+// from one CORDIC pass. That's a multiply and a subtract per harmonic,
+// against four multiplies for sin_cos_multiples() and a whole pass each
+// for n separate calls. The recurrence amplifies rounding by about
+// 1 / |sin theta| on top of the kth harmonic's k-fold share of the
+// pass's error, so near theta = 0 or pi sin_cos_multiples() is the
+// better choice
+pub fn harmonics(theta: FixedPoint, n: usize) -> Vec<FixedPoint> {
+    let [cos, sin] = sincos(theta);
+    let twice_cos = cos + cos;
+    let mut harmonics = Vec::with_capacity(n);
+    let (mut previous, mut current) = (FixedPoint::new(0.0), sin);
+    for _ in 0..n {
+        harmonics.push(current);
+        let next = twice_cos * current - previous;
+        previous = current;
+        current = next;
+    }
+    harmonics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(v: [FixedPoint; 2], theta: f64, tolerance: f64) -> bool {
+        (v[0].to_f64() - theta.cos()).abs() < tolerance
+            && (v[1].to_f64() - theta.sin()).abs() < tolerance
+    }
+
+    #[test]
+    fn identities() {
+        for i in -31..=31 {
+            let a = i as f64 * 0.2;
+            let b = 1.0 - i as f64 * 0.13;
+            let (pa, pb) = (sincos(FixedPoint::new(a)), sincos(FixedPoint::new(b)));
+            assert![close(sin_cos_sum(pa, pb), a + b, 1e-14)];
+            assert![close(sin_cos_difference(pa, pb), a - b, 1e-14)];
+            assert![close(sin_cos_double(pa), 2.0 * a, 1e-14)];
+
+            let halves = sin_cos_half(FixedPoint::new(a));
+            assert![close(halves.half, a / 2.0, 1e-14)];
+            assert![close(halves.full, a, 1e-14)];
+        }
+    }
+
+    #[test]
+    fn multiples() {
+        assert![sin_cos_multiples(FixedPoint::new(1.0), 0).is_empty()];
+        let theta = 0.1234;
+        let harmonics = sin_cos_multiples(FixedPoint::new(theta), 100);
+        assert_eq![harmonics.len(), 100];
+        for (k, &v) in harmonics.iter().enumerate() {
+            // The first pass's error, k times over
+            let k = (k + 1) as f64;
+            assert![close(v, k * theta, k * 1e-15)];
+        }
+    }
+
+    #[test]
+    fn chebyshev() {
+        assert![harmonics(FixedPoint::new(1.0), 0).is_empty()];
+        for &theta in &[0.5, 1.0, 1.5, 2.5] {
+            let recurrence = harmonics(FixedPoint::new(theta), 100);
+            let chain = sin_cos_multiples(FixedPoint::new(theta), 100);
+            assert_eq![recurrence[0], chain[0][1]];
+            for (k, &sin) in recurrence.iter().enumerate() {
+                let exact = ((k + 1) as f64 * theta).sin();
+                assert![(sin.to_f64() - exact).abs() < 1e-13, "{} {}", theta, k + 1];
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use crate::cordic::sincos;
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::math;
+
+// An 8 bit greyscale (PGM) or RGB (PPM) image, just enough to show the
+// rotation working on real data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    // 1 for PGM, 3 for PPM
+    pub channels: usize,
+    pub maxval: u8,
+    // Row major, channels interleaved
+    pub pixels: Vec<u8>,
+}
+
+// Fractional bits used for coordinates and sin/cos while resampling
+const FRAC: u32 = 16;
+const ONE: i64 = 1 << FRAC;
+const HALF: i64 = ONE / 2;
+
+impl Image {
+    // Reads a binary PGM (P5) or PPM (P6) with a maxval of at most 255
+    pub fn from_pnm(bytes: &[u8]) -> Result<Self, CordicError> {
+        let channels = match bytes.get(0..2) {
+            Some(b"P5") => 1,
+            Some(b"P6") => 3,
+            _ => return Err(CordicError::InvalidImage("not a binary PGM or PPM")),
+        };
+        let mut at = 2;
+        let mut fields = [0_usize; 3];
+        for field in fields.iter_mut() {
+            *field = header_number(bytes, &mut at)?;
+        }
+        let [width, height, maxval] = fields;
+        if maxval == 0 || maxval > 255 {
+            return Err(CordicError::InvalidImage(
+                "maxval must be between 1 and 255",
+            ));
+        }
+        // Exactly one whitespace byte separates the header from the raster
+        at += 1;
+        let len = width
+            .checked_mul(height)
+            .and_then(|len| len.checked_mul(channels))
+            .ok_or(CordicError::InvalidImage("image too large"))?;
+        match bytes.get(at..).and_then(|raster| raster.get(..len)) {
+            Some(pixels) => Ok(Self {
+                width,
+                height,
+                channels,
+                maxval: maxval as u8,
+                pixels: pixels.to_vec(),
+            }),
+            None => Err(CordicError::InvalidImage("truncated raster")),
+        }
+    }
+
+    pub fn to_pnm(&self) -> Vec<u8> {
+        let magic = if self.channels == 1 { "P5" } else { "P6" };
+        let mut bytes = format!(
+            "{}\n{} {}\n{}\n",
+            magic, self.width, self.height, self.maxval
+        )
+        .into_bytes();
+        bytes.extend_from_slice(&self.pixels);
+        bytes
+    }
+
+    // Rotates counterclockwise by theta about the centre, keeping the
+    // same size and filling uncovered corners with black. sin and cos
+    // come from CORDIC once, after which every output pixel is mapped
+    // back into the source and bilinearly interpolated using only
+    // integer multiplies and shifts, same as a hardware blitter would.
+    //
+    // The fields are public, so nothing stops the pixels disagreeing with
+    // the size. Such an image comes back as it is rather than rotated
+    pub fn rotate(&self, theta: FixedPoint) -> Self {
+        if !self.consistent() {
+            return self.clone();
+        }
+        let [cos, sin] = sincos(theta);
+        let cos = to_q16(cos);
+        let sin = to_q16(sin);
+        let cx = (self.width as i64) << (FRAC - 1);
+        let cy = (self.height as i64) << (FRAC - 1);
+
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                // Offset of this pixel's centre from the image centre
+                let dx = ((x as i64) << FRAC) + HALF - cx;
+                let dy = ((y as i64) << FRAC) + HALF - cy;
+                // Inverse rotation (y points down, so the signs on sin
+                // are flipped from the textbook matrix), then back to
+                // coordinates where integers land on pixel centres
+                let sx = ((cos * dx - sin * dy) >> FRAC) + cx - HALF;
+                let sy = ((sin * dx + cos * dy) >> FRAC) + cy - HALF;
+                for channel in 0..self.channels {
+                    pixels.push(self.bilinear(sx, sy, channel));
+                }
+            }
+        }
+        Self {
+            pixels,
+            ..self.clone()
+        }
+    }
+
+    // Whether there are exactly width * height pixels, on sides short
+    // enough that a Q16 coordinate times a Q16 sine stays within an i64
+    fn consistent(&self) -> bool {
+        let len = self
+            .width
+            .checked_mul(self.height)
+            .and_then(|len| len.checked_mul(self.channels));
+        let limit = 1 << (62 - 2 * FRAC - 2);
+        len == Some(self.pixels.len()) && self.width < limit && self.height < limit
+    }
+
+    // Samples at a Q16 position, treating everything outside as black
+    fn bilinear(&self, sx: i64, sy: i64, channel: usize) -> u8 {
+        let (x0, y0) = (sx >> FRAC, sy >> FRAC);
+        let (fx, fy) = (sx & (ONE - 1), sy & (ONE - 1));
+        let at = |x: i64, y: i64| -> i64 {
+            if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+                0
+            } else {
+                let i = (y as usize * self.width + x as usize) * self.channels + channel;
+                self.pixels[i] as i64
+            }
+        };
+        let top = at(x0, y0) * (ONE - fx) + at(x0 + 1, y0) * fx;
+        let bottom = at(x0, y0 + 1) * (ONE - fx) + at(x0 + 1, y0 + 1) * fx;
+        let value = (top * (ONE - fy) + bottom * fy + (1 << (2 * FRAC - 1))) >> (2 * FRAC);
+        value as u8
+    }
+}
+
+fn to_q16(x: FixedPoint) -> i64 {
+    math::round(x.to_f64() * ONE as f64) as i64
+}
+
+// Skips whitespace and `#` comments, then reads a decimal number
+fn header_number(bytes: &[u8], at: &mut usize) -> Result<usize, CordicError> {
+    loop {
+        match bytes.get(*at) {
+            Some(b'#') => {
+                while bytes.get(*at).is_some_and(|&b| b != b'\n') {
+                    *at += 1;
+                }
+            }
+            Some(b) if b.is_ascii_whitespace() => *at += 1,
+            _ => break,
+        }
+    }
+    let start = *at;
+    while bytes.get(*at).is_some_and(|b| b.is_ascii_digit()) {
+        *at += 1;
+    }
+    // Only ASCII digits, so always UTF-8
+    std::str::from_utf8(&bytes[start..*at])
+        .unwrap()
+        .parse::<usize>()
+        .map_err(|_| CordicError::InvalidImage("bad header"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grey(width: usize, height: usize, pixels: &[u8]) -> Image {
+        Image {
+            width,
+            height,
+            channels: 1,
+            maxval: 255,
+            pixels: pixels.to_vec(),
+        }
+    }
+
+    #[test]
+    fn pnm_round_trip() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            channels: 3,
+            maxval: 255,
+            pixels: vec![1, 2, 3, 4, 5, 6],
+        };
+        assert_eq![Image::from_pnm(&image.to_pnm()).unwrap(), image];
+
+        let commented = b"P5 # made by hand\n2 2\n# still the header\n255\n\x01\x02\x03\x04";
+        assert_eq![
+            Image::from_pnm(commented).unwrap(),
+            grey(2, 2, &[1, 2, 3, 4])
+        ];
+    }
+
+    #[test]
+    fn pnm_errors() {
+        assert![Image::from_pnm(b"P3\n1 1\n255\n0 0 0").is_err()];
+        assert![Image::from_pnm(b"P5\n2 2\n255\n\x01").is_err()];
+        assert![Image::from_pnm(b"P5\n1 1\n65535\n\x00\x00").is_err()];
+    }
+
+    #[test]
+    fn quarter_turns() {
+        let image = grey(3, 3, &[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        let pi = std::f64::consts::PI;
+        assert_eq![image.rotate(FixedPoint::new(0.0)), image];
+        assert_eq![
+            image.rotate(FixedPoint::new(pi)).pixels,
+            [9, 8, 7, 6, 5, 4, 3, 2, 1]
+        ];
+        // Counterclockwise, so the top right corner ends up top left
+        assert_eq![
+            image.rotate(FixedPoint::new(pi / 2.0)).pixels,
+            [3, 6, 9, 2, 5, 8, 1, 4, 7]
+        ];
+    }
+
+    #[test]
+    fn corners_go_black() {
+        let image = grey(8, 8, &[200; 64]);
+        let rotated = image.rotate(FixedPoint::new(std::f64::consts::FRAC_PI_4));
+        assert_eq![rotated.pixels[0], 0];
+        assert_eq![rotated.pixels[8 * 3 + 3], 200];
+    }
+}
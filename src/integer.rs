@@ -0,0 +1,886 @@
+// Integer backend: the micro-rotations done on i64s with 60 fractional
+// bits (Q60), using nothing but shifts, adds and compares, the way the
+// datapath in real hardware works.
+//
+// The float backend builds its angle table with f64::atan, which comes
+// from the platform's libm and isn't guaranteed to round the same way on
+// x86, ARM and wasm. Here the tables are constants, and the only floating
+// point involved is converting in and out (scaling by powers of two and
+// rounding, which IEEE 754 pins down exactly), so results come out the
+// same bit for bit everywhere.
+//
+// rotate() and vector() take and return FixedPoint just like the float
+// kernels in cordic.rs, so CordicConfig can switch between them without
+// anything else changing. Q60 and sincos_q60() are the same kernel with
+// no floating point at all, which is all that's left with the `float`
+// feature off
+
+#[cfg(not(feature = "float"))]
+#[allow(unused_imports)]
+use crate::float_guard::{f32, f64};
+
+#[cfg(feature = "float")]
+use crate::cordic::{fold_quadrant, reduce, MAX_ITERS};
+use crate::error::CordicError;
+#[cfg(feature = "float")]
+use crate::fixed::FixedPoint;
+#[cfg(feature = "float")]
+use crate::hyperbolic::{scale_pow2, split_pow2};
+use crate::log::{enabled, log, LogLevel};
+#[cfg(feature = "float")]
+use crate::math;
+use std::fmt;
+use std::str::FromStr;
+
+const FRAC_BITS: i32 = 60;
+
+const PI: i64 = 3_622_009_729_038_561_421;
+const HALF_PI: i64 = 1_811_004_864_519_280_711;
+pub(crate) const TWO_PI: i64 = 7_244_019_458_077_122_842;
+
+// ANGLES[i] = atan(2^-i) in Q60, computed to 80 digits and rounded. From
+// i = 30 on, atan(2^-i) rounds to plain 2^-i
+#[rustfmt::skip]
+pub(crate) const ANGLES: [i64; 61] = [
+    905502432259640355, 534549298976576474, 282441168888798124,
+    143371547418228444, 71963988336308046, 36017075762092179,
+    18012932708689205, 9007016009513623, 4503576721087964,
+    2251796950380271, 1125899548928887, 562949908682076,
+    281474971118251, 140737487656277, 70368744090283,
+    35184372077909, 17592186043051, 8796093022037,
+    4398046511083, 2199023255549, 1099511627776,
+    549755813888, 274877906944, 137438953472,
+    68719476736, 34359738368, 17179869184,
+    8589934592, 4294967296, 2147483648,
+    1073741824, 536870912, 268435456,
+    134217728, 67108864, 33554432,
+    16777216, 8388608, 4194304,
+    2097152, 1048576, 524288,
+    262144, 131072, 65536,
+    32768, 16384, 8192,
+    4096, 2048, 1024,
+    512, 256, 128,
+    64, 32, 16,
+    8, 4, 2,
+    1,
+];
+
+// GAINS[n] is the gain K of n micro-rotations in Q60, the same numbers
+// as cordic::GAINS but exact to the last bit of the integer format
+#[rustfmt::skip]
+pub(crate) const GAINS: [i64; 62] = [
+    1152921504606846976, 815238614083298888, 729171583589189486,
+    707400343138147148, 701937710475640567, 700570741874588358,
+    700228916656934815, 700143455142409313, 700122089437857660,
+    700116747991345222, 700115412628443634, 700115078787638644,
+    700114995327432421, 700114974462380555, 700114969246117569,
+    700114967942051821, 700114967616035384, 700114967534531275,
+    700114967514155248, 700114967509061241, 700114967507787739,
+    700114967507469364, 700114967507389770, 700114967507369871,
+    700114967507364897, 700114967507363653, 700114967507363342,
+    700114967507363264, 700114967507363245, 700114967507363240,
+    700114967507363239, 700114967507363239, 700114967507363239,
+    700114967507363239, 700114967507363238, 700114967507363238,
+    700114967507363238, 700114967507363238, 700114967507363238,
+    700114967507363238, 700114967507363238, 700114967507363238,
+    700114967507363238, 700114967507363238, 700114967507363238,
+    700114967507363238, 700114967507363238, 700114967507363238,
+    700114967507363238, 700114967507363238, 700114967507363238,
+    700114967507363238, 700114967507363238, 700114967507363238,
+    700114967507363238, 700114967507363238, 700114967507363238,
+    700114967507363238, 700114967507363238, 700114967507363238,
+    700114967507363238, 700114967507363238,
+];
+
+// ANGLES as Q60s, so angle_table_q60() can hand out a slice
+const ANGLE_TABLE: [Q60; ANGLES.len()] = {
+    let mut table = [Q60(0); ANGLES.len()];
+    let mut i = 0;
+    while i < ANGLES.len() {
+        table[i] = Q60(ANGLES[i]);
+        i += 1;
+    }
+    table
+};
+
+// The gain K of `iters` micro-rotations in Q60, the constant the integer
+// backend starts its vector at. Iteration counts are checked like
+// sincos_q60()'s
+pub fn gain_q60(iters: usize) -> Result<Q60, CordicError> {
+    if iters == 0 {
+        return Err(CordicError::IterationOutOfRange(iters));
+    }
+    Ok(Q60(GAINS[iters.min(ANGLES.len())]))
+}
+
+// atan(2^-i) in Q60 for i in 0..iters, the integer backend's micro-angles
+// bit for bit. Iteration counts are checked like sincos_q60()'s
+pub fn angle_table_q60(iters: usize) -> Result<&'static [Q60], CordicError> {
+    if iters == 0 {
+        return Err(CordicError::IterationOutOfRange(iters));
+    }
+    Ok(&ANGLE_TABLE[..iters.min(ANGLES.len())])
+}
+
+// Every valid iteration count needs a table entry
+#[cfg(feature = "float")]
+const _: () = assert!(MAX_ITERS <= ANGLES.len());
+
+// A Q60 number: an i64 holding value * 2^60, so it covers [-8, 8) in
+// steps of about 8.7e-19
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Q60(i64);
+
+impl Q60 {
+    pub const FRAC_BITS: u32 = FRAC_BITS as u32;
+
+    #[inline]
+    pub fn from_bits(bits: i64) -> Self {
+        Self(bits)
+    }
+
+    #[inline]
+    pub fn to_bits(self) -> i64 {
+        self.0
+    }
+}
+
+// Decimal with up to 19 places (enough to round trip through FromStr),
+// dropping trailing zeros
+impl fmt::Display for Q60 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const PLACES: i128 = 10_000_000_000_000_000_000;
+        let magnitude = (self.0 as i128).abs();
+        let scaled = (magnitude * PLACES + (1 << (FRAC_BITS - 1))) >> FRAC_BITS;
+        if self.0 < 0 && scaled != 0 {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", scaled / PLACES)?;
+        let fraction = scaled % PLACES;
+        if fraction != 0 {
+            let digits = format!("{:019}", fraction);
+            write!(f, ".{}", digits.trim_end_matches('0'))?;
+        }
+        Ok(())
+    }
+}
+
+// Parses plain decimals like "-1.25" or "0.7853981633974483" straight
+// into Q60, rounding to nearest with ties to even, so numbers never pass
+// through a float. Every digit counts, however many there are: one far
+// past the 60th bit can still decide which way a value right at a
+// halfway point goes
+impl FromStr for Q60 {
+    type Err = CordicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, unsigned) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (whole, fraction) = match unsigned.find('.') {
+            Some(i) => (&unsigned[..i], &unsigned[i + 1..]),
+            None => (unsigned, ""),
+        };
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+            return Err(CordicError::ParseError("expected a decimal number"));
+        }
+
+        // Up to 8 before the point, which only fits as -8 (i64::MIN)
+        let mut bits: i128 = 0;
+        for digit in whole.bytes() {
+            bits = bits * 10 + (digit - b'0') as i128;
+            if bits > 8 {
+                return Err(CordicError::ParseError("out of range for Q60"));
+            }
+        }
+        bits <<= FRAC_BITS;
+
+        // floor(fraction * 2^(FRAC_BITS + 1)), i.e. the fraction's bits
+        // plus the one below for rounding, from the last digit back:
+        // 0.d... is (d + 0....) / 10, and flooring before each division by
+        // 10 floors the same as flooring after. Whatever a division leaves
+        // over is the sticky bit, since then the value is past halfway
+        // rather than on it. Nothing is stored per digit, so any number
+        // of them parses in constant space
+        let one = 1_i128 << (FRAC_BITS + 1);
+        let mut quotient: i128 = 0;
+        let mut sticky = false;
+        for digit in fraction.bytes().rev() {
+            let scaled = (digit - b'0') as i128 * one + quotient;
+            sticky |= scaled % 10 != 0;
+            quotient = scaled / 10;
+        }
+        let (round, lsb) = (quotient & 1 == 1, quotient & 2 == 2);
+        bits += (quotient >> 1) + (round && (sticky || lsb)) as i128;
+
+        // The magnitude of i64::MIN is one more than i64::MAX
+        let limit = i64::MAX as i128 + negative as i128;
+        if bits > limit {
+            return Err(CordicError::ParseError("out of range for Q60"));
+        }
+        Ok(Self(if negative { -bits } else { bits } as i64))
+    }
+}
+
+// cos and sin of theta in Q60, with no floating point anywhere. The
+// iteration count is checked the same way as checked_iters() (0 is an
+// error), but clamps at the size of the angle table. The vector starts
+// out at (K, 0) so the gain needs no multiply at the end
+pub fn sincos_q60(theta: Q60, iters: usize) -> Result<[Q60; 2], CordicError> {
+    if iters == 0 {
+        return Err(CordicError::IterationOutOfRange(iters));
+    }
+    let iters = iters.min(ANGLES.len());
+
+    let (z, negate) = fold(theta.0);
+    let k = GAINS[iters];
+    let v = if negate { [-k, 0] } else { [k, 0] };
+    let (v, _) = rotations(z, v, iters);
+    Ok([Q60(v[0]), Q60(v[1])])
+}
+
+// An iteration count checked when the program is built: naming
+// Iters::<N>::CHECKED is a compile error unless 1 <= N <= the table size
+struct Iters<const N: usize>;
+
+impl<const N: usize> Iters<N> {
+    const CHECKED: usize = {
+        assert!(N >= 1 && N <= ANGLES.len(), "iteration count out of range");
+        N
+    };
+}
+
+// sincos_q60 with the iteration count fixed at compile time, for no_std
+// code that has nowhere to send an error (an interrupt handler, say). A
+// bad count fails the build rather than the call, so there's no Result,
+// and like everything here it never allocates. Once inlined, 16, 24 and
+// 32 go straight to the unrolled kernels
+#[inline]
+pub fn sincos_q60_fixed<const ITERS: usize>(theta: Q60) -> [Q60; 2] {
+    let iters = Iters::<ITERS>::CHECKED;
+    let (z, negate) = fold(theta.0);
+    let k = GAINS[iters];
+    let v = if negate { [-k, 0] } else { [k, 0] };
+    let (v, _) = rotations(z, v, iters);
+    [Q60(v[0]), Q60(v[1])]
+}
+
+// Reduces a Q60 angle into [-pi/2, pi/2], returning whether the outputs
+// need negating, see cordic::reduce and cordic::fold_quadrant
+pub(crate) fn fold(mut z: i64) -> (i64, bool) {
+    // Q60 only reaches +-8, so one or two turns is all the reducing
+    // there can be
+    while z > PI {
+        z -= TWO_PI;
+    }
+    while z < -PI {
+        z += TWO_PI;
+    }
+    let negate = !(-HALF_PI..=HALF_PI).contains(&z);
+    if z > HALF_PI {
+        z -= PI;
+    } else if z < -HALF_PI {
+        z += PI;
+    }
+    (z, negate)
+}
+
+// sincos_q60 on a narrower datapath, the way hardware would work it
+// out with `registers` fractional bits in x and y, `angles` in z and the
+// angle table, and `gain` in K (each at most 60). theta and the constants
+// are rounded to their widths, and every shift drops whatever falls off
+// the end of its register. The results come back as Q60 so they line up
+// with the full width ones. Narrowing one width at a time shows how much
+// error each contributes
+#[cfg(feature = "float")]
+pub(crate) fn sincos_narrow(
+    theta: Q60,
+    iters: usize,
+    registers: u32,
+    angles: u32,
+    gain: u32,
+) -> [Q60; 2] {
+    let iters = iters.min(ANGLES.len());
+    // Rounds to `bits` fractional bits and floors to the register width,
+    // both staying in Q60
+    let round = |x: i64, bits: u32| {
+        let drop = FRAC_BITS as u32 - bits.min(FRAC_BITS as u32);
+        if drop == 0 {
+            x
+        } else {
+            ((x + (1 << (drop - 1))) >> drop) << drop
+        }
+    };
+    let drop = FRAC_BITS as u32 - registers.min(FRAC_BITS as u32);
+    let truncate = |x: i64| (x >> drop) << drop;
+
+    // z only ever has angles added to it, so rounding theta is all it
+    // takes to keep it at its width
+    let (z, negate) = fold(theta.0);
+    let mut z = round(z, angles);
+    let k = truncate(round(GAINS[iters], gain));
+    let mut v = if negate { [-k, 0] } else { [k, 0] };
+    for (i, &angle) in ANGLES.iter().enumerate().take(iters) {
+        let angle = round(angle, angles);
+        let [x, y] = [truncate(v[0] >> i), truncate(v[1] >> i)];
+        // Flooring to a register of a bit or two adds up to a whole unit
+        // of error per step, which can outgrow Q60's integer bits. The
+        // register then wraps, as it would in hardware
+        if z >= 0 {
+            v = [v[0].wrapping_sub(y), v[1].wrapping_add(x)];
+            z -= angle;
+        } else {
+            v = [v[0].wrapping_add(y), v[1].wrapping_sub(x)];
+            z += angle;
+        }
+    }
+    [Q60(v[0]), Q60(v[1])]
+}
+
+// `iters` micro-rotations of the Q60 vector `v` by the Q60 angle `z`,
+// which must already be within [-pi/2, pi/2]. Returns the rotated vector
+// without the gain undone and the residual angle. Arithmetic shifts round
+// towards -inf, same as dropping the low bits of a two's complement
+// register
+fn micro_rotations(mut z: i64, mut v: [i64; 2], iters: usize) -> ([i64; 2], i64) {
+    for (i, angle) in ANGLES.iter().enumerate().take(iters) {
+        let counterclockwise = z >= 0;
+        if counterclockwise {
+            v = [v[0] - (v[1] >> i), v[1] + (v[0] >> i)];
+            z -= angle;
+        } else {
+            v = [v[0] + (v[1] >> i), v[1] - (v[0] >> i)];
+            z += angle;
+        }
+        log_iteration(i, counterclockwise, z, v);
+    }
+    (v, z)
+}
+
+// micro_rotations written out in full for the usual iteration counts,
+// so each shift is an immediate and each angle a constant operand, with
+// no loop counter and no table index left to bounds check. Each step is
+// branchless too: s is all ones when z is negative, and (x ^ s) - s
+// negates x exactly when the step goes clockwise, which avoids a branch
+// the predictor gets wrong half the time. The trace is left out (that's
+// a load and a branch every iteration), so these only run while Debug
+// logging is off. They're kept out of line so each shows up as its own
+// function in the assembly, which python/check_codegen.py inspects
+macro_rules! unrolled {
+    ($($name:ident: $($i:literal)*;)*) => {
+        $(
+            #[inline(never)]
+            fn $name(mut z: i64, mut v: [i64; 2]) -> ([i64; 2], i64) {
+                $(
+                    let s = z >> 63;
+                    let x = v[0] >> $i;
+                    let y = v[1] >> $i;
+                    v = [v[0] - ((y ^ s) - s), v[1] + ((x ^ s) - s)];
+                    z -= (ANGLES[$i] ^ s) - s;
+                )*
+                (v, z)
+            }
+        )*
+    };
+}
+
+unrolled! {
+    micro_rotations_16: 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15;
+    micro_rotations_24: 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23;
+    micro_rotations_32: 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27
+        28 29 30 31;
+}
+
+// The usual iteration counts get the unrolled kernel, anything else
+// (or anything being traced) the loop
+fn rotations(z: i64, v: [i64; 2], iters: usize) -> ([i64; 2], i64) {
+    if enabled(LogLevel::Debug) {
+        return micro_rotations(z, v, iters);
+    }
+    match iters {
+        16 => micro_rotations_16(z, v),
+        24 => micro_rotations_24(z, v),
+        32 => micro_rotations_32(z, v),
+        _ => micro_rotations(z, v, iters),
+    }
+}
+
+// Vectoring counterpart, driving y of the right half-plane vector `v`
+// to zero and adding the angles taken onto `z`
+#[cfg(feature = "float")]
+fn micro_vectoring(mut v: [i64; 2], mut z: i64, iters: usize) -> ([i64; 2], i64) {
+    for (i, angle) in ANGLES.iter().enumerate().take(iters) {
+        let counterclockwise = v[1] < 0;
+        if counterclockwise {
+            v = [v[0] - (v[1] >> i), v[1] + (v[0] >> i)];
+            z -= angle;
+        } else {
+            v = [v[0] + (v[1] >> i), v[1] - (v[0] >> i)];
+            z += angle;
+        }
+        log_iteration(i, counterclockwise, z, v);
+    }
+    (v, z)
+}
+
+// Same trace as cordic.rs logs for the float backend, except v is the
+// raw register contents, which rotate() and vector() have scaled by a
+// power of two to keep in range
+fn log_iteration(i: usize, counterclockwise: bool, z: i64, v: [i64; 2]) {
+    log(
+        LogLevel::Debug,
+        format_args!(
+            "  iteration {}: sigma {}, z {}, v ({}, {})",
+            i,
+            if counterclockwise { "+1" } else { "-1" },
+            Q60(z),
+            Q60(v[0]),
+            Q60(v[1])
+        ),
+    );
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn to_q60(x: FixedPoint) -> i64 {
+    math::round(scale_pow2(x, FRAC_BITS).to_f64()) as i64
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn from_q60(x: i64) -> FixedPoint {
+    scale_pow2(FixedPoint::new(x as f64), -FRAC_BITS)
+}
+
+// Splits `v` into a Q60 vector with its largest component in [0.5, 1)
+// and the power of two that was taken out, which keeps the iterations
+// well inside the 3 integer bits no matter how big `v` is (the most the
+// micro-rotations can grow it is 1.65 * sqrt(2)). None if `v` isn't
+// finite
+#[cfg(feature = "float")]
+pub(crate) fn normalize(v: [FixedPoint; 2]) -> Option<([i64; 2], i32)> {
+    let largest = if v[0].abs() > v[1].abs() {
+        v[0].abs()
+    } else {
+        v[1].abs()
+    };
+    if !largest.to_f64().is_finite() {
+        return None;
+    }
+    let e = if largest == FixedPoint::new(0.0) {
+        0
+    } else {
+        split_pow2(largest).1
+    };
+    Some((
+        [to_q60(scale_pow2(v[0], -e)), to_q60(scale_pow2(v[1], -e))],
+        e,
+    ))
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn denormalize(v: [i64; 2], e: i32) -> [FixedPoint; 2] {
+    [scale_pow2(from_q60(v[0]), e), scale_pow2(from_q60(v[1]), e)]
+}
+
+// Integer counterpart of cordic::rotate_raw: `iters` (already validated)
+// micro-rotations of `v` by theta, returning the rotated vector without
+// the gain undone and the residual angle
+#[cfg(feature = "float")]
+pub(crate) fn rotate(
+    theta: FixedPoint,
+    v: [FixedPoint; 2],
+    iters: usize,
+) -> ([FixedPoint; 2], FixedPoint) {
+    let nan = FixedPoint::new(f64::NAN);
+    let (theta, negate) = fold_quadrant(reduce(theta));
+    let (v, e) = match normalize(v) {
+        Some(normalized) if theta.to_f64().is_finite() => normalized,
+        _ => return ([nan, nan], nan),
+    };
+
+    let v = if negate { [-v[0], -v[1]] } else { v };
+    let (v, z) = rotations(to_q60(theta), v, iters);
+    (denormalize(v, e), from_q60(z))
+}
+
+// rotate() with the given shifts (already validated) in place of
+// 0..iters, see CordicConfig::schedule()
+#[cfg(feature = "float")]
+pub(crate) fn rotate_scheduled(
+    theta: FixedPoint,
+    v: [FixedPoint; 2],
+    shifts: &[u8],
+) -> ([FixedPoint; 2], FixedPoint) {
+    let nan = FixedPoint::new(f64::NAN);
+    let (theta, negate) = fold_quadrant(reduce(theta));
+    let (v, e) = match normalize(v) {
+        Some(normalized) if theta.to_f64().is_finite() => normalized,
+        _ => return ([nan, nan], nan),
+    };
+
+    let mut v = if negate { [-v[0], -v[1]] } else { v };
+    let mut z = to_q60(theta);
+    for (step, &shift) in shifts.iter().enumerate() {
+        let i = shift as usize;
+        let Some(&angle) = ANGLES.get(i) else {
+            continue;
+        };
+        let counterclockwise = z >= 0;
+        if counterclockwise {
+            v = [v[0] - (v[1] >> i), v[1] + (v[0] >> i)];
+            z -= angle;
+        } else {
+            v = [v[0] + (v[1] >> i), v[1] - (v[0] >> i)];
+            z += angle;
+        }
+        log_iteration(step, counterclockwise, z, v);
+    }
+    (denormalize(v, e), from_q60(z))
+}
+
+// The directions rotate() takes for theta, as a mask with bit i set
+// where iteration i goes counterclockwise, and whether the quadrant fold
+// negates the vector. None if theta isn't finite
+#[cfg(feature = "float")]
+pub(crate) fn directions(theta: FixedPoint, iters: usize) -> Option<(u64, bool)> {
+    let (theta, negate) = fold_quadrant(reduce(theta));
+    if !theta.to_f64().is_finite() {
+        return None;
+    }
+    let mut z = to_q60(theta);
+    let mut mask = 0;
+    for (i, angle) in ANGLES.iter().enumerate().take(iters.min(64)) {
+        if z >= 0 {
+            mask |= 1 << i;
+            z -= angle;
+        } else {
+            z += angle;
+        }
+    }
+    Some((mask, negate))
+}
+
+// rotate() with the directions already decided, so there's nothing left
+// per vector but shifts and adds
+#[cfg(feature = "float")]
+pub(crate) fn replay(v: [FixedPoint; 2], mask: u64, iters: usize, negate: bool) -> [FixedPoint; 2] {
+    let (v, e) = match normalize(v) {
+        Some(normalized) => normalized,
+        None => return [FixedPoint::new(f64::NAN); 2],
+    };
+    let mut v = if negate { [-v[0], -v[1]] } else { v };
+    for i in 0..iters.min(64) {
+        v = if mask >> i & 1 == 1 {
+            [v[0] - (v[1] >> i), v[1] + (v[0] >> i)]
+        } else {
+            [v[0] + (v[1] >> i), v[1] - (v[0] >> i)]
+        };
+    }
+    denormalize(v, e)
+}
+
+// Integer counterpart of cordic::vector_raw, returning the magnitude
+// (without the gain undone) and angle of `v`
+#[cfg(feature = "float")]
+pub(crate) fn vector(v: [FixedPoint; 2], iters: usize) -> (FixedPoint, FixedPoint) {
+    let nan = FixedPoint::new(f64::NAN);
+    let zero = FixedPoint::new(0.0);
+
+    // Left half-plane folding, see vector_raw. This has to look at the
+    // FixedPoint since -0.0 doesn't survive conversion to an integer
+    let (v, z) = if v[0] < zero {
+        let z = if v[1].is_sign_negative() { -PI } else { PI };
+        ([-v[0], -v[1]], z)
+    } else {
+        (v, 0)
+    };
+    let (v, e) = match normalize(v) {
+        Some(normalized) => normalized,
+        None => return (nan, nan),
+    };
+
+    let (v, z) = micro_vectoring(v, z, iters);
+    (denormalize(v, e)[0], from_q60(z))
+}
+
+#[cfg(test)]
+mod q60_tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!["1".parse::<Q60>().unwrap(), Q60::from_bits(1 << 60)];
+        assert_eq!["-0.5".parse::<Q60>().unwrap(), Q60::from_bits(-(1 << 59))];
+        assert_eq!["+.25".parse::<Q60>().unwrap(), Q60::from_bits(1 << 58)];
+        assert_eq![
+            "3.14159265358979323846264338327950288"
+                .parse::<Q60>()
+                .unwrap(),
+            Q60(PI)
+        ];
+        for bad in &[
+            "",
+            "-",
+            ".",
+            "abc",
+            "1.2.3",
+            "1e3",
+            "8",
+            "-8.5",
+            "-8.000000000000000001",
+            "7.9999999999999999999",
+            "18",
+        ] {
+            assert![bad.parse::<Q60>().is_err(), "{:?}", bad];
+        }
+
+        // The whole range, i64::MIN included
+        assert_eq!["-8".parse::<Q60>().unwrap(), Q60::from_bits(i64::MIN)];
+        assert_eq!["-8.0000".parse::<Q60>().unwrap(), Q60::from_bits(i64::MIN)];
+        assert_eq![
+            "-7.9999999999999999999".parse::<Q60>().unwrap(),
+            Q60::from_bits(i64::MIN)
+        ];
+        assert_eq![
+            "7.999999999999999999".parse::<Q60>().unwrap(),
+            Q60::from_bits(i64::MAX)
+        ];
+    }
+
+    #[test]
+    fn parse_rounding() {
+        // 2^-61 and 3 * 2^-61 exactly, halfway between Q60 values, go to
+        // the even one. Anything past them, however far down, goes up
+        let half = "0.0000000000000000004336808689942017736029811203479766845703125";
+        let three_halves = "0.0000000000000000013010426069826053208089433610439300537109375";
+        let parse = |s: &str| s.parse::<Q60>().unwrap().to_bits();
+        assert_eq![parse(half), 0];
+        assert_eq![parse(three_halves), 2];
+        assert_eq![parse(&format!("{}000000000000000000000000001", half)), 1];
+        assert_eq![parse(&format!("-{}1", half)), -1];
+        assert_eq![parse(&format!("{}1", three_halves)), 2];
+        // Just under halfway, past where digits used to be cut off
+        assert_eq![
+            parse("0.00000000000000000043368086899420177360298112034797668"),
+            0
+        ];
+        assert_eq![
+            parse("0.000000000000000000433680868994201773602981120347976685"),
+            1
+        ];
+        assert_eq![parse(&format!("1.{}", "0".repeat(500))), 1 << 60];
+    }
+
+    #[test]
+    fn display_round_trips() {
+        assert_eq![Q60::from_bits(1 << 60).to_string(), "1"];
+        assert_eq![Q60::from_bits(-(3 << 58)).to_string(), "-0.75"];
+        for &bits in &[
+            1,
+            -1,
+            PI,
+            -TWO_PI,
+            i64::MAX,
+            i64::MIN + 1,
+            i64::MIN,
+            0x0123_4567_89ab_cdef,
+        ] {
+            let q = Q60::from_bits(bits);
+            assert_eq![q.to_string().parse::<Q60>().unwrap(), q, "{}", q];
+        }
+    }
+
+    #[test]
+    fn sincos() {
+        // cos(1) and sin(1) to 30 places
+        let cos = "0.540302305868139717400936607442".parse::<Q60>().unwrap();
+        let sin = "0.841470984807896506652502321630".parse::<Q60>().unwrap();
+        let [c, s] = sincos_q60("1".parse().unwrap(), 60).unwrap();
+        assert![(c.to_bits() - cos.to_bits()).abs() < 64, "{} {}", c, cos];
+        assert![(s.to_bits() - sin.to_bits()).abs() < 64, "{} {}", s, sin];
+
+        // Reduction and the quadrant fold
+        let [c, s] = sincos_q60("-2".parse().unwrap(), 60).unwrap();
+        let [c7, s7] = sincos_q60(Q60::from_bits(-2 * (1 << 60) + TWO_PI), 60).unwrap();
+        assert![(c.to_bits() - c7.to_bits()).abs() < 64 && (s.to_bits() - s7.to_bits()).abs() < 64];
+        assert![c.to_bits() < 0 && s.to_bits() < 0];
+
+        assert_eq![
+            sincos_q60(Q60::from_bits(0), 0),
+            Err(CordicError::IterationOutOfRange(0))
+        ];
+    }
+
+    #[test]
+    fn unrolled() {
+        for i in -200..=200 {
+            let z = i * (1 << 53);
+            let v = [GAINS[16], 1 << 40];
+            assert_eq![micro_rotations_16(z, v), micro_rotations(z, v, 16)];
+            assert_eq![micro_rotations_24(z, v), micro_rotations(z, v, 24)];
+            assert_eq![micro_rotations_32(z, v), micro_rotations(z, v, 32)];
+            assert_eq![rotations(z, v, 24), micro_rotations(z, v, 24)];
+        }
+    }
+
+    #[test]
+    fn fixed_iterations() {
+        // sincos_q60_fixed::<0>() or ::<62>() wouldn't compile
+        for i in -100..=100 {
+            let theta = Q60::from_bits(i * (1 << 55));
+            assert_eq![
+                sincos_q60_fixed::<24>(theta),
+                sincos_q60(theta, 24).unwrap()
+            ];
+            assert_eq![
+                sincos_q60_fixed::<61>(theta),
+                sincos_q60(theta, 61).unwrap()
+            ];
+        }
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+    use crate::cordic::{Backend, CordicConfig};
+
+    // Bit patterns produced by the integer backend. These only involve
+    // integer arithmetic plus correctly rounded conversions and
+    // multiplies, so they have to come out identical on every target; a
+    // mismatch means either the kernel changed or a platform is doing
+    // something it shouldn't
+    #[rustfmt::skip]
+    const GOLDEN_ROTATIONS: [(f64, usize, u64, u64); 6] = [
+        (0.0, 53, 0x3fefffffffffffff, 0xbc8e5cd66add286d),
+        (1.0, 53, 0x3fe14a280fb5068d, 0x3feaed548f090cec),
+        (-2.5, 53, 0xbfe9a2f7ef858b7e, 0xbfe326af0dcfcaaf),
+        (0.3, 16, 0x3fee921c01ae5916, 0x3fd2e9d95dddd1c6),
+        (3.0, 8, 0xbfefa83bcbeec267, 0x3fc2afbe1a526985),
+        (100.0, 32, 0x3feb981dbf84f199, 0xbfe03425b7583ed9),
+    ];
+
+    #[rustfmt::skip]
+    const GOLDEN_VECTORS: [(f64, f64, usize, u64, u64); 3] = [
+        (3.0, 4.0, 53, 0x4013ffffffffffff, 0x3fedac670561bb4f),
+        (-1.0, 0.5, 24, 0x3ff1e3779b97f49b, 0x40056c6e6a0ef583),
+        (1e6, -1e-3, 40, 0x412e847fffffffff, 0xbe1131e230800000),
+    ];
+
+    #[test]
+    fn golden_rotations() {
+        for &(theta, iters, x, y) in GOLDEN_ROTATIONS.iter() {
+            let ret = CordicConfig::new(iters)
+                .rotate(FixedPoint::new(theta))
+                .unwrap();
+            assert_eq![
+                ret.x.to_f64().to_bits(),
+                x,
+                "cos({}) at {} iterations",
+                theta,
+                iters
+            ];
+            assert_eq![
+                ret.y.to_f64().to_bits(),
+                y,
+                "sin({}) at {} iterations",
+                theta,
+                iters
+            ];
+        }
+    }
+
+    #[test]
+    fn golden_vectors() {
+        for &(x, y, iters, magnitude, angle) in GOLDEN_VECTORS.iter() {
+            let ret = CordicConfig::new(iters)
+                .vector([FixedPoint::new(x), FixedPoint::new(y)])
+                .unwrap();
+            assert_eq![ret.magnitude.to_f64().to_bits(), magnitude];
+            assert_eq![ret.angle.to_f64().to_bits(), angle];
+        }
+    }
+
+    // The two backends are different roundings of the same iterations,
+    // so they should agree to within a few f64 ulps at full precision
+    #[test]
+    fn matches_float_backend() {
+        for i in -628..=628 {
+            let theta = FixedPoint::new(i as f64 / 100.0);
+            let integer = CordicConfig::default().rotate(theta).unwrap();
+            let float = CordicConfig::default()
+                .backend(Backend::Float)
+                .rotate(theta)
+                .unwrap();
+            assert![(integer.x - float.x).abs() < FixedPoint::new(1e-14)];
+            assert![(integer.y - float.y).abs() < FixedPoint::new(1e-14)];
+        }
+    }
+
+    #[test]
+    fn angle_table() {
+        for (i, &angle) in ANGLES.iter().enumerate() {
+            let expected = 2_f64.powi(-(i as i32)).atan();
+            let diff = (from_q60(angle).to_f64() - expected).abs();
+            // libm's rounding of atan, plus half a Q60 step
+            let allowed = expected * f64::EPSILON + 2_f64.powi(-FRAC_BITS - 1);
+            assert![diff <= allowed, "atan(2^-{})", i];
+        }
+        assert_eq![from_q60(PI).to_f64(), std::f64::consts::PI];
+    }
+
+    // The public tables are the ones the backends rotate with, and the
+    // two backends' agree to f64 precision, or Q60's where that's coarser
+    #[test]
+    fn public_tables() {
+        assert_eq![angle_table_q60(0), Err(CordicError::IterationOutOfRange(0))];
+        assert_eq![gain_q60(0), Err(CordicError::IterationOutOfRange(0))];
+        assert_eq![angle_table_q60(usize::MAX).unwrap().len(), ANGLES.len()];
+        assert_eq![gain_q60(usize::MAX), Ok(Q60(GAINS[ANGLES.len()]))];
+
+        let float = crate::cordic::angle_table(MAX_ITERS).unwrap();
+        let integer = angle_table_q60(MAX_ITERS).unwrap();
+        assert_eq![float.len(), integer.len()];
+        for (f, q) in float.iter().zip(integer) {
+            // libm's rounding of atan, plus half a Q60 step
+            let allowed = f.to_f64() * f64::EPSILON + 2_f64.powi(-FRAC_BITS - 1);
+            assert![(from_q60(q.0) - *f).abs().to_f64() <= allowed];
+        }
+        for iters in 1..=MAX_ITERS {
+            let k = from_q60(gain_q60(iters).unwrap().0);
+            assert![(k - crate::cordic::gain(iters).unwrap()).abs().to_f64() <= f64::EPSILON];
+        }
+    }
+
+    #[test]
+    fn non_finite() {
+        let one = [FixedPoint::new(1.0), FixedPoint::new(0.0)];
+        let (v, _) = rotate(FixedPoint::new(f64::NAN), one, 20);
+        assert![v[0].to_f64().is_nan()];
+        let (v, _) = rotate(FixedPoint::new(f64::INFINITY), one, 20);
+        assert![v[1].to_f64().is_nan()];
+        let (magnitude, _) = vector([FixedPoint::new(f64::INFINITY), FixedPoint::new(0.0)], 20);
+        assert![magnitude.to_f64().is_nan()];
+    }
+
+    #[test]
+    fn narrow() {
+        for i in -600..=600 {
+            let theta = Q60(i * (1 << 50));
+            assert_eq![
+                sincos_narrow(theta, 40, 60, 60, 60),
+                sincos_q60(theta, 40).unwrap()
+            ];
+
+            // 16 fractional bits leave the last few bits to rounding
+            let wide = sincos_q60(theta, 16).unwrap();
+            let narrow = sincos_narrow(theta, 16, 16, 16, 16);
+            for (a, b) in wide.iter().zip(&narrow) {
+                assert![(a.0 - b.0).abs() < 16 << 44];
+                assert_eq![b.0 & ((1 << 44) - 1), 0];
+            }
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use crate::cordic::{
+    angles, fold_octant, fold_quadrant, gain, reduce, Backend, CordicConfig, GainCompensation, MAX_ITERS,
+};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::integer::{self, ANGLES};
+use crate::math;
+use crate::schedule::stretch;
+
+// The engine's state after each micro-rotation, for code that wants to
+// watch the computation (plots, convergence analysis) or decide for
+// itself when to stop. rotate() runs a fixed number of iterations and
+// hands back only the end; iterations() hands back every step and lets
+// the caller take as many as it likes:
+//
+//   for step in config.iterations(theta)?.take_while(|s| s.z.abs() > tol) {
+//       plot(step.x * step.gain, step.y * step.gain);
+//   }
+//
+// It runs every micro-rotation the datapath can make (MAX_ITERS of
+// them, or the custom schedule), whatever the config's iteration count.
+// Taking that many steps lands on exactly the vector rotate() would
+// have before undoing the gain. The fast paths (special and small
+// angles) and io_width's narrow datapath aren't iterations at all, so
+// they're left out and a config with io_width set is a DomainError
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    // Counting from 0
+    pub iteration: usize,
+    // The micro-rotation was by atan(2^-shift)
+    pub shift: usize,
+    pub counterclockwise: bool,
+    // The vector so far, without the gain undone: x * gain and y * gain
+    // are the cosine and sine estimates
+    pub x: FixedPoint,
+    pub y: FixedPoint,
+    // The angle still left to rotate by
+    pub z: FixedPoint,
+    // K for the micro-rotations so far
+    pub gain: FixedPoint,
+}
+
+// The iterator CordicConfig::iterations() returns
+#[derive(Debug, Clone)]
+pub struct Iterations {
+    datapath: Datapath,
+    shifts: Vec<u8>,
+    custom: bool,
+    next: usize,
+    gain: f64,
+}
+
+// The registers of whichever backend is running. The integer one keeps
+// the normalized Q60 vector and the power of two taken out of it, like
+// integer::rotate()
+#[derive(Debug, Clone)]
+enum Datapath {
+    Integer { v: [i64; 2], e: i32, z: i64 },
+    Float { v: [FixedPoint; 2], z: FixedPoint },
+}
+
+impl CordicConfig {
+    // Steps of the rotation of (1, 0) by theta, folded into range the
+    // same way rotate() folds it. theta has to be finite
+    pub fn iterations(&self, theta: FixedPoint) -> Result<Iterations, CordicError> {
+        if self.io_bits.is_some() {
+            return Err(CordicError::DomainError("iterations"));
+        }
+        if !theta.to_f64().is_finite() {
+            return Err(CordicError::InvalidAngle("iterations"));
+        }
+        let (shifts, custom) = match self.custom_shifts()? {
+            Some(shifts) => (shifts.as_slice().to_vec(), true),
+            None => ((0..MAX_ITERS as u8).collect(), false),
+        };
+
+        let unit = [FixedPoint::new(1.0), FixedPoint::new(0.0)];
+        let (theta, v) = if self.octant {
+            fold_octant(reduce(theta), unit)
+        } else {
+            (theta, unit)
+        };
+        let (theta, negate) = fold_quadrant(reduce(theta));
+        let v = if negate { [-v[0], -v[1]] } else { v };
+        let datapath = match self.backend {
+            Backend::Integer => {
+                // v is a unit vector, which always normalizes
+                let (v, e) = integer::normalize(v).expect("(1, 0) is finite");
+                Datapath::Integer {
+                    v,
+                    e,
+                    z: integer::to_q60(theta),
+                }
+            }
+            Backend::Float => Datapath::Float { v, z: theta },
+        };
+        Ok(Iterations {
+            datapath,
+            shifts,
+            custom,
+            next: 0,
+            gain: 1.0,
+        })
+    }
+
+    // [cos theta, sin theta] from as many iterations as it takes for
+    // `stop` to say so, along with the step it stopped at. stop sees each
+    // step as it's made, and a stop that never says yes gets every step
+    // iterations() has. It can keep state of its own, e.g. to stop once
+    // the outputs have settled:
+    //
+    //   let mut last = None;
+    //   config.sincos_until(theta, |step| {
+    //       let bits = ((step.y * step.gain).to_f64() * 2f64.powi(20)).round();
+    //       last.replace(bits) == Some(bits)
+    //   })?;
+    //
+    // The gain is undone with a multiply by the gain of the steps taken,
+    // whatever the config's compensation (the CSD constant and the
+    // prescale are only known for a fixed count), unless it's
+    // GainCompensation::None, which leaves the raw vector
+    pub fn sincos_until(
+        &self,
+        theta: FixedPoint,
+        mut stop: impl FnMut(&Step) -> bool,
+    ) -> Result<([FixedPoint; 2], Step), CordicError> {
+        let mut steps = self.iterations(theta)?;
+        let mut last = steps.next().expect("every schedule has a step");
+        while !stop(&last) {
+            match steps.next() {
+                Some(step) => last = step,
+                None => break,
+            }
+        }
+        let ret = match self.compensation {
+            GainCompensation::None => [last.x, last.y],
+            GainCompensation::Post | GainCompensation::Prescale | GainCompensation::Csd => {
+                [last.x * last.gain, last.y * last.gain]
+            }
+        };
+        Ok((ret, last))
+    }
+}
+
+impl Iterator for Iterations {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        let iteration = self.next;
+        let shift = *self.shifts.get(iteration)? as usize;
+        self.next += 1;
+
+        let (counterclockwise, [x, y], z) = match &mut self.datapath {
+            Datapath::Integer { v, e, z } => {
+                let counterclockwise = *z >= 0;
+                if counterclockwise {
+                    *v = [v[0] - (v[1] >> shift), v[1] + (v[0] >> shift)];
+                    *z -= ANGLES[shift];
+                } else {
+                    *v = [v[0] + (v[1] >> shift), v[1] - (v[0] >> shift)];
+                    *z += ANGLES[shift];
+                }
+                (
+                    counterclockwise,
+                    integer::denormalize(*v, *e),
+                    integer::from_q60(*z),
+                )
+            }
+            Datapath::Float { v, z } => {
+                let angle = angles(MAX_ITERS)[shift];
+                let poweroftwo = FixedPoint::new(math::powi(2.0, -(shift as i32)));
+                let counterclockwise = *z >= FixedPoint::new(0.0);
+                if counterclockwise {
+                    *v = [v[0] - poweroftwo * v[1], poweroftwo * v[0] + v[1]];
+                    *z -= angle;
+                } else {
+                    *v = [v[0] + poweroftwo * v[1], -(poweroftwo * v[0]) + v[1]];
+                    *z += angle;
+                }
+                (counterclockwise, *v, *z)
+            }
+        };
+
+        // The usual schedule's gains are tabulated, a custom one's are
+        // built up the way schedule_gain() builds them
+        self.gain /= stretch(shift);
+        let gain = if self.custom {
+            FixedPoint::new(self.gain)
+        } else {
+            gain(iteration + 1).expect("the usual schedule stops at MAX_ITERS")
+        };
+        Some(Step {
+            iteration,
+            shift,
+            counterclockwise,
+            x,
+            y,
+            z,
+            gain,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.shifts.len() - self.next;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for Iterations {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_rotate() {
+        let thetas = [0.3, -1.2, 2.9, -3.0, 0.0, 100.0];
+        for backend in [Backend::Integer, Backend::Float] {
+            for octant in [false, true] {
+                for iters in [1, 16, 24, MAX_ITERS] {
+                    let config = CordicConfig::new(iters)
+                        .backend(backend)
+                        .octant_reduction(octant);
+                    for &theta in &thetas {
+                        let theta = FixedPoint::new(theta);
+                        let steps = config.iterations(theta).unwrap();
+                        assert_eq![steps.len(), MAX_ITERS];
+                        let last = steps.take(iters).last().unwrap();
+                        let ret = config.rotate(theta).unwrap();
+                        assert_eq![last.iteration, iters - 1];
+                        assert_eq![last.gain, ret.gain];
+                        assert_eq![[last.x * last.gain, last.y * last.gain], [ret.x, ret.y]];
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn steps() {
+        let config = CordicConfig::new(24);
+        let steps = config
+            .iterations(FixedPoint::new(0.5))
+            .unwrap()
+            .collect::<Vec<_>>();
+        // Starting from z = 0.5 the first step goes counterclockwise by
+        // pi/4 and overshoots
+        assert_eq![steps[0].shift, 0];
+        assert![steps[0].counterclockwise];
+        assert_eq![
+            [steps[0].x, steps[0].y],
+            [FixedPoint::new(1.0), FixedPoint::new(1.0)]
+        ];
+        assert![(steps[0].z.to_f64() - (0.5 - std::f64::consts::FRAC_PI_4)).abs() < 1e-15];
+        assert![!steps[1].counterclockwise];
+        // After the step by atan(2^-i) what's left is never more than it
+        for step in &steps[..40] {
+            let bound = 0.5f64.powi(step.shift as i32).atan();
+            assert![step.z.abs().to_f64() <= bound + 1e-15, "{:?}", step];
+        }
+
+        // A stopping rule of the caller's own
+        let tolerance = FixedPoint::new(1e-6);
+        let taken = config
+            .iterations(FixedPoint::new(0.5))
+            .unwrap()
+            .take_while(|step| step.z.abs() > tolerance)
+            .count();
+        assert![(15..=25).contains(&taken)];
+
+        // A custom schedule runs as given, repeats and all
+        let scheduled = CordicConfig::new(24).schedule(&[0, 1, 1, 3]);
+        let shifts = scheduled
+            .iterations(FixedPoint::new(0.5))
+            .unwrap()
+            .map(|step| step.shift)
+            .collect::<Vec<_>>();
+        assert_eq![shifts, [0, 1, 1, 3]];
+        let last = scheduled
+            .iterations(FixedPoint::new(0.5))
+            .unwrap()
+            .last()
+            .unwrap();
+        let ret = scheduled.rotate(FixedPoint::new(0.5)).unwrap();
+        assert_eq![last.gain, ret.gain];
+        assert_eq![last.x * last.gain, ret.x];
+
+        assert_eq![
+            config.sincos_until(FixedPoint::new(f64::NAN), |_| true),
+            Err(CordicError::InvalidAngle("iterations"))
+        ];
+        assert_eq![
+            config.iterations(FixedPoint::new(f64::NAN)).unwrap_err(),
+            CordicError::InvalidAngle("iterations")
+        ];
+        assert_eq![
+            config
+                .io_width(16)
+                .iterations(FixedPoint::new(0.5))
+                .unwrap_err(),
+            CordicError::DomainError("iterations")
+        ];
+    }
+
+    #[test]
+    fn until() {
+        let theta = FixedPoint::new(0.7);
+        for backend in [Backend::Integer, Backend::Float] {
+            let config = CordicConfig::new(24).backend(backend);
+
+            // Stopping at a count is rotate() at that count
+            let ([cos, sin], step) = config.sincos_until(theta, |s| s.iteration >= 23).unwrap();
+            let ret = config.rotate(theta).unwrap();
+            assert_eq![[cos, sin], [ret.x, ret.y]];
+            assert_eq![step.iteration, 23];
+
+            // On the residual angle, which the error follows
+            let eps = FixedPoint::new(1e-9);
+            let ([cos, sin], step) = config
+                .sincos_until(theta, |s| s.z.abs() < eps || s.iteration >= 40)
+                .unwrap();
+            assert![step.z.abs() < eps && step.iteration < 40];
+            assert![(cos.to_f64() - 0.7f64.cos()).abs() < 2e-9];
+            assert![(sin.to_f64() - 0.7f64.sin()).abs() < 2e-9];
+
+            // Once 20 bits of sine stop changing, with the stop keeping
+            // the last value itself
+            let mut last = None;
+            let mut calls = 0;
+            let ([_, sin], step) = config
+                .sincos_until(theta, |s| {
+                    calls += 1;
+                    let bits = ((s.y * s.gain).to_f64() * 2f64.powi(20)).round();
+                    last.replace(bits) == Some(bits)
+                })
+                .unwrap();
+            assert_eq![calls, step.iteration + 1];
+            assert![(sin.to_f64() - 0.7f64.sin()).abs() < 2f64.powi(-18)];
+
+            // Never stopping runs the lot, and no compensation is the raw
+            // vector
+            let raw = config.compensate_gain(false);
+            let ([x, _], step) = raw.sincos_until(theta, |_| false).unwrap();
+            assert_eq![step.iteration, MAX_ITERS - 1];
+            assert_eq![x, step.x];
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use crate::cordic::CordicConfig;
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+
+// The rotation that diagonalizes a symmetric 2x2 matrix [[a, b], [b, d]],
+// which is the step every CORDIC based Jacobi eigenvalue or SVD array
+// repeats on pairs of rows and columns. With J = [[cos, -sin], [sin, cos]],
+// J^T A J = diag(eigenvalues), larger first.
+//
+// The angle is half of atan2(2b, a - d), so it takes one vectoring pass
+// for that (whose magnitude r is also what the eigenvalues need, as
+// (a + d) / 2 +- r / 2) and one rotation pass by half of it, which in
+// hardware is just a shift of z
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schur {
+    pub theta: FixedPoint,
+    pub cos: FixedPoint,
+    pub sin: FixedPoint,
+    pub eigenvalues: [FixedPoint; 2],
+}
+
+pub fn jacobi_rotation(matrix: [[FixedPoint; 2]; 2], iters: usize) -> Result<Schur, CordicError> {
+    let [[a, b], [c, d]] = matrix;
+    if b != c {
+        return Err(CordicError::DomainError("jacobi_rotation"));
+    }
+    let config = CordicConfig::new(iters);
+    let two = FixedPoint::new(2.0);
+    let mean = (a + d) / two;
+
+    // Already diagonal, so there's nothing to vector (and vectoring a
+    // vector on the axis would only add error). Just order the diagonal
+    if b == FixedPoint::new(0.0) {
+        let (theta, cos, sin) = if a >= d {
+            (0.0, 1.0, 0.0)
+        } else {
+            (std::f64::consts::FRAC_PI_2, 0.0, 1.0)
+        };
+        return Ok(Schur {
+            theta: FixedPoint::new(theta),
+            cos: FixedPoint::new(cos),
+            sin: FixedPoint::new(sin),
+            eigenvalues: if a >= d { [a, d] } else { [d, a] },
+        });
+    }
+
+    let polar = config.vector([a - d, two * b])?;
+    let theta = polar.angle / two;
+    let rotation = config.rotate(theta)?;
+    let half_r = polar.magnitude / two;
+    Ok(Schur {
+        theta,
+        cos: rotation.x,
+        sin: rotation.y,
+        eigenvalues: [mean + half_r, mean - half_r],
+    })
+}
+
+impl Schur {
+    // J^T A J, which should come out diagonal
+    pub fn apply(&self, matrix: [[FixedPoint; 2]; 2]) -> [[FixedPoint; 2]; 2] {
+        let (c, s) = (self.cos, self.sin);
+        let j = [[c, -s], [s, c]];
+        let mut out = [[FixedPoint::new(0.0); 2]; 2];
+        for (row, out_row) in out.iter_mut().enumerate() {
+            for (col, entry) in out_row.iter_mut().enumerate() {
+                for k in 0..2 {
+                    for l in 0..2 {
+                        *entry += j[k][row] * matrix[k][l] * j[l][col];
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+// The singular value decomposition of any 2x2 matrix, M = U diag(sigma)
+// V^T with the singular values non-negative and the larger first, built
+// from two CORDIC rotations the way systolic SVD arrays do it: a Givens
+// rotation R by atan2(c - b, a + d) makes R^T M symmetric, and
+// jacobi_rotation() then diagonalizes that, so U = R J and V = J (with
+// columns negated or swapped to put the singular values in order)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Svd {
+    pub u: [[FixedPoint; 2]; 2],
+    pub sigma: [FixedPoint; 2],
+    pub v: [[FixedPoint; 2]; 2],
+}
+
+pub fn svd2(matrix: [[FixedPoint; 2]; 2], iters: usize) -> Result<Svd, CordicError> {
+    let [[a, b], [c, d]] = matrix;
+    let config = CordicConfig::new(iters);
+    let two = FixedPoint::new(2.0);
+
+    // Already symmetric means no Givens rotation, and nothing to vector
+    let phi = if b == c {
+        FixedPoint::new(0.0)
+    } else {
+        config.vector([a + d, c - b])?.angle
+    };
+    let givens = config.rotate(phi)?;
+    let (cos, sin) = (givens.x, givens.y);
+    let r = [[cos, -sin], [sin, cos]];
+
+    // R^T M, symmetric up to rounding, so the two off diagonal entries
+    // get averaged
+    let off = ((cos * b + sin * d) + (cos * c - sin * a)) / two;
+    let symmetric = [[cos * a + sin * c, off], [off, cos * d - sin * b]];
+    let schur = jacobi_rotation(symmetric, iters)?;
+    let j = [[schur.cos, -schur.sin], [schur.sin, schur.cos]];
+
+    let mut u = multiply(r, j);
+    let mut v = j;
+    let mut sigma = schur.eigenvalues;
+    for (k, value) in sigma.iter_mut().enumerate() {
+        if *value < FixedPoint::new(0.0) {
+            *value = -*value;
+            for row in u.iter_mut() {
+                row[k] = -row[k];
+            }
+        }
+    }
+    if sigma[0] < sigma[1] {
+        sigma.swap(0, 1);
+        for row in u.iter_mut().chain(v.iter_mut()) {
+            row.swap(0, 1);
+        }
+    }
+    Ok(Svd { u, sigma, v })
+}
+
+impl Svd {
+    // U diag(sigma) V^T, which should give back the original matrix
+    pub fn reconstruct(&self) -> [[FixedPoint; 2]; 2] {
+        let scaled = [
+            [self.u[0][0] * self.sigma[0], self.u[0][1] * self.sigma[1]],
+            [self.u[1][0] * self.sigma[0], self.u[1][1] * self.sigma[1]],
+        ];
+        let vt = [[self.v[0][0], self.v[1][0]], [self.v[0][1], self.v[1][1]]];
+        multiply(scaled, vt)
+    }
+}
+
+fn multiply(a: [[FixedPoint; 2]; 2], b: [[FixedPoint; 2]; 2]) -> [[FixedPoint; 2]; 2] {
+    let mut out = [[FixedPoint::new(0.0); 2]; 2];
+    for (row, out_row) in out.iter_mut().enumerate() {
+        for (col, entry) in out_row.iter_mut().enumerate() {
+            *entry = a[row][0] * b[0][col] + a[row][1] * b[1][col];
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(a: f64, b: f64, d: f64) -> [[FixedPoint; 2]; 2] {
+        [
+            [FixedPoint::new(a), FixedPoint::new(b)],
+            [FixedPoint::new(b), FixedPoint::new(d)],
+        ]
+    }
+
+    #[test]
+    fn diagonalizes() {
+        for &(a, b, d) in &[
+            (2.0, 1.0, 2.0),
+            (4.0, -1.5, 1.0),
+            (-1.0, 3.0, 5.0),
+            (0.0, 1e-3, 0.0),
+            (1e6, 2e5, -3e5),
+        ] {
+            let m = matrix(a, b, d);
+            let schur = jacobi_rotation(m, 40).unwrap();
+            let diagonal = schur.apply(m);
+            let scale = a.abs().max(b.abs()).max(d.abs());
+            let tolerance = scale * 1e-10;
+
+            let off = diagonal[0][1]
+                .to_f64()
+                .abs()
+                .max(diagonal[1][0].to_f64().abs());
+            assert![off < tolerance, "{:?} {}", (a, b, d), off];
+            for (k, eigenvalue) in schur.eigenvalues.iter().enumerate() {
+                let error = (diagonal[k][k] - *eigenvalue).to_f64().abs();
+                assert![error < tolerance, "{:?} {}", (a, b, d), error];
+            }
+            assert![schur.eigenvalues[0] >= schur.eigenvalues[1]];
+            // The trace is preserved
+            let trace = (schur.eigenvalues[0] + schur.eigenvalues[1]).to_f64();
+            assert![(trace - (a + d)).abs() < tolerance];
+        }
+    }
+
+    #[test]
+    fn diagonal_and_invalid() {
+        let schur = jacobi_rotation(matrix(1.0, 0.0, 3.0), 24).unwrap();
+        assert_eq![
+            schur.eigenvalues,
+            [FixedPoint::new(3.0), FixedPoint::new(1.0)]
+        ];
+        assert_eq![
+            schur.apply(matrix(1.0, 0.0, 3.0))[0][0],
+            FixedPoint::new(3.0)
+        ];
+
+        let asymmetric = [
+            [FixedPoint::new(1.0), FixedPoint::new(2.0)],
+            [FixedPoint::new(0.0), FixedPoint::new(1.0)],
+        ];
+        assert![jacobi_rotation(asymmetric, 24).is_err()];
+        assert![jacobi_rotation(matrix(1.0, 1.0, 1.0), 0).is_err()];
+    }
+
+    // The singular values straight from the f64 formula, the square
+    // roots of the eigenvalues of M^T M
+    fn reference(a: f64, b: f64, c: f64, d: f64) -> [f64; 2] {
+        let t = a * a + b * b + c * c + d * d;
+        let det = a * d - b * c;
+        let root = (t * t - 4.0 * det * det).max(0.0).sqrt();
+        [
+            ((t + root) / 2.0).sqrt(),
+            ((t - root) / 2.0).max(0.0).sqrt(),
+        ]
+    }
+
+    #[test]
+    fn svd() {
+        for &(a, b, c, d) in &[
+            (1.0, 2.0, 3.0, 4.0),
+            (0.5, -0.25, 0.125, 0.75),
+            (-3.0, 0.0, 0.0, 1.0),
+            (2.0, 1.0, 1.0, 2.0),
+            (1.0, 1.0, 1.0, 1.0),
+            (0.0, -1.0, 1.0, 0.0),
+            (0.0, 0.0, 0.0, 0.0),
+        ] {
+            let m = [
+                [FixedPoint::new(a), FixedPoint::new(b)],
+                [FixedPoint::new(c), FixedPoint::new(d)],
+            ];
+            let svd = svd2(m, 40).unwrap();
+            let expected = reference(a, b, c, d);
+            for k in 0..2 {
+                let error = (svd.sigma[k].to_f64() - expected[k]).abs();
+                assert![
+                    error < 1e-9,
+                    "{:?} {:?} {:?}",
+                    (a, b, c, d),
+                    svd.sigma,
+                    expected
+                ];
+            }
+            let back = svd.reconstruct();
+            for (row, m_row) in back.iter().zip(&m) {
+                for (x, y) in row.iter().zip(m_row) {
+                    assert![
+                        (*x - *y).to_f64().abs() < 1e-9,
+                        "{:?} {:?}",
+                        (a, b, c, d),
+                        back
+                    ];
+                }
+            }
+        }
+    }
+}
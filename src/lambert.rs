@@ -0,0 +1,76 @@
+use crate::cordic::{checked_iters, fold_quadrant, reduce};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+
+// Lambert's continued fraction for the tangent, as a foil to CORDIC:
+//
+//   tan x = x / (1 - x^2 / (3 - x^2 / (5 - x^2 / (7 - ...))))
+//
+// Where CORDIC never divides, this is nothing but divides, one per term.
+// It converges much faster than the Taylor series (ten terms are about
+// f64 precision for |x| <= pi/4), so it's a fair question which wins on
+// a core with a fast divider. Evaluated from the bottom up, `terms`
+// terms are `terms` divides.
+//
+// cos and sin come from the tangent of the half angle, t = tan(x / 2):
+//   cos x = (1 - t^2) / (1 + t^2),  sin x = 2t / (1 + t^2)
+// which never meets the tangent's poles once x is folded into [-pi/2,
+// pi/2] the way CORDIC does it. Like iteration counts, 0 terms is an
+// error and anything past MAX_ITERS is clamped
+
+// tan x, truncated to `terms` terms. Accurate for |x| up to about pi/4
+// with enough terms and fine out to the poles with more, but there's no
+// reduction, so big x wants reducing first
+pub fn lambert_tan(x: FixedPoint, terms: usize) -> Result<FixedPoint, CordicError> {
+    let terms = checked_iters(terms)?;
+    let square = x * x;
+    let mut denominator = FixedPoint::new((2 * terms - 1) as f64);
+    for k in (1..terms).rev() {
+        denominator = FixedPoint::new((2 * k - 1) as f64) - square / denominator;
+    }
+    Ok(x / denominator)
+}
+
+// [cos theta, sin theta] from lambert_tan() of the half angle
+pub fn lambert(theta: FixedPoint, terms: usize) -> Result<[FixedPoint; 2], CordicError> {
+    let (theta, negate) = fold_quadrant(reduce(theta));
+    let t = lambert_tan(theta * FixedPoint::new(0.5), terms)?;
+    let one = FixedPoint::new(1.0);
+    let square = t * t;
+    let scale = one / (one + square);
+    let ret = [(one - square) * scale, (t + t) * scale];
+    Ok(if negate { ret.map(|x| -x) } else { ret })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converges() {
+        assert_eq![
+            lambert_tan(FixedPoint::new(0.5), 0),
+            Err(CordicError::IterationOutOfRange(0))
+        ];
+        // One term is just x
+        assert_eq![
+            lambert_tan(FixedPoint::new(0.5), 1),
+            Ok(FixedPoint::new(0.5))
+        ];
+
+        let mut previous = f64::INFINITY;
+        for terms in 1..=10 {
+            let mut worst = 0.0f64;
+            for i in -628..=628 {
+                let theta = i as f64 / 100.0;
+                let [cos, sin] = lambert(FixedPoint::new(theta), terms).unwrap();
+                worst = worst
+                    .max((cos.to_f64() - theta.cos()).abs())
+                    .max((sin.to_f64() - theta.sin()).abs());
+            }
+            assert![worst < previous || worst < 1e-15, "{} terms", terms];
+            previous = worst;
+        }
+        assert![previous < 1e-15];
+    }
+}
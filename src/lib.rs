@@ -0,0 +1,188 @@
+// CORDIC Demonstration in Rust
+//
+// Feel free to copy-paste into https://play.rust-lang.org
+//
+// This uses fixed-point integers to demonstrate the efficiency
+// realized by power-of-two multiplications and divisions.
+//
+// Floating point integers are normally broken up into three segments
+//   1. Base
+//   2. Exponent
+//   3. "Special" bits
+//
+// Floating point works similarly to scientific notation, where base is
+// the base, exponent is the exponent (although normally expressed as
+// 2^n instead of 10^n). The "Special" bits define things like
+// Not-a-Number (NaN) for cases like 0/0, +Inf and -Inf in cases
+// where the base and exponents do not have enough bits to express
+// the full value
+//
+// Fixed point is just an integer with a set number of places reserved
+// at the end for fractional components
+//
+// If you have any questions, feel free to email me at djh4@illinois.edu
+//
+// NOTE: The library is split into a handful of small modules, and
+// src/main.rs is the command line driver. Each module only depends on
+// the ones it imports, so pasting fixed.rs, error.rs and cordic.rs
+// together (minus the `use crate::` lines) still works on
+// play.rust-lang.org
+//
+// No public function panics, whatever it's given: bad arguments come back
+// as a CordicError, or as NaN where a function returns plain numbers the
+// way f64's do. tests/panic_free.rs holds everything to that. The
+// exceptions are running out of memory and the `checks` feature, which
+// panics on purpose
+
+#![cfg_attr(not(feature = "float"), deny(clippy::float_arithmetic))]
+
+// Without the `float` feature (on by default) only the integer backend
+// (plus errors and logging) is built, for soft-float targets where any
+// f64 means pulling in a software float library. Everything that needs
+// floating point is declared through this
+macro_rules! float_only {
+    ($($item:item)*) => {
+        $(#[cfg(feature = "float")] $item)*
+    };
+}
+
+pub mod chebyshev;
+pub mod csd;
+mod error;
+#[cfg(not(feature = "float"))]
+mod float_guard;
+mod integer;
+mod log;
+mod recoding;
+mod redundant;
+mod selftest;
+
+float_only! {
+    mod algorithm;
+    mod bench;
+    mod cache;
+    #[cfg(feature = "cmsis")]
+    pub mod cmsis;
+    mod complex;
+    mod cordic;
+    mod correctly_rounded;
+    mod edge;
+    mod export;
+    mod fault;
+    #[cfg(feature = "ffi")]
+    pub mod ffi;
+    mod fixed;
+    #[cfg(feature = "fixed")]
+    mod fixed_interop;
+    mod global;
+    mod heatmap;
+    mod hyperbolic;
+    mod identity;
+    mod image;
+    mod iterations;
+    mod jacobi;
+    mod lambert;
+    mod linear;
+    mod manifest;
+    mod math;
+    mod monotone;
+    mod nco;
+    #[cfg(feature = "num-traits")]
+    mod num_interop;
+    mod pipeline;
+    mod pll;
+    mod power;
+    #[cfg(feature = "python")]
+    pub mod python;
+    mod qformat;
+    mod quantization;
+    mod reference;
+    mod remez;
+    mod resample;
+    mod schedule;
+    mod sequence;
+    #[cfg(feature = "serve")]
+    pub mod serve;
+    mod sinc;
+    mod sintable;
+    mod spectrum;
+    mod stress;
+    mod table;
+    mod taylor;
+    mod trig;
+    #[cfg(feature = "wasm")]
+    pub mod wasm;
+    pub mod window;
+    mod wordlength;
+    mod workbench;
+}
+
+pub use csd::{csd, gain_csd, CsdMultiplier};
+pub use error::CordicError;
+pub use integer::{angle_table_q60, gain_q60, sincos_q60, sincos_q60_fixed, Q60};
+pub use log::{set_logger, set_max_level, LogLevel, Logger};
+pub use recoding::{sincos_recoded, Recoded};
+pub use redundant::{
+    sincos_carry_save, sincos_sigma, CarrySave, ExactSign, LookAhead, MsbWindow, SigmaCordic,
+    SigmaSelection,
+};
+pub use selftest::{selftest, Check};
+
+float_only! {
+    pub use algorithm::{
+        accuracy, accuracy_against, pareto, relative_error, ulp_distance, Accuracy, Cost, Lambert, Registry, Taylor,
+        Tradeoff, TrigAlgorithm,
+    };
+    pub use bench::{sweep, sweep_angles, ErrorStats, SweepPoint, SweepResult};
+    pub use cache::warm_tables;
+    pub use complex::{cis, expi, Complex};
+    pub use cordic::{
+        angle_table, atan2, checked_iters, cordic, fold_octant, fold_quadrant, gain, reduce, Backend, CordicConfig,
+        GainCompensation, Rotation, Vectoring, GAIN_LIMIT, MAX_ITERS, MAX_SCHEDULE,
+    };
+    pub use edge::{edge_cases, edge_inputs, EdgeCase};
+    pub use export::{matlab_arrays, npy, npy_grid, npz, wav_pcm16};
+    pub use fault::{inject, random_faults, Fault, FaultEffect, Register};
+    #[cfg(feature = "ffi")]
+    pub use ffi::{CordicStatus, CordicStep};
+    // self:: since with the `fixed` feature there's a crate of that name too
+    pub use self::fixed::FixedPoint;
+    pub use global::{default_config, set_default, sincos};
+    pub use heatmap::{heatmap, Heatmap};
+    pub use hyperbolic::{acosh, asinh, atanh, cosh, exp, exp2, ln, log, log2, powf, sinh, sqrt, tanh};
+    pub use identity::{
+        harmonics, sin_cos_difference, sin_cos_double, sin_cos_half, sin_cos_multiples, sin_cos_sum,
+        HalfAngle,
+    };
+    pub use image::Image;
+    pub use iterations::{Iterations, Step};
+    pub use jacobi::{jacobi_rotation, svd2, Schur, Svd};
+    pub use lambert::{lambert, lambert_tan};
+    pub use linear::{recip, rsqrt};
+    pub use manifest::{table_fingerprint, Manifest};
+    pub use nco::{Chirp, Nco};
+    pub use pipeline::{Architecture, Pipeline, PipelineReport};
+    pub use pll::Pll;
+    pub use power::{toggles, Toggles};
+    pub use qformat::{convert, Overflow, QFormat, Rounding};
+    pub use quantization::{
+        analyze_quantization, quantize_coefficients, QuantizationAnalysis, Strategy, STRATEGIES,
+    };
+    pub use reference::{DoubleDouble, Reference};
+    pub use remez::{minimax, Minimax, QuantizedPolynomial, MAX_DEGREE};
+    pub use resample::Resampler;
+    pub use schedule::{prune, Schedule};
+    pub use sequence::{precompute_sigmas, rotate_points, SigmaSequence};
+    pub use sinc::{sinc, sinc_normalized};
+    pub use sintable::{generate_table, sin_lut, Interpolation, Lut, SinTable};
+    pub use spectrum::{fft, power_spectrum, sfdr, tone_quality, Sfdr, ToneQuality};
+    pub use stress::{minimize, stress, Failure};
+    pub use table::{Endian, Tables};
+    pub use taylor::taylor;
+    pub use trig::FixedTrig;
+    pub use wordlength::{
+        attribute, bit_agreement, min_word_length, word_error, BitAgreement, ErrorSources,
+        WordLength,
+    };
+    pub use workbench::{Approximation, Composed, FunctionAccuracy, FunctionTable, Workbench};
+}
@@ -0,0 +1,116 @@
+use crate::cordic::MAX_ITERS;
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::hyperbolic::{limit, scale_pow2, split_pow2, sqrt};
+
+// Linear CORDIC is the degenerate case where the "rotation" is a shear:
+//
+//   x' = x
+//   y' = y - sigma * 2^-i * x
+//   z' = z + sigma * 2^-i
+//
+// Driving y to zero (vectoring) leaves y / x in z, so it divides with
+// nothing but shifts and adds, one quotient bit per iteration. It only
+// converges for |y / x| <= sum(2^-i) ~= 2
+fn divide(y: FixedPoint, x: FixedPoint) -> FixedPoint {
+    let fixed_point_zero = FixedPoint::new(0.0);
+    let mut y = y;
+    let mut z = fixed_point_zero;
+    let mut poweroftwo = FixedPoint::new(1.0);
+    for _ in 0..MAX_ITERS {
+        if y < fixed_point_zero {
+            y += poweroftwo * x;
+            z -= poweroftwo;
+        } else {
+            y -= poweroftwo * x;
+            z += poweroftwo;
+        }
+        poweroftwo /= FixedPoint::new(2.0);
+    }
+    z
+}
+
+// 1 / x. With x = m * 2^e and m in [1, 2), 1 / m is in (0.5, 1] which
+// linear vectoring handles, and 2^-e is a shift. 1 / +-inf is +-0 and
+// NaN stays NaN, like f64
+pub fn recip(x: FixedPoint) -> Result<FixedPoint, CordicError> {
+    let zero = FixedPoint::new(0.0);
+    if x == zero {
+        return Err(CordicError::DomainError("recip"));
+    }
+    if let Some(ret) = limit(x, -0.0, 0.0) {
+        return Ok(ret);
+    }
+
+    let (m, e) = split_pow2(x.abs());
+    let ret = scale_pow2(
+        divide(FixedPoint::new(1.0), m * FixedPoint::new(2.0)),
+        1 - e,
+    );
+    if x < zero {
+        Ok(-ret)
+    } else {
+        Ok(ret)
+    }
+}
+
+// 1 / sqrt(x), as the square root from hyperbolic vectoring followed by
+// a linear-mode reciprocal
+pub fn rsqrt(x: FixedPoint) -> Result<FixedPoint, CordicError> {
+    if x <= FixedPoint::new(0.0) {
+        return Err(CordicError::DomainError("rsqrt"));
+    }
+
+    recip(sqrt(x)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relatively_close(a: FixedPoint, b: f64) -> bool {
+        ((a.to_f64() - b) / b).abs() < 1e-12
+    }
+
+    #[test]
+    fn reciprocals() {
+        for x in [1e-12, 0.001, 0.3, 0.5, 1.0, 1.5, 2.0, 3.0, 1000.0, 1e12] {
+            assert![relatively_close(
+                recip(FixedPoint::new(x)).unwrap(),
+                1.0 / x
+            )];
+            assert![relatively_close(
+                recip(FixedPoint::new(-x)).unwrap(),
+                -1.0 / x
+            )];
+            assert![relatively_close(
+                rsqrt(FixedPoint::new(x)).unwrap(),
+                1.0 / x.sqrt()
+            )];
+        }
+
+        // Like f64: 1 / +-inf is +-0 and NaN stays NaN
+        let (inf, nan) = (f64::INFINITY, f64::NAN);
+        let bits = |ret: Result<FixedPoint, CordicError>| ret.unwrap().to_f64().to_bits();
+        assert![bits(recip(FixedPoint::new(inf))) == 0.0_f64.to_bits()];
+        assert![bits(recip(FixedPoint::new(-inf))) == (-0.0_f64).to_bits()];
+        assert![bits(rsqrt(FixedPoint::new(inf))) == 0.0_f64.to_bits()];
+        assert![recip(FixedPoint::new(nan)).unwrap().to_f64().is_nan()];
+        assert![rsqrt(FixedPoint::new(nan)).unwrap().to_f64().is_nan()];
+    }
+
+    #[test]
+    fn domains() {
+        let zero = FixedPoint::new(0.0);
+        assert_eq![recip(zero), Err(CordicError::DomainError("recip"))];
+        assert_eq![rsqrt(zero), Err(CordicError::DomainError("rsqrt"))];
+        assert_eq![
+            rsqrt(FixedPoint::new(-4.0)),
+            Err(CordicError::DomainError("rsqrt"))
+        ];
+        assert_eq![
+            rsqrt(FixedPoint::new(f64::NEG_INFINITY)),
+            Err(CordicError::DomainError("rsqrt"))
+        ];
+    }
+}
@@ -0,0 +1,89 @@
+// A very small logging facade, so the CLI (or anything else embedding
+// the library) can watch what the iterations are doing without
+// recompiling with print statements. Nothing is logged until a logger
+// is installed, and a disabled level costs one atomic load
+#[cfg(not(feature = "float"))]
+#[allow(unused_imports)]
+use crate::float_guard::{f32, f64};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    // One line per operation (rotate, vector) with its configuration
+    Info = 1,
+    // One line per micro-rotation: direction, residual angle and vector
+    Debug = 2,
+}
+
+pub type Logger = fn(LogLevel, fmt::Arguments<'_>);
+
+// 0 means nothing gets through
+static MAX_LEVEL: AtomicUsize = AtomicUsize::new(0);
+static LOGGER: RwLock<Option<Logger>> = RwLock::new(None);
+
+// Installs `logger` and passes it everything at or below `level`, e.g.
+// Info only gets Info lines while Debug gets both
+pub fn set_logger(logger: Logger, level: LogLevel) {
+    if let Ok(mut current) = LOGGER.write() {
+        *current = Some(logger);
+    }
+    set_max_level(Some(level));
+}
+
+// Changes the level without replacing the logger, None turns it off
+pub fn set_max_level(level: Option<LogLevel>) {
+    MAX_LEVEL.store(level.map_or(0, |level| level as usize), Ordering::Relaxed);
+}
+
+// Whether `level` would get through, for skipping work that only feeds
+// the log
+pub(crate) fn enabled(level: LogLevel) -> bool {
+    MAX_LEVEL.load(Ordering::Relaxed) >= level as usize
+}
+
+pub(crate) fn log(level: LogLevel, args: fmt::Arguments<'_>) {
+    if !enabled(level) {
+        return;
+    }
+    if let Ok(logger) = LOGGER.read() {
+        if let Some(logger) = *logger {
+            logger(level, args);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+    use crate::cordic::CordicConfig;
+    use crate::fixed::FixedPoint;
+    use std::sync::Mutex;
+
+    static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    fn record(level: LogLevel, args: fmt::Arguments<'_>) {
+        LINES.lock().unwrap().push(format!("{:?} {}", level, args));
+    }
+
+    #[test]
+    fn logs_micro_rotations() {
+        set_logger(record, LogLevel::Debug);
+        CordicConfig::new(3).rotate(FixedPoint::new(0.25)).unwrap();
+        set_max_level(None);
+
+        // Other tests may be rotating at the same time, so only look for
+        // the lines this one must have produced
+        let lines = LINES.lock().unwrap();
+        assert![lines
+            .iter()
+            .any(|line| line.starts_with("Info rotate: 1 angle(s), 3 iterations"))];
+        assert![lines
+            .iter()
+            .any(|line| line.starts_with("Debug   iteration 0: sigma +1, z -0.535"))];
+        assert![lines
+            .iter()
+            .any(|line| line.starts_with("Debug   iteration 2: sigma -1"))];
+    }
+}
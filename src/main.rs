@@ -1,308 +1,1822 @@
-// CORDIC Demonstration in Rust
-//
-// Feel free to copy-paste into https://play.rust-lang.org
-//
-// This uses fixed-point integers to demonstrate the efficiency
-// realized by power-of-two multiplications and divisions.
-//
-// Floating point integers are normally broken up into three segments
-//   1. Base
-//   2. Exponent
-//   3. "Special" bits
-//
-// Floating point works similarly to scientific notation, where base is
-// the base, exponent is the exponent (although normally expressed as
-// 2^n instead of 10^n). The "Special" bits define things like
-// Not-a-Number (NaN) for cases like 0/0, +Inf and -Inf in cases
-// where the base and exponents do not have enough bits to express
-// the full value
-//
-// Fixed point is just an integer with a set number of places reserved
-// at the end for fractional components
-//
-// If you have any questions, feel free to email me at djh4@illinois.edu
-//
-// NOTE: This entire file is 100% self contained and will work on
-// play.rust-lang.org (you may need to replace theta and iters with
-// constants, but that should be it)
-
-use std::cmp::{Ordering, PartialEq, PartialOrd};
-use std::fmt;
-use std::ops::{Add, Div, Mul, Rem, Sub};
-
-// FixedPoint actually wraps floating point numbers, so currently there
-// isn't a difference, but this is an opportunity to come up with your own
-// real number representation. External libraries do exist that can
-// handle this
-pub struct FixedPoint {
-    val: f64,
-}
+// Command line driver for the CORDIC demonstration. All of the actual
+// math lives in the library (src/lib.rs), this just parses arguments
+// and prints results
+
+#[cfg(feature = "float")]
+use cordic_rs::csd;
+#[cfg(feature = "float")]
+use cordic_rs::window::{Window, WindowTable};
+#[cfg(feature = "float")]
+use cordic_rs::{
+    analyze_quantization, atan2, attribute, bit_agreement, checked_iters, cordic, edge_cases, exp,
+    exp2, generate_table, harmonics, jacobi_rotation, ln, log2, matlab_arrays, min_word_length,
+    minimize, npz, pareto, power_spectrum, prune, quantize_coefficients, relative_error, sfdr,
+    sin_cos_multiples, sincos_carry_save, sincos_q60, sincos_recoded, sqrt, stress, svd2, sweep,
+    sweep_angles, table_fingerprint, taylor, tone_quality, ulp_distance, wav_pcm16, Architecture,
+    Backend, Chirp, Composed, CordicConfig, Endian, ExactSign, Fault, FixedPoint, GainCompensation,
+    Image, Interpolation, Lambert, LookAhead, Lut, Manifest, MsbWindow, Nco, Pipeline, Pll,
+    QFormat, Reference, Register, Registry, Rounding, SigmaCordic, SigmaSelection, Strategy,
+    SweepPoint, Tables, TrigAlgorithm, Workbench, MAX_ITERS, Q60,
+};
+use cordic_rs::{selftest, set_logger, CordicError, LogLevel};
+#[cfg(not(feature = "float"))]
+use cordic_rs::{sincos_q60, Q60};
+#[cfg(feature = "float")]
+use std::io::Write;
 
-impl FixedPoint {
-    fn new(val: f64) -> Self {
-        Self { val }
+// Parses the iteration count argument, warning on stderr if it had to
+// be clamped and exiting if it's unusable
+#[cfg(feature = "float")]
+fn parse_iters(arg: String) -> usize {
+    let requested = parse_number::<usize>(&arg);
+    match checked_iters(requested) {
+        Ok(iters) => {
+            if iters != requested {
+                eprintln!(
+                    "warning: {} iterations is past the precision of FixedPoint, using {} instead",
+                    requested, iters
+                );
+            }
+            iters
+        }
+        Err(e) => fail_on(e),
     }
 }
 
-impl Copy for FixedPoint {}
+// Looks for `--name value` anywhere in the arguments
+#[cfg(feature = "float")]
+fn flag(name: &str) -> Option<String> {
+    let args = std::env::args().collect::<Vec<String>>();
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1).cloned())
+}
+
+// Plotting half of `bench --plot matlab`, appended after the sweep arrays
+#[cfg(feature = "float")]
+const MATLAB_PLOT: &str = "\
+cos_error = abs(cordic_cos - std_cos);
+sin_error = abs(cordic_sin - std_sin);
+libm_cos_error = abs(cordic_cos - libm_cos);
+libm_sin_error = abs(cordic_sin - libm_sin);
+
+figure;
+subplot(2, 1, 1);
+plot(theta, cordic_cos, theta, cordic_sin);
+legend('CORDIC cosine', 'CORDIC sine');
+xlabel('theta (rad)');
+grid on;
+subplot(2, 1, 2);
+semilogy(theta, cos_error, theta, sin_error, theta, libm_cos_error, '--', theta, libm_sin_error, '--');
+legend('Cosine error (std)', 'Sine error (std)', 'Cosine error (libm)', 'Sine error (libm)');
+xlabel('theta (rad)');
+ylabel('absolute error');
+grid on;
+";
 
-impl Clone for FixedPoint {
-    fn clone(&self) -> Self {
-        Self { val: self.val }
+// Integer micro-rotations unless --float asks for the f64 ones, see
+// Backend
+#[cfg(feature = "float")]
+fn backend() -> Backend {
+    if std::env::args().any(|arg| arg == "--float") {
+        Backend::Float
+    } else {
+        Backend::Integer
     }
 }
 
-impl Add for FixedPoint {
-    type Output = Self;
-    fn add(self, other: Self) -> Self {
-        Self {
-            val: self.val + other.val,
-        }
-    }
+// The manifest for this run's output, with the backend's arithmetic and
+// tables when `backend` is given. Text outputs start with it as comments,
+// binary ones written to --out get it as a JSON file next to them
+#[cfg(feature = "float")]
+fn manifest(backend: Option<(Backend, usize)>) -> Manifest {
+    let manifest = Manifest::new(&std::env::args().collect::<Vec<_>>());
+    let Some((backend, iters)) = backend else {
+        return manifest;
+    };
+    let fingerprint = table_fingerprint(backend, iters).unwrap_or_else(|e| fail_on(e));
+    manifest
+        .field("backend", format!("{:?}", backend))
+        .field("iterations", iters)
+        .field(
+            "q format",
+            match backend {
+                Backend::Integer => "Q3.60",
+                Backend::Float => "f64",
+            },
+        )
+        .field("table fingerprint", format!("{:016x}", fingerprint))
 }
 
-impl Sub for FixedPoint {
-    type Output = Self;
-    fn sub(self, other: Self) -> Self {
-        Self {
-            val: self.val - other.val,
+// Binary output to --out plus `<out>.json` holding the manifest, or to
+// stdout alone
+#[cfg(feature = "float")]
+fn write_binary(bytes: &[u8], manifest: &Manifest) {
+    match flag("--out") {
+        Some(path) => {
+            std::fs::write(&path, bytes).unwrap_or_else(|e| fail(&e.to_string()));
+            std::fs::write(format!("{}.json", path), manifest.to_json())
+                .unwrap_or_else(|e| fail(&e.to_string()));
         }
+        None => std::io::stdout()
+            .write_all(bytes)
+            .unwrap_or_else(|e| fail(&e.to_string())),
     }
 }
 
-impl Mul for FixedPoint {
-    type Output = Self;
-    fn mul(self, other: Self) -> Self {
-        Self {
-            val: self.val * other.val,
-        }
-    }
+// A binary table minus the CRC-32 it ends with. Checksumming that gives
+// the CRC stored in the file, where including it would always give the
+// same residue
+#[cfg(feature = "float")]
+fn without_crc(bytes: &[u8]) -> &[u8] {
+    &bytes[..bytes.len().saturating_sub(4)]
 }
 
-impl Div for FixedPoint {
-    type Output = Self;
-    fn div(self, other: Self) -> Self {
-        Self {
-            val: self.val / other.val,
+// `selftest` for both builds. One PASS or FAIL line per check, then a
+// key=value summary line for scripts, exiting with 1 if anything failed
+fn run_selftest() -> ! {
+    let checks = selftest();
+    for check in &checks {
+        if check.passed {
+            println!("PASS {}", check.name);
+        } else {
+            println!("FAIL {}: {}", check.name, check.detail);
         }
     }
+    let failed = checks.iter().filter(|check| !check.passed).count();
+    println!(
+        "result={} passed={} failed={} version={} target={}-{}",
+        if failed == 0 { "pass" } else { "fail" },
+        checks.len() - failed,
+        failed,
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::ARCH,
+        std::env::consts::OS
+    );
+    std::process::exit(if failed == 0 { 0 } else { 1 });
 }
 
-impl Rem for FixedPoint {
-    type Output = Self;
-    fn rem(self, modulus: FixedPoint) -> Self {
-        Self {
-            val: self.val.rem(modulus.val),
-        }
-    }
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(1);
 }
 
-impl PartialOrd for FixedPoint {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.val.partial_cmp(&other.val)
+// What the process exits with for each kind of library error, so a
+// script can tell a bad argument from a bad file without reading
+// stderr. Anything the CLI rejects itself (an unknown mode or flag, an
+// unreadable file) exits with 1
+fn exit_code(error: &CordicError) -> i32 {
+    match error {
+        CordicError::IterationOutOfRange(_) => 2,
+        CordicError::InvalidAngle(_) => 3,
+        CordicError::DomainError(_) => 4,
+        CordicError::Overflow(_) => 5,
+        CordicError::ParseError(_) => 6,
+        CordicError::InvalidTable(_) => 7,
+        CordicError::InvalidImage(_) => 8,
+        CordicError::WorkerPanicked(_) => 9,
     }
 }
 
-impl PartialEq for FixedPoint {
-    fn eq(&self, other: &Self) -> bool {
-        self.val == other.val
+fn fail_on(error: CordicError) -> ! {
+    eprintln!("error: {}", error);
+    std::process::exit(exit_code(&error));
+}
+
+// --function for minimax and quantize: its name and one of std's f64
+// functions to fit
+#[cfg(feature = "float")]
+fn reference_function() -> (String, fn(f64) -> f64) {
+    let name = flag("--function").unwrap_or_else(|| "sin".to_string());
+    let f: fn(f64) -> f64 = match name.as_str() {
+        "sin" => f64::sin,
+        "cos" => f64::cos,
+        "tan" => f64::tan,
+        "atan" => f64::atan,
+        "exp" => f64::exp,
+        "exp2" => f64::exp2,
+        "ln" => f64::ln,
+        "log2" => f64::log2,
+        "sqrt" => f64::sqrt,
+        other => fail(&format!(
+            "unknown --function {}, expected sin, cos, tan, atan, exp, exp2, ln, log2 or sqrt",
+            other
+        )),
+    };
+    (name, f)
+}
+
+// The crate's own CORDIC version of a --function, for approx to compare
+// against
+#[cfg(feature = "float")]
+fn cordic_function(name: &str) -> fn(FixedPoint) -> Result<FixedPoint, CordicError> {
+    match name {
+        "sin" => |x| cordic(x, MAX_ITERS).map(|[_, sin]| sin),
+        "cos" => |x| cordic(x, MAX_ITERS).map(|[cos, _]| cos),
+        "tan" => |x| cordic(x, MAX_ITERS).map(|[cos, sin]| sin / cos),
+        "atan" => |x| Ok(atan2(x, FixedPoint::new(1.0))),
+        "exp" => |x| Ok(exp(x)),
+        "exp2" => |x| Ok(exp2(x)),
+        "ln" => ln,
+        "log2" => log2,
+        "sqrt" => sqrt,
+        // reference_function() has already turned anything else away
+        _ => unreachable!(),
     }
 }
 
-impl fmt::Display for FixedPoint {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.val)
+// --interval A,B, [0, pi/2] by default
+#[cfg(feature = "float")]
+fn interval() -> (f64, f64) {
+    let interval = flag("--interval").unwrap_or_else(|| "0,1.5707963267948966".to_string());
+    match interval.split_once(',') {
+        Some((a, b)) => (parse_number::<f64>(a), parse_number::<f64>(b)),
+        None => fail("--interval takes two numbers separated by a comma"),
     }
 }
 
-fn cordic(mut theta: FixedPoint, iters: usize) -> [FixedPoint; 2] {
-    // CORDIC *can* calculate theta even if theta wraps around, but
-    // at the expense of accuracy. We make sure that theta is in
-    // its lowest valid representation (remainder after division
-    // by 2*pi). Another issue with floating point numbers is
-    // buildup of error across iterations. Fixed point arithmetic
-    // can fix this, but I haven't gotten around to implementing that.
-    //
-    // NOTE: The "buildup of error across iterations" is why the
-    // current unit test fails. Moving over to proper fixed points
-    // should fix this
-    theta = theta.rem(FixedPoint::new(2.0 * std::f64::consts::PI));
-    // CORDIC (for trig functions, at least) does require some
-    // compile time constants. However, this is far more space
-    // efficient than naively storing sine itself. These are
-    // included here to allow for larger iteration countes, but
-    // any reasonable implementation would pre-compute a certain
-    // amount and keep them in a global array
-    // atan(2^-x)
-    let angles = (0..(iters as i32 + 2))
-        .map(|x| FixedPoint::new((2_f64.powi(-1 * x)).atan()))
-        .collect::<Vec<FixedPoint>>();
-
-    // cumprod(1 / sqrt(1 + 2^-2y))
-    // NOTE The cumulative product is done by re-calculating and multiplying
-    // all elements of the vector together with fold()
-    //
-    // NOTE 2: This is static for a given number of iters, so in instances
-    // where we only compute a set number of iterations, this can be
-    // computed ahead of time
-    let kvalue = FixedPoint::new(
-        (0_i32..(iters as i32 - 1))
-            .map(|y| 1.0_f64 / (1.0_f64 + 2_f64.powi(-2 * y)).sqrt().abs())
-            .fold(1.0_f64, |x, y| x * y),
-    );
+// Parses a numeric argument, exiting the way a ParseError from the
+// library does rather than panicking when it isn't one
+fn parse_number<T: std::str::FromStr>(value: &str) -> T {
+    value.parse::<T>().unwrap_or_else(|_| {
+        let error = CordicError::ParseError("not a number");
+        eprintln!("error: {} ({:?})", error, value);
+        std::process::exit(exit_code(&error));
+    })
+}
 
-    let mut poweroftwo = FixedPoint::new(1.0);
-    let mut angle = angles[0];
-    let fixed_point_zero = FixedPoint::new(0.0);
-    let fixed_point_pos_one = FixedPoint::new(1.0);
-    let fixed_point_neg_one = FixedPoint::new(-1.0);
-    let fixed_point_pos_two = FixedPoint::new(2.0);
-
-    let mut v = [fixed_point_pos_one, fixed_point_zero]; // Initialize as cos = 1, sine = 0
-    for i in 0..iters {
-        let sigma_is_neg = theta < fixed_point_zero;
-
-        // v = R * v
-        // NOTE: Matrix is always of the form
-        // [ 1.0, -factor; factor, 1.0 ]
-        //
-        // You can imagine `v` as a vector whose cosine is
-        // one and sine is zero. The following matrix is a
-        // rotation matrix, and it is normally of the form
-        // [ cos theta, -sin theta; sin theta, cos theta ].
-        // However, this also magnfiies the vector by some
-        // set amount, and we account for all these magnitude
-        // increases in one multiplication at the end of the
-        // calculation
-        //
-        // The following simplifies down to a rotation of
-        // tan^-1(2^-i) and a increase in magnitude of
-        // (1 + 2^(-2j))^(1/2)
-
-/*
-        let factor = if sigma_is_neg {
-            // NOTE: Almost all compilers will optimize multiplication by
-            // -1 to be flipping a single bit, so for performance reasons
-            // this isn't a normal multiplication
-            //
-            // HOWEVER, if -1 were stored in a variable, that optimization
-            // isn't as obvious, so the compiler might not catch it. We store
-            // sigma_is_neg instead of sigma = -1 or sigma = 1 because of this
-            FixedPoint::new(-1.0) * poweroftwo
-        } else {
-            poweroftwo
+// The positional argument at `index`, exiting if it isn't there
+#[cfg(feature = "float")]
+fn positional(index: usize, name: &str) -> String {
+    std::env::args()
+        .nth(index)
+        .unwrap_or_else(|| fail(&format!("missing [{}]", name)))
+}
+
+// Counts -v (or -vv, -vvv...) flags, anywhere after the mode
+fn verbosity() -> usize {
+    std::env::args()
+        .skip(2)
+        .filter(|arg| arg.len() > 1 && arg.starts_with('-') && arg[1..].chars().all(|c| c == 'v'))
+        .map(|arg| arg.len() - 1)
+        .sum()
+}
+
+fn log_to_stderr(level: LogLevel, args: std::fmt::Arguments<'_>) {
+    eprintln!("[{:?}] {}", level, args);
+}
+
+// How the modes are called, printed when the mode is missing or
+// unknown
+#[cfg(feature = "float")]
+const USAGE: &str = "\
+usage:
+  ./cordic-rs compute [theta] [iters] [--float] [--stable BITS] [--monotone]
+  ./cordic-rs monotone [--iters N] [--bits B]
+  ./cordic-rs rounding [--iters N] [--bits B] [--double-word]
+  ./cordic-rs steps [theta] [iters] [--float]
+  ./cordic-rs selftest
+  ./cordic-rs taylor [theta] [iters]
+  ./cordic-rs bench [--float] [--no-header] [--plot matlab] [--npz FILE] [--summary]
+  ./cordic-rs accuracy [--iters N] [--schedule 0,1,2,2,...] [--reference std|libm|double-double]
+  ./cordic-rs compare [theta] [--iters N]
+  ./cordic-rs heatmap [--iters N] [--points N] [--float] [--format csv|npy|svg] [--out FILE]
+  ./cordic-rs faults [--iters N] [--theta T --iteration N --register x|y|z --bit N] [--random N --seed N]
+  ./cordic-rs edge-cases [--iters N]
+  ./cordic-rs harmonics [--n N]
+  ./cordic-rs explore [--bits N] [--max-entries N]
+  ./cordic-rs schedule --angles FILE [--iters N] [--bits N]
+  ./cordic-rs wordlength [--iters N] [--bits N]
+  ./cordic-rs bits [--width N]
+  ./cordic-rs attribute [--iters N] [--width N]
+  ./cordic-rs carrysave [--iters N]
+  ./cordic-rs recode
+  ./cordic-rs speed [--calls N] [--small-angle] [--threads N]
+  ./cordic-rs pipeline [--iters N] [--width N] [--angle-width N] [--growth N] [--stage-delay N] [--iterative] [--prescale|--csd]
+  ./cordic-rs pll [--freq HZ] [--offset HZ] [--rate HZ] [--samples N] [--iters N]
+  ./cordic-rs tone [--freq HZ] [--sweep-to HZ] [--rate HZ] [--secs N] [--iters N] [--shape] [--out FILE]
+  ./cordic-rs shaping [--freq HZ] [--rate HZ] [--bits N] [--samples N] [--iters N]
+  ./cordic-rs dds [--freq HZ] [--rate HZ] [--samples N] [--acc 32,24] [--phase 32,16,12] [--iters 12,16,24] [--bits 16,12]
+  ./cordic-rs spectrum [--freq HZ] [--rate HZ] [--samples N] [--iters N] [--bits N] [--shape]
+  ./cordic-rs rotate-image [in.pgm|in.ppm] [out] [theta]
+  ./cordic-rs jacobi [a] [b] [c] [d] [--iters N]
+  ./cordic-rs svd [a] [b] [c] [d] [--iters N]
+  ./cordic-rs serve [--port N] (needs --features serve)
+  ./cordic-rs minimax [--function sin|cos|tan|atan|exp|exp2|ln|log2|sqrt] [--interval A,B] [--degree N] [--format Q15 [--strategy S] [--lang rust|c] [--name NAME]] [--out FILE]
+  ./cordic-rs approx [--function F] [--interval A,B] [--points N] [--pareto]
+  ./cordic-rs stress [--iters N] [--count N] [--seed N] [--slack S] [--float] [--octant]
+  ./cordic-rs quantize [--function F] [--interval A,B] [--degree N | --coeffs C0,C1,...] [--format Q15]
+  ./cordic-rs sintable [--entries N] [--format Q15] [--lang bin|rust|c] [--name NAME] [--endian little|big] [--out FILE]
+  ./cordic-rs window [--kind hann|hamming|blackman] [--points N] [--format Q15] [--lang rust|c] [--name NAME] [--out FILE]
+  ./cordic-rs gen --lang bin [--iters N] [--frac-bits N] [--endian little|big] [--out FILE]
+  ./cordic-rs gen --constant C [--frac-bits N] [--lang rust|c|verilog] [--name NAME] [--width N] [--out FILE]
+";
+
+#[cfg(feature = "float")]
+fn main() {
+    // Pull parameters from string, see USAGE for how each mode is
+    // called. Any mode also takes -v (each operation) or -vv (each
+    // micro-rotation too), logged to stderr. Errors exit with a code per
+    // CordicError variant, see exit_code()
+    let mode = std::env::args().nth(1).unwrap_or_else(|| {
+        eprint!("{}", USAGE);
+        std::process::exit(1);
+    });
+    match verbosity() {
+        0 => {}
+        1 => set_logger(log_to_stderr, LogLevel::Info),
+        _ => set_logger(log_to_stderr, LogLevel::Debug),
+    }
+
+    if mode == "selftest" {
+        run_selftest();
+    } else if mode == "compute" {
+        let theta = FixedPoint::new(parse_number::<f64>(&positional(2, "theta")));
+        let iters = parse_iters(positional(3, "iters"));
+
+        let config = CordicConfig::new(iters)
+            .backend(backend())
+            .monotone(std::env::args().any(|arg| arg == "--monotone"));
+        let ret = match flag("--stable") {
+            // Up to [iters] iterations, stopping early once both outputs
+            // rounded to --stable fractional bits stop changing
+            Some(bits) => {
+                let scale = 2f64.powi(parse_number::<i32>(&bits));
+                let mut last = None;
+                let (ret, step) = config
+                    .sincos_until(theta, |step| {
+                        let rounded =
+                            [step.x, step.y].map(|v| ((v * step.gain).to_f64() * scale).round());
+                        last.replace(rounded) == Some(rounded) || step.iteration + 1 >= iters
+                    })
+                    .unwrap_or_else(|e| fail_on(e));
+                eprintln!("stopped after {} iterations", step.iteration + 1);
+                ret
+            }
+            None => config
+                .rotate(theta)
+                .map(|ret| [ret.x, ret.y])
+                .unwrap_or_else(|e| fail_on(e)),
         };
+        println!("cos {} == {}\nsin {} == {}", theta, ret[0], theta, ret[1]);
+    } else if mode == "monotone" {
+        // Every Q<bits> input in [0, pi/2], plain and with monotone(true):
+        // how often sin steps down or cos steps up from one input to the
+        // next, and the worst error against std
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| "12".to_string()));
+        let bits = parse_number::<u32>(&flag("--bits").unwrap_or_else(|| "12".to_string()));
+        // Every input of Q24 is already 26 million rotations each way
+        if bits > 24 {
+            fail("--bits must be at most 24 to sweep every input");
+        }
+        let scale = 2f64.powi(bits as i32);
+        let thetas = (0..=(std::f64::consts::FRAC_PI_2 * scale) as i64)
+            .map(|i| i as f64 / scale)
+            .collect::<Vec<f64>>();
+        println!("Mode, Reversals, Max Error");
+        for monotone in [false, true] {
+            let config = CordicConfig::new(iters).io_width(bits).monotone(monotone);
+            let mut reversals = 0;
+            let mut max_error = 0.0f64;
+            let mut last: Option<[f64; 2]> = None;
+            for &theta in &thetas {
+                let ret = config
+                    .rotate(FixedPoint::new(theta))
+                    .unwrap_or_else(|e| fail_on(e));
+                let [x, y] = [ret.x.to_f64(), ret.y.to_f64()];
+                if let Some([last_x, last_y]) = last {
+                    reversals += (x > last_x) as usize + (y < last_y) as usize;
+                }
+                last = Some([x, y]);
+                max_error = max_error
+                    .max((x - theta.cos()).abs())
+                    .max((y - theta.sin()).abs());
+            }
+            let name = if monotone { "monotone" } else { "plain" };
+            println!("{},{},{:e}", name, reversals, max_error);
+        }
+    } else if mode == "rounding" {
+        // Every Q<bits> input in [-pi, pi] through a fast config and the
+        // correctly rounded one: how many outputs differ and by how many
+        // LSBs at most
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| "16".to_string()));
+        let bits = parse_number::<u32>(&flag("--bits").unwrap_or_else(|| "14".to_string()));
+        let config = CordicConfig::new(iters)
+            .io_width(bits)
+            .double_word(std::env::args().any(|arg| arg == "--double-word"));
+        let exact = config.correctly_rounded(true);
+        let scale = 2f64.powi(bits as i32);
+        let limit = (std::f64::consts::PI * scale) as i64;
+        let (mut inputs, mut misrounded, mut max_lsbs) = (0, [0; 2], 0.0f64);
+        for i in -limit..=limit {
+            let theta = FixedPoint::new(i as f64 / scale);
+            let fast = config.rotate(theta).unwrap_or_else(|e| fail_on(e));
+            let want = exact.rotate(theta).unwrap_or_else(|e| fail_on(e));
+            for (j, (got, want)) in [(fast.x, want.x), (fast.y, want.y)].iter().enumerate() {
+                let lsbs = ((*got - *want).to_f64() * scale).abs();
+                misrounded[j] += (lsbs != 0.0) as usize;
+                max_lsbs = max_lsbs.max(lsbs);
+            }
+            inputs += 1;
+        }
+        println!("Config, Inputs, Misrounded Cosines, Misrounded Sines, Max LSBs Off");
+        println!(
+            "{},{},{},{},{}",
+            config.name(),
+            inputs,
+            misrounded[0],
+            misrounded[1],
+            max_lsbs
+        );
+    } else if mode == "steps" {
+        // Every micro-rotation of theta as CSV, the estimates with the
+        // gain so far undone alongside the raw registers, see
+        // src/iterations.rs. [iters] stops after that many
+        let theta = FixedPoint::new(parse_number::<f64>(&positional(2, "theta")));
+        let iters = std::env::args()
+            .nth(3)
+            .filter(|arg| !arg.starts_with("--"))
+            .map_or(MAX_ITERS, parse_iters);
+        let steps = CordicConfig::new(iters)
+            .backend(backend())
+            .iterations(theta)
+            .unwrap_or_else(|e| fail_on(e));
 
-        let matrix = [
-            [fixed_point_pos_one, fixed_point_neg_one * factor],
-            [factor, fixed_point_pos_one],
+        println!("Iteration, Shift, Sigma, X, Y, Z, Cosine, Sine");
+        for step in steps.take(iters) {
+            println!(
+                "{},{},{},{},{},{},{},{}",
+                step.iteration,
+                step.shift,
+                if step.counterclockwise { 1 } else { -1 },
+                step.x,
+                step.y,
+                step.z,
+                step.x * step.gain,
+                step.y * step.gain
+            );
+        }
+    } else if mode == "taylor" {
+        let theta = FixedPoint::new(parse_number::<f64>(&positional(2, "theta")));
+        let iters = parse_number::<usize>(&positional(3, "iters"));
+
+        let ret = taylor(theta, iters);
+        println!("cos {} == {}\nsin {} == {}", theta, ret[0], theta, ret[1]);
+    } else if mode == "bench" {
+        // Sweep [-2pi, 2pi] so negative angles and both wrap-arounds
+        // get exercised. Errors are against std, which is the platform's
+        // libm, and against the libm crate's (musl's ported to Rust),
+        // which is the same everywhere
+        let result = sweep(
+            &CordicConfig::new(MAX_ITERS).backend(backend()),
+            &sweep_angles(),
+        )
+        .unwrap_or_else(|e| fail_on(e));
+        let column = |value: fn(&SweepPoint) -> f64| result.points.iter().map(value).collect();
+        let theta: Vec<f64> = column(|p| p.theta);
+        let cordic_cos: Vec<f64> = column(|p| p.cos);
+        let std_cos: Vec<f64> = column(|p| p.std_cos);
+        let libm_cos: Vec<f64> = column(|p| p.libm_cos);
+        let cordic_sin: Vec<f64> = column(|p| p.sin);
+        let std_sin: Vec<f64> = column(|p| p.std_sin);
+        let libm_sin: Vec<f64> = column(|p| p.libm_sin);
+
+        let columns: [(&str, &[f64]); 7] = [
+            ("theta", &theta),
+            ("cordic_cos", &cordic_cos),
+            ("std_cos", &std_cos),
+            ("libm_cos", &libm_cos),
+            ("cordic_sin", &cordic_sin),
+            ("std_sin", &std_sin),
+            ("libm_sin", &libm_sin),
         ];
+        let manifest = manifest(Some((backend(), MAX_ITERS)));
+        if let Some(path) = flag("--npz") {
+            std::fs::write(&path, npz(&columns)).unwrap_or_else(|e| fail(&e.to_string()));
+            std::fs::write(format!("{}.json", path), manifest.to_json())
+                .unwrap_or_else(|e| fail(&e.to_string()));
+            return;
+        }
+        // Just the numbers the sweep boils down to, one row per output
+        if std::env::args().any(|arg| arg == "--summary") {
+            println!("Output, Max Error, Mean Error, RMS Error, Max Relative Error, Max ULPs, Worst Theta, Bits");
+            for (name, stats) in [
+                ("cos", result.cos_stats()),
+                ("sin", result.sin_stats()),
+                ("cos (libm)", result.libm_cos_stats()),
+                ("sin (libm)", result.libm_sin_stats()),
+            ] {
+                println!(
+                    "{},{:e},{:e},{:e},{:e},{},{},{:.2}",
+                    name,
+                    stats.max,
+                    stats.mean,
+                    stats.rms,
+                    stats.max_relative,
+                    stats.max_ulps,
+                    stats.worst_theta,
+                    stats.bits()
+                );
+            }
+            return;
+        }
 
-        */
+        match flag("--plot").as_deref() {
+            None => {
+                // NOTE: Output is a CSV file that I will open in Excel
+                // Absolute error alone says little near the zeros of sin
+                // and cos, so the errors against std are also given
+                // relative to it and in ulps. Relative error is left empty
+                // where std gives exactly zero
+                let relative = |value: f64, reference: f64| {
+                    relative_error(value, reference).map_or(String::new(), |e| e.to_string())
+                };
+                // The # lines say what produced the sweep (see manifest()),
+                // bumping the format version whenever the columns change. --no-header
+                // leaves them and the column names out, for appending
+                // sweeps to one file
+                if !std::env::args().any(|arg| arg == "--no-header") {
+                    println!(
+                        "# cordic-rs {} bench sweep, format version 2",
+                        env!("CARGO_PKG_VERSION")
+                    );
+                    print!("{}", manifest.to_comments("# "));
+                    println!("# theta and values in radians and plain numbers, errors against std except the libm ones, ulps as a count of f64s");
+                    println!("Theta, CORDIC Cosine, Standard Cosine, Libm Cosine, Cosine Error, Libm Cosine Error, Cosine Relative Error, Cosine ULPs, CORDIC Sine, Standard Sine, Libm Sine, Sine Error, Libm Sine Error, Sine Relative Error, Sine ULPs");
+                }
+                for i in 0..theta.len() {
+                    println!(
+                        "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                        theta[i],
+                        FixedPoint::new(cordic_cos[i]),
+                        std_cos[i],
+                        libm_cos[i],
+                        (cordic_cos[i] - std_cos[i]).abs(),
+                        (cordic_cos[i] - libm_cos[i]).abs(),
+                        relative(cordic_cos[i], std_cos[i]),
+                        ulp_distance(cordic_cos[i], std_cos[i]),
+                        FixedPoint::new(cordic_sin[i]),
+                        std_sin[i],
+                        libm_sin[i],
+                        (cordic_sin[i] - std_sin[i]).abs(),
+                        (cordic_sin[i] - libm_sin[i]).abs(),
+                        relative(cordic_sin[i], std_sin[i]),
+                        ulp_distance(cordic_sin[i], std_sin[i])
+                    );
+                }
+            }
+            Some("matlab") => {
+                print!(
+                    "% CORDIC bench sweep generated by cordic-rs, run with octave or matlab\n{}{}",
+                    manifest.to_comments("% "),
+                    matlab_arrays(&columns)
+                );
+                print!("{}", MATLAB_PLOT);
+            }
+            Some(other) => fail(&format!("unknown --plot {}, expected matlab", other)),
+        }
+    } else if mode == "accuracy" {
+        // Every built in algorithm over the same sweep as bench, one
+        // summary row each. Other crates can run their own algorithms
+        // through cordic_rs::Registry the same way
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| MAX_ITERS.to_string()));
+        let thetas = sweep_angles();
 
-        // NOTE: v = matrix * v using the variables as defined above, but the
-        // following is much faster for a few reasons
-        //   1. Multiplication by constants has optimizations done at compile time
-        //     a. -1 * x simplifies to flipping a single bit
-        //     b. Two constants multiplied by each other are pre-multiplied
+        // Plus Q31 in and out with the registers at the same width and
+        // at double width, to show what rounding once at the end buys
+        let mut registry = Registry::with_builtins(iters);
+        registry
+            .register(CordicConfig::new(iters).io_width(31))
+            .register(CordicConfig::new(iters).io_width(31).double_word(true));
+        // And the direction selection fast hardware uses in place of the
+        // exact sign of z, see src/redundant.rs
+        for strategy in [
+            Box::new(ExactSign) as Box<dyn SigmaSelection>,
+            Box::new(MsbWindow(16)),
+            Box::new(LookAhead(16)),
+        ] {
+            registry.register(SigmaCordic { iters, strategy });
+        }
+        // And an experimental shift schedule on both backends, e.g.
+        // --schedule 0,1,2,2,3,4
+        if let Some(schedule) = flag("--schedule") {
+            let shifts = schedule
+                .split(',')
+                .map(|shift| parse_number::<usize>(shift.trim()))
+                .collect::<Vec<_>>();
+            for backend in [Backend::Integer, Backend::Float] {
+                registry.register(CordicConfig::new(iters).backend(backend).schedule(&shifts));
+            }
+        }
+        // Errors against std by default, or the libm crate or the double-double
+        // reference (src/reference.rs), which sees errors smaller than an
+        // ulp of f64
+        let reference = flag("--reference").map_or(Reference::Std, |name| {
+            name.parse::<Reference>().unwrap_or_else(|e| fail_on(e))
+        });
+        let results = match registry.accuracy_against(&thetas, reference) {
+            Ok(results) => results,
+            Err(e) => fail_on(e),
+        };
+        let integer = table_fingerprint(Backend::Integer, iters).unwrap_or_else(|e| fail_on(e));
+        let float = table_fingerprint(Backend::Float, iters).unwrap_or_else(|e| fail_on(e));
+        print!(
+            "{}",
+            manifest(None)
+                .field("iterations", iters)
+                .field("reference", reference)
+                .field("integer table fingerprint", format!("{:016x}", integer))
+                .field("float table fingerprint", format!("{:016x}", float))
+                .to_comments("# ")
+        );
+        println!("Algorithm, Max Cosine Error, RMS Cosine Error, Max Sine Error, RMS Sine Error");
+        for result in results {
+            println!(
+                "{},{},{},{},{}",
+                result.name,
+                result.max_cos_error,
+                result.rms_cos_error,
+                result.max_sin_error,
+                result.rms_sin_error
+            );
+        }
+    } else if mode == "compare" {
+        // Every algorithm the crate has on one angle, with its value,
+        // error against std and cost model, to pick a method for a job.
+        // The same count (--iters) goes to CORDIC's iterations and the
+        // series' terms; the tables are 1024 entries of Q31
+        let theta = parse_number::<f64>(&std::env::args().nth(2).unwrap_or_default());
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| MAX_ITERS.to_string()));
+        let mut registry = Registry::with_builtins(iters);
+        registry
+            .register(CordicConfig::new(iters).gain_compensation(GainCompensation::Prescale))
+            .register(CordicConfig::new(iters).gain_compensation(GainCompensation::Csd))
+            .register(CordicConfig::new(iters).io_width(31))
+            .register(CordicConfig::new(iters).io_width(31).double_word(true))
+            .register(SigmaCordic {
+                iters,
+                strategy: Box::new(MsbWindow(16)),
+            })
+            .register(Lambert(iters));
+        for interpolation in [
+            Interpolation::Nearest,
+            Interpolation::Linear,
+            Interpolation::Quadratic,
+        ] {
+            let table = generate_table(1024, QFormat::Q31).unwrap_or_else(|e| fail_on(e));
+            registry.register(Lut {
+                table,
+                interpolation,
+            });
+        }
 
-        v = if sigma_is_neg {
-            [
-                v[0] + poweroftwo * v[1],
-                FixedPoint::new(-1.0) * poweroftwo * v[0] + v[1]
-            ]
-        } else {
-            [
-                v[0] + poweroftwo * v[1],
-                FixedPoint::new(-1.0) * poweroftwo * v[0] + v[1]
-            ]
+        let [std_cos, std_sin] = [theta.cos(), theta.sin()];
+        println!("Algorithm, Cosine, Sine, Cosine Error, Sine Error, Adds, Shifts, Multiplies, Divides, Table Entries, Cycles, Toggles");
+        println!("std,{},{},0e0,0e0,,,,,,,", std_cos, std_sin);
+        let [cos, sin] = [libm::cos(theta), libm::sin(theta)];
+        let errors = [(cos - std_cos).abs(), (sin - std_sin).abs()];
+        println!(
+            "libm,{},{},{:e},{:e},,,,,,,",
+            cos, sin, errors[0], errors[1]
+        );
+        for algorithm in registry.algorithms() {
+            let [cos, sin] = match algorithm.sincos(FixedPoint::new(theta)) {
+                Ok(ret) => ret.map(|x| x.to_f64()),
+                Err(e) => fail_on(e),
+            };
+            let cost = algorithm.cost().map_or(String::from(",,,,,,"), |cost| {
+                format!(
+                    "{},{},{},{},{},{},{}",
+                    cost.adds,
+                    cost.shifts,
+                    cost.multiplies,
+                    cost.divides,
+                    cost.table_entries,
+                    cost.cycles(),
+                    cost.toggles
+                        .map_or(String::new(), |toggles| toggles.to_string())
+                )
+            });
+            println!(
+                "{},{},{},{:e},{:e},{}",
+                algorithm.name(),
+                cos,
+                sin,
+                (cos - std_cos).abs(),
+                (sin - std_sin).abs(),
+                cost
+            );
+        }
+    } else if mode == "heatmap" {
+        // Error over a turn at every iteration count, as a grid for
+        // plotting. Written to --out if given, stdout otherwise
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| MAX_ITERS.to_string()));
+        let points = flag("--points").map_or(256, |points| parse_number::<usize>(&points));
+        let config = CordicConfig::new(iters).backend(backend());
+        let map = cordic_rs::heatmap(&config, points, iters).unwrap_or_else(|e| fail_on(e));
+        let bytes = match flag("--format").as_deref().unwrap_or("csv") {
+            "csv" => map.to_csv().into_bytes(),
+            "npy" => map.to_npy(),
+            "svg" => map.to_svg().into_bytes(),
+            other => fail(&format!(
+                "unknown --format {}, expected csv, npy or svg",
+                other
+            )),
         };
+        match flag("--out") {
+            Some(path) => std::fs::write(path, bytes).unwrap_or_else(|e| fail(&e.to_string())),
+            None => std::io::stdout()
+                .write_all(&bytes)
+                .unwrap_or_else(|e| fail(&e.to_string())),
+        }
+    } else if mode == "faults" {
+        // Bit flips on the Q60 datapath, either the one given or a
+        // seeded random campaign, one row per fault with how far the
+        // output moved
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| MAX_ITERS.to_string()));
+        let effects = match flag("--random") {
+            Some(count) => {
+                let seed = flag("--seed").map_or(0, |seed| parse_number::<u64>(&seed));
+                cordic_rs::random_faults(seed, parse_number::<usize>(&count), iters)
+            }
+            None => {
+                let register = match flag("--register").as_deref().unwrap_or("z") {
+                    "x" => Register::X,
+                    "y" => Register::Y,
+                    "z" => Register::Z,
+                    other => fail(&format!("unknown --register {}, expected x, y or z", other)),
+                };
+                let fault = Fault {
+                    iteration: flag("--iteration").map_or(0, |i| parse_number::<usize>(&i)),
+                    register,
+                    bit: flag("--bit").map_or(0, |bit| parse_number::<u32>(&bit)),
+                };
+                let theta = flag("--theta").map_or(1.0, |theta| parse_number::<f64>(&theta));
+                cordic_rs::inject(FixedPoint::new(theta), iters, fault).map(|effect| vec![effect])
+            }
+        }
+        .unwrap_or_else(|e| fail_on(e));
+        println!("Iteration, Register, Bit, Theta, Cosine, Sine, Deviation");
+        for effect in &effects {
+            println!(
+                "{},{:?},{},{},{},{},{:e}",
+                effect.fault.iteration,
+                effect.fault.register,
+                effect.fault.bit,
+                effect.theta,
+                effect.cos,
+                effect.sin,
+                effect.deviation
+            );
+        }
+    } else if mode == "edge-cases" {
+        // The inputs reduction and the quadrant folds find hardest (see
+        // src/edge.rs) through each backend and the options that change
+        // how angles get into the iterations, one row per pair
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| MAX_ITERS.to_string()));
+        let configs = [
+            ("Integer", CordicConfig::new(iters)),
+            ("Float", CordicConfig::new(iters).backend(Backend::Float)),
+            ("Q31", CordicConfig::new(iters).io_width(31)),
+            (
+                "Q31 double word",
+                CordicConfig::new(iters).io_width(31).double_word(true),
+            ),
+            ("Small angle", CordicConfig::new(iters).small_angle(true)),
+            (
+                "Special angles",
+                CordicConfig::new(iters).special_angles(true),
+            ),
+            ("Octant", CordicConfig::new(iters).octant_reduction(true)),
+        ];
+        println!("Configuration, Input, Theta, Cosine Error, Sine Error, Handled");
+        for (name, config) in &configs {
+            for case in edge_cases(config) {
+                println!(
+                    "{},{},{:e},{:e},{:e},{}",
+                    name, case.name, case.theta, case.cos_error, case.sin_error, case.handled
+                );
+            }
+        }
+    } else if mode == "harmonics" {
+        // The first N harmonics of a few angles three ways: the Chebyshev
+        // recurrence, the chain of angle sums and one CORDIC pass per
+        // harmonic. Rows at 1, 10, 100, ... and N give the worst sine
+        // error against f64 up to that harmonic
+        let n = flag("--n").map_or(1000, |value| parse_number::<usize>(&value));
+        println!("Theta, Harmonics, Recurrence Error, Rotation Chain Error, Independent Error");
+        for &theta in &[
+            0.001,
+            0.01,
+            0.1,
+            0.5,
+            1.0,
+            std::f64::consts::FRAC_PI_2,
+            3.0,
+            std::f64::consts::PI - 0.01,
+        ] {
+            let recurrence = harmonics(FixedPoint::new(theta), n);
+            let chain = sin_cos_multiples(FixedPoint::new(theta), n);
+            let mut worst = [0.0f64; 3];
+            for k in 0..n {
+                let angle = (k + 1) as f64 * theta;
+                let independent =
+                    cordic(FixedPoint::new(angle), MAX_ITERS).unwrap_or_else(|e| fail_on(e))[1];
+                for (worst, sin) in worst
+                    .iter_mut()
+                    .zip([recurrence[k], chain[k][1], independent])
+                {
+                    *worst = worst.max((sin.to_f64() - angle.sin()).abs());
+                }
+                if k + 1 == n || 10usize.pow((k + 1).ilog10()) == k + 1 {
+                    println!(
+                        "{},{},{:e},{:e},{:e}",
+                        theta,
+                        k + 1,
+                        worst[0],
+                        worst[1],
+                        worst[2]
+                    );
+                }
+            }
+        }
+    } else if mode == "explore" {
+        // Every configuration in Registry::exploration() over the bench
+        // sweep, keeping only the Pareto-optimal ones. --bits N narrows
+        // that down to the cheapest one with at least N correct bits, and
+        // --max-entries leaves out anything needing a bigger table
+        let thetas = sweep_angles();
+        let mut results = match Registry::exploration().accuracy(&thetas) {
+            Ok(results) => results,
+            Err(e) => fail_on(e),
+        };
+        if let Some(max) = flag("--max-entries") {
+            let max = parse_number::<usize>(&max);
+            results.retain(|result| result.cost.is_some_and(|cost| cost.table_entries <= max));
+        }
+        let mut frontier = pareto(&results);
+        if let Some(bits) = flag("--bits") {
+            let bits = parse_number::<f64>(&bits);
+            match frontier.iter().position(|result| result.bits() >= bits) {
+                Some(i) => frontier = vec![frontier.swap_remove(i)],
+                None => fail(&format!("nothing explored reaches {} bits", bits)),
+            }
+        }
 
-        // sigma
-        theta = if sigma_is_neg {
-            theta + angle
-        } else {
-            theta - angle
+        println!(
+            "Algorithm, Cycles, Adds, Shifts, Multiplies, Divides, Table Entries, Toggles, Max Error, Bits"
+        );
+        for result in frontier {
+            // pareto() leaves out anything without a cost
+            let Some(cost) = result.cost else {
+                fail(&format!("{} has no cost", result.name))
+            };
+            println!(
+                "{},{},{},{},{},{},{},{},{},{:.1}",
+                result.name,
+                cost.cycles(),
+                cost.adds,
+                cost.shifts,
+                cost.multiplies,
+                cost.divides,
+                cost.table_entries,
+                cost.toggles
+                    .map_or(String::new(), |toggles| toggles.to_string()),
+                result.max_error(),
+                result.bits()
+            );
+        }
+    } else if mode == "schedule" {
+        // Prunes the iteration schedule down to the micro-rotations the
+        // angles in --angles (separated by whitespace or commas) need
+        let path = flag("--angles").unwrap_or_else(|| fail("schedule needs --angles FILE"));
+        let text = std::fs::read_to_string(path).unwrap_or_else(|e| fail(&e.to_string()));
+        let thetas = text
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty())
+            .map(parse_number::<f64>)
+            .collect::<Vec<f64>>();
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| MAX_ITERS.to_string()));
+        let tolerance = flag("--bits").map(|bits| 2f64.powf(-parse_number::<f64>(&bits)));
+
+        let (schedule, worst) = match prune(&thetas, iters, tolerance) {
+            Ok(pruned) => pruned,
+            Err(e) => fail_on(e),
+        };
+        let list = |shifts: Vec<usize>| {
+            shifts
+                .iter()
+                .map(|shift| shift.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
         };
-        poweroftwo = poweroftwo / fixed_point_pos_two;
+        let dropped = (0..iters)
+            .filter(|shift| !schedule.shifts().contains(shift))
+            .collect();
+        println!("shifts: {}", list(schedule.shifts().to_vec()));
+        println!("dropped: {}", list(dropped));
+        println!("worst error: {}", worst);
+    } else if mode == "wordlength" {
+        // Narrowest integer datapath that keeps --bits correct bits at
+        // --iters iterations over the bench sweep
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| "24".to_string()));
+        let bits = flag("--bits").map_or(20, |bits| parse_number::<u32>(&bits));
+        let thetas = sweep_angles();
 
-        angle = angles[i as usize + 1];
-    }
+        match min_word_length(iters, bits, &thetas) {
+            Ok(word) => {
+                println!("fractional bits: {}", word.frac_bits);
+                println!("guard bits: {}", word.guard_bits);
+                println!("total bits: {}", word.total_bits);
+                println!("worst error: {}", word.worst_error);
+            }
+            Err(_) => fail(&format!(
+                "{} iterations can't reach {} bits at any width, try more iterations",
+                iters, bits
+            )),
+        }
+    } else if mode == "bits" {
+        // Heatmap of how often each output bit is right, one row per
+        // iteration count, on a --width fractional bit datapath
+        let width = flag("--width").map_or(24, |width| parse_number::<u32>(&width));
+        let thetas = sweep_angles();
 
-    // Scale vector back such that magnitude is 1
-    // NOTE: This can be done either by keeping track of the
-    // initial values or performing a square root. If the machine
-    // is slow enough that CORDIC is practical (i.e. expensive
-    // hardware multiplication), then it is too slow for square roots
-    // and divisions
-    [v[0] * kvalue, v[1] * kvalue]
-}
+        let bits = (1..=width)
+            .map(|k| format!("Bit {}", k))
+            .collect::<Vec<String>>();
+        println!("Iterations, {}, Noisy Bits", bits.join(", "));
+        for iters in 1..=(width as usize + 4).min(MAX_ITERS) {
+            let row = match bit_agreement(iters, width, &thetas) {
+                Ok(row) => row,
+                Err(e) => fail_on(e),
+            };
+            let agreement = row
+                .agreement
+                .iter()
+                .map(|agreement| format!("{:.3}", agreement))
+                .collect::<Vec<String>>();
+            println!("{},{},{}", iters, agreement.join(","), row.noisy_bits(0.99));
+        }
+    } else if mode == "attribute" {
+        // Splits the worst error over the bench sweep into the part the
+        // iterations leave and the parts each narrowed width adds
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| "24".to_string()));
+        let width = flag("--width").map_or(24, |width| parse_number::<u32>(&width));
+        let thetas = sweep_angles();
 
-fn taylor(mut theta: FixedPoint, iters: usize) -> [FixedPoint; 2] {
-    todo!()
-}
+        match attribute(iters, width, &thetas) {
+            Ok(sources) => {
+                println!("iterations: {}", sources.iterations);
+                println!("angle table: {}", sources.angle_table);
+                println!("gain: {}", sources.gain);
+                println!("rounding: {}", sources.rounding);
+                println!("total: {}", sources.total);
+            }
+            Err(e) => fail_on(e),
+        }
+    } else if mode == "carrysave" {
+        // Direction decisions taken from a window of the top bits of a
+        // carry-save z, one row per window width: how often the window
+        // can't tell the sign, how often that picks the wrong way, and
+        // what it does to the worst error against exact sign detection
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| "32".to_string()));
+        let thetas = (-628..=628)
+            .map(|i| Q60::from_bits((i as f64 / 100.0 * 2f64.powi(60)).round() as i64))
+            .collect::<Vec<Q60>>();
+
+        println!("Window Bits, Ambiguous Decisions, Wrong Decisions, Max Error");
+        for window in (2..=32).step_by(2) {
+            let (mut ambiguous, mut wrong, mut worst) = (0, 0, 0.0f64);
+            for &theta in &thetas {
+                let (ret, exact) = match (
+                    sincos_carry_save(theta, iters, window),
+                    sincos_q60(theta, iters),
+                ) {
+                    (Ok(ret), Ok(exact)) => (ret, exact),
+                    (Err(e), _) | (_, Err(e)) => fail_on(e),
+                };
+                ambiguous += ret.ambiguous;
+                wrong += ret.wrong;
+                for (a, b) in ret.sincos.iter().zip(&exact) {
+                    let error = (a.to_bits() - b.to_bits()).abs() as f64 * 2f64.powi(-60);
+                    worst = worst.max(error);
+                }
+            }
+            println!("{},{},{},{}", window, ambiguous, wrong, worst);
+        }
+    } else if mode == "speed" {
+        // Time per sincos_q60 call at and either side of the iteration
+        // counts with unrolled kernels (16, 24 and 32), so the gain from
+        // unrolling shows up against the loop doing nearly the same work.
+        // Build with --release for numbers that mean anything
+        // --small-angle times rotate() on angles below each backend's
+        // small angle threshold instead, with and without the fast path.
+        // --threads N times sincos_parallel() on 1 up to N threads, all
+        // sharing one config
+        let calls = flag("--calls").map_or(1_000_000, |value| parse_number::<usize>(&value));
+        let time = |call: &mut dyn FnMut(usize)| {
+            let start = std::time::Instant::now();
+            for i in 0..calls {
+                call(std::hint::black_box(i));
+            }
+            start.elapsed().as_nanos() as f64 / calls as f64
+        };
+        if std::env::args().any(|arg| arg == "--small-angle") {
+            println!("Backend, Fast Path, ns per call");
+            for &backend in &[Backend::Integer, Backend::Float] {
+                let limit = 0.5f64.powi(backend.small_angle_bits() as i32);
+                let thetas = (0..1024)
+                    .map(|i| FixedPoint::new(limit * (i as f64 - 512.0) / 512.0))
+                    .collect::<Vec<FixedPoint>>();
+                for &fast in &[false, true] {
+                    let config = CordicConfig::new(MAX_ITERS)
+                        .backend(backend)
+                        .small_angle(fast);
+                    let elapsed = time(&mut |i| {
+                        std::hint::black_box(
+                            config
+                                .rotate(thetas[i % thetas.len()])
+                                .unwrap_or_else(|e| fail_on(e)),
+                        );
+                    });
+                    println!("{:?},{},{:.2}", backend, fast, elapsed);
+                }
+            }
+            return;
+        }
+        if let Some(threads) = flag("--threads") {
+            let threads = parse_number::<usize>(&threads);
+            let config = CordicConfig::new(MAX_ITERS);
+            let thetas = (0..calls)
+                .map(|i| FixedPoint::new(((i % 1257) as f64 - 628.0) / 100.0))
+                .collect::<Vec<FixedPoint>>();
+            let mut sin = vec![FixedPoint::new(0.0); calls];
+            let mut cos = vec![FixedPoint::new(0.0); calls];
+            println!("Threads, ns per call");
+            for threads in 1..=threads {
+                let start = std::time::Instant::now();
+                config
+                    .sincos_parallel(&thetas, &mut sin, &mut cos, threads)
+                    .unwrap_or_else(|e| fail_on(e));
+                let elapsed = start.elapsed().as_nanos() as f64 / calls as f64;
+                println!("{},{:.2}", threads, elapsed);
+            }
+            return;
+        }
 
+        let thetas = (0..1024)
+            .map(|i| Q60::from_bits((i - 512) * (3 << 51)))
+            .collect::<Vec<Q60>>();
+        println!("Iterations, ns per call");
+        for &iters in &[15, 16, 17, 23, 24, 25, 31, 32, 33] {
+            let elapsed = time(&mut |i| {
+                std::hint::black_box(
+                    sincos_q60(thetas[i % thetas.len()], iters).unwrap_or_else(|e| fail_on(e)),
+                );
+            });
+            println!("{},{:.2}", iters, elapsed);
+        }
+    } else if mode == "recode" {
+        // Angle recoding against plain CORDIC at a range of iteration
+        // counts: how many micro-rotations recoding actually does on
+        // average, and the worst error of each over the bench sweep
+        let thetas = sweep_angles();
+        let error = |theta: f64, sincos: [Q60; 2]| {
+            let [cos, sin] = sincos.map(|x| x.to_bits() as f64 * 2f64.powi(-60));
+            (cos - theta.cos()).abs().max((sin - theta.sin()).abs())
+        };
 
-fn main() {
-    // Pull parameters from string, should be called as either
-    // ./cordic-rs [theta] [iters]
-    // or
-    // cargo run [theta] [iters]
-    let mode = std::env::args().nth(1).unwrap().parse::<String>().unwrap();
-    
-    if mode == "compute" {
-        let theta = FixedPoint::new(std::env::args().nth(2).unwrap().parse::<f64>().unwrap());
-        let iters = std::env::args().nth(3).unwrap().parse::<usize>().unwrap();
-
-        let ret = cordic(theta, iters);
-        println!("cos {} == {}\nsin {} == {}", theta, ret[0], theta, ret[1]);
-    } else if mode == "bench" {
-        // NOTE: Output is a CSV file that I will open in Excel
-        println!("Theta, CORDIC Cosine, Standard Cosine, Cosine Error, CORDIC Sine, Standard Sine, Sine Error");
-        for i in 0..314 {
-            let theta = i as f64 / 100.0;
+        println!("Iterations, Average Rotations, Max Error, Plain Max Error");
+        for iters in (8..=48).step_by(8) {
+            let (mut rotations, mut worst, mut plain) = (0, 0.0f64, 0.0f64);
+            for &theta in &thetas {
+                let q60 = Q60::from_bits((theta * 2f64.powi(60)).round() as i64);
+                let (ret, exact) = match (sincos_recoded(q60, iters), sincos_q60(q60, iters)) {
+                    (Ok(ret), Ok(exact)) => (ret, exact),
+                    (Err(e), _) | (_, Err(e)) => fail_on(e),
+                };
+                rotations += ret.rotations;
+                worst = worst.max(error(theta, ret.sincos));
+                plain = plain.max(error(theta, exact));
+            }
+            let average = rotations as f64 / thetas.len() as f64;
+            println!("{},{:.2},{},{}", iters, average, worst, plain);
+        }
+    } else if mode == "pipeline" {
+        // Latency, throughput and register count of a hardware rotator,
+        // see src/pipeline.rs for the model
+        let number = |name: &str, default: usize| {
+            flag(name).map_or(default, |value| parse_number::<usize>(&value))
+        };
+        let iters = number("--iters", 16);
+        let width = number("--width", 16) as u32;
+        let mut pipeline = Pipeline::new(iters, width)
+            .angle_width(number("--angle-width", width as usize) as u32)
+            .growth(number("--growth", 2) as u32)
+            .stage_delay(number("--stage-delay", 1));
+        if std::env::args().any(|arg| arg == "--iterative") {
+            pipeline = pipeline.architecture(Architecture::Iterative);
+        }
+        if std::env::args().any(|arg| arg == "--prescale") {
+            pipeline = pipeline.gain_compensation(GainCompensation::Prescale);
+        }
+        if std::env::args().any(|arg| arg == "--csd") {
+            pipeline = pipeline.gain_compensation(GainCompensation::Csd);
+        }
+
+        match pipeline.report() {
+            Ok(report) => {
+                println!("stages: {}", report.stages);
+                println!("latency: {} cycles", report.latency);
+                println!("initiation interval: {} cycles", report.initiation_interval);
+                println!("flip-flops: {}", report.flip_flops);
+                println!("adders: {}", report.adders);
+                println!("multipliers: {}", report.multipliers);
+            }
+            Err(e) => fail_on(e),
+        }
+    } else if mode == "tone" {
+        // Every sample goes through the NCO and out as Q15, so lowering
+        // --iters makes the CORDIC error audible as harmonics and noise
+        let number = |name: &str, default: f64| {
+            flag(name).map_or(default, |value| parse_number::<f64>(&value))
+        };
+        let freq = number("--freq", 440.0);
+        let rate = number("--rate", 48000.0);
+        let secs = number("--secs", 2.0);
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| MAX_ITERS.to_string()));
 
-            let cordic_val = cordic(FixedPoint::new(theta), 100);
-            let cos_val = (theta).cos();
-            let sin_val = (theta).sin();
+        // --sweep-to HZ makes it a linear sweep from --freq to there
+        let count = (rate * secs).round() as usize;
+        let tone: Box<dyn Iterator<Item = [FixedPoint; 2]>> = match flag("--sweep-to") {
+            Some(end) => {
+                let end = parse_number::<f64>(&end);
+                match Chirp::new(freq, end, rate, count, iters) {
+                    Ok(chirp) => Box::new(chirp),
+                    Err(e) => fail_on(e),
+                }
+            }
+            // --shape truncates to 16 bits with error feedback rather
+            // than rounding
+            None => {
+                let shape = std::env::args().any(|arg| arg == "--shape");
+                let nco = Nco::new(freq, rate, iters).and_then(|nco| match shape {
+                    true => nco.amplitude_bits(16, true),
+                    false => Ok(nco),
+                });
+                match nco {
+                    Ok(nco) => Box::new(nco),
+                    Err(e) => fail_on(e),
+                }
+            }
+        };
+        let samples = tone
+            .take(count)
+            .map(|[_, sin]| sin.to_q15())
+            .collect::<Vec<i16>>();
+        let bytes = wav_pcm16(&samples, rate as u32);
+        match flag("--out") {
+            Some(path) => std::fs::write(path, bytes).unwrap_or_else(|e| fail(&e.to_string())),
+            None => std::io::stdout()
+                .write_all(&bytes)
+                .unwrap_or_else(|e| fail(&e.to_string())),
+        }
+    } else if mode == "shaping" {
+        // Truncates the NCO's output to --bits with and without error
+        // feedback, and prints how much of the truncation error lands in
+        // each eighth of the band, in dB against a full scale sine.
+        // --samples has to be a power of two, 16 or more
+        let number = |name: &str, default: f64| {
+            flag(name).map_or(default, |value| parse_number::<f64>(&value))
+        };
+        let freq = number("--freq", 1234.5);
+        let rate = number("--rate", 48000.0);
+        let bits = number("--bits", 8.0) as u32;
+        let samples = number("--samples", 4096.0) as usize;
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| MAX_ITERS.to_string()));
+        // Eight bands need at least a bin each, and the spectrum has half
+        // as many bins as there are samples
+        if samples < 16 {
+            fail("--samples must be at least 16");
+        }
 
-            // NOTE: Pulling from `val` directly is a hack and shouldn't be allowed
+        let mut errors = vec![];
+        for feedback in &[false, true] {
+            let pair = (
+                Nco::new(freq, rate, iters),
+                Nco::new(freq, rate, iters).and_then(|nco| nco.amplitude_bits(bits, *feedback)),
+            );
+            let (ideal, quantized) = match pair {
+                (Ok(ideal), Ok(quantized)) => (ideal, quantized),
+                (Err(e), _) | (_, Err(e)) => fail_on(e),
+            };
+            let error = ideal
+                .zip(quantized)
+                .take(samples)
+                .map(|([_, exact], [_, sample])| sample.to_f64() - exact.to_f64())
+                .collect::<Vec<f64>>();
+            // Rectangular, the error is noise and spurs spread all over
+            match power_spectrum(&error, None) {
+                Ok(spectrum) => errors.push(
+                    spectrum
+                        .chunks(spectrum.len() / 8)
+                        .map(|band| band.iter().sum::<f64>())
+                        .collect::<Vec<f64>>(),
+                ),
+                Err(e) => fail_on(e),
+            }
+        }
+        println!("Band (Hz), Truncated (dB), Error Feedback (dB)");
+        for (band, (plain, shaped)) in errors[0].iter().zip(&errors[1]).enumerate() {
+            let edge = rate / 16.0;
             println!(
-                "{},{},{},{},{},{}", 
-                cordic_val[0], cos_val, (cordic_val[0].val - cos_val).abs(),
-                cordic_val[1], sin_val, (cordic_val[1].val - sin_val).abs()
+                "{}-{},{},{}",
+                band as f64 * edge,
+                (band + 1) as f64 * edge,
+                10.0 * (plain / 0.5).log10(),
+                10.0 * (shaped / 0.5).log10()
             );
         }
-    }
-}
+    } else if mode == "spectrum" {
+        // The SFDR of the NCO's sine output, optionally truncated to
+        // --bits (with error feedback if --shape), so the effect of the
+        // iteration count and output width can be read off directly.
+        // The tone is moved onto the nearest odd FFT bin, which a power
+        // of two --samples makes exact for the NCO's 2^32 step, so the
+        // spectrum needs no window and has no leakage to hide spurs under
+        let number = |name: &str, default: f64| {
+            flag(name).map_or(default, |value| parse_number::<f64>(&value))
+        };
+        let rate = number("--rate", 48000.0);
+        let samples = number("--samples", 8192.0) as usize;
+        let bin = (number("--freq", 1234.5) * samples as f64 / rate / 2.0).floor() * 2.0 + 1.0;
+        let freq = bin * rate / samples as f64;
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| "16".to_string()));
+        let shape = std::env::args().any(|arg| arg == "--shape");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let nco = Nco::new(freq, rate, iters).and_then(|nco| match flag("--bits") {
+            Some(bits) => nco.amplitude_bits(parse_number::<u32>(&bits), shape),
+            None => Ok(nco),
+        });
+        let signal = match nco {
+            Ok(nco) => nco
+                .take(samples)
+                .map(|[_, sin]| sin.to_f64())
+                .collect::<Vec<f64>>(),
+            Err(e) => fail_on(e),
+        };
+        match sfdr(&signal, None) {
+            Ok(result) => {
+                let hz = |bin: usize| bin as f64 * rate / samples as f64;
+                println!("carrier: {} Hz", hz(result.carrier_bin));
+                println!("worst spur: {} Hz", hz(result.spur_bin));
+                println!("SFDR: {:.1} dBc", result.dbc);
+            }
+            Err(e) => fail_on(e),
+        }
+    } else if mode == "dds" {
+        // The SFDR and SNR of every combination of accumulator width,
+        // phase bits kept, iterations and output width, each flag taking
+        // a comma separated list. The error against the exact tone is
+        // what gets analyzed, so --freq doesn't need to sit on a bin
+        let number = |name: &str, default: f64| {
+            flag(name).map_or(default, |value| parse_number::<f64>(&value))
+        };
+        let list = |name: &str, default: &str| {
+            flag(name)
+                .unwrap_or_else(|| default.to_string())
+                .split(',')
+                .map(|value| parse_number::<u32>(value.trim()))
+                .collect::<Vec<u32>>()
+        };
+        let freq = number("--freq", 1234.5);
+        let rate = number("--rate", 48000.0);
+        let samples = number("--samples", 8192.0) as usize;
+        let accumulators = list("--acc", "32,24");
+        let phases = list("--phase", "32,16,12");
+        let iterations = list("--iters", "12,16,24");
+        let widths = list("--bits", "16,12");
 
-    fn close_enough(a: FixedPoint, b: FixedPoint) -> bool {
-        (a - b) < FixedPoint::new(0.01)
-    }
+        println!("Accumulator Bits, Phase Bits, Iterations, Output Bits, SFDR (dBc), SNR (dB)");
+        for &acc in &accumulators {
+            for &phase in &phases {
+                for &iters in &iterations {
+                    for &bits in &widths {
+                        let nco = Nco::new(freq, rate, iters as usize)
+                            .and_then(|nco| nco.accumulator_bits(acc))
+                            .and_then(|nco| nco.phase_bits(phase))
+                            .and_then(|nco| nco.amplitude_bits(bits, false));
+                        let mut nco = match nco {
+                            Ok(nco) => nco,
+                            Err(e) => fail_on(e),
+                        };
+                        let (mut output, mut ideal) = (vec![], vec![]);
+                        for _ in 0..samples {
+                            ideal.push(nco.phase().to_f64().sin());
+                            // The NCO only stops if a rotation fails
+                            let Some([_, sin]) = nco.next() else {
+                                fail("the NCO stopped")
+                            };
+                            output.push(sin.to_f64());
+                        }
+                        match tone_quality(&output, &ideal) {
+                            Ok(quality) => println!(
+                                "{},{},{},{},{:.1},{:.1}",
+                                acc, phase, iters, bits, quality.sfdr, quality.snr
+                            ),
+                            Err(e) => fail_on(e),
+                        }
+                    }
+                }
+            }
+        }
+    } else if mode == "pll" {
+        // Locks a PLL centred on --freq onto a tone --offset Hz away from
+        // it, printing the phase error of every sample, and on stderr
+        // how long it took to settle within 0.001 radians
+        let number = |name: &str, default: f64| {
+            flag(name).map_or(default, |value| parse_number::<f64>(&value))
+        };
+        let freq = number("--freq", 1000.0);
+        let offset = number("--offset", 50.0);
+        let rate = number("--rate", 48000.0);
+        let samples = number("--samples", 2000.0) as usize;
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| "32".to_string()));
+
+        let (input, mut pll) = match (
+            Nco::new(freq + offset, rate, iters),
+            Pll::new(freq, rate, iters),
+        ) {
+            (Ok(input), Ok(pll)) => (input, pll),
+            (Err(e), _) | (_, Err(e)) => fail_on(e),
+        };
+        println!("Sample, Phase Error");
+        let mut settled = None;
+        for (i, sample) in input.take(samples).enumerate() {
+            let error = match pll.process(sample) {
+                Ok(error) => error.to_f64(),
+                Err(e) => fail_on(e),
+            };
+            println!("{},{}", i, error);
+            if error.abs() >= 1e-3 {
+                settled = None;
+            } else if settled.is_none() {
+                settled = Some(i);
+            }
+        }
+        match settled {
+            Some(i) => eprintln!(
+                "locked after {} samples, tracking {} Hz",
+                i,
+                pll.frequency() * rate
+            ),
+            None => eprintln!("not locked after {} samples", samples),
+        }
+    } else if mode == "rotate-image" {
+        let input = positional(2, "in");
+        let output = positional(3, "out");
+        let theta = FixedPoint::new(parse_number::<f64>(&positional(4, "theta")));
 
-    #[test]
-    fn basic() {
-        for i in 0..157 {
-            // pi/2
-            let ret = cordic(FixedPoint::new(i as f64 / 100.0), 1000);
-            let cos = FixedPoint::new((i as f64 / 100.0).cos());
-            let sin = FixedPoint::new((i as f64 / 100.0).sin());
+        let bytes = std::fs::read(input).unwrap_or_else(|e| fail(&e.to_string()));
+        match Image::from_pnm(&bytes) {
+            Ok(image) => std::fs::write(output, image.rotate(theta).to_pnm())
+                .unwrap_or_else(|e| fail(&e.to_string())),
+            Err(e) => fail_on(e),
+        }
+    } else if mode == "jacobi" {
+        // Diagonalizes the symmetric matrix [[a, b], [c, d]] (so b == c)
+        // and shows the rotation, the eigenvalues and what's left off the
+        // diagonal after applying it
+        let entries = (2..6)
+            .map(
+                |i| match std::env::args().nth(i).map(|arg| arg.parse::<f64>()) {
+                    Some(Ok(entry)) => FixedPoint::new(entry),
+                    _ => fail("jacobi needs the four entries a b c d"),
+                },
+            )
+            .collect::<Vec<FixedPoint>>();
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| "32".to_string()));
+        let matrix = [[entries[0], entries[1]], [entries[2], entries[3]]];
+
+        let schur = match jacobi_rotation(matrix, iters) {
+            Ok(schur) => schur,
+            Err(e) => fail(&format!("{} (the matrix must be symmetric)", e)),
+        };
+        let diagonal = schur.apply(matrix);
+        println!("Rotation Angle, Cosine, Sine, Eigenvalue 1, Eigenvalue 2, Off Diagonal");
+        println!(
+            "{},{},{},{},{},{}",
+            schur.theta,
+            schur.cos,
+            schur.sin,
+            schur.eigenvalues[0],
+            schur.eigenvalues[1],
+            diagonal[0][1]
+        );
+    } else if mode == "svd" {
+        // The SVD of [[a, b], [c, d]] from CORDIC rotations, next to the
+        // singular values worked out directly in f64
+        let entries = (2..6)
+            .map(
+                |i| match std::env::args().nth(i).map(|arg| arg.parse::<f64>()) {
+                    Some(Ok(entry)) => entry,
+                    _ => fail("svd needs the four entries a b c d"),
+                },
+            )
+            .collect::<Vec<f64>>();
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| "32".to_string()));
+        let [a, b, c, d] = [entries[0], entries[1], entries[2], entries[3]];
+        let matrix = [
+            [FixedPoint::new(a), FixedPoint::new(b)],
+            [FixedPoint::new(c), FixedPoint::new(d)],
+        ];
 
+        let svd = match svd2(matrix, iters) {
+            Ok(svd) => svd,
+            Err(e) => fail_on(e),
+        };
+        let t = a * a + b * b + c * c + d * d;
+        let det = a * d - b * c;
+        let root = (t * t - 4.0 * det * det).max(0.0).sqrt();
+        let reference = [
+            ((t + root) / 2.0).sqrt(),
+            ((t - root) / 2.0).max(0.0).sqrt(),
+        ];
+        let back = svd.reconstruct();
+        let error = (0..4)
+            .map(|k| (back[k / 2][k % 2].to_f64() - entries[k]).abs())
+            .fold(0.0, f64::max);
+
+        println!(
+            "U = [{}, {}; {}, {}]",
+            svd.u[0][0], svd.u[0][1], svd.u[1][0], svd.u[1][1]
+        );
+        println!(
+            "V = [{}, {}; {}, {}]",
+            svd.v[0][0], svd.v[0][1], svd.v[1][0], svd.v[1][1]
+        );
+        println!("Singular Value, CORDIC, f64");
+        for (k, (sigma, reference)) in svd.sigma.iter().zip(&reference).enumerate() {
+            println!("{},{},{}", k + 1, sigma, reference);
+        }
+        println!("Max reconstruction error: {}", error);
+    } else if mode == "serve" {
+        let port = flag("--port").map_or(8080, |port| parse_number::<u16>(&port));
+        #[cfg(feature = "serve")]
+        {
+            eprintln!("listening on http://127.0.0.1:{}", port);
+            if let Err(e) = cordic_rs::serve::run(port) {
+                fail(&e.to_string());
+            }
+        }
+        #[cfg(not(feature = "serve"))]
+        fail(&format!(
+            "can't serve on port {}, rebuild with --features serve",
+            port
+        ));
+    } else if mode == "minimax" {
+        // Remez coefficients for one of std's functions, see src/remez.rs
+        let (name, f) = reference_function();
+        let (a, b) = interval();
+        let degree = flag("--degree").map_or(5, |degree| parse_number::<usize>(&degree));
+        let poly = cordic_rs::minimax(f, a, b, degree).unwrap_or_else(|e| fail_on(e));
+        eprintln!(
+            "max error {:e} after {} exchanges",
+            poly.max_error, poly.exchanges
+        );
+
+        let manifest = manifest(None)
+            .field("function", &name)
+            .field("interval", format!("[{}, {}]", a, b))
+            .field("degree", degree);
+        let text = match flag("--format") {
+            None => {
+                let rows = poly
+                    .coeffs
+                    .iter()
+                    .enumerate()
+                    .map(|(power, c)| format!("{},{:e}\n", power, c))
+                    .collect::<String>();
+                manifest.to_comments("# ") + "Power, Coefficient\n" + &rows
+            }
+            Some(format) => {
+                let format = format.parse::<QFormat>().unwrap_or_else(|e| fail_on(e));
+                let strategy = flag("--strategy").map_or(Strategy::Round(Rounding::Nearest), |s| {
+                    s.parse::<Strategy>().unwrap_or_else(|e| fail_on(e))
+                });
+                let quantized = quantize_coefficients(f, &poly.coeffs, (a, b), format, strategy)
+                    .unwrap_or_else(|e| fail_on(e));
+                eprintln!(
+                    "max error {:e} quantized to {} by {}",
+                    quantized.max_error, format, strategy
+                );
+                let table = flag("--name").unwrap_or_else(|| format!("{}_poly", name));
+                let code = match flag("--lang").as_deref() {
+                    None | Some("rust") => quantized.to_rust(&table),
+                    Some("c") => quantized.to_c(&table),
+                    Some(other) => {
+                        fail(&format!("unsupported --lang {}, expected rust or c", other))
+                    }
+                };
+                manifest
+                    .field("q format", format)
+                    .field("strategy", strategy)
+                    .to_comments("// ")
+                    + &code
+            }
+        };
+        match flag("--out") {
+            Some(path) => std::fs::write(path, text).unwrap_or_else(|e| fail(&e.to_string())),
+            None => print!("{}", text),
+        }
+    } else if mode == "quantize" {
+        // What each way of rounding the coefficients to --format costs,
+        // for --coeffs (of 1, x, x^2, ...) or the --degree minimax
+        // polynomial, see src/quantization.rs
+        let (name, f) = reference_function();
+        let (a, b) = interval();
+        let format = flag("--format")
+            .unwrap_or_else(|| "Q15".to_string())
+            .parse::<QFormat>()
+            .unwrap_or_else(|e| fail_on(e));
+        let coeffs = match flag("--coeffs") {
+            Some(coeffs) => coeffs
+                .split(',')
+                .map(parse_number::<f64>)
+                .collect::<Vec<_>>(),
+            None => {
+                let degree = flag("--degree").map_or(5, |degree| parse_number::<usize>(&degree));
+                cordic_rs::minimax(f, a, b, degree)
+                    .unwrap_or_else(|e| fail_on(e))
+                    .coeffs
+            }
+        };
+        let analysis =
+            analyze_quantization(f, &coeffs, (a, b), format).unwrap_or_else(|e| fail_on(e));
+        print!(
+            "{}",
+            manifest(None)
+                .field("function", &name)
+                .field("interval", format!("[{}, {}]", a, b))
+                .field("q format", format)
+                .to_comments("# ")
+        );
+        println!("# unquantized max error: {:e}", analysis.ideal_error);
+        println!("Strategy, Max error, Added error, Coefficients");
+        for (strategy, quantized) in &analysis.results {
+            let bits = quantized
+                .coeffs
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
             println!(
-                "Theta == {}\t{} vs {}\t{} vs {}",
-                (i as f64 / 100.0),
-                ret[0],
-                cos,
-                ret[1],
-                sin
+                "{},{:e},{:e},{}",
+                strategy,
+                quantized.max_error,
+                quantized.max_error - analysis.ideal_error,
+                bits
             );
+        }
+        eprintln!("best: {}", analysis.best().0);
+    } else if mode == "approx" {
+        // Minimax polynomials, tables and the CORDIC version of --function
+        // over --interval, measured at --points points, see
+        // src/workbench.rs. --pareto keeps only the cheapest way to each
+        // level of accuracy
+        let (name, f) = reference_function();
+        let (a, b) = interval();
+        let points = flag("--points").map_or(1001, |points| parse_number::<usize>(&points));
+        let mut bench = Workbench::with_builtins(f, a, b).unwrap_or_else(|e| fail_on(e));
+        bench.register(Composed::new(
+            &format!("cordic-{}", name),
+            cordic_function(&name),
+        ));
+        let mut results = bench.accuracy(f, points).unwrap_or_else(|e| fail_on(e));
+        if std::env::args().any(|arg| arg == "--pareto") {
+            results = pareto(&results);
+        }
+
+        print!(
+            "{}",
+            manifest(None)
+                .field("function", &name)
+                .field("interval", format!("[{}, {}]", a, b))
+                .field("points", points)
+                .to_comments("# ")
+        );
+        println!("Approximation, Cycles, Table Entries, Max Error, RMS Error, Bits");
+        for result in results {
+            let (cycles, entries) = result.cost.map_or((String::new(), String::new()), |cost| {
+                (cost.cycles().to_string(), cost.table_entries.to_string())
+            });
+            println!(
+                "{},{},{},{:e},{:e},{:.1}",
+                result.name,
+                cycles,
+                entries,
+                result.max_error,
+                result.rms_error,
+                result.bits()
+            );
+        }
+    } else if mode == "stress" {
+        // --count seeded random angles at --iters iterations against a
+        // bound of --slack * 2^(1 - iters), each failure shrunk and
+        // printed as a regression test, see src/stress.rs
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| "24".to_string()));
+        let count = flag("--count").map_or(10000, |count| parse_number::<usize>(&count));
+        let seed = flag("--seed").map_or(0, |seed| parse_number::<u64>(&seed));
+        let slack = flag("--slack").map_or(1.0, |slack| parse_number::<f64>(&slack));
+        let octant = std::env::args().any(|arg| arg == "--octant");
+        let config = CordicConfig::new(iters)
+            .backend(backend())
+            .octant_reduction(octant);
+        let bound = |iters: usize| slack * 2f64.powi(1 - iters as i32) + 1e-14;
 
-            assert![close_enough(ret[0], cos)];
-            assert![close_enough(ret[1], sin)];
+        let failures = stress(&config, seed, count, bound).unwrap_or_else(|e| fail_on(e));
+        eprintln!("{} of {} angles out of bound", failures.len(), count);
+        let mut minimal = Vec::new();
+        for failure in &failures {
+            let shrunk = minimize(&config, failure, bound).unwrap_or_else(|e| fail_on(e));
+            if !minimal.contains(&shrunk) {
+                minimal.push(shrunk);
+            }
         }
+        for (i, failure) in minimal.iter().enumerate() {
+            let name = format!("stress_seed_{}_{}", seed, i);
+            println!("{}", failure.regression_test(&config, &name));
+        }
+        if !failures.is_empty() {
+            std::process::exit(1);
+        }
+    } else if mode == "sintable" {
+        // Sine lookup table in the given Q format, see src/sintable.rs
+        let entries = flag("--entries").map_or(256, |entries| parse_number::<usize>(&entries));
+        let format = match flag("--format").map_or(Ok(QFormat::Q15), |q| q.parse::<QFormat>()) {
+            Ok(format) => format,
+            Err(e) => fail_on(e),
+        };
+        let table = match generate_table(entries, format) {
+            Ok(table) => table,
+            Err(e) => fail_on(e),
+        };
+        eprintln!("max linear interpolation error {:e}", table.max_error());
+        eprintln!("fingerprint {:016x}", table.fingerprint());
+
+        let name = flag("--name").unwrap_or_else(|| "sin_table".to_string());
+        let manifest = manifest(None)
+            .field("entries", entries)
+            .field("q format", format)
+            .field("fingerprint", format!("{:016x}", table.fingerprint()))
+            .checksum("table crc32", without_crc(&table.to_bytes(Endian::Little)));
+        let bytes = match flag("--lang").as_deref() {
+            None | Some("bin") => {
+                let endian = match flag("--endian").as_deref() {
+                    None | Some("little") => Endian::Little,
+                    Some("big") => Endian::Big,
+                    Some(other) => fail(&format!(
+                        "unknown --endian {}, expected little or big",
+                        other
+                    )),
+                };
+                write_binary(&table.to_bytes(endian), &manifest);
+                return;
+            }
+            Some("rust") => table.to_rust(&name).into_bytes(),
+            Some("c") => table.to_c(&name).into_bytes(),
+            Some(other) => fail(&format!(
+                "unsupported --lang {}, expected bin, rust or c",
+                other
+            )),
+        };
+        let bytes = [manifest.to_comments("// ").into_bytes(), bytes].concat();
+        match flag("--out") {
+            Some(path) => std::fs::write(path, bytes).unwrap_or_else(|e| fail(&e.to_string())),
+            None => std::io::stdout()
+                .write_all(&bytes)
+                .unwrap_or_else(|e| fail(&e.to_string())),
+        }
+    } else if mode == "window" {
+        // Window coefficients in the given Q format, see src/window.rs
+        let window = match flag("--kind").map_or(Ok(Window::Hann), |kind| kind.parse::<Window>()) {
+            Ok(window) => window,
+            Err(e) => fail_on(e),
+        };
+        let points = flag("--points").map_or(64, |points| parse_number::<usize>(&points));
+        let format = match flag("--format").map_or(Ok(QFormat::Q15), |q| q.parse::<QFormat>()) {
+            Ok(format) => format,
+            Err(e) => fail_on(e),
+        };
+        let table = WindowTable::new(window, points, format);
+
+        let name = flag("--name").unwrap_or_else(|| window.to_string());
+        let text = match flag("--lang").as_deref() {
+            None | Some("c") => table.to_c(&name),
+            Some("rust") => table.to_rust(&name),
+            Some(other) => fail(&format!("unsupported --lang {}, expected rust or c", other)),
+        };
+        let manifest = manifest(None)
+            .field("window", window)
+            .field("points", points)
+            .field("q format", format);
+        let text = manifest.to_comments("// ") + &text;
+        match flag("--out") {
+            Some(path) => std::fs::write(path, text).unwrap_or_else(|e| fail(&e.to_string())),
+            None => print!("{}", text),
+        }
+    } else if mode == "gen" && flag("--constant").is_some() {
+        // A constant multiplier as a shift-add network, see src/csd.rs
+        let constant = parse_number::<f64>(&flag("--constant").unwrap_or_default());
+        let frac_bits = flag("--frac-bits").map_or(30, |bits| parse_number::<u32>(&bits));
+        let multiplier = csd::recode(constant, frac_bits).unwrap_or_else(|e| fail_on(e));
+        eprintln!(
+            "{} shifted copies, {} adders",
+            multiplier.shifts(),
+            multiplier.adders()
+        );
+        let name = flag("--name").unwrap_or_else(|| "mul_constant".to_string());
+        let text = match flag("--lang").as_deref() {
+            None | Some("rust") => multiplier.to_rust(&name),
+            Some("c") => multiplier.to_c(&name),
+            Some("verilog") => {
+                let width = flag("--width").map_or(32, |width| parse_number::<u32>(&width));
+                multiplier.to_verilog(&name, width)
+            }
+            Some(other) => fail(&format!(
+                "unsupported --lang {}, expected rust, c or verilog",
+                other
+            )),
+        };
+        let manifest = manifest(None)
+            .field("constant", constant)
+            .field("fractional bits", frac_bits)
+            .field("adders", multiplier.adders());
+        let text = manifest.to_comments("// ") + &text;
+        match flag("--out") {
+            Some(path) => std::fs::write(path, text).unwrap_or_else(|e| fail(&e.to_string())),
+            None => print!("{}", text),
+        }
+    } else if mode == "gen" {
+        // Angle/gain tables for loading onto a device, see src/table.rs
+        // for the layout
+        let lang = flag("--lang").unwrap_or_else(|| "bin".to_string());
+        if lang != "bin" {
+            fail(&format!("unsupported --lang {}, expected bin", lang));
+        }
+        let iters = parse_iters(flag("--iters").unwrap_or_else(|| MAX_ITERS.to_string()));
+        let frac_bits = flag("--frac-bits").map_or(30, |bits| parse_number::<u32>(&bits));
+        let endian = match flag("--endian").as_deref() {
+            None | Some("little") => Endian::Little,
+            Some("big") => Endian::Big,
+            Some(other) => fail(&format!(
+                "unknown --endian {}, expected little or big",
+                other
+            )),
+        };
+
+        let tables = Tables::generate(iters, frac_bits).unwrap_or_else(|e| fail_on(e));
+        eprintln!("fingerprint {:016x}", tables.fingerprint());
+        let bytes = tables.to_bytes(endian);
+        let manifest = manifest(None)
+            .field("iterations", iters)
+            .field(
+                "q format",
+                format!("Q{}.{}", 31 - frac_bits.min(31), frac_bits),
+            )
+            .field("endian", format!("{:?}", endian))
+            .field("fingerprint", format!("{:016x}", tables.fingerprint()))
+            .checksum("file crc32", without_crc(&bytes));
+        write_binary(&bytes, &manifest);
+    } else {
+        eprint!("error: unknown mode {}\n{}", mode, USAGE);
+        std::process::exit(1);
+    }
+}
+
+// Without floating point only `compute` (and `selftest`) is left, running the integer
+// backend directly with theta parsed and the results printed as exact
+// Q60 decimals
+#[cfg(not(feature = "float"))]
+fn main() {
+    let mode = std::env::args().nth(1).unwrap_or_default();
+    if mode == "selftest" {
+        run_selftest();
+    }
+    if mode != "compute" {
+        fail("only compute and selftest are available when built without the float feature");
+    }
+    if verbosity() > 0 {
+        set_logger(log_to_stderr, LogLevel::Debug);
+    }
+
+    let arg = std::env::args().nth(2).unwrap_or_default();
+    let theta = arg.parse::<Q60>().unwrap_or_else(|e| fail_on(e));
+    let iters = std::env::args()
+        .nth(3)
+        .map_or(60, |arg| parse_number::<usize>(&arg));
+    match sincos_q60(theta, iters) {
+        Ok([cos, sin]) => println!("cos {} == {}\nsin {} == {}", theta, cos, theta, sin),
+        Err(e) => fail_on(e),
     }
 }
@@ -0,0 +1,162 @@
+use crate::cordic::{angle_table, gain, Backend};
+use crate::error::CordicError;
+use crate::integer::{angle_table_q60, gain_q60};
+use crate::table::{crc32, fnv1a};
+use std::fmt::Display;
+
+// What produced an output file, so a CSV or table found months later can
+// be traced back to the exact run: the crate version and command line
+// first, then whatever the output depends on (backend, Q format, table
+// fingerprints...) in the order they were added. Text outputs carry it as
+// comment lines, binary ones as a JSON file next to them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub fields: Vec<(String, String)>,
+}
+
+impl Manifest {
+    // `args` is the whole command line, program name included. Arguments
+    // with spaces or quotes in them are single quoted so the line can be
+    // pasted back into a shell
+    pub fn new(args: &[String]) -> Self {
+        let quote = |arg: &String| {
+            if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '\'' || c == '"')
+            {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            } else {
+                arg.clone()
+            }
+        };
+        Manifest {
+            fields: vec![
+                ("version".to_string(), env!("CARGO_PKG_VERSION").to_string()),
+                (
+                    "command".to_string(),
+                    args.iter().map(quote).collect::<Vec<_>>().join(" "),
+                ),
+            ],
+        }
+    }
+
+    pub fn field(mut self, key: &str, value: impl Display) -> Self {
+        self.fields.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    // A CRC-32 of `bytes` as eight hex digits, e.g. of a generated table
+    pub fn checksum(self, key: &str, bytes: &[u8]) -> Self {
+        self.field(key, format!("{:08x}", crc32(bytes)))
+    }
+
+    // One "key: value" line per field behind `prefix`, e.g. "# " for CSV,
+    // "% " for MATLAB or "// " for Rust and C
+    pub fn to_comments(&self, prefix: &str) -> String {
+        self.fields
+            .iter()
+            .map(|(key, value)| format!("{}{}: {}\n", prefix, key, value))
+            .collect()
+    }
+
+    // A flat JSON object of strings, for a `<output>.json` sidecar
+    pub fn to_json(&self) -> String {
+        let fields = self
+            .fields
+            .iter()
+            .map(|(key, value)| format!("  {}: {}", json_string(key), json_string(value)))
+            .collect::<Vec<_>>();
+        format!("{{\n{}\n}}\n", fields.join(",\n"))
+    }
+}
+
+// A fingerprint of the tables a rotation on `backend` at `iters`
+// iterations reads, the angles then the gain, hashing each entry's bits
+// in little endian order (see Tables::fingerprint()). Two runs with the
+// same fingerprint used the same constants, which the crate version alone
+// doesn't promise if the tables are regenerated
+pub fn table_fingerprint(backend: Backend, iters: usize) -> Result<u64, CordicError> {
+    let mut bytes = Vec::new();
+    match backend {
+        Backend::Integer => {
+            for angle in angle_table_q60(iters)? {
+                bytes.extend_from_slice(&angle.to_bits().to_le_bytes());
+            }
+            bytes.extend_from_slice(&gain_q60(iters)?.to_bits().to_le_bytes());
+        }
+        Backend::Float => {
+            for angle in angle_table(iters)? {
+                bytes.extend_from_slice(&angle.to_f64().to_bits().to_le_bytes());
+            }
+            bytes.extend_from_slice(&gain(iters)?.to_f64().to_bits().to_le_bytes());
+        }
+    }
+    Ok(fnv1a(&bytes))
+}
+
+// What table_fingerprint() gives at MAX_ITERS, pinned by the tests and
+// checked on the target by selftest()
+pub(crate) const INTEGER_FINGERPRINT: u64 = 0x23a0_f5c1_6d5a_bfd5;
+pub(crate) const FLOAT_FINGERPRINT: u64 = 0xebff_5db1_10b8_db0b;
+
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cordic::MAX_ITERS;
+
+    #[test]
+    fn renders() {
+        let args = ["cordic-rs", "bench", "--out", "my sweep.csv"].map(String::from);
+        let manifest = Manifest::new(&args).field("backend", "Integer");
+        assert_eq![
+            manifest.to_comments("# "),
+            format!(
+                "# version: {}\n# command: cordic-rs bench --out 'my sweep.csv'\n# backend: Integer\n",
+                env!("CARGO_PKG_VERSION")
+            )
+        ];
+        let manifest = Manifest::new(&[]).checksum("crc32", b"123456789");
+        assert_eq![manifest.fields[2].1, "cbf43926"];
+        let json = Manifest::new(&["a\"b\\".to_string()]).to_json();
+        assert![json.starts_with("{\n  \"version\": ")];
+        assert![json.ends_with("  \"command\": \"'a\\\"b\\\\'\"\n}\n")];
+    }
+
+    #[test]
+    fn fingerprints() {
+        for backend in [Backend::Integer, Backend::Float] {
+            let fingerprint = table_fingerprint(backend, 24).unwrap();
+            assert_eq![table_fingerprint(backend, 24), Ok(fingerprint)];
+            assert_ne![table_fingerprint(backend, 25), Ok(fingerprint)];
+            assert_eq![
+                table_fingerprint(backend, 0),
+                Err(CordicError::IterationOutOfRange(0))
+            ];
+        }
+        // Pinned, so any change to the constants fails here first. If the
+        // change was meant, update these, since older manifests will stop
+        // matching. The float angles come from the platform's atan, so a
+        // failure on just one platform means its atan rounds differently
+        assert_eq![
+            table_fingerprint(Backend::Integer, MAX_ITERS),
+            Ok(0x23a0_f5c1_6d5a_bfd5)
+        ];
+        assert_eq![
+            table_fingerprint(Backend::Float, MAX_ITERS),
+            Ok(0xebff_5db1_10b8_db0b)
+        ];
+    }
+}
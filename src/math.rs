@@ -0,0 +1,135 @@
+// The handful of f64 functions the float backend needs that live in std
+// rather than core. Normally these are just the std methods, but with the
+// `libm` feature they come from the libm crate (musl's math library
+// ported to Rust) instead, so nothing depends on the platform's math library,
+// e.g. for checking results on a no_std target. Rotations and their
+// error bounds call these rather than the f64 methods directly
+
+#[cfg(not(feature = "libm"))]
+mod imp {
+    // atan(x), used for the angle table
+    pub(crate) fn atan(x: f64) -> f64 {
+        x.atan()
+    }
+
+    // atanh(x) for |x| <= 1/2, used for the hyperbolic angle table
+    pub(crate) fn atanh(x: f64) -> f64 {
+        x.atanh()
+    }
+
+    // x^n, only ever used with x = 2 where it's exact
+    pub(crate) fn powi(x: f64, n: i32) -> f64 {
+        x.powi(n)
+    }
+
+    // Rounds half away from zero, like f64::round
+    pub(crate) fn round(x: f64) -> f64 {
+        x.round()
+    }
+
+    pub(crate) fn floor(x: f64) -> f64 {
+        x.floor()
+    }
+
+    pub(crate) fn ceil(x: f64) -> f64 {
+        x.ceil()
+    }
+
+    pub(crate) fn trunc(x: f64) -> f64 {
+        x.trunc()
+    }
+
+    // Square roots of the library's own values go through hyperbolic
+    // CORDIC (see hyperbolic::sqrt), this is for the gains and error
+    // bounds
+    pub(crate) fn sqrt(x: f64) -> f64 {
+        x.sqrt()
+    }
+}
+
+#[cfg(feature = "libm")]
+mod imp {
+    pub(crate) use libm::{atan, atanh, ceil, floor, round, sqrt, trunc};
+
+    // pow() is exact for the powers of two this is used for
+    pub(crate) fn powi(x: f64, n: i32) -> f64 {
+        libm::pow(x, n as f64)
+    }
+}
+
+pub(crate) use imp::{atan, atanh, ceil, floor, powi, round, sqrt, trunc};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ulps(a: f64, b: f64) -> u64 {
+        (a.to_bits() as i64 - b.to_bits() as i64).unsigned_abs()
+    }
+
+    #[test]
+    fn atan_matches_std() {
+        for i in -2000..=2000 {
+            let x = i as f64 / 100.0;
+            assert![ulps(atan(x), x.atan()) <= 1, "atan({})", x];
+            let x = (i as f64 / 50.0).exp();
+            assert![ulps(atan(x), x.atan()) <= 1, "atan({})", x];
+        }
+        assert![atan(f64::NAN).is_nan()];
+        assert_eq![atan(f64::INFINITY), std::f64::consts::FRAC_PI_2];
+        assert_eq![atan(-1e-300), -1e-300];
+    }
+
+    #[test]
+    fn atanh_matches_std() {
+        for i in 1..=60 {
+            let x = 2_f64.powi(-i);
+            assert![ulps(atanh(x), x.atanh()) <= 1, "atanh(2^-{})", i];
+        }
+    }
+
+    #[test]
+    fn powi_of_two_is_exact() {
+        for n in -1000..=1000 {
+            assert_eq![powi(2.0, n), 2_f64.powi(n)];
+        }
+    }
+
+    #[test]
+    fn sqrt_matches_std() {
+        for i in 0..=10_000 {
+            let x = i as f64 * 0.123;
+            assert_eq![sqrt(x), x.sqrt()];
+        }
+        assert![sqrt(-1.0).is_nan() && sqrt(f64::NAN).is_nan()];
+        assert_eq![sqrt(f64::INFINITY), f64::INFINITY];
+    }
+
+    #[test]
+    fn round_matches_std() {
+        let values = [
+            0.0,
+            -0.0,
+            0.49999999999999994,
+            0.5,
+            -0.5,
+            1.5,
+            2.5,
+            -2.5,
+            1e300,
+            4503599627370495.5,
+            -7.25,
+            123.456,
+            f64::INFINITY,
+        ];
+        for &x in values.iter() {
+            for &x in [x, -x].iter() {
+                assert_eq![round(x).to_bits(), x.round().to_bits(), "round({})", x];
+                assert_eq![floor(x).to_bits(), x.floor().to_bits(), "floor({})", x];
+                assert_eq![ceil(x).to_bits(), x.ceil().to_bits(), "ceil({})", x];
+                assert_eq![trunc(x).to_bits(), x.trunc().to_bits(), "trunc({})", x];
+            }
+        }
+        assert![round(f64::NAN).is_nan()];
+    }
+}
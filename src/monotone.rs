@@ -0,0 +1,255 @@
+use crate::cordic::{checked_iters, reduce, CordicConfig, GainCompensation, Rotation, SmallAngle};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::integer::Q60;
+use crate::math;
+
+// CordicConfig::monotone(). Which way each micro-rotation goes flips
+// back and forth as theta creeps up, so the angle rotate() actually
+// turns through lands anywhere within the last micro-angle of theta, and
+// the outputs wiggle by about that much. Near pi/2, where sin flattens
+// out, that's enough for sin(theta + ulp) to come out below sin(theta),
+// which a control loop reading it as a position or gain can trip on.
+//
+// Monotone mode rotates only to the points k * w of an even grid over
+// [0, pi/2], with exact (1, 0) and (0, 1) at the ends, and interpolates
+// linearly between them. w is picked from the config's worst error e
+// (error_bound() below) so that sin rises and cos falls by more than 2e
+// from one grid point to the next everywhere, even at the flat ends,
+// where a step of w changes them by at least 0.39 w^2. The grid values
+// then go strictly the right way whatever the iterations did, and
+// interpolating between them keeps that. The other quadrants are the
+// same curve mirrored. It costs a second rotation and a couple of
+// multiplies, and some accuracy: the chords sit up to w^2 / 8 inside
+// the arc, which is e once the grid is fine and at most 4e when it's
+// coarse
+
+// The worst |error| of rotate() in cos or sin, without monotone: the
+// angle the micro-rotations leave over (under the last one, atan(2^-(n -
+// 1)) <= 2^-(n - 1)) plus the datapath's rounding. A narrow (io_width)
+// datapath rounds theta in and cos and sin out, half an LSB each, and
+// truncates every shift on the registers inside
+pub(crate) fn error_bound(config: &CordicConfig) -> Result<f64, CordicError> {
+    let iters = checked_iters(config.iters)?;
+    let residual = math::powi(2.0, 1 - iters as i32);
+    let rounding = match config.io_bits {
+        Some(io_bits) => {
+            let internal = if config.double_word {
+                (2 * io_bits).min(Q60::FRAC_BITS)
+            } else {
+                io_bits
+            };
+            math::powi(2.0, -(io_bits as i32))
+                + 4.0 * (iters + 2) as f64 * math::powi(2.0, -(internal as i32))
+        }
+        // Plus rounding the integer backend's 60 bits to an f64
+        None => {
+            (iters + 4) as f64 * math::powi(2.0, -(config.backend.precision() as i32))
+                + f64::EPSILON
+        }
+    };
+    Ok(residual + rounding)
+}
+
+impl CordicConfig {
+    pub(crate) fn rotate_monotone(&self, theta: FixedPoint) -> Result<Rotation, CordicError> {
+        // The grid points go through the plain iterations: the fast paths
+        // have errors of their own that error_bound() doesn't cover
+        let plain = Self {
+            monotone: false,
+            small_angle: SmallAngle::Off,
+            special_angles: false,
+            ..*self
+        };
+        if self.shifts.is_some() {
+            return Err(CordicError::DomainError("schedule"));
+        }
+        if self.compensation == GainCompensation::None {
+            return Err(CordicError::DomainError("monotone"));
+        }
+        // Whatever the config does with NaN and infinities
+        if !theta.to_f64().is_finite() {
+            return plain.rotate(theta);
+        }
+
+        let half_pi = std::f64::consts::FRAC_PI_2;
+        let points = math::floor(half_pi / math::sqrt(8.0 * error_bound(self)?)).max(1.0);
+        let w = half_pi / points;
+
+        // cos is even and sin odd, and past pi/2 they mirror, so only
+        // a in [0, pi/2] needs the grid
+        let theta = reduce(theta).to_f64();
+        let a = theta.abs();
+        let (a, cos_sign) = if a > half_pi {
+            (std::f64::consts::PI - a, -1.0)
+        } else {
+            (a, 1.0)
+        };
+        let sin_sign = if theta.is_sign_negative() { -1.0 } else { 1.0 };
+
+        let k = math::floor(a / w).clamp(0.0, points - 1.0);
+        let fraction = (a / w - k).clamp(0.0, 1.0);
+        // The ends still go through rotate(), which checks the rest of
+        // the config
+        let at = |k: f64| -> Result<(f64, f64, FixedPoint), CordicError> {
+            let ret = plain.rotate(FixedPoint::new(k * w))?;
+            Ok(if k == 0.0 {
+                (1.0, 0.0, ret.gain)
+            } else if k == points {
+                (0.0, 1.0, ret.gain)
+            } else {
+                (ret.x.to_f64(), ret.y.to_f64(), ret.gain)
+            })
+        };
+        let (x0, y0, gain) = at(k)?;
+        let (x1, y1, _) = at(k + 1.0)?;
+        // Rounding can push a chord a hair past its far end, and the
+        // clamps keep it within the segment so the next one picks up
+        // where it left off
+        let x = (x0 + fraction * (x1 - x0)).clamp(x1, x0);
+        let y = (y0 + fraction * (y1 - y0)).clamp(y0, y1);
+        // Back onto the output's grid, which rounding can't unsort
+        let round = |v: f64| match self.io_bits {
+            Some(io_bits) => {
+                let scale = math::powi(2.0, io_bits as i32);
+                math::round(v * scale) / scale
+            }
+            None => v,
+        };
+        Ok(Rotation {
+            x: FixedPoint::new(cos_sign * round(x)),
+            y: FixedPoint::new(sin_sign * round(y)),
+            gain,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cordic::{Backend, MAX_ITERS};
+
+    // Every input the format has in [0, pi/2], in order
+    fn grid(frac_bits: u32) -> Vec<f64> {
+        let scale = 2f64.powi(frac_bits as i32);
+        (0..=(std::f64::consts::FRAC_PI_2 * scale) as i64)
+            .map(|i| i as f64 / scale)
+            .collect()
+    }
+
+    fn is_monotone(config: &CordicConfig, thetas: &[f64]) -> bool {
+        let values = thetas
+            .iter()
+            .map(|&theta| config.rotate(FixedPoint::new(theta)).unwrap())
+            .collect::<Vec<Rotation>>();
+        values
+            .windows(2)
+            .all(|pair| pair[0].y <= pair[1].y && pair[0].x >= pair[1].x)
+    }
+
+    #[test]
+    fn exhaustive_small_formats() {
+        // Plain CORDIC wiggles on a Q12 grid at 8 to 12 iterations
+        let q12 = grid(12);
+        assert![(8..=12).any(|iters| !is_monotone(&CordicConfig::new(iters).io_width(12), &q12))];
+
+        // Every input of every Q format up to Q14, at every iteration
+        // count that isn't far more than the format can use
+        for bits in 4..=14 {
+            let thetas = grid(bits);
+            for iters in 1..=bits as usize + 2 {
+                for double_word in [false, true] {
+                    let config = CordicConfig::new(iters)
+                        .io_width(bits)
+                        .double_word(double_word)
+                        .monotone(true);
+                    assert![
+                        is_monotone(&config, &thetas),
+                        "Q{} {} iterations",
+                        bits,
+                        iters
+                    ];
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn full_precision() {
+        let thetas = (0..=20000)
+            .map(|i| i as f64 * std::f64::consts::FRAC_PI_2 / 20000.0)
+            .collect::<Vec<f64>>();
+        for backend in [Backend::Integer, Backend::Float] {
+            for iters in [4, 8, 16, 24, MAX_ITERS] {
+                let config = CordicConfig::new(iters).backend(backend);
+                let monotone = config.monotone(true);
+                assert![is_monotone(&monotone, &thetas), "{:?} {}", backend, iters];
+
+                // The grid values' error plus the chords'
+                let bound = error_bound(&config).unwrap();
+                for &theta in &thetas {
+                    let ret = monotone.rotate(FixedPoint::new(theta)).unwrap();
+                    assert![(ret.x.to_f64() - theta.cos()).abs() <= 5.0 * bound];
+                    assert![(ret.y.to_f64() - theta.sin()).abs() <= 5.0 * bound];
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn symmetry() {
+        let config = CordicConfig::new(12).io_width(14).monotone(true);
+        for theta in grid(14) {
+            let ret = config.rotate(FixedPoint::new(theta)).unwrap();
+            let mirror = config.rotate(FixedPoint::new(-theta)).unwrap();
+            assert_eq![(mirror.x, mirror.y), (ret.x, -ret.y)];
+        }
+        // Past pi/2 sin comes back down and cos keeps falling
+        let second = grid(14)
+            .iter()
+            .map(|theta| theta + std::f64::consts::FRAC_PI_2)
+            .collect::<Vec<f64>>();
+        let values = second
+            .iter()
+            .map(|&theta| config.rotate(FixedPoint::new(theta)).unwrap())
+            .collect::<Vec<Rotation>>();
+        assert![values
+            .windows(2)
+            .all(|pair| pair[0].y >= pair[1].y && pair[0].x >= pair[1].x)];
+        // Exact ends, and the angle's sign carried through 0
+        let end = config.rotate(FixedPoint::new(0.0)).unwrap();
+        assert_eq![(end.x.to_f64(), end.y.to_f64()), (1.0, 0.0)];
+        let top = CordicConfig::new(24).monotone(true);
+        let ret = top
+            .rotate(FixedPoint::new(std::f64::consts::FRAC_PI_2))
+            .unwrap();
+        assert_eq![(ret.x.to_f64(), ret.y.to_f64()), (0.0, 1.0)];
+    }
+
+    #[test]
+    fn unsupported() {
+        let config = CordicConfig::new(8).monotone(true);
+        assert_eq![
+            config.schedule(&[0, 1, 2]).rotate(FixedPoint::new(0.5)),
+            Err(CordicError::DomainError("schedule"))
+        ];
+        assert_eq![
+            config.compensate_gain(false).rotate(FixedPoint::new(0.5)),
+            Err(CordicError::DomainError("monotone"))
+        ];
+        assert_eq![
+            config.iters(0).rotate(FixedPoint::new(0.5)),
+            Err(CordicError::IterationOutOfRange(0))
+        ];
+        assert_eq![
+            config.io_width(12).rotate(FixedPoint::new(f64::NAN)),
+            Err(CordicError::InvalidAngle("rotate"))
+        ];
+        assert![config
+            .rotate(FixedPoint::new(f64::NAN))
+            .unwrap()
+            .y
+            .to_f64()
+            .is_nan()];
+    }
+}
@@ -0,0 +1,345 @@
+use crate::cordic::{checked_iters, CordicConfig};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+
+// Numerically controlled oscillator. The phase is a 32 bit accumulator
+// counting fractions of a turn, the same as you'd build in hardware, so
+// it wraps around for free and never drifts. Each sample feeds the
+// accumulated phase through a CORDIC rotation to get (cos, sin)
+pub struct Nco {
+    phase: u32,
+    step: u32,
+    // Which top bits of the phase get rotated
+    phase_mask: u32,
+    config: CordicConfig,
+    quantizer: Option<Quantizer>,
+}
+
+// Cuts the output down to a DAC's word width. Plain truncation of a
+// periodic signal gives a periodic error, which shows up as spurs at
+// harmonics of the tone. With error feedback each sample's truncation
+// error is subtracted from the next sample before it's truncated, so the
+// output is x[n] + e[n] - e[n-1]: the same error, but filtered by
+// 1 - z^-1, which pushes it up towards Nyquist and away from low tones
+struct Quantizer {
+    // FixedPoint raw bits dropped by the truncation
+    shift: u32,
+    // Full scale of the output word, in output LSBs
+    limit: i64,
+    feedback: bool,
+    error: [i64; 2],
+}
+
+impl Quantizer {
+    fn apply(&mut self, value: FixedPoint, channel: usize) -> FixedPoint {
+        let wanted = value.raw_bits() - self.error[channel];
+        let truncated = (wanted >> self.shift).clamp(-self.limit, self.limit - 1) << self.shift;
+        if self.feedback {
+            self.error[channel] = truncated - wanted;
+        }
+        FixedPoint::from_raw_bits(truncated)
+    }
+}
+
+// One full turn of the phase accumulator
+pub(crate) const TURN: f64 = 4_294_967_296.0;
+
+impl Nco {
+    // An oscillator at `freq` Hz sampled at `rate` Hz, using `iters`
+    // CORDIC iterations per sample. The frequency is quantized to the
+    // nearest rate / 2^32
+    pub fn new(freq: f64, rate: f64, iters: usize) -> Result<Self, CordicError> {
+        let iters = checked_iters(iters)?;
+        if !(rate > 0.0 && rate.is_finite() && freq.is_finite()) {
+            return Err(CordicError::DomainError("Nco::new"));
+        }
+        let step = ((freq / rate).rem_euclid(1.0) * TURN).round() as u64 as u32;
+        Ok(Self {
+            phase: 0,
+            step,
+            phase_mask: !0,
+            config: CordicConfig::new(iters),
+            quantizer: None,
+        })
+    }
+
+    // Narrows the accumulator to its top `bits` bits, as a cheaper
+    // design would have it. This only coarsens the frequency, to the
+    // nearest rate / 2^bits, it doesn't add spurs
+    pub fn accumulator_bits(mut self, bits: u32) -> Result<Self, CordicError> {
+        if !(1..=32).contains(&bits) {
+            return Err(CordicError::DomainError("Nco::accumulator_bits"));
+        }
+        let drop = 32 - bits;
+        let half = (1u64 << drop) >> 1;
+        self.step = ((self.step as u64 + half) >> drop << drop) as u32;
+        Ok(self)
+    }
+
+    // Only feeds the top `bits` bits of the accumulator to the CORDIC,
+    // the classic way of keeping the angle datapath narrow. The dropped
+    // bits make a periodic phase error, which shows up as spurs
+    pub fn phase_bits(mut self, bits: u32) -> Result<Self, CordicError> {
+        if !(1..=32).contains(&bits) {
+            return Err(CordicError::DomainError("Nco::phase_bits"));
+        }
+        self.phase_mask = (!0u64 << (32 - bits)) as u32;
+        Ok(self)
+    }
+
+    // Truncates the output to `bits` bit two's complement (Q15 for 16),
+    // with first order error feedback if `error_feedback` is set
+    pub fn amplitude_bits(mut self, bits: u32, error_feedback: bool) -> Result<Self, CordicError> {
+        let frac = FixedPoint::format().frac_bits();
+        if !(2..=frac + 1).contains(&bits) {
+            return Err(CordicError::DomainError("Nco::amplitude_bits"));
+        }
+        self.quantizer = Some(Quantizer {
+            shift: frac + 1 - bits,
+            limit: 1 << (bits - 1),
+            feedback: error_feedback,
+            error: [0; 2],
+        });
+        Ok(self)
+    }
+
+    // The current phase as an angle in [-pi, pi), before any truncation
+    pub fn phase(&self) -> FixedPoint {
+        angle(self.phase)
+    }
+}
+
+fn angle(phase: u32) -> FixedPoint {
+    FixedPoint::new(phase as i32 as f64 * std::f64::consts::PI * 2.0 / TURN)
+}
+
+impl Iterator for Nco {
+    type Item = [FixedPoint; 2];
+
+    // Never ends, the iteration count was checked up front so rotate()
+    // can't fail
+    fn next(&mut self) -> Option<Self::Item> {
+        let ret = self
+            .config
+            .rotate(angle(self.phase & self.phase_mask))
+            .ok()?;
+        self.phase = self.phase.wrapping_add(self.step);
+        match &mut self.quantizer {
+            Some(quantizer) => Some([quantizer.apply(ret.x, 0), quantizer.apply(ret.y, 1)]),
+            None => Some([ret.x, ret.y]),
+        }
+    }
+}
+
+// A linear frequency sweep from `start` to `end` Hz over `samples`
+// samples, for characterizing filters. The phase is accumulated the same
+// way as Nco's, except that the step grows by a constant every sample,
+// which makes the phase quadratic. A sweep over many samples only
+// changes the step by a tiny amount each time, so the phase and step
+// carry 32 more fractional bits than Nco's and the top 32 bits of the
+// phase are what gets rotated
+pub struct Chirp {
+    phase: u64,
+    step: u64,
+    delta: u64,
+    remaining: usize,
+    config: CordicConfig,
+}
+
+impl Chirp {
+    pub fn new(
+        start: f64,
+        end: f64,
+        rate: f64,
+        samples: usize,
+        iters: usize,
+    ) -> Result<Self, CordicError> {
+        let iters = checked_iters(iters)?;
+        if !(rate > 0.0 && rate.is_finite() && start.is_finite() && end.is_finite()) {
+            return Err(CordicError::DomainError("Chirp::new"));
+        }
+        // Both in units of 2^-64 turns, the delta as a two's complement
+        // amount so downward sweeps wrap the step around
+        let wide_turn = TURN * TURN;
+        let step = ((start / rate).rem_euclid(1.0) * wide_turn) as u64;
+        let slope = (end - start) / rate / samples.max(1) as f64;
+        let delta = (slope * wide_turn).round() as i64 as u64;
+        Ok(Self {
+            phase: 0,
+            step,
+            delta,
+            remaining: samples,
+            config: CordicConfig::new(iters),
+        })
+    }
+
+    // The current phase as an angle in [-pi, pi)
+    pub fn phase(&self) -> FixedPoint {
+        angle((self.phase >> 32) as u32)
+    }
+}
+
+impl Iterator for Chirp {
+    type Item = [FixedPoint; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let ret = self.config.rotate(self.phase()).ok()?;
+        self.phase = self.phase.wrapping_add(self.step);
+        self.step = self.step.wrapping_add(self.delta);
+        Some([ret.x, ret.y])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_enough(a: FixedPoint, b: f64) -> bool {
+        let diff = a.to_f64() - b;
+        diff < 0.01 && -diff < 0.01
+    }
+
+    #[test]
+    fn tracks_sine() {
+        let nco = Nco::new(1000.0, 8000.0, 30).unwrap();
+        for (i, [cos, sin]) in nco.take(20).enumerate() {
+            let theta = std::f64::consts::PI * 2.0 * i as f64 / 8.0;
+            assert![close_enough(cos, theta.cos())];
+            assert![close_enough(sin, theta.sin())];
+        }
+    }
+
+    #[test]
+    fn negative_frequency() {
+        let mut nco = Nco::new(-1000.0, 8000.0, 30).unwrap();
+        nco.next();
+        let [_, sin] = nco.next().unwrap();
+        assert![close_enough(sin, -(std::f64::consts::FRAC_1_SQRT_2))];
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert![Nco::new(440.0, 0.0, 30).is_err()];
+        assert![Nco::new(f64::NAN, 48000.0, 30).is_err()];
+        assert![Nco::new(440.0, 48000.0, 0).is_err()];
+    }
+
+    #[test]
+    fn narrow_phase() {
+        // 8 phase bits rotate a multiple of 2 pi / 256, whatever the
+        // accumulator holds
+        let mut nco = Nco::new(1234.5, 48000.0, 40)
+            .unwrap()
+            .phase_bits(8)
+            .unwrap();
+        for _ in 0..50 {
+            let full = nco.phase().to_f64();
+            let [cos, sin] = nco.next().unwrap();
+            let step = std::f64::consts::PI * 2.0 / 256.0;
+            let truncated = (full / step).floor() * step;
+            assert![(sin.to_f64() - truncated.sin()).abs() < 1e-9];
+            assert![(cos.to_f64() - truncated.cos()).abs() < 1e-9];
+        }
+
+        // 12 accumulator bits land the frequency on a multiple of
+        // rate / 4096
+        let mut coarse = Nco::new(1000.0, 48000.0, 40)
+            .unwrap()
+            .accumulator_bits(12)
+            .unwrap();
+        coarse.next();
+        let turns = coarse.phase().to_f64() / (std::f64::consts::PI * 2.0);
+        assert_eq![turns * 4096.0, 85.0];
+
+        assert![Nco::new(440.0, 48000.0, 30).unwrap().phase_bits(0).is_err()];
+        assert![Nco::new(440.0, 48000.0, 30)
+            .unwrap()
+            .accumulator_bits(33)
+            .is_err()];
+    }
+
+    #[test]
+    fn error_feedback() {
+        // An 8 bit output, with the error measured against the unquantized
+        // oscillator. Feedback costs a little more error in total but
+        // moves it out of the bottom of the band, where the tone is
+        let (n, bits) = (1024, 8);
+        let ideal = Nco::new(1234.5, 48000.0, 40).unwrap();
+        let plain = Nco::new(1234.5, 48000.0, 40)
+            .unwrap()
+            .amplitude_bits(bits, false)
+            .unwrap();
+        let shaped = Nco::new(1234.5, 48000.0, 40)
+            .unwrap()
+            .amplitude_bits(bits, true)
+            .unwrap();
+        let lsb = 1.0 / 128.0;
+        let mut errors = [vec![], vec![]];
+        for ([_, exact], [_, a], [_, b]) in ideal
+            .zip(plain)
+            .zip(shaped)
+            .map(|((x, y), z)| (x, y, z))
+            .take(n)
+        {
+            for (error, sample) in errors.iter_mut().zip(&[a, b]) {
+                assert_eq![(sample.to_f64() / lsb).fract(), 0.0];
+                error.push(sample.to_f64() - exact.to_f64());
+            }
+        }
+        assert![errors[0].iter().all(|e| e.abs() < lsb)];
+        assert![errors[1].iter().all(|e| e.abs() < 2.0 * lsb)];
+
+        // Power in the lowest eighth of the band, by DFT
+        let low_band = |error: &[f64]| {
+            (0..n / 16)
+                .map(|k| {
+                    let (mut re, mut im) = (0.0, 0.0);
+                    for (i, e) in error.iter().enumerate() {
+                        let theta = std::f64::consts::PI * 2.0 * (k * i % n) as f64 / n as f64;
+                        re += e * theta.cos();
+                        im -= e * theta.sin();
+                    }
+                    re * re + im * im
+                })
+                .sum::<f64>()
+        };
+        assert![low_band(&errors[1]) * 10.0 < low_band(&errors[0])];
+
+        assert![Nco::new(440.0, 48000.0, 30)
+            .unwrap()
+            .amplitude_bits(1, true)
+            .is_err()];
+        assert![Nco::new(440.0, 48000.0, 30)
+            .unwrap()
+            .amplitude_bits(53, true)
+            .is_err()];
+    }
+
+    #[test]
+    fn chirp() {
+        // 100 Hz to 1 kHz over a second at 8 kHz, against the phase
+        // 2 pi (f0 t + (f1 - f0) t^2 / 2T) sampled at t = i / rate,
+        // which the discrete sum lags by half a sample's worth of slope
+        let (start, end, rate, samples) = (100.0, 1000.0, 8000.0, 8000);
+        let chirp = Chirp::new(start, end, rate, samples, 32).unwrap();
+        let slope = (end - start) / rate / samples as f64;
+        let mut count = 0;
+        for (i, [cos, sin]) in chirp.enumerate() {
+            let i = i as f64;
+            let turns = start / rate * i + slope * i * (i - 1.0) / 2.0;
+            let theta = std::f64::consts::PI * 2.0 * turns;
+            assert![(cos.to_f64() - theta.cos()).abs() < 1e-6, "{}", i];
+            assert![(sin.to_f64() - theta.sin()).abs() < 1e-6, "{}", i];
+            count += 1;
+        }
+        assert_eq![count, samples];
+
+        let down = Chirp::new(1000.0, -1000.0, 8000.0, 10, 32).unwrap();
+        assert_eq![down.count(), 10];
+        assert![Chirp::new(100.0, 200.0, 0.0, 10, 32).is_err()];
+    }
+}
@@ -0,0 +1,144 @@
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use num_traits::{FromPrimitive, Num, One, Signed, ToPrimitive, Zero};
+use std::convert::TryFrom;
+
+// The num-traits impls for FixedPoint, behind `--features num-traits`,
+// so it slots into generic numeric code (and DSP crates written against
+// num-traits) without a wrapper type. Each forwards to the inherent item
+// of the same name in src/fixed.rs, which work without the feature:
+//
+//   fn mean<T: Num + FromPrimitive + Copy>(xs: &[T]) -> Option<T> { ... }
+//   mean(&[FixedPoint::new(0.5), FixedPoint::new(1.5)])
+
+impl Zero for FixedPoint {
+    fn zero() -> Self {
+        Self::ZERO
+    }
+
+    fn is_zero(&self) -> bool {
+        FixedPoint::is_zero(*self)
+    }
+}
+
+impl One for FixedPoint {
+    fn one() -> Self {
+        Self::ONE
+    }
+}
+
+impl Num for FixedPoint {
+    type FromStrRadixErr = CordicError;
+
+    // Decimal only, since that's all f64 parses
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, CordicError> {
+        if radix != 10 {
+            return Err(CordicError::ParseError("only radix 10 is supported"));
+        }
+        s.parse::<f64>()
+            .map(Self::new)
+            .map_err(|_| CordicError::ParseError("not a number"))
+    }
+}
+
+impl Signed for FixedPoint {
+    fn abs(&self) -> Self {
+        FixedPoint::abs(*self)
+    }
+
+    // self - other if that's positive, otherwise 0
+    fn abs_sub(&self, other: &Self) -> Self {
+        if *self > *other {
+            *self - *other
+        } else {
+            Self::ZERO
+        }
+    }
+
+    fn signum(&self) -> Self {
+        FixedPoint::signum(*self)
+    }
+
+    fn is_positive(&self) -> bool {
+        FixedPoint::is_positive(*self)
+    }
+
+    fn is_negative(&self) -> bool {
+        FixedPoint::is_negative(*self)
+    }
+}
+
+impl FromPrimitive for FixedPoint {
+    fn from_i64(n: i64) -> Option<Self> {
+        FixedPoint::from_i64(n)
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        i64::try_from(n).ok().and_then(FixedPoint::from_i64)
+    }
+
+    fn from_f64(n: f64) -> Option<Self> {
+        Some(Self::new(n))
+    }
+}
+
+impl ToPrimitive for FixedPoint {
+    fn to_i64(&self) -> Option<i64> {
+        FixedPoint::to_i64(*self)
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.to_i64().and_then(|n| u64::try_from(n).ok())
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(FixedPoint::to_f64(*self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only knows num-traits, not FixedPoint
+    fn mean<T: Num + FromPrimitive + Copy>(xs: &[T]) -> Option<T> {
+        let sum = xs.iter().fold(T::zero(), |total, &x| total + x);
+        Some(sum / T::from_usize(xs.len())?)
+    }
+
+    #[test]
+    fn generic_code() {
+        let xs = [0.5, 1.5, -4.0].map(FixedPoint::new);
+        assert_eq![mean(&xs), Some(FixedPoint::new(-2.0 / 3.0))];
+        assert![<FixedPoint as Zero>::is_zero(&FixedPoint::zero())];
+        assert_eq![FixedPoint::one(), FixedPoint::new(1.0)];
+        assert_eq![
+            <FixedPoint as Num>::from_str_radix("-2.25", 10),
+            Ok(FixedPoint::new(-2.25))
+        ];
+        assert![matches![
+            <FixedPoint as Num>::from_str_radix("ff", 16),
+            Err(CordicError::ParseError(_))
+        ]];
+    }
+
+    #[test]
+    fn signed_and_primitive() {
+        let x = FixedPoint::new(-2.5);
+        assert_eq![Signed::abs(&x), FixedPoint::new(2.5)];
+        assert_eq![Signed::signum(&x), FixedPoint::new(-1.0)];
+        assert![Signed::is_negative(&x) && !Signed::is_positive(&x)];
+        assert_eq![x.abs_sub(&FixedPoint::ONE), FixedPoint::ZERO];
+        assert_eq![FixedPoint::ONE.abs_sub(&x), FixedPoint::new(3.5)];
+
+        assert_eq![
+            <FixedPoint as FromPrimitive>::from_u64(7),
+            Some(FixedPoint::new(7.0))
+        ];
+        assert_eq![<FixedPoint as FromPrimitive>::from_u64(u64::MAX), None];
+        assert_eq![ToPrimitive::to_i64(&x), Some(-2)];
+        assert_eq![ToPrimitive::to_u64(&x), None];
+        assert_eq![ToPrimitive::to_u8(&FixedPoint::new(300.0)), None];
+        assert_eq![ToPrimitive::to_f64(&x), Some(-2.5)];
+    }
+}
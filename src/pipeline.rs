@@ -0,0 +1,211 @@
+use crate::cordic::{checked_iters, GainCompensation};
+use crate::csd::gain_csd;
+use crate::error::CordicError;
+use crate::integer::Q60;
+
+// Timing and area model of a hardware CORDIC rotator, for the numbers
+// that otherwise end up in a spreadsheet. Built up the same way as
+// CordicConfig:
+//
+//   Pipeline::new(16, 18).growth(2).stage_delay(2).report()
+//
+// Stages are the angle reduction, then one stage per micro-rotation,
+// then (with post or CSD gain compensation) the multiply by K. Each stage
+// registers x and y, the residual angle z and the quadrant flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pipeline {
+    iters: usize,
+    width: u32,
+    angle_width: u32,
+    growth: u32,
+    stage_delay: usize,
+    architecture: Architecture,
+    compensation: GainCompensation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Architecture {
+    // One stage of hardware per micro-rotation, all working on different
+    // inputs at once, so a new input goes in every stage_delay cycles
+    Unrolled,
+    // A single micro-rotation stage that the vector loops through, so
+    // each input holds the hardware for every iteration
+    Iterative,
+}
+
+// What the model works out for a Pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineReport {
+    pub stages: usize,
+    // Cycles from an input going in to its result coming out
+    pub latency: usize,
+    // Cycles between one input and the next
+    pub initiation_interval: usize,
+    pub flip_flops: usize,
+    pub adders: usize,
+    pub multipliers: usize,
+}
+
+impl Pipeline {
+    // `iters` micro-rotations on `width` bit inputs, with the angle
+    // register the same width and 2 bits of growth, one cycle per stage
+    pub fn new(iters: usize, width: u32) -> Self {
+        Self {
+            iters,
+            width,
+            angle_width: width,
+            growth: 2,
+            stage_delay: 1,
+            architecture: Architecture::Unrolled,
+            compensation: GainCompensation::Post,
+        }
+    }
+
+    pub fn angle_width(mut self, angle_width: u32) -> Self {
+        self.angle_width = angle_width;
+        self
+    }
+
+    // Extra bits x and y carry past the input width: one integer bit for
+    // the 1/K ~= 1.65 magnification, plus any guard bits against
+    // rounding
+    pub fn growth(mut self, growth: u32) -> Self {
+        self.growth = growth;
+        self
+    }
+
+    // Register stages (and so cycles) per stage of logic, for when one
+    // micro-rotation's adders don't fit in a single clock period
+    pub fn stage_delay(mut self, stage_delay: usize) -> Self {
+        self.stage_delay = stage_delay;
+        self
+    }
+
+    pub fn architecture(mut self, architecture: Architecture) -> Self {
+        self.architecture = architecture;
+        self
+    }
+
+    pub fn gain_compensation(mut self, compensation: GainCompensation) -> Self {
+        self.compensation = compensation;
+        self
+    }
+
+    pub fn report(&self) -> Result<PipelineReport, CordicError> {
+        let iters = checked_iters(self.iters)?;
+        if self.width == 0 || self.angle_width == 0 || self.stage_delay == 0 {
+            return Err(CordicError::DomainError("Pipeline::report"));
+        }
+
+        // x, y, z and the quadrant flag
+        let datapath =
+            2 * (self.width as usize + self.growth as usize) + self.angle_width as usize + 1;
+        // A multiplier each for x and y, or for CSD compensation, the
+        // adders that sum K's shifted copies at the datapath's width
+        let (gain_stages, multipliers, gain_adders) = match self.compensation {
+            GainCompensation::Post => (1, 2, 0),
+            GainCompensation::Csd => {
+                let k = gain_csd(iters, self.width.min(Q60::FRAC_BITS))?;
+                (1, 0, 2 * k.adders())
+            }
+            GainCompensation::Prescale | GainCompensation::None => (0, 0, 0),
+        };
+        let stages = 1 + iters + gain_stages;
+        // Widths and delays big enough to overflow a count of flip-flops
+        // describe no hardware anyone could build
+        let overflow = CordicError::DomainError("Pipeline::report");
+        let latency = stages.checked_mul(self.stage_delay).ok_or(overflow)?;
+
+        let report = match self.architecture {
+            Architecture::Unrolled => PipelineReport {
+                stages,
+                latency,
+                initiation_interval: self.stage_delay,
+                flip_flops: latency.checked_mul(datapath).ok_or(overflow)?,
+                adders: 3 * iters + gain_adders,
+                multipliers,
+            },
+            // The loop needs an iteration counter on top of its single
+            // set of registers per stage
+            Architecture::Iterative => {
+                let counter = (usize::BITS - iters.leading_zeros()) as usize;
+                let flip_flops = (2 + gain_stages)
+                    .checked_mul(self.stage_delay)
+                    .and_then(|registers| registers.checked_mul(datapath))
+                    .and_then(|bits| bits.checked_add(counter));
+                PipelineReport {
+                    stages: 1 + 1 + gain_stages,
+                    latency,
+                    initiation_interval: iters.checked_mul(self.stage_delay).ok_or(overflow)?,
+                    flip_flops: flip_flops.ok_or(overflow)?,
+                    adders: 3 + gain_adders,
+                    multipliers,
+                }
+            }
+        };
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unrolled() {
+        let report = Pipeline::new(16, 16).report().unwrap();
+        // Reduction, 16 micro-rotations, gain
+        assert_eq![report.stages, 18];
+        assert_eq![report.latency, 18];
+        assert_eq![report.initiation_interval, 1];
+        // 2 * (16 + 2) + 16 + 1 = 53 bits per stage
+        assert_eq![report.flip_flops, 18 * 53];
+        assert_eq![report.adders, 48];
+        assert_eq![report.multipliers, 2];
+
+        // K at 16 bits has seven CSD digits, so six adders each for x
+        // and y replace the multipliers
+        let csd = Pipeline::new(16, 16)
+            .gain_compensation(GainCompensation::Csd)
+            .report()
+            .unwrap();
+        assert_eq![csd.stages, 18];
+        assert_eq![csd.adders, 48 + 2 * 6];
+        assert_eq![csd.multipliers, 0];
+    }
+
+    #[test]
+    fn stage_delay_and_prescale() {
+        let report = Pipeline::new(16, 16)
+            .stage_delay(2)
+            .gain_compensation(GainCompensation::Prescale)
+            .report()
+            .unwrap();
+        assert_eq![report.stages, 17];
+        assert_eq![report.latency, 34];
+        assert_eq![report.initiation_interval, 2];
+        assert_eq![report.flip_flops, 34 * 53];
+        assert_eq![report.multipliers, 0];
+    }
+
+    #[test]
+    fn iterative() {
+        let unrolled = Pipeline::new(16, 16).report().unwrap();
+        let iterative = Pipeline::new(16, 16)
+            .architecture(Architecture::Iterative)
+            .report()
+            .unwrap();
+        assert_eq![iterative.latency, unrolled.latency];
+        assert_eq![iterative.initiation_interval, 16];
+        // Three sets of registers and a 5 bit counter
+        assert_eq![iterative.flip_flops, 3 * 53 + 5];
+        assert_eq![iterative.adders, 3];
+    }
+
+    #[test]
+    fn invalid() {
+        assert![Pipeline::new(0, 16).report().is_err()];
+        assert![Pipeline::new(16, 0).report().is_err()];
+        assert![Pipeline::new(16, 16).stage_delay(0).report().is_err()];
+    }
+}
@@ -0,0 +1,172 @@
+use crate::cordic::{checked_iters, gain, reduce, Backend, CordicConfig, GainCompensation};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::integer::{fold, to_q60, ANGLES, Q60};
+
+// Bit toggles in the datapath registers, the usual first guess at
+// dynamic power: every flip-flop that changes state charges or drains its
+// share of the wiring, so a design that flips fewer bits per result
+// burns less. It's crude (it ignores glitches in the adders, clocking and
+// which wires are long) but it ranks configurations the right way round
+// more often than not, long before there's a netlist to simulate.
+//
+// The model follows the integer backend: x, y and z each a register of
+// the datapath's fractional bits plus a sign and three integer bits,
+// loaded once per evaluation and rewritten at every micro-rotation. A
+// toggle is one bit of one register differing from its previous value.
+// Loading the registers and the post compensation multiply aren't
+// counted, they're the same for every iteration count
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Toggles {
+    // Average toggles per evaluation in each register
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Toggles {
+    pub fn total(&self) -> f64 {
+        self.x + self.y + self.z
+    }
+}
+
+// Average toggles per evaluation of `config` over `points` angles evenly
+// spread across a turn. Only the integer backend has registers to count,
+// so the float one is a DomainError
+pub fn toggles(config: &CordicConfig, points: usize) -> Result<Toggles, CordicError> {
+    if config.backend != Backend::Integer || points == 0 {
+        return Err(CordicError::DomainError("toggles"));
+    }
+    // The same shifts and register width rotate() would use
+    let shifts = match config.custom_shifts()? {
+        Some(shifts) => shifts.as_slice().iter().map(|&i| i as usize).collect(),
+        None => (0..checked_iters(config.iters)?).collect::<Vec<_>>(),
+    };
+    // Widths rotate() would turn down are turned down here too
+    let registers = match config.io_bits {
+        Some(_) if config.shifts.is_some() => return Err(CordicError::DomainError("schedule")),
+        Some(bits)
+            if bits == 0
+                || bits > Q60::FRAC_BITS
+                || config.compensation == GainCompensation::None =>
+        {
+            return Err(CordicError::DomainError("io_width"))
+        }
+        Some(bits) if config.double_word => (2 * bits).min(Q60::FRAC_BITS),
+        Some(bits) => bits,
+        None => Q60::FRAC_BITS,
+    };
+    let start = match config.compensation {
+        GainCompensation::Prescale => match config.custom_shifts()? {
+            Some(shifts) => to_q60(shifts.gain()),
+            None => to_q60(gain(shifts.len())?),
+        },
+        GainCompensation::Post | GainCompensation::Csd | GainCompensation::None => {
+            1 << Q60::FRAC_BITS
+        }
+    };
+
+    let pi = std::f64::consts::PI;
+    let mut counts = [0u64; 3];
+    for i in 0..points {
+        let theta = FixedPoint::new(-pi + 2.0 * pi * i as f64 / points as f64);
+        for (count, toggles) in counts
+            .iter_mut()
+            .zip(count(theta, start, &shifts, registers))
+        {
+            *count += toggles;
+        }
+    }
+    let [x, y, z] = counts.map(|count| count as f64 / points as f64);
+    Ok(Toggles { x, y, z })
+}
+
+// Toggles in x, y and z over one evaluation, stepping the registers the
+// way sincos_narrow() does: each shifted operand floored to the register
+// width, and wrapping rather than panicking on overflow
+fn count(theta: FixedPoint, start: i64, shifts: &[usize], registers: u32) -> [u64; 3] {
+    let drop = Q60::FRAC_BITS - registers;
+    let truncate = |x: i64| (x >> drop) << drop;
+    // The register's bits, the fractional ones plus sign and integer
+    let width = registers + 4;
+    let mask = if width >= 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    };
+    let flips = |old: i64, new: i64| (((old ^ new) >> drop) as u64 & mask).count_ones() as u64;
+
+    let (z, negate) = fold(to_q60(reduce(theta)));
+    let mut z = truncate(z);
+    let start = truncate(start);
+    let mut v = if negate { [-start, 0] } else { [start, 0] };
+    let mut counts = [0; 3];
+    for &i in shifts {
+        let angle = truncate(ANGLES[i]);
+        let [x, y] = [truncate(v[0] >> i), truncate(v[1] >> i)];
+        let (next, next_z) = if z >= 0 {
+            (
+                [v[0].wrapping_sub(y), v[1].wrapping_add(x)],
+                z.wrapping_sub(angle),
+            )
+        } else {
+            (
+                [v[0].wrapping_add(y), v[1].wrapping_sub(x)],
+                z.wrapping_add(angle),
+            )
+        };
+        counts[0] += flips(v[0], next[0]);
+        counts[1] += flips(v[1], next[1]);
+        counts[2] += flips(z, next_z);
+        v = next;
+        z = next_z;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_register_flips() {
+        let full = toggles(&CordicConfig::new(32), 256).unwrap();
+        let more = toggles(&CordicConfig::new(48), 256).unwrap();
+        let narrow = toggles(&CordicConfig::new(32).io_width(16), 256).unwrap();
+        // Each step rewrites a register's low bits with about half of
+        // them flipping, so on a 64 bit register it's a couple of dozen
+        // flips a step, fewer with fewer steps or narrower registers
+        for toggles in [full, more, narrow] {
+            for register in [toggles.x, toggles.y, toggles.z] {
+                assert![register > 0.0 && register < 64.0 * 48.0];
+            }
+        }
+        assert![full.total() > 32.0 * 3.0 * 8.0];
+        assert![more.total() > full.total()];
+        assert![narrow.total() < full.total() / 2.0];
+        assert_eq![toggles(&CordicConfig::new(32), 256), Ok(full)];
+
+        // A schedule of the first 32 shifts is the same datapath
+        let schedule = (0..32).collect::<Vec<_>>();
+        assert_eq![
+            toggles(&CordicConfig::new(32).schedule(&schedule), 256),
+            Ok(full)
+        ];
+        assert_eq![
+            toggles(&CordicConfig::new(32).backend(Backend::Float), 256),
+            Err(CordicError::DomainError("toggles"))
+        ];
+        assert_eq![
+            toggles(&CordicConfig::new(32).io_width(61), 256),
+            Err(CordicError::DomainError("io_width"))
+        ];
+        assert_eq![
+            toggles(&CordicConfig::new(32), 0),
+            Err(CordicError::DomainError("toggles"))
+        ];
+        assert_eq![
+            toggles(&CordicConfig::new(0), 16),
+            Err(CordicError::IterationOutOfRange(0))
+        ];
+    }
+}
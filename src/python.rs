@@ -0,0 +1,216 @@
+// A `cordic_rs` Python extension module, for error analysis from a
+// notebook without round-tripping CSVs. Build it with maturin
+//
+//   maturin develop --release --features python
+//
+// or with cargo, copying target/release/libcordic_rs.so to cordic_rs.so
+// somewhere on the Python path. Then
+//
+//   import cordic_rs
+//   cos, sin = cordic_rs.sincos(1.0, iters=24)
+//   cos, sin = cordic_rs.sincos_batch(numpy.linspace(-3, 3, 1000), iters=24)
+//   steps = cordic_rs.trace(1.0, iters=24)
+//   cos_stats, sin_stats = cordic_rs.error_stats(numpy.linspace(-3, 3, 1000), iters=24)
+//
+// Everything runs CordicConfig::new(iters), i.e. the default integer
+// backend. Arrays come in as any sequence of floats (NumPy's included)
+// and go back out as lists, which numpy.asarray() takes as they are. A
+// CordicError is raised as a ValueError with the error's message
+
+use crate::bench::{sweep, ErrorStats as SweepStats};
+use crate::cordic::{checked_iters, CordicConfig};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::iterations::Step as IterationStep;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+impl From<CordicError> for PyErr {
+    fn from(err: CordicError) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+// One micro-rotation of trace(), see Step (src/iterations.rs). cos and
+// sin are the estimates so far with the gain undone
+#[pyclass(name = "Step", get_all, frozen, skip_from_py_object)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub iteration: usize,
+    pub shift: usize,
+    pub counterclockwise: bool,
+    pub cos: f64,
+    pub sin: f64,
+    // The angle still left to rotate by
+    pub z: f64,
+    pub gain: f64,
+}
+
+impl From<IterationStep> for Step {
+    fn from(step: IterationStep) -> Self {
+        Self {
+            iteration: step.iteration,
+            shift: step.shift,
+            counterclockwise: step.counterclockwise,
+            cos: (step.x * step.gain).to_f64(),
+            sin: (step.y * step.gain).to_f64(),
+            z: step.z.to_f64(),
+            gain: step.gain.to_f64(),
+        }
+    }
+}
+
+#[pymethods]
+impl Step {
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+// Errors over a sweep against std's sin and cos, see ErrorStats
+// (src/bench.rs)
+#[pyclass(name = "ErrorStats", get_all, frozen, skip_from_py_object)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErrorStats {
+    pub max: f64,
+    pub mean: f64,
+    pub rms: f64,
+    pub max_relative: f64,
+    pub max_ulps: u64,
+    pub worst_theta: f64,
+}
+
+impl From<SweepStats> for ErrorStats {
+    fn from(stats: SweepStats) -> Self {
+        Self {
+            max: stats.max,
+            mean: stats.mean,
+            rms: stats.rms,
+            max_relative: stats.max_relative,
+            max_ulps: stats.max_ulps,
+            worst_theta: stats.worst_theta,
+        }
+    }
+}
+
+#[pymethods]
+impl ErrorStats {
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+// (cos theta, sin theta) with `iters` iterations
+#[pyfunction]
+#[pyo3(signature = (theta, iters = 32))]
+pub fn sincos(theta: f64, iters: usize) -> PyResult<(f64, f64)> {
+    let ret = CordicConfig::new(iters).rotate(FixedPoint::new(theta))?;
+    Ok((ret.x.to_f64(), ret.y.to_f64()))
+}
+
+// (cos, sin) lists for every angle in `thetas`. A bad iteration count
+// raises, but an angle the engine rejects just gets NaN for both, so
+// one bad angle doesn't lose the rest of the batch
+#[pyfunction]
+#[pyo3(signature = (thetas, iters = 32))]
+pub fn sincos_batch(thetas: Vec<f64>, iters: usize) -> PyResult<(Vec<f64>, Vec<f64>)> {
+    checked_iters(iters)?;
+    let config = CordicConfig::new(iters);
+    Ok(thetas
+        .iter()
+        .map(|&theta| match config.rotate(FixedPoint::new(theta)) {
+            Ok(ret) => (ret.x.to_f64(), ret.y.to_f64()),
+            Err(_) => (f64::NAN, f64::NAN),
+        })
+        .unzip())
+}
+
+// The first `iters` micro-rotations of theta, for plotting convergence
+#[pyfunction]
+#[pyo3(signature = (theta, iters = 24))]
+pub fn trace(theta: f64, iters: usize) -> PyResult<Vec<Step>> {
+    let steps = CordicConfig::new(iters).iterations(FixedPoint::new(theta))?;
+    Ok(steps.take(iters).map(Step::from).collect())
+}
+
+// Error statistics of the cosines and of the sines over `thetas`
+#[pyfunction]
+#[pyo3(signature = (thetas, iters = 32))]
+pub fn error_stats(thetas: Vec<f64>, iters: usize) -> PyResult<(ErrorStats, ErrorStats)> {
+    let result = sweep(&CordicConfig::new(iters), &thetas)?;
+    Ok((result.cos_stats().into(), result.sin_stats().into()))
+}
+
+#[pymodule]
+fn cordic_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Step>()?;
+    m.add_class::<ErrorStats>()?;
+    m.add_function(wrap_pyfunction!(sincos, m)?)?;
+    m.add_function(wrap_pyfunction!(sincos_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(trace, m)?)?;
+    m.add_function(wrap_pyfunction!(error_stats, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn functions() {
+        let (cos, sin) = sincos(1.0, 32).unwrap();
+        assert![(cos - 1.0_f64.cos()).abs() < 1e-9];
+        assert![(sin - 1.0_f64.sin()).abs() < 1e-9];
+        assert![sincos(1.0, 0).is_err()];
+
+        let (cos, sin) = sincos_batch(vec![0.5, f64::NAN, 1.0], 32).unwrap();
+        assert_eq![(cos[0], sin[0]), sincos(0.5, 32).unwrap()];
+        assert![cos[1].is_nan() && sin[1].is_nan()];
+        assert_eq![(cos[2], sin[2]), sincos(1.0, 32).unwrap()];
+        assert![sincos_batch(vec![1.0], 0).is_err()];
+
+        let steps = trace(1.0, 24).unwrap();
+        assert_eq![steps.len(), 24];
+        assert_eq![steps[23].iteration, 23];
+        assert![(steps[23].cos - 1.0_f64.cos()).abs() < 1e-6];
+        assert![steps[23].z.abs() < steps[0].z.abs()];
+        assert![trace(f64::NAN, 24).is_err()];
+
+        let thetas: Vec<f64> = (-300..=300).map(|i| i as f64 / 100.0).collect();
+        let (cos, sin) = error_stats(thetas, 32).unwrap();
+        assert![cos.max < 1e-8 && sin.max < 1e-8];
+        assert![cos.rms <= cos.max];
+    }
+
+    #[test]
+    fn module() {
+        // The same calls from Python, through the module's own bindings
+        use pyo3::types::IntoPyDict;
+        Python::initialize();
+        Python::attach(|py| -> PyResult<()> {
+            let module = pyo3::wrap_pymodule!(cordic_rs)(py);
+            let locals = [("cordic_rs", module)].into_py_dict(py)?;
+            py.run(
+                pyo3::ffi::c_str!("
+import math
+cos, sin = cordic_rs.sincos(1.0)
+assert abs(cos - math.cos(1.0)) < 1e-9 and abs(sin - math.sin(1.0)) < 1e-9
+cos, sin = cordic_rs.sincos_batch([0.5, float('nan')], iters=24)
+assert len(cos) == 2 and math.isnan(sin[1])
+steps = cordic_rs.trace(1.0, iters=8)
+assert [step.iteration for step in steps] == list(range(8))
+cos_stats, sin_stats = cordic_rs.error_stats([0.1, 0.2, 0.3])
+assert cos_stats.max >= cos_stats.mean
+try:
+    cordic_rs.sincos(1.0, iters=0)
+    raise AssertionError('no ValueError')
+except ValueError:
+    pass
+"),
+                None,
+                Some(&locals),
+            )
+        })
+        .unwrap();
+    }
+}
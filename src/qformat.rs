@@ -0,0 +1,428 @@
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::math;
+use std::fmt;
+use std::str::FromStr;
+
+// A signed fixed point format Qm.n: a sign bit, m integer bits and n
+// fractional bits, stored in the low m + n + 1 bits of an i64. Q15 (that
+// is, Q0.15) is the usual 16 bit DSP format covering [-1, 1)
+//
+// The `fixed` crate's IxFy types are the same thing with the sign bit
+// counted in x, so I16F16 is Q15.16. To hand one across:
+//
+//   let q = "I16F16".parse::<QFormat>()?;
+//   let theta = q.fixed_from_bits(x.to_bits() as i64);
+//   let y = I16F16::from_bits(q.fixed_to_bits(theta.sin()) as i32);
+//
+// or with `--features fixed`, FixedPoint::from(x) and I16F16::try_from()
+// (see src/fixed_interop.rs)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QFormat {
+    int_bits: u32,
+    frac_bits: u32,
+}
+
+impl QFormat {
+    pub const Q15: QFormat = QFormat {
+        int_bits: 0,
+        frac_bits: 15,
+    };
+    pub const Q31: QFormat = QFormat {
+        int_bits: 0,
+        frac_bits: 31,
+    };
+
+    // Anything up to 64 bits including the sign
+    pub fn new(int_bits: u32, frac_bits: u32) -> Result<Self, CordicError> {
+        if int_bits
+            .checked_add(frac_bits)
+            .is_none_or(|bits| bits >= 64)
+        {
+            return Err(CordicError::DomainError("QFormat::new"));
+        }
+        Ok(Self {
+            int_bits,
+            frac_bits,
+        })
+    }
+
+    pub fn int_bits(&self) -> u32 {
+        self.int_bits
+    }
+
+    pub fn frac_bits(&self) -> u32 {
+        self.frac_bits
+    }
+
+    // Total width, sign bit included
+    pub fn bits(&self) -> u32 {
+        self.int_bits + self.frac_bits + 1
+    }
+
+    // The smallest step, 2^-n
+    pub fn resolution(&self) -> f64 {
+        math::powi(2.0, -(self.frac_bits as i32))
+    }
+
+    pub fn max_bits(&self) -> i64 {
+        (((1u64 << (self.bits() - 1)) - 1) as i64).max(0)
+    }
+
+    pub fn min_bits(&self) -> i64 {
+        -self.max_bits() - 1
+    }
+
+    pub fn max(&self) -> f64 {
+        self.to_f64(self.max_bits())
+    }
+
+    pub fn min(&self) -> f64 {
+        self.to_f64(self.min_bits())
+    }
+
+    // Rounds to the nearest representable value, saturating at the ends
+    // of the range. NaN has nowhere to go and comes out as 0
+    pub fn quantize(&self, x: f64) -> i64 {
+        let scaled = math::round(x * math::powi(2.0, self.frac_bits as i32));
+        if scaled.is_nan() {
+            0
+        } else if scaled >= self.max_bits() as f64 {
+            self.max_bits()
+        } else if scaled <= self.min_bits() as f64 {
+            self.min_bits()
+        } else {
+            scaled as i64
+        }
+    }
+
+    pub fn to_f64(&self, bits: i64) -> f64 {
+        bits as f64 * self.resolution()
+    }
+
+    // Largest over smallest magnitude, at 20 log10(2) ~= 6.02 dB per
+    // bit past the sign
+    pub fn dynamic_range_db(&self) -> f64 {
+        (self.bits() - 1) as f64 * 6.020_599_913_279_624
+    }
+
+    // Raw bits in this format to a FixedPoint and back. Formats up to 53
+    // bits wide round trip exactly; wider ones lose the bits past f64's
+    // mantissa on the way in
+    pub fn fixed_from_bits(&self, bits: i64) -> FixedPoint {
+        FixedPoint::new(self.to_f64(bits))
+    }
+
+    pub fn fixed_to_bits(&self, x: FixedPoint) -> i64 {
+        self.quantize(x.to_f64())
+    }
+}
+
+impl fmt::Display for QFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.int_bits == 0 {
+            write!(f, "Q{}", self.frac_bits)
+        } else {
+            write!(f, "Q{}.{}", self.int_bits, self.frac_bits)
+        }
+    }
+}
+
+// How convert() drops fractional bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    // Halves away from zero, like quantize()
+    Nearest,
+    // Halves to the even neighbour, for no bias over many conversions
+    NearestEven,
+    // Toward minus infinity: an arithmetic shift, the cheapest in
+    // hardware
+    Floor,
+    // Toward zero: sign-magnitude truncation
+    TowardZero,
+}
+
+// What convert() does with a value outside the target format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    Saturate,
+    // Keeps the low bits, as two's complement hardware does
+    Wrap,
+    Error,
+}
+
+// Moves `bits` from one format to another entirely in integers. Gaining
+// fractional bits is exact; losing them rounds as asked, and then the
+// overflow policy applies if the result doesn't fit. It's an error for
+// `bits` to be outside `from` in the first place
+pub fn convert(
+    bits: i64,
+    from: QFormat,
+    to: QFormat,
+    rounding: Rounding,
+    overflow: Overflow,
+) -> Result<i64, CordicError> {
+    if bits < from.min_bits() || bits > from.max_bits() {
+        return Err(CordicError::DomainError("convert"));
+    }
+
+    let bits = bits as i128;
+    let value = if to.frac_bits >= from.frac_bits {
+        bits << (to.frac_bits - from.frac_bits)
+    } else {
+        let drop = from.frac_bits - to.frac_bits;
+        let floor = bits >> drop;
+        let rest = bits - (floor << drop);
+        let half = 1i128 << (drop - 1);
+        match rounding {
+            Rounding::Floor => floor,
+            Rounding::TowardZero if bits < 0 && rest != 0 => floor + 1,
+            Rounding::TowardZero => floor,
+            Rounding::Nearest if rest > half || (rest == half && bits >= 0) => floor + 1,
+            Rounding::NearestEven if rest > half || (rest == half && floor & 1 == 1) => floor + 1,
+            Rounding::Nearest | Rounding::NearestEven => floor,
+        }
+    };
+
+    let (min, max) = (to.min_bits() as i128, to.max_bits() as i128);
+    if (min..=max).contains(&value) {
+        return Ok(value as i64);
+    }
+    match overflow {
+        Overflow::Saturate => Ok(value.clamp(min, max) as i64),
+        Overflow::Wrap => {
+            let unused = 128 - to.bits();
+            Ok(((value << unused) >> unused) as i64)
+        }
+        Overflow::Error => Err(CordicError::Overflow("convert")),
+    }
+}
+
+// FixedPoint is really an f64, which doesn't have a single Q format, but
+// over |x| < 4 (every reduced angle and every x or y, gain included) its
+// 53 bit significand holds the whole Q2.51 grid exactly. Analysis code
+// and error reports can use these rather than assuming a width
+impl FixedPoint {
+    pub fn format() -> QFormat {
+        QFormat {
+            int_bits: 2,
+            frac_bits: FixedPoint::PRECISION_BITS as u32 - 2,
+        }
+    }
+
+    pub fn max_representable() -> FixedPoint {
+        FixedPoint::new(Self::format().max())
+    }
+
+    pub fn resolution() -> FixedPoint {
+        FixedPoint::new(Self::format().resolution())
+    }
+
+    pub fn dynamic_range_db() -> f64 {
+        Self::format().dynamic_range_db()
+    }
+
+    // The value in format(), rounded and saturated like quantize()
+    pub fn raw_bits(self) -> i64 {
+        Self::format().fixed_to_bits(self)
+    }
+
+    pub fn from_raw_bits(bits: i64) -> FixedPoint {
+        Self::format().fixed_from_bits(bits)
+    }
+}
+
+// Parses "Q15" (no integer bits) or "Q1.30", as written by Display, or
+// the `fixed` crate's "I16F16", whose integer bits include the sign
+impl FromStr for QFormat {
+    type Err = CordicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(rest) = s.strip_prefix('I') {
+            let (int_bits, frac_bits) = rest
+                .split_once('F')
+                .and_then(|(int_bits, frac_bits)| {
+                    Some((
+                        int_bits.parse::<u32>().ok()?,
+                        frac_bits.parse::<u32>().ok()?,
+                    ))
+                })
+                .ok_or(CordicError::ParseError("bad fixed type name"))?;
+            if int_bits == 0 {
+                return Err(CordicError::ParseError("fixed type needs a sign bit"));
+            }
+            return Self::new(int_bits - 1, frac_bits)
+                .map_err(|_| CordicError::ParseError("Q format wider than 64 bits"));
+        }
+        let rest = s
+            .strip_prefix('Q')
+            .ok_or(CordicError::ParseError("Q format must start with Q"))?;
+        let number = |digits: &str| {
+            digits
+                .parse::<u32>()
+                .map_err(|_| CordicError::ParseError("bad Q format bit count"))
+        };
+        let (int_bits, frac_bits) = match rest.split_once('.') {
+            Some((int_bits, frac_bits)) => (number(int_bits)?, number(frac_bits)?),
+            None => (0, number(rest)?),
+        };
+        Self::new(int_bits, frac_bits)
+            .map_err(|_| CordicError::ParseError("Q format wider than 64 bits"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range() {
+        assert_eq![QFormat::Q15.bits(), 16];
+        assert_eq![QFormat::Q15.max_bits(), 32767];
+        assert_eq![QFormat::Q15.min(), -1.0];
+        assert_eq![QFormat::Q15.resolution(), 1.0 / 32768.0];
+        let wide = QFormat::new(3, 60).unwrap();
+        assert_eq![wide.max_bits(), i64::MAX];
+        assert_eq![wide.min_bits(), i64::MIN];
+        assert![QFormat::new(4, 60).is_err()];
+    }
+
+    #[test]
+    fn quantize() {
+        let q = QFormat::Q15;
+        assert_eq![q.quantize(0.5), 16384];
+        assert_eq![q.quantize(1.0), 32767];
+        assert_eq![q.quantize(-2.0), -32768];
+        assert_eq![q.quantize(f64::NAN), 0];
+        assert_eq![q.quantize(f64::INFINITY), 32767];
+        assert_eq![q.quantize(1.0 / 65536.0), 1];
+        assert_eq![q.to_f64(q.quantize(0.25)), 0.25];
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!["Q15".parse::<QFormat>().unwrap(), QFormat::Q15];
+        assert_eq![
+            "Q1.30".parse::<QFormat>().unwrap(),
+            QFormat::new(1, 30).unwrap()
+        ];
+        assert_eq![QFormat::new(1, 30).unwrap().to_string(), "Q1.30"];
+        assert_eq![QFormat::Q31.to_string(), "Q31"];
+        for bad in &[
+            "15", "Q", "Qx", "Q1.", "Q70", "Q10.60", "I0F16", "I16", "I40F40",
+        ] {
+            assert![bad.parse::<QFormat>().is_err(), "{:?}", bad];
+        }
+    }
+
+    #[test]
+    fn fixed_crate() {
+        let q = "I16F16".parse::<QFormat>().unwrap();
+        assert_eq![q, QFormat::new(15, 16).unwrap()];
+        assert_eq!["I1F31".parse::<QFormat>().unwrap(), QFormat::Q31];
+
+        // 1.5 is 0x18000 as an I16F16
+        let x = q.fixed_from_bits(0x18000);
+        assert_eq![x, FixedPoint::new(1.5)];
+        assert_eq![q.fixed_to_bits(x), 0x18000];
+        for &bits in &[i32::MIN as i64, -1, 0, 1, i32::MAX as i64] {
+            assert_eq![q.fixed_to_bits(q.fixed_from_bits(bits)), bits];
+        }
+        assert_eq![q.fixed_to_bits(FixedPoint::new(1e9)), i32::MAX as i64];
+    }
+
+    #[test]
+    fn fixed_point_format() {
+        let format = FixedPoint::format();
+        assert_eq![format.to_string(), "Q2.51"];
+        assert_eq![format.bits() as usize, FixedPoint::PRECISION_BITS + 1];
+        assert![FixedPoint::max_representable() < FixedPoint::new(4.0)];
+        assert_eq![
+            FixedPoint::max_representable() + FixedPoint::resolution(),
+            FixedPoint::new(4.0)
+        ];
+        assert![(FixedPoint::dynamic_range_db() - 319.09).abs() < 0.01];
+        assert![(QFormat::Q15.dynamic_range_db() - 90.31).abs() < 0.01];
+
+        // Every bit pattern is held exactly
+        for &bits in &[format.min_bits(), -1, 0, 1, 12345, format.max_bits()] {
+            assert_eq![FixedPoint::from_raw_bits(bits).raw_bits(), bits];
+        }
+        assert_eq![FixedPoint::new(1.0).raw_bits(), 1 << 51];
+    }
+
+    // Every Q15 value into Q7, against rounding the exact value in f64
+    #[test]
+    fn narrowing() {
+        let q7 = QFormat::new(0, 7).unwrap();
+        let policies = [
+            (Rounding::Nearest, f64::round as fn(f64) -> f64),
+            (Rounding::Floor, f64::floor),
+            (Rounding::TowardZero, f64::trunc),
+            (Rounding::NearestEven, f64::round_ties_even),
+        ];
+        for bits in i16::MIN..=i16::MAX {
+            let exact = bits as f64 / 256.0;
+            for &(rounding, round) in &policies {
+                let expected = (round(exact) as i64).clamp(-128, 127);
+                let got = convert(bits as i64, QFormat::Q15, q7, rounding, Overflow::Saturate);
+                assert_eq![got, Ok(expected), "{} {:?}", bits, rounding];
+            }
+        }
+    }
+
+    #[test]
+    fn widening() {
+        let q7 = QFormat::new(0, 7).unwrap();
+        for bits in -128..=127 {
+            let wide = convert(bits, q7, QFormat::Q31, Rounding::Floor, Overflow::Error).unwrap();
+            assert_eq![wide, bits << 24];
+            assert_eq![
+                convert(wide, QFormat::Q31, q7, Rounding::Floor, Overflow::Error),
+                Ok(bits)
+            ];
+        }
+        // The extremes of the widest formats
+        let q60 = QFormat::new(3, 60).unwrap();
+        let q63 = QFormat::new(0, 63).unwrap();
+        assert_eq![
+            convert(i64::MIN, q60, q63, Rounding::Nearest, Overflow::Saturate),
+            Ok(i64::MIN)
+        ];
+        assert_eq![
+            convert(i64::MAX, q63, q60, Rounding::Nearest, Overflow::Error),
+            Ok(1 << 60)
+        ];
+    }
+
+    #[test]
+    fn overflow() {
+        let q1_14 = QFormat::new(1, 14).unwrap();
+        // 1.5 doesn't fit in Q15
+        let bits = 3 << 13;
+        let to = |overflow| convert(bits, q1_14, QFormat::Q15, Rounding::Floor, overflow);
+        assert_eq![to(Overflow::Saturate), Ok(32767)];
+        assert_eq![to(Overflow::Wrap), Ok(-16384)];
+        assert_eq![to(Overflow::Error), Err(CordicError::Overflow("convert"))];
+
+        // Rounding up past the top is an overflow too
+        assert_eq![
+            convert(
+                i32::MAX as i64,
+                QFormat::Q31,
+                QFormat::Q15,
+                Rounding::Nearest,
+                Overflow::Wrap
+            ),
+            Ok(-32768)
+        ];
+        assert![convert(
+            1 << 15,
+            QFormat::Q15,
+            QFormat::Q31,
+            Rounding::Floor,
+            Overflow::Error
+        )
+        .is_err()];
+    }
+}
@@ -0,0 +1,286 @@
+use crate::error::CordicError;
+use crate::qformat::{QFormat, Rounding};
+use crate::remez::{horner, max_error, QuantizedPolynomial};
+use std::fmt;
+use std::str::FromStr;
+
+// What rounding polynomial coefficients to a word length costs. Each
+// coefficient alone is off by under an LSB however it's rounded, but the
+// errors add up across the interval in ways that depend on the rounding,
+// and the set that rounds each coefficient to nearest is often not the
+// best set on the grid. The usual answer is to try a few by hand; this
+// tries them all and measures each against f:
+//
+//   nearest, nearest-even, floor, toward-zero  each coefficient rounded
+//                                              on its own, as convert()
+//                                              would
+//   search                                     starts from nearest and
+//                                              moves single coefficients
+//                                              an LSB at a time while
+//                                              the worst error shrinks
+//
+// The search is a plain coordinate descent, so it finds a local best
+// rather than the best set there is, but it never ends up worse than
+// nearest. Errors are measured with the polynomial evaluated in f64, so
+// they're what the coefficients cost and nothing of the datapath's own
+// rounding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    Round(Rounding),
+    Search,
+}
+
+pub const STRATEGIES: [Strategy; 5] = [
+    Strategy::Round(Rounding::Nearest),
+    Strategy::Round(Rounding::NearestEven),
+    Strategy::Round(Rounding::Floor),
+    Strategy::Round(Rounding::TowardZero),
+    Strategy::Search,
+];
+
+// Passes over the coefficients the search gives up after. Each pass
+// that changes nothing ends it, and few get past three
+const MAX_PASSES: usize = 16;
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Strategy::Round(Rounding::Nearest) => "nearest",
+            Strategy::Round(Rounding::NearestEven) => "nearest-even",
+            Strategy::Round(Rounding::Floor) => "floor",
+            Strategy::Round(Rounding::TowardZero) => "toward-zero",
+            Strategy::Search => "search",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = CordicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        STRATEGIES
+            .iter()
+            .copied()
+            .find(|strategy| strategy.to_string() == s)
+            .ok_or(CordicError::ParseError(
+                "strategy must be nearest, nearest-even, floor, toward-zero or search",
+            ))
+    }
+}
+
+// Every strategy's coefficients and error for one set of coefficients
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizationAnalysis {
+    pub format: QFormat,
+    // The largest |p(x) - f(x)| with the coefficients as given
+    pub ideal_error: f64,
+    pub results: Vec<(Strategy, QuantizedPolynomial)>,
+}
+
+impl QuantizationAnalysis {
+    // The strategy with the smallest error, the first on a tie
+    pub fn best(&self) -> &(Strategy, QuantizedPolynomial) {
+        self.results
+            .iter()
+            .reduce(|best, next| {
+                if next.1.max_error < best.1.max_error {
+                    next
+                } else {
+                    best
+                }
+            })
+            .expect("STRATEGIES isn't empty")
+    }
+}
+
+// `coeffs` (of 1, x, x^2, ..., from minimax() or anywhere else) in
+// `format` under `strategy`. A coefficient that doesn't fit is an
+// Overflow, and no coefficients, a non-finite one or a bad interval is a
+// DomainError
+pub fn quantize_coefficients(
+    f: impl Fn(f64) -> f64,
+    coeffs: &[f64],
+    interval: (f64, f64),
+    format: QFormat,
+    strategy: Strategy,
+) -> Result<QuantizedPolynomial, CordicError> {
+    let (a, b) = interval;
+    if coeffs.is_empty()
+        || coeffs.iter().any(|c| !c.is_finite())
+        || !(a.is_finite() && b.is_finite() && a < b)
+    {
+        return Err(CordicError::DomainError("quantize"));
+    }
+    let measure = |bits: &[i64]| {
+        let values = bits.iter().map(|&c| format.to_f64(c)).collect::<Vec<_>>();
+        max_error(|x| horner(&values, x) - f(x), a, b)
+    };
+
+    let rounding = match strategy {
+        Strategy::Round(rounding) => rounding,
+        Strategy::Search => Rounding::Nearest,
+    };
+    let mut bits = coeffs
+        .iter()
+        .map(|&c| round(c, format, rounding))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut error = measure(&bits);
+    if strategy == Strategy::Search {
+        for _ in 0..MAX_PASSES {
+            let mut moved = false;
+            for j in 0..bits.len() {
+                for step in [-1, 1] {
+                    let mut trial = bits.clone();
+                    trial[j] += step;
+                    if trial[j] > format.max_bits() || trial[j] < format.min_bits() {
+                        continue;
+                    }
+                    let trial_error = measure(&trial);
+                    if trial_error < error {
+                        bits = trial;
+                        error = trial_error;
+                        moved = true;
+                    }
+                }
+            }
+            if !moved {
+                break;
+            }
+        }
+    }
+    Ok(QuantizedPolynomial {
+        interval,
+        format,
+        coeffs: bits,
+        max_error: error,
+    })
+}
+
+// quantize_coefficients() under each of STRATEGIES
+pub fn analyze_quantization(
+    f: impl Fn(f64) -> f64,
+    coeffs: &[f64],
+    interval: (f64, f64),
+    format: QFormat,
+) -> Result<QuantizationAnalysis, CordicError> {
+    let results = STRATEGIES
+        .iter()
+        .map(|&strategy| {
+            quantize_coefficients(&f, coeffs, interval, format, strategy).map(|q| (strategy, q))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let (a, b) = interval;
+    Ok(QuantizationAnalysis {
+        format,
+        ideal_error: max_error(|x| horner(coeffs, x) - f(x), a, b),
+        results,
+    })
+}
+
+fn round(c: f64, format: QFormat, rounding: Rounding) -> Result<i64, CordicError> {
+    let scaled = c / format.resolution();
+    let bits = match rounding {
+        Rounding::Nearest => scaled.round(),
+        Rounding::NearestEven => scaled.round_ties_even(),
+        Rounding::Floor => scaled.floor(),
+        Rounding::TowardZero => scaled.trunc(),
+    };
+    if bits > format.max_bits() as f64 || bits < format.min_bits() as f64 {
+        return Err(CordicError::Overflow("quantize"));
+    }
+    Ok(bits as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remez::minimax;
+
+    #[test]
+    fn strategies() {
+        let sin = minimax(f64::sin, 0.0, std::f64::consts::FRAC_PI_2, 5).unwrap();
+        let q15 = QFormat::Q15;
+        let analysis = analyze_quantization(f64::sin, &sin.coeffs, sin.interval, q15).unwrap();
+        assert![(analysis.ideal_error - sin.max_error).abs() < 1e-15];
+        assert_eq![analysis.results.len(), STRATEGIES.len()];
+
+        let error = |strategy| {
+            let (_, q) = analysis
+                .results
+                .iter()
+                .find(|(s, _)| *s == strategy)
+                .unwrap();
+            q.max_error
+        };
+        let nearest = Strategy::Round(Rounding::Nearest);
+        for (strategy, q) in &analysis.results {
+            assert![q.max_error >= analysis.ideal_error, "{}", strategy];
+            assert_eq![q.coeffs.len(), 6];
+        }
+        // Minimax::quantize is the nearest case
+        assert_eq![analysis.results[0].1, sin.quantize(f64::sin, q15).unwrap()];
+        assert![error(Strategy::Search) <= error(nearest)];
+        assert![analysis.best().1.max_error <= error(Strategy::Search)];
+
+        // floor and toward-zero agree on the positive coefficients and
+        // sit an LSB apart on the negative ones that aren't exact
+        let floor = &analysis.results[2].1.coeffs;
+        let toward_zero = &analysis.results[3].1.coeffs;
+        for ((&c, &down), &truncated) in sin.coeffs.iter().zip(floor).zip(toward_zero) {
+            assert_eq![down, (c / q15.resolution()).floor() as i64];
+            assert![truncated == down || (c < 0.0 && truncated == down + 1)];
+        }
+    }
+
+    #[test]
+    fn user_coefficients() {
+        // The Taylor cubic for sin, which minimax would beat, still
+        // quantizes like any other set
+        let taylor = [0.0, 1.0, 0.0, -1.0 / 6.0];
+        let q = QFormat::new(1, 20).unwrap();
+        let analysis = analyze_quantization(f64::sin, &taylor, (-0.5, 0.5), q).unwrap();
+        let cubic = |x: f64| x - x * x * x / 6.0;
+        assert![(analysis.ideal_error - (cubic(0.5) - 0.5f64.sin()).abs()).abs() < 1e-12];
+        let (_, nearest) = &analysis.results[0];
+        assert_eq![nearest.coeffs[..3], [0, 1 << 20, 0]];
+        assert![(nearest.eval(0.25) - cubic(0.25)).abs() < 1e-6];
+
+        assert_eq!["toward-zero".parse::<Strategy>(), Ok(STRATEGIES[3])];
+        for strategy in STRATEGIES {
+            assert_eq![strategy.to_string().parse::<Strategy>(), Ok(strategy)];
+        }
+        assert!["up".parse::<Strategy>().is_err()];
+    }
+
+    #[test]
+    fn rejects() {
+        let search = Strategy::Search;
+        let domain = Err(CordicError::DomainError("quantize"));
+        let q15 = QFormat::Q15;
+        assert_eq![
+            quantize_coefficients(f64::sin, &[], (0.0, 1.0), q15, search),
+            domain
+        ];
+        assert_eq![
+            quantize_coefficients(f64::sin, &[f64::NAN], (0.0, 1.0), q15, search),
+            domain
+        ];
+        assert_eq![
+            quantize_coefficients(f64::sin, &[0.5], (1.0, 0.0), q15, search),
+            domain
+        ];
+        assert_eq![
+            analyze_quantization(f64::sin, &[0.0, 1.5], (0.0, 1.0), q15),
+            Err(CordicError::Overflow("quantize"))
+        ];
+        // 1 - 2^-16 rounds up out of Q15 but floors to its top
+        let edge = 1.0 - QFormat::Q15.resolution() / 2.0;
+        let floor = Strategy::Round(Rounding::Floor);
+        assert![quantize_coefficients(f64::sin, &[edge], (0.0, 1.0), q15, floor).is_ok()];
+        assert_eq![
+            quantize_coefficients(f64::sin, &[edge], (0.0, 1.0), q15, STRATEGIES[0]),
+            Err(CordicError::Overflow("quantize"))
+        ];
+    }
+}
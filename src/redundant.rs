@@ -0,0 +1,263 @@
+use crate::error::CordicError;
+use crate::integer::{fold, ANGLES, GAINS, Q60};
+
+// Simulates a CORDIC whose angle register is kept in carry-save form,
+// the redundant representation fast hardware uses so that an add takes
+// one full adder delay whatever the width. z is held as a pair (sum,
+// carry) whose total is the real residual angle, and adding an angle is
+// a row of 3:2 compressors with no carry chain at all.
+//
+// The catch is the direction decision: the sign of z depends on every
+// bit of sum + carry, and resolving it means the carry chain we were
+// avoiding. Fast designs instead add up just the top few bits of each.
+// Flooring both halves to `window` fractional bits puts the estimate at
+// most 2 units below the true value, so an estimate of exactly -1 unit
+// can't say which side of zero z is on. This counts how often that
+// happens and how often the decision goes the wrong way, which is why
+// redundant CORDIC uses different sigma selection (a sigma of 0, or
+// extra correcting iterations) rather than plain sign detection.
+//
+// Only z is redundant here; x and y are ordinary Q60 registers, since
+// rotation mode never looks at their sign
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CarrySave {
+    pub sincos: [Q60; 2],
+    // Iterations where the window estimate couldn't tell the sign
+    pub ambiguous: usize,
+    // Iterations where the decision differed from the exact sign of z
+    pub wrong: usize,
+}
+
+// How each micro-rotation's direction gets chosen from the carry-save
+// z = sum + carry. select() fills in the directions (true for
+// counterclockwise) of iteration `i` onwards, as many as it decides from
+// this one z (1, or 2 for look-ahead), and returns how many
+pub trait SigmaSelection {
+    fn name(&self) -> String;
+
+    fn select(&self, z: [i64; 2], i: usize, directions: &mut [bool; 2]) -> usize;
+
+    // Whether the decision at iteration `i` can't really be made from
+    // what select() looks at, for counting
+    fn ambiguous(&self, _z: [i64; 2], _i: usize) -> bool {
+        false
+    }
+}
+
+// The exact sign of sum + carry, paying for the full carry chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExactSign;
+
+impl SigmaSelection for ExactSign {
+    fn name(&self) -> String {
+        "exact".to_string()
+    }
+
+    fn select(&self, z: [i64; 2], _i: usize, directions: &mut [bool; 2]) -> usize {
+        directions[0] = z[0].wrapping_add(z[1]) >= 0;
+        1
+    }
+}
+
+// The sign of just the top bits of sum and carry added together, down to
+// this many fractional bits (at most 60), as high speed designs do. See
+// the top of the file for where it goes wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MsbWindow(pub u32);
+
+impl MsbWindow {
+    // z floored to the window, in units of its last bit. The registers
+    // wrap like two's complement hardware, so the estimate is sign
+    // extended back from the bits it kept
+    fn estimate(&self, z: [i64; 2]) -> i64 {
+        let drop = Q60::FRAC_BITS - self.0.min(Q60::FRAC_BITS);
+        ((z[0] >> drop).wrapping_add(z[1] >> drop) << drop) >> drop
+    }
+}
+
+impl SigmaSelection for MsbWindow {
+    fn name(&self) -> String {
+        format!("msb-{}", self.0)
+    }
+
+    fn select(&self, z: [i64; 2], _i: usize, directions: &mut [bool; 2]) -> usize {
+        directions[0] = self.estimate(z) >= 0;
+        1
+    }
+
+    fn ambiguous(&self, z: [i64; 2], _i: usize) -> bool {
+        self.0 < Q60::FRAC_BITS && self.estimate(z) == -1
+    }
+}
+
+// Two directions per decision from one windowed estimate: the second
+// comes from the estimate with the first angle (floored to the window)
+// already taken off, so the hardware doesn't wait for the first add
+// before deciding it. Halves the decisions on the critical path at the
+// cost of the second one seeing a rougher z
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LookAhead(pub u32);
+
+impl SigmaSelection for LookAhead {
+    fn name(&self) -> String {
+        format!("lookahead-{}", self.0)
+    }
+
+    fn select(&self, z: [i64; 2], i: usize, directions: &mut [bool; 2]) -> usize {
+        let window = MsbWindow(self.0);
+        let drop = Q60::FRAC_BITS - self.0.min(Q60::FRAC_BITS);
+        let estimate = window.estimate(z);
+        directions[0] = estimate >= 0;
+        let angle = ANGLES.get(i).map_or(0, |angle| angle >> drop);
+        let next = if directions[0] {
+            estimate - angle
+        } else {
+            estimate + angle
+        };
+        directions[1] = next >= 0;
+        2
+    }
+
+    fn ambiguous(&self, z: [i64; 2], i: usize) -> bool {
+        MsbWindow(self.0).ambiguous(z, i)
+    }
+}
+
+// [cos, sin] of `theta` with `iters` iterations, deciding each direction
+// from the top `window` fractional bits of the carry-save z (at most 60,
+// which is the exact sign)
+pub fn sincos_carry_save(theta: Q60, iters: usize, window: u32) -> Result<CarrySave, CordicError> {
+    if window > Q60::FRAC_BITS {
+        return Err(CordicError::DomainError("sincos_carry_save"));
+    }
+    sincos_sigma(theta, iters, &MsbWindow(window))
+}
+
+// The same with any direction selection. `wrong` counts every direction
+// that differs from the exact sign of z at its own iteration, so for
+// look-ahead the second of each pair is checked against z after the
+// first add
+pub fn sincos_sigma(
+    theta: Q60,
+    iters: usize,
+    strategy: &dyn SigmaSelection,
+) -> Result<CarrySave, CordicError> {
+    if iters == 0 {
+        return Err(CordicError::IterationOutOfRange(iters));
+    }
+    let iters = iters.min(ANGLES.len());
+
+    let (z, negate) = fold(theta.to_bits());
+    let k = GAINS[iters];
+    let mut v = if negate { [-k, 0] } else { [k, 0] };
+    let (mut sum, mut carry) = (z, 0i64);
+    let (mut ambiguous, mut wrong) = (0, 0);
+    let mut i = 0;
+    while i < iters {
+        if strategy.ambiguous([sum, carry], i) {
+            ambiguous += 1;
+        }
+        let mut directions = [false; 2];
+        let decided = strategy.select([sum, carry], i, &mut directions);
+        for &counterclockwise in directions.iter().take(decided.clamp(1, 2).min(iters - i)) {
+            if counterclockwise != (sum.wrapping_add(carry) >= 0) {
+                wrong += 1;
+            }
+
+            let angle = ANGLES[i];
+            let addend = if counterclockwise {
+                v = [v[0] - (v[1] >> i), v[1] + (v[0] >> i)];
+                -angle
+            } else {
+                v = [v[0] + (v[1] >> i), v[1] - (v[0] >> i)];
+                angle
+            };
+            let (s, c, a) = (sum, carry, addend);
+            sum = s ^ c ^ a;
+            carry = ((s & c) | (s & a) | (c & a)).wrapping_shl(1);
+            i += 1;
+        }
+    }
+    Ok(CarrySave {
+        sincos: [Q60::from_bits(v[0]), Q60::from_bits(v[1])],
+        ambiguous,
+        wrong,
+    })
+}
+
+// A CORDIC with a particular direction selection, for running it
+// through the comparison tooling (see algorithm.rs)
+pub struct SigmaCordic {
+    pub iters: usize,
+    pub strategy: Box<dyn SigmaSelection>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integer::sincos_q60;
+
+    fn sweep() -> impl Iterator<Item = Q60> {
+        (-314..=314).map(|i| Q60::from_bits((i << 60) / 100))
+    }
+
+    #[test]
+    fn full_window_is_exact() {
+        // With every bit in the window this is plain sign detection, so
+        // it has to agree with the ordinary kernel bit for bit
+        for theta in sweep() {
+            let ret = sincos_carry_save(theta, 40, 60).unwrap();
+            assert_eq![ret.sincos, sincos_q60(theta, 40).unwrap()];
+            assert_eq![(ret.ambiguous, ret.wrong), (0, 0)];
+        }
+    }
+
+    #[test]
+    fn narrow_window() {
+        let mut ambiguous = 0;
+        let mut worst = 0;
+        for theta in sweep() {
+            let ret = sincos_carry_save(theta, 40, 8).unwrap();
+            let exact = sincos_q60(theta, 40).unwrap();
+            ambiguous += ret.ambiguous;
+            assert![ret.wrong <= ret.ambiguous];
+            for (a, b) in ret.sincos.iter().zip(&exact) {
+                worst = worst.max((a.to_bits() - b.to_bits()).abs());
+            }
+        }
+        // Once z is below the window it's always ambiguous, and the
+        // wrong turns it takes leave errors around the window's size
+        assert![ambiguous > 0];
+        assert![worst > 1 << 40];
+        assert![worst < 1 << 54];
+    }
+
+    #[test]
+    fn invalid() {
+        assert![sincos_carry_save(Q60::from_bits(0), 0, 8).is_err()];
+        assert![sincos_carry_save(Q60::from_bits(0), 8, 61).is_err()];
+    }
+
+    #[test]
+    fn strategies() {
+        let worst = |strategy: &dyn SigmaSelection| {
+            sweep()
+                .map(|theta| {
+                    let ret = sincos_sigma(theta, 40, strategy).unwrap();
+                    let exact = sincos_q60(theta, 40).unwrap();
+                    let [a, b] =
+                        [0, 1].map(|k| (ret.sincos[k].to_bits() - exact[k].to_bits()).abs());
+                    a.max(b)
+                })
+                .max()
+                .unwrap()
+        };
+        assert_eq![worst(&ExactSign), 0];
+        assert_eq![worst(&LookAhead(60)), 0];
+        // Deciding the second step from the same rough estimate does no
+        // better than deciding each from its own
+        assert![worst(&LookAhead(16)) >= worst(&MsbWindow(16))];
+        assert![worst(&MsbWindow(16)) > worst(&MsbWindow(24))];
+        assert_eq![LookAhead(16).name(), "lookahead-16"];
+    }
+}
@@ -0,0 +1,307 @@
+use crate::error::CordicError;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+// What errors get measured against. std and the libm crate are f64s, within an ulp of the true value but no closer, so a CORDIC
+// config that's itself within an ulp or two of the truth can't be told
+// apart from their rounding. DoubleDouble carries about 106 bits, more
+// than x87's 80 bit long double (64) and not far off binary128 (113), so
+// measured against it the error is the config's alone:
+//
+//   let results = registry.accuracy_against(&thetas, Reference::DoubleDouble)?;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reference {
+    // The platform's libm, through f64::cos and f64::sin
+    Std,
+    // The libm crate (musl's ported to Rust), the same on every platform
+    Libm,
+    // sincos() below
+    DoubleDouble,
+}
+
+impl Reference {
+    // [cos theta, sin theta]. The f64 references come back with lo = 0
+    pub fn sincos(self, theta: f64) -> [DoubleDouble; 2] {
+        match self {
+            Reference::Std => [
+                DoubleDouble::new(theta.cos()),
+                DoubleDouble::new(theta.sin()),
+            ],
+            Reference::Libm => [
+                DoubleDouble::new(libm::cos(theta)),
+                DoubleDouble::new(libm::sin(theta)),
+            ],
+            Reference::DoubleDouble => sincos(theta),
+        }
+    }
+
+    // |value - reference|, worked out in double-double so the reference's
+    // low part isn't lost before it's compared
+    pub fn error(value: f64, reference: DoubleDouble) -> f64 {
+        (DoubleDouble::new(value) - reference).to_f64().abs()
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Reference::Std => "std",
+            Reference::Libm => "libm",
+            Reference::DoubleDouble => "double-double",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Reference {
+    type Err = CordicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "std" => Ok(Reference::Std),
+            "libm" => Ok(Reference::Libm),
+            "double-double" => Ok(Reference::DoubleDouble),
+            _ => Err(CordicError::ParseError(
+                "reference must be std, libm or double-double",
+            )),
+        }
+    }
+}
+
+// An unevaluated sum hi + lo of two f64s with |lo| at most half an ulp
+// of hi, the usual software extended precision (see Dekker, and Hida, Li
+// and Bailey's QD library). Only what the reference needs is here: +, -
+// and * are accurate to about 2^-104 relative, and two_prod leans on
+// f64::mul_add being fused, which std guarantees even without an FMA
+// instruction
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+impl DoubleDouble {
+    pub const fn new(x: f64) -> Self {
+        Self { hi: x, lo: 0.0 }
+    }
+
+    // Rounded to the nearest f64
+    pub fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+
+    // a + b exactly
+    fn two_sum(a: f64, b: f64) -> Self {
+        let hi = a + b;
+        let b_virtual = hi - a;
+        let lo = (a - (hi - b_virtual)) + (b - b_virtual);
+        Self { hi, lo }
+    }
+
+    // a + b exactly, given |a| >= |b|
+    fn fast_two_sum(a: f64, b: f64) -> Self {
+        let hi = a + b;
+        Self {
+            hi,
+            lo: b - (hi - a),
+        }
+    }
+
+    // a * b exactly, barring overflow and underflow
+    fn two_prod(a: f64, b: f64) -> Self {
+        let hi = a * b;
+        Self {
+            hi,
+            lo: a.mul_add(b, -hi),
+        }
+    }
+
+    fn div_f64(self, d: f64) -> Self {
+        let q1 = self.hi / d;
+        let remainder = self - Self::two_prod(q1, d);
+        Self::fast_two_sum(q1, remainder.hi / d)
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let s = Self::two_sum(self.hi, other.hi);
+        let t = Self::two_sum(self.lo, other.lo);
+        let s = Self::fast_two_sum(s.hi, s.lo + t.hi);
+        Self::fast_two_sum(s.hi, s.lo + t.lo)
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let p = Self::two_prod(self.hi, other.hi);
+        Self::fast_two_sum(p.hi, p.lo + (self.hi * other.lo + self.lo * other.hi))
+    }
+}
+
+// pi/2 to 159 bits, as three f64s
+const FRAC_PI_2: [f64; 3] = [
+    std::f64::consts::FRAC_PI_2,
+    6.123_233_995_736_766e-17,
+    -1.497_384_904_859_169_8e-33,
+];
+
+// [cos theta, sin theta] to about 2^-104, for |theta| up to a million or
+// so; past that the 159 bits of pi/2 start running out in the reduction.
+// theta comes off the nearest multiple of pi/2, leaving |r| <= pi/4, and
+// the Taylor series in r are summed until the terms stop mattering.
+// Infinity and NaN give NaN
+pub(crate) fn sincos(theta: f64) -> [DoubleDouble; 2] {
+    if !theta.is_finite() {
+        return [DoubleDouble::new(f64::NAN); 2];
+    }
+    let k = (theta / FRAC_PI_2[0]).round();
+    let r = FRAC_PI_2.iter().fold(DoubleDouble::new(theta), |r, &part| {
+        r - DoubleDouble::two_prod(k, part)
+    });
+
+    let r2 = r * r;
+    let series = |first: DoubleDouble, mut n: f64| {
+        let (mut sum, mut term) = (first, first);
+        // Stops on an exact zero too, e.g. sin(0)
+        while term.hi.abs() > 1e-34 * sum.hi.abs() {
+            term = -(term * r2).div_f64(n * (n + 1.0));
+            sum = sum + term;
+            n += 2.0;
+        }
+        sum
+    };
+    let cos = series(DoubleDouble::new(1.0), 1.0);
+    let sin = series(r, 2.0);
+    match k.rem_euclid(4.0) as usize {
+        0 => [cos, sin],
+        1 => [-sin, cos],
+        2 => [-cos, -sin],
+        _ => [sin, -cos],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_values() {
+        // cos and sin of the f64s themselves to 50 digits, split into
+        // high and low parts
+        let cases = [
+            (
+                0.5,
+                [0.8775825618903728, -4.2623149864279997e-17],
+                [0.479425538604203, -5.103969860556013e-18],
+            ),
+            (
+                1.0,
+                [0.5403023058681398, -4.760954612604417e-17],
+                [0.8414709848078965, 1.776845092935536e-18],
+            ),
+            (
+                3.0,
+                [-0.9899924966004454, -4.2060261566099734e-17],
+                [0.1411200080598672, 8.577269787017502e-18],
+            ),
+            (
+                1000.0,
+                [0.5623790762907029, 5.1611423543848204e-17],
+                [0.8268795405320025, 3.867581909641312e-17],
+            ),
+            (
+                1e-10,
+                [1.0, -5.0000000000000005e-21],
+                [1e-10, -1.6666666666666669e-31],
+            ),
+        ];
+        for (theta, cos, sin) in cases {
+            let [c, s] = sincos(theta);
+            let close = |got: DoubleDouble, want: [f64; 2]| {
+                let want = DoubleDouble {
+                    hi: want[0],
+                    lo: want[1],
+                };
+                (got - want).to_f64().abs() <= 1e-31 * want.hi.abs()
+            };
+            assert![close(c, cos), "cos {} {:?}", theta, c];
+            assert![close(s, sin), "sin {} {:?}", theta, s];
+            assert_eq![sincos(-theta), [c, -s]];
+        }
+        assert_eq![
+            sincos(0.0),
+            [DoubleDouble::new(1.0), DoubleDouble::new(0.0)]
+        ];
+        assert![sincos(f64::INFINITY)[0].hi.is_nan()];
+        assert![sincos(f64::NAN)[1].hi.is_nan()];
+    }
+
+    #[test]
+    fn agrees_with_f64() {
+        // Rounded to f64 it's within an ulp of std and the port, which
+        // are both within an ulp of the truth
+        for i in -10000..=10000 {
+            let theta = i as f64 / 1000.0;
+            for reference in [Reference::Std, Reference::Libm] {
+                let [c, s] = reference.sincos(theta);
+                let [dc, ds] = sincos(theta);
+                assert![(c.hi - dc.to_f64()).abs() <= f64::EPSILON * c.hi.abs().max(1e-300)];
+                assert![(s.hi - ds.to_f64()).abs() <= f64::EPSILON * s.hi.abs().max(1e-300)];
+                assert_eq![(c.lo, s.lo), (0.0, 0.0)];
+            }
+            // And the error of a value against itself is 0
+            let [c, _] = sincos(theta);
+            assert![Reference::error(c.hi, c) <= c.lo.abs()];
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        let third = DoubleDouble::new(1.0).div_f64(3.0);
+        let one = third * DoubleDouble::new(3.0);
+        assert![(one - DoubleDouble::new(1.0)).to_f64().abs() < 1e-31];
+        assert![third.lo != 0.0];
+        // 1 + 2^-80 survives as a double-double but not as an f64
+        let tiny = 2f64.powi(-80);
+        let sum = DoubleDouble::new(1.0) + DoubleDouble::new(tiny);
+        assert_eq![(sum.hi, sum.lo, sum.to_f64()), (1.0, tiny, 1.0)];
+        assert_eq![(sum - DoubleDouble::new(1.0)).to_f64(), tiny];
+        assert_eq![Reference::error(1.0, sum), tiny];
+    }
+
+    #[test]
+    fn names() {
+        for reference in [Reference::Std, Reference::Libm, Reference::DoubleDouble] {
+            assert_eq![reference.to_string().parse::<Reference>(), Ok(reference)];
+        }
+        assert![matches![
+            "f128".parse::<Reference>(),
+            Err(CordicError::ParseError(_))
+        ]];
+    }
+}
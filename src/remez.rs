@@ -0,0 +1,416 @@
+use crate::error::CordicError;
+use crate::qformat::{QFormat, Rounding};
+use crate::quantization::{quantize_coefficients, Strategy};
+use crate::sintable::{array_rows, byte_width};
+
+// Minimax polynomials by the Remez exchange: the degree n polynomial
+// whose worst error against f on [a, b] is as small as it can be. The
+// best one's error equioscillates, touching +-E at n + 2 points with
+// alternating signs, and the exchange works towards that directly:
+//
+//   1. take n + 2 reference points, at first the Chebyshev extrema
+//   2. solve p(x_i) + (-1)^i E = f(x_i) for p's coefficients and E
+//   3. find the extremum of p - f in each stretch where its sign
+//      holds, and make those the new reference
+//   4. repeat until the extrema are all (nearly) the same size
+//
+// Chebyshev interpolation (src/chebyshev.rs) is usually within a bit of
+// this, the exchange gets the last bit back, which is often the
+// difference between fitting a word length and not. It's all in f64, so
+// errors under about 1e-15 times the size of f are lost to rounding
+//
+// The solve and the search run on t = (2x - a - b) / (b - a) in [-1, 1],
+// where powers of t stay well conditioned, and the result is expanded
+// into powers of x for plain Horner evaluation. Far from 0 (say [100,
+// 101]) that expansion cancels badly, better to shift x in the caller
+
+// Highest degree minimax() takes. Past this the powers of t are too
+// close to linearly dependent for an f64 solve
+pub const MAX_DEGREE: usize = 24;
+
+// Exchanges minimax() gives up after. Smooth functions settle in well
+// under ten
+const MAX_EXCHANGES: usize = 64;
+
+// Points on the search grid, Chebyshev spaced so they crowd towards the
+// ends where the error of a polynomial moves fastest
+const GRID: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Minimax {
+    pub interval: (f64, f64),
+    // Coefficients of 1, x, x^2, ...
+    pub coeffs: Vec<f64>,
+    // The largest |p(x) - f(x)| found on the interval
+    pub max_error: f64,
+    // The smallest and largest of the error's alternating extrema. They
+    // match when the exchange has converged, and the true minimax error
+    // lies between them (de la Vallee Poussin)
+    pub levelled_error: (f64, f64),
+    pub exchanges: usize,
+}
+
+// The degree `degree` minimax polynomial for `f` on [a, b]. f must be
+// finite across the interval
+pub fn minimax(
+    f: impl Fn(f64) -> f64,
+    a: f64,
+    b: f64,
+    degree: usize,
+) -> Result<Minimax, CordicError> {
+    if !(a.is_finite() && b.is_finite() && a < b) || degree > MAX_DEGREE {
+        return Err(CordicError::DomainError("minimax"));
+    }
+    let to_x = |t: f64| 0.5 * (a + b) + 0.5 * (b - a) * t;
+    let g = |t: f64| f(to_x(t));
+    let n = degree + 2;
+    let pi = std::f64::consts::PI;
+    // The Chebyshev extrema, with the inner ones nudged off symmetry: for
+    // an even f and a symmetric reference E comes out exactly 0, and then
+    // there's no alternation to exchange with
+    let mut reference = (0..n)
+        .map(|i| {
+            let nudge = if i == 0 || i == n - 1 { 0.0 } else { 0.05 };
+            -(pi * (i as f64 + nudge) / (n - 1) as f64).cos()
+        })
+        .collect::<Vec<_>>();
+    let grid = (0..GRID)
+        .map(|k| -(pi * k as f64 / (GRID - 1) as f64).cos())
+        .collect::<Vec<_>>();
+
+    let mut coeffs = vec![0.0; degree + 1];
+    let mut levelled = (0.0, 0.0);
+    let mut exchanges = 0;
+    while exchanges < MAX_EXCHANGES {
+        exchanges += 1;
+        coeffs = solve_reference(&reference, g)?;
+        let error = |t: f64| horner(&coeffs, t) - g(t);
+        let extrema = alternating_extrema(error, &grid);
+        if extrema.len() < n {
+            // Fewer sign changes than a minimax error has, which only
+            // happens once p - f is down at rounding noise
+            break;
+        }
+        let trimmed = trim(extrema, n, error);
+        let sizes = trimmed.iter().map(|&t| error(t).abs());
+        levelled = sizes.fold((f64::INFINITY, 0.0f64), |(low, high), size| {
+            (low.min(size), high.max(size))
+        });
+        // Level to 1e-9, or to what f64 rounding in f leaves
+        let noise = 64.0 * f64::EPSILON * trimmed.iter().map(|&t| g(t).abs()).fold(0.0, f64::max);
+        reference = trimmed;
+        if levelled.1 - levelled.0 <= 1e-9 * levelled.1 + noise {
+            break;
+        }
+    }
+    if coeffs.iter().any(|c| !c.is_finite()) {
+        return Err(CordicError::DomainError("minimax"));
+    }
+
+    let coeffs = expand(&coeffs, a, b);
+    let max_error = max_error(|x| horner(&coeffs, x) - f(x), a, b);
+    Ok(Minimax {
+        interval: (a, b),
+        coeffs,
+        max_error,
+        levelled_error: levelled,
+        exchanges,
+    })
+}
+
+impl Minimax {
+    pub fn eval(&self, x: f64) -> f64 {
+        horner(&self.coeffs, x)
+    }
+
+    // The coefficients rounded to nearest in `format`, with the worst
+    // error of the polynomial they make (still evaluated in f64, so it's
+    // the error the rounding of the coefficients alone adds). A
+    // coefficient outside the format is an Overflow, pick one with more
+    // integer bits. src/quantization.rs has the other ways to round them
+    pub fn quantize(
+        &self,
+        f: impl Fn(f64) -> f64,
+        format: QFormat,
+    ) -> Result<QuantizedPolynomial, CordicError> {
+        let nearest = Strategy::Round(Rounding::Nearest);
+        quantize_coefficients(f, &self.coeffs, self.interval, format, nearest)
+    }
+}
+
+// A Minimax with its coefficients as raw bits of `format`
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizedPolynomial {
+    pub interval: (f64, f64),
+    pub format: QFormat,
+    // Coefficients of 1, x, x^2, ...
+    pub coeffs: Vec<i64>,
+    // The largest |p(x) - f(x)| on the interval with these coefficients
+    pub max_error: f64,
+}
+
+impl QuantizedPolynomial {
+    pub fn eval(&self, x: f64) -> f64 {
+        let values = self
+            .coeffs
+            .iter()
+            .map(|&c| self.format.to_f64(c))
+            .collect::<Vec<_>>();
+        horner(&values, x)
+    }
+
+    // `pub const NAME: [iN; degree + 1] = [...];`, constant term first
+    pub fn to_rust(&self, name: &str) -> String {
+        format!(
+            "// {}\npub const {}: [i{}; {}] = [\n{}];\n",
+            self.describe(),
+            name.to_uppercase(),
+            8 * byte_width(self.format),
+            self.coeffs.len(),
+            array_rows(&self.coeffs)
+        )
+    }
+
+    // `static const intN_t name[degree + 1] = {...};`
+    pub fn to_c(&self, name: &str) -> String {
+        format!(
+            "#include <stdint.h>\n\n/* {} */\nstatic const int{}_t {}[{}] = {{\n{}}};\n",
+            self.describe(),
+            8 * byte_width(self.format),
+            name,
+            self.coeffs.len(),
+            array_rows(&self.coeffs)
+        )
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "coefficients of x^0 to x^{} in {} on [{}, {}], max error {:e}, generated by cordic-rs",
+            self.coeffs.len().saturating_sub(1),
+            self.format,
+            self.interval.0,
+            self.interval.1,
+            self.max_error
+        )
+    }
+}
+
+pub(crate) fn horner(coeffs: &[f64], x: f64) -> f64 {
+    coeffs.iter().rev().fold(0.0, |sum, &c| sum * x + c)
+}
+
+// Solves sum c_j t_i^j + (-1)^i E = g(t_i) for the c_j (E is dropped)
+// by Gaussian elimination with partial pivoting
+fn solve_reference(reference: &[f64], g: impl Fn(f64) -> f64) -> Result<Vec<f64>, CordicError> {
+    let n = reference.len();
+    let mut rows = reference
+        .iter()
+        .enumerate()
+        .map(|(i, &t)| {
+            let mut row = (0..n - 1).map(|j| t.powi(j as i32)).collect::<Vec<_>>();
+            row.push(if i % 2 == 0 { 1.0 } else { -1.0 });
+            row.push(g(t));
+            row
+        })
+        .collect::<Vec<_>>();
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|&i, &j| rows[i][column].abs().total_cmp(&rows[j][column].abs()))
+            .unwrap_or(column);
+        rows.swap(column, pivot);
+        let divisor = rows[column][column];
+        if !divisor.is_normal() {
+            return Err(CordicError::DomainError("minimax"));
+        }
+        let (upper, lower) = rows.split_at_mut(column + 1);
+        for row in lower {
+            let factor = row[column] / divisor;
+            for (entry, above) in row.iter_mut().zip(&upper[column]).skip(column) {
+                *entry -= factor * above;
+            }
+        }
+    }
+    let mut solution = vec![0.0; n];
+    for i in (0..n).rev() {
+        let known = (i + 1..n).map(|j| rows[i][j] * solution[j]).sum::<f64>();
+        solution[i] = (rows[i][n] - known) / rows[i][i];
+    }
+    solution.pop();
+    Ok(solution)
+}
+
+// The point of largest |error| in each run of the grid where error keeps
+// its sign, polished by a golden section search between the neighbouring
+// grid points
+fn alternating_extrema(error: impl Fn(f64) -> f64, grid: &[f64]) -> Vec<f64> {
+    let values = grid.iter().map(|&t| error(t)).collect::<Vec<_>>();
+    let mut extrema = Vec::new();
+    let mut best = 0;
+    for k in 1..=grid.len() {
+        let sign_changed = k == grid.len() || (values[k] > 0.0) != (values[best] > 0.0);
+        if sign_changed {
+            let low = grid[best.saturating_sub(1)];
+            let high = grid[(best + 1).min(grid.len() - 1)];
+            extrema.push(golden_max(&error, low, high, grid[best]));
+            best = k;
+        } else if values[k].abs() > values[best].abs() {
+            best = k;
+        }
+    }
+    extrema
+}
+
+// Where |error| peaks in [low, high], or `start` if nothing beats it
+fn golden_max(error: impl Fn(f64) -> f64, mut low: f64, mut high: f64, start: f64) -> f64 {
+    let ratio = 0.5 * (5f64.sqrt() - 1.0);
+    for _ in 0..40 {
+        let left = high - ratio * (high - low);
+        let right = low + ratio * (high - low);
+        if error(left).abs() > error(right).abs() {
+            high = right;
+        } else {
+            low = left;
+        }
+    }
+    let found = 0.5 * (low + high);
+    if error(found).abs() > error(start).abs() {
+        found
+    } else {
+        start
+    }
+}
+
+// Drops extrema off the ends, the smaller end first, until there are
+// `n`. Dropping from the ends keeps the signs alternating
+fn trim(mut extrema: Vec<f64>, n: usize, error: impl Fn(f64) -> f64) -> Vec<f64> {
+    while extrema.len() > n {
+        let last = extrema.len() - 1;
+        if error(extrema[0]).abs() < error(extrema[last]).abs() {
+            extrema.remove(0);
+        } else {
+            extrema.pop();
+        }
+    }
+    extrema
+}
+
+// sum c_j t^j with t = alpha x + beta, as powers of x
+fn expand(coeffs: &[f64], a: f64, b: f64) -> Vec<f64> {
+    let alpha = 2.0 / (b - a);
+    let beta = -(a + b) / (b - a);
+    let mut out = vec![0.0; coeffs.len()];
+    // Horner on polynomials: out = out * (alpha x + beta) + c_j
+    for &c in coeffs.iter().rev() {
+        let mut next = vec![0.0; coeffs.len()];
+        for (k, &o) in out.iter().enumerate() {
+            next[k] += beta * o;
+            if k + 1 < next.len() {
+                next[k + 1] += alpha * o;
+            }
+        }
+        next[0] += c;
+        out = next;
+    }
+    out
+}
+
+// Worst |error| over a dense grid of [a, b], with each peak polished
+pub(crate) fn max_error(error: impl Fn(f64) -> f64, a: f64, b: f64) -> f64 {
+    let pi = std::f64::consts::PI;
+    let grid = (0..GRID)
+        .map(|k| 0.5 * (a + b) - 0.5 * (b - a) * (pi * k as f64 / (GRID - 1) as f64).cos())
+        .collect::<Vec<_>>();
+    alternating_extrema(&error, &grid)
+        .into_iter()
+        .map(|x| error(x).abs())
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chebyshev::fit;
+    use crate::fixed::FixedPoint;
+
+    #[test]
+    fn classic_cases() {
+        // The best quadratic for |x| on [-1, 1] is x^2 + 1/8, off by 1/8
+        let abs = minimax(f64::abs, -1.0, 1.0, 2).unwrap();
+        assert![(abs.coeffs[0] - 0.125).abs() < 1e-9];
+        assert![abs.coeffs[1].abs() < 1e-9];
+        assert![(abs.coeffs[2] - 1.0).abs() < 1e-9];
+        assert![(abs.max_error - 0.125).abs() < 1e-9];
+
+        // A polynomial of the degree asked for comes back as itself,
+        // shifted interval and all
+        let cubic = |x: f64| 1.0 - 2.0 * x + 0.5 * x * x * x;
+        let fitted = minimax(cubic, 1.0, 3.0, 3).unwrap();
+        for (c, expected) in fitted.coeffs.iter().zip([1.0, -2.0, 0.0, 0.5]) {
+            assert![(c - expected).abs() < 1e-12, "{:?}", fitted.coeffs];
+        }
+        assert![fitted.max_error < 1e-13];
+    }
+
+    #[test]
+    fn beats_chebyshev() {
+        for degree in [3, 5, 8] {
+            let best = minimax(f64::exp, -1.0, 1.0, degree).unwrap();
+            let (low, high) = best.levelled_error;
+            assert![high - low <= 1e-6 * high + 1e-13, "{} {:?}", degree, best];
+            assert![(best.max_error - high).abs() <= 1e-6 * high + 1e-13];
+            assert![best.exchanges < 10];
+
+            let series = fit(|x| FixedPoint::new(x.to_f64().exp()), degree + 1);
+            let chebyshev = max_error(
+                |x| crate::chebyshev::eval(&series, FixedPoint::new(x)).to_f64() - x.exp(),
+                -1.0,
+                1.0,
+            );
+            assert![best.max_error < chebyshev, "{} {}", degree, chebyshev];
+            // but not by much
+            assert![best.max_error > 0.5 * chebyshev];
+        }
+    }
+
+    #[test]
+    fn quantized() {
+        let sin = minimax(f64::sin, 0.0, std::f64::consts::FRAC_PI_2, 5).unwrap();
+        assert![sin.max_error < 1e-5 && sin.max_error > 1e-7];
+        assert![(sin.eval(1.0) - 1f64.sin()).abs() <= sin.max_error];
+
+        let q15 = sin.quantize(f64::sin, QFormat::Q15).unwrap();
+        assert_eq![q15.coeffs.len(), 6];
+        assert![q15.max_error > sin.max_error];
+        // Rounding adds at most half a unit per coefficient, times x^j
+        let bound = (0..6)
+            .map(|j| 0.5 * QFormat::Q15.resolution() * 1.6f64.powi(j))
+            .sum::<f64>();
+        assert![q15.max_error <= sin.max_error + bound];
+        assert![(q15.eval(1.0) - 1f64.sin()).abs() <= q15.max_error];
+
+        // Q2.29 has the room for a coefficient of 3
+        let wide = minimax(|x| 3.0 * x, 0.0, 1.0, 1).unwrap();
+        assert_eq![
+            wide.quantize(|x| 3.0 * x, QFormat::Q15),
+            Err(CordicError::Overflow("quantize"))
+        ];
+        let q = QFormat::new(2, 29).unwrap();
+        assert_eq![wide.quantize(|x| 3.0 * x, q).unwrap().coeffs[1], 3 << 29];
+
+        let rust = q15.to_rust("sin_poly");
+        assert![rust.starts_with("// coefficients of x^0 to x^5 in Q15 on [0, 1.5707963267948966]")];
+        assert![rust.contains("pub const SIN_POLY: [i16; 6] = [\n")];
+        assert![q15
+            .to_c("sin_poly")
+            .contains("static const int16_t sin_poly[6] = {\n")];
+    }
+
+    #[test]
+    fn rejects() {
+        let domain = Err(CordicError::DomainError("minimax"));
+        assert_eq![minimax(f64::sin, 1.0, 1.0, 3), domain];
+        assert_eq![minimax(f64::sin, 0.0, f64::INFINITY, 3), domain];
+        assert_eq![minimax(f64::sin, 0.0, 1.0, MAX_DEGREE + 1), domain];
+        assert_eq![minimax(|_| f64::NAN, 0.0, 1.0, 3), domain];
+    }
+}
@@ -0,0 +1,161 @@
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::qformat::QFormat;
+use crate::sinc::sinc_normalized;
+use crate::window::Window;
+use std::collections::VecDeque;
+
+// Rational resampling by up / down with a polyphase FIR, all in fixed
+// point: Q15 samples in and out, Q15 coefficients and a wide
+// accumulator, the way it would run on a DSP.
+//
+// The prototype lowpass is a windowed sinc, both from this crate, cut
+// off at whichever of the input and output Nyquist frequencies is lower.
+// Conceptually the input gets up - 1 zeros stuffed between samples, is
+// filtered at that rate and then only every down-th output is kept. The
+// polyphase form skips all the work on the zeros and the dropped
+// outputs: each output only needs the one phase (every up-th tap) that
+// lines up with real input samples
+pub struct Resampler {
+    up: usize,
+    down: usize,
+    // phases[p][k] is tap p + k * up of the prototype
+    phases: Vec<Vec<i64>>,
+    // Newest input first
+    history: VecDeque<i64>,
+    // Where the next output falls, in upsampled samples past the newest
+    // input
+    phase: usize,
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Resampler {
+    // `taps` per phase, so the prototype is up * taps long; more taps
+    // give a sharper cutoff
+    pub fn new(up: usize, down: usize, taps: usize, window: Window) -> Result<Self, CordicError> {
+        if up == 0 || down == 0 || taps == 0 {
+            return Err(CordicError::DomainError("Resampler::new"));
+        }
+        let common = gcd(up, down);
+        let (up, down) = (up / common, down / common);
+
+        // Cutoff as a fraction of the upsampled rate, times 2 so sinc's
+        // zeros land in the right places. The gain of up makes up for
+        // the stuffed zeros
+        let length = up
+            .checked_mul(taps)
+            .ok_or(CordicError::DomainError("Resampler::new"))?;
+        let cutoff = 1.0 / up.max(down) as f64;
+        let center = (length as f64 - 1.0) / 2.0;
+        let shape = window.generate(length);
+        let prototype = shape.iter().enumerate().map(|(n, &w)| {
+            let x = FixedPoint::new(cutoff * (n as f64 - center));
+            FixedPoint::new(cutoff * up as f64) * sinc_normalized(x) * w
+        });
+
+        let mut phases = vec![Vec::with_capacity(taps); up];
+        for (n, tap) in prototype.enumerate() {
+            phases[n % up].push(QFormat::Q15.fixed_to_bits(tap));
+        }
+        Ok(Self {
+            up,
+            down,
+            phases,
+            history: VecDeque::from(vec![0; taps]),
+            phase: 0,
+        })
+    }
+
+    // The ratio actually used, after reducing up / down
+    pub fn ratio(&self) -> (usize, usize) {
+        (self.up, self.down)
+    }
+
+    // Takes the next block of input and returns whatever output it
+    // completes. Blocks can be any size; the filter state carries over
+    pub fn process(&mut self, input: &[i16]) -> Vec<i16> {
+        let mut output = Vec::with_capacity(input.len() * self.up / self.down + 1);
+        for &x in input {
+            self.history.pop_back();
+            self.history.push_front(x as i64);
+            while self.phase < self.up {
+                let acc = self.phases[self.phase]
+                    .iter()
+                    .zip(&self.history)
+                    .map(|(h, x)| h * x)
+                    .sum::<i64>();
+                let y = (acc + (1 << 14)) >> 15;
+                output.push(y.clamp(i16::MIN as i64, i16::MAX as i64) as i16);
+                self.phase += self.down;
+            }
+            self.phase -= self.up;
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(freq: f64, samples: usize) -> Vec<i16> {
+        (0..samples)
+            .map(|i| {
+                (16000.0 * (std::f64::consts::PI * 2.0 * freq * i as f64).sin()).round() as i16
+            })
+            .collect()
+    }
+
+    #[test]
+    fn three_halves() {
+        // 0.02 cycles per input sample, up 3 and down 2
+        let freq = 0.02;
+        let taps = 16;
+        let mut resampler = Resampler::new(3, 2, taps, Window::Blackman).unwrap();
+        let input = tone(freq, 600);
+        let output = resampler.process(&input);
+        assert_eq![output.len(), 900];
+
+        // Output n is at upsampled time 2n, delayed by half the filter
+        let delay = (3 * taps) as f64 / 2.0 - 0.5;
+        for (n, &y) in output.iter().enumerate().skip(50) {
+            let t = (2.0 * n as f64 - delay) / 3.0;
+            let expected = 16000.0 * (std::f64::consts::PI * 2.0 * freq * t).sin();
+            assert![
+                (y as f64 - expected).abs() < 40.0,
+                "{} {} {}",
+                n,
+                y,
+                expected
+            ];
+        }
+    }
+
+    #[test]
+    fn blocks_and_dc() {
+        // Same output whatever the block size, and DC passes at unity
+        let input = vec![10000i16; 300];
+        let mut whole = Resampler::new(2, 3, 12, Window::Hann).unwrap();
+        let mut pieces = Resampler::new(4, 6, 12, Window::Hann).unwrap();
+        assert_eq![pieces.ratio(), (2, 3)];
+        let expected = whole.process(&input);
+        let chunked = input
+            .chunks(7)
+            .flat_map(|chunk| pieces.process(chunk))
+            .collect::<Vec<i16>>();
+        assert_eq![chunked, expected];
+        assert_eq![expected.len(), 200];
+        assert![expected[100..]
+            .iter()
+            .all(|&y| (y as i64 - 10000).abs() < 20)];
+
+        assert![Resampler::new(0, 1, 8, Window::Hann).is_err()];
+    }
+}
@@ -0,0 +1,221 @@
+use crate::algorithm::{Cost, TrigAlgorithm, REDUCTION_ADDS};
+use crate::cordic::{checked_iters, rotate_scheduled, MAX_ITERS};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::math;
+
+// Which micro-rotations to run, as the shift amounts i of each
+// atan(2^-i) step, in order. The usual schedule is 0, 1, ..., iters - 1,
+// but when the angles an application actually evaluates are known up
+// front, some of those steps never help (a workload of small angles has
+// no use for the 45 degree one) and prune() drops them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    shifts: Vec<usize>,
+}
+
+// Shifts past the precision of FixedPoint are rejected, the same as
+// iteration counts past MAX_ITERS would be clamped. CordicConfig's
+// custom schedules go through this too
+pub(crate) fn check_shifts(shifts: &[usize]) -> Result<(), CordicError> {
+    match shifts.iter().find(|&&shift| shift >= MAX_ITERS) {
+        Some(&shift) => Err(CordicError::IterationOutOfRange(shift)),
+        None => Ok(()),
+    }
+}
+
+// How much the micro-rotation by atan(2^-shift) stretches the vector,
+// sqrt(1 + 2^-2 shift)
+pub(crate) fn stretch(shift: usize) -> f64 {
+    math::sqrt(1.0 + math::powi(2.0, -2 * shift as i32))
+}
+
+// Product of 1 / stretch() over the shifts actually run, since a skipped
+// step doesn't stretch the vector either
+pub(crate) fn schedule_gain(shifts: impl IntoIterator<Item = usize>) -> FixedPoint {
+    let gain = shifts
+        .into_iter()
+        .fold(1.0, |gain, shift| gain / stretch(shift));
+    FixedPoint::new(gain)
+}
+
+impl Schedule {
+    pub fn new(shifts: Vec<usize>) -> Result<Self, CordicError> {
+        check_shifts(&shifts)?;
+        Ok(Self { shifts })
+    }
+
+    // The usual schedule for `iters` iterations
+    pub fn full(iters: usize) -> Result<Self, CordicError> {
+        Self::new((0..checked_iters(iters)?).collect())
+    }
+
+    pub fn shifts(&self) -> &[usize] {
+        &self.shifts
+    }
+
+    pub fn gain(&self) -> FixedPoint {
+        schedule_gain(self.shifts.iter().copied())
+    }
+
+    // [cos theta, sin theta] using only this schedule's micro-rotations,
+    // on the float backend with post gain compensation
+    pub fn sincos(&self, theta: FixedPoint) -> [FixedPoint; 2] {
+        let one = [FixedPoint::new(1.0), FixedPoint::new(0.0)];
+        let ([x, y], _) = rotate_scheduled(theta, one, self.shifts.iter().copied());
+        let gain = self.gain();
+        [x * gain, y * gain]
+    }
+
+    // Worst absolute error of either output against f64::cos and f64::sin
+    pub fn worst_error(&self, thetas: &[f64]) -> f64 {
+        thetas
+            .iter()
+            .map(|&theta| {
+                let [cos, sin] = self.sincos(FixedPoint::new(theta));
+                let cos_error = (cos.to_f64() - theta.cos()).abs();
+                let sin_error = (sin.to_f64() - theta.sin()).abs();
+                cos_error.max(sin_error)
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
+// Starting from the full schedule for `iters` iterations, drops every
+// micro-rotation the angles in `thetas` can do without, returning the
+// pruned schedule and its worst error over `thetas`.
+//
+// A step can go as long as the worst error stays within `tolerance`, or
+// with None, as long as the result still has as many correct bits as
+// the full schedule (rounded down, so there's some slack for the path
+// of micro-rotations changing when a step goes). Steps are tried
+// largest angle first, since those are the ones small-angle workloads
+// never need
+pub fn prune(
+    thetas: &[f64],
+    iters: usize,
+    tolerance: Option<f64>,
+) -> Result<(Schedule, f64), CordicError> {
+    if thetas.is_empty() {
+        return Err(CordicError::DomainError("prune"));
+    }
+    if !thetas.iter().all(|theta| theta.is_finite()) {
+        return Err(CordicError::InvalidAngle("prune"));
+    }
+
+    let mut schedule = Schedule::full(iters)?;
+    let mut worst = schedule.worst_error(thetas);
+    let tolerance = tolerance.unwrap_or_else(|| math::powi(2.0, worst.log2().floor() as i32 + 1));
+
+    let mut i = 0;
+    while i < schedule.shifts.len() {
+        let mut candidate = schedule.clone();
+        candidate.shifts.remove(i);
+        let error = candidate.worst_error(thetas);
+        if error <= tolerance {
+            schedule = candidate;
+            worst = error;
+        } else {
+            i += 1;
+        }
+    }
+    Ok((schedule, worst))
+}
+
+impl TrigAlgorithm for Schedule {
+    fn name(&self) -> String {
+        format!("cordic-schedule-{}", self.shifts.len())
+    }
+
+    fn sincos(&self, theta: FixedPoint) -> Result<[FixedPoint; 2], CordicError> {
+        Ok(Schedule::sincos(self, theta))
+    }
+
+    // The same as CordicConfig with post compensation, but only for the
+    // steps that are left
+    fn cost(&self) -> Option<Cost> {
+        Some(Cost {
+            adds: REDUCTION_ADDS + 3 * self.shifts.len(),
+            shifts: 2 * self.shifts.len(),
+            multiplies: 2,
+            divides: 0,
+            table_entries: self.shifts.len() + 1,
+            toggles: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cordic::{Backend, CordicConfig};
+
+    #[test]
+    fn full_matches_float_backend() {
+        let schedule = Schedule::full(32).unwrap();
+        let config = CordicConfig::new(32).backend(Backend::Float);
+        for i in -628..=628 {
+            let theta = FixedPoint::new(i as f64 / 100.0);
+            let ret = config.rotate(theta).unwrap();
+            let [cos, sin] = schedule.sincos(theta);
+            assert![(cos - ret.x).abs() < FixedPoint::new(1e-15)];
+            assert![(sin - ret.y).abs() < FixedPoint::new(1e-15)];
+        }
+    }
+
+    // The same shifts through CordicConfig::schedule() run the same code
+    #[test]
+    fn matches_custom_config() {
+        let shifts = vec![0, 1, 1, 3, 4, 4, 7, 20];
+        let schedule = Schedule::new(shifts.clone()).unwrap();
+        let config = CordicConfig::new(1)
+            .backend(Backend::Float)
+            .schedule(&shifts);
+        for i in -628..=628 {
+            let theta = FixedPoint::new(i as f64 / 100.0);
+            let ret = config.rotate(theta).unwrap();
+            assert_eq![schedule.sincos(theta), [ret.x, ret.y]];
+            assert_eq![schedule.gain(), ret.gain];
+        }
+    }
+
+    #[test]
+    fn small_angles_drop_large_steps() {
+        let thetas = (-50..=50).map(|i| i as f64 / 1000.0).collect::<Vec<f64>>();
+        let full = Schedule::full(24).unwrap().worst_error(&thetas);
+        let (schedule, worst) = prune(&thetas, 24, None).unwrap();
+
+        // Everything under 0.05 rad is reachable from atan(2^-5) down,
+        // and the tolerance never allows losing a whole bit
+        assert![worst <= 2.0 * full];
+        assert![schedule.shifts()[0] >= 4];
+        assert![schedule.shifts().len() < 24];
+        assert_eq![worst, schedule.worst_error(&thetas)];
+    }
+
+    #[test]
+    fn full_range_keeps_large_steps() {
+        let thetas = (-157..=157).map(|i| i as f64 / 100.0).collect::<Vec<f64>>();
+        let (schedule, _) = prune(&thetas, 24, None).unwrap();
+        assert_eq![schedule.shifts()[..3], [0, 1, 2]];
+    }
+
+    #[test]
+    fn tolerance() {
+        let thetas = [0.1, 0.2, 0.3];
+        let (schedule, worst) = prune(&thetas, 40, Some(1e-6)).unwrap();
+        assert![worst <= 1e-6];
+        assert![schedule.shifts().len() < 40];
+    }
+
+    #[test]
+    fn invalid() {
+        assert![prune(&[], 24, None).is_err()];
+        assert_eq![
+            prune(&[f64::NAN], 24, None),
+            Err(CordicError::InvalidAngle("prune"))
+        ];
+        assert![prune(&[0.1], 0, None).is_err()];
+        assert![Schedule::new(vec![0, MAX_ITERS]).is_err()];
+    }
+}
@@ -0,0 +1,231 @@
+// A suite that checks the library on whatever it's running on, for
+// cross-compiled builds that have to be validated on the device itself
+// rather than trusted from the host's tests. It looks for the things a
+// different compiler, libm or CPU can actually break:
+//   golden vectors, exact Q60 results that every target must reproduce
+//     bit for bit, plus f64 results to within their error
+//   identities, sin^2 + cos^2 = 1, symmetry and angle sums
+//   determinism, the same call giving the same bits every time and the
+//     backends agreeing with each other
+//   table fingerprints, the runtime tables matching the pinned ones
+// Only the Q60 checks are there without `float`
+
+use crate::error::CordicError;
+use crate::integer::{sincos_q60, sincos_q60_fixed, Q60, TWO_PI};
+
+#[cfg(feature = "float")]
+use crate::cordic::{cordic, Backend, CordicConfig, MAX_ITERS};
+#[cfg(feature = "float")]
+use crate::fixed::FixedPoint;
+#[cfg(feature = "float")]
+use crate::identity::sin_cos_sum;
+#[cfg(feature = "float")]
+use crate::manifest::{table_fingerprint, FLOAT_FINGERPRINT, INTEGER_FINGERPRINT};
+
+// One check's outcome. `detail` says what was wrong when it fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl Check {
+    // Each check gives Some(what's wrong) or None, or an error from the
+    // library, which fails it too
+    fn new(name: &'static str, result: Result<Option<String>, CordicError>) -> Self {
+        let failure = match result {
+            Ok(failure) => failure,
+            Err(e) => Some(e.to_string()),
+        };
+        Check {
+            name,
+            passed: failure.is_none(),
+            detail: failure.unwrap_or_default(),
+        }
+    }
+}
+
+// Runs every check, in a fixed order. None of them panic, a failure
+// (including an error from the library) is just a Check with `passed`
+// false
+pub fn selftest() -> Vec<Check> {
+    let checks = vec![
+        Check::new("q60 golden vectors", q60_golden()),
+        Check::new("q60 identities", q60_identities()),
+        Check::new("q60 determinism", q60_determinism()),
+    ];
+    #[cfg(feature = "float")]
+    let checks = [
+        checks,
+        vec![
+            Check::new("table fingerprints", fingerprints()),
+            Check::new("f64 golden vectors", f64_golden()),
+            Check::new("f64 identities", f64_identities()),
+            Check::new("backend agreement", backend_agreement()),
+        ],
+    ]
+    .concat();
+    checks
+}
+
+// Q60 angles and the bits sincos_q60() gives for them at 60 iterations:
+// 0, 1, -2.5, pi/6 and the largest Q60, which needs reducing
+const Q60_GOLDEN: [(i64, [i64; 2]); 5] = [
+    (0, [0x1000_0000_0000_000d, 0x3]),
+    (1 << 60, [0x08a5_1407_da83_45c8, 0x0d76_aa47_8486_7706]),
+    (
+        -(5 << 59),
+        [
+            0xf32e_8408_3d3a_416d_u64 as i64,
+            0xf66c_a879_181a_a7be_u64 as i64,
+        ],
+    ),
+    (TWO_PI / 12, [0x0ddb_3d74_2c26_5545, 0x0800_0000_0000_0004]),
+    (
+        i64::MAX,
+        [0xfdac_0828_139a_0d96_u64 as i64, 0x0fd4_6950_1467_bd74],
+    ),
+];
+
+// Every angle the Q60 checks sweep, a little over a turn each way
+fn q60_sweep() -> impl Iterator<Item = Q60> {
+    (-60..=60).map(|i| Q60::from_bits(i * (TWO_PI / 50)))
+}
+
+fn q60_golden() -> Result<Option<String>, CordicError> {
+    for &(theta, expected) in &Q60_GOLDEN {
+        let result = sincos_q60(Q60::from_bits(theta), 60)?.map(Q60::to_bits);
+        if result != expected {
+            return Ok(Some(format!(
+                "theta bits {:#x}: got {:#x?}, expected {:#x?}",
+                theta, result, expected
+            )));
+        }
+    }
+    Ok(None)
+}
+
+// cos^2 + sin^2 within 2^-52 of 1, and cos even and sin odd to within
+// 64 units. Shifts round negative values down, so -theta takes a
+// slightly different path and lands a couple of dozen units away
+fn q60_identities() -> Result<Option<String>, CordicError> {
+    let one = 1i128 << Q60::FRAC_BITS;
+    for theta in q60_sweep() {
+        let [cos, sin] = sincos_q60(theta, 60)?.map(|x| x.to_bits() as i128);
+        let norm = (cos * cos + sin * sin) >> Q60::FRAC_BITS;
+        if (norm - one).abs() > one >> 52 {
+            return Ok(Some(format!("cos^2 + sin^2 off at {}", theta)));
+        }
+        let negated = Q60::from_bits(-theta.to_bits());
+        let [neg_cos, neg_sin] = sincos_q60(negated, 60)?.map(|x| x.to_bits() as i128);
+        if (neg_cos - cos).abs() > 64 || (neg_sin + sin).abs() > 64 {
+            return Ok(Some(format!("not symmetric at {}", theta)));
+        }
+    }
+    Ok(None)
+}
+
+// Repeated calls and the const generic version agree to the bit
+fn q60_determinism() -> Result<Option<String>, CordicError> {
+    for theta in q60_sweep() {
+        let first = sincos_q60(theta, 60)?;
+        if sincos_q60(theta, 60)? != first || sincos_q60_fixed::<60>(theta) != first {
+            return Ok(Some(format!("different results at {}", theta)));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(feature = "float")]
+fn fingerprints() -> Result<Option<String>, CordicError> {
+    for (backend, expected) in [
+        (Backend::Integer, INTEGER_FINGERPRINT),
+        (Backend::Float, FLOAT_FINGERPRINT),
+    ] {
+        let fingerprint = table_fingerprint(backend, MAX_ITERS)?;
+        if fingerprint != expected {
+            // For the float tables this means the target's atan rounds
+            // differently, see manifest.rs
+            return Ok(Some(format!(
+                "{:?} tables are {:016x}, expected {:016x}",
+                backend, fingerprint, expected
+            )));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(feature = "float")]
+fn f64_golden() -> Result<Option<String>, CordicError> {
+    let golden = [
+        (0.0, 1.0, 0.0),
+        (1.0, 0.540_302_305_868_139_8, 0.841_470_984_807_896_5),
+        (std::f64::consts::FRAC_PI_6, 0.866_025_403_784_438_6, 0.5),
+        (-2.5, -0.801_143_615_546_934_2, -0.598_472_144_103_956_5),
+    ];
+    for &(theta, cos, sin) in &golden {
+        let [got_cos, got_sin] = cordic(FixedPoint::new(theta), MAX_ITERS)?.map(|x| x.to_f64());
+        if (got_cos - cos).abs() > 1e-15 || (got_sin - sin).abs() > 1e-15 {
+            return Ok(Some(format!(
+                "theta {}: got [{}, {}], expected [{}, {}]",
+                theta, got_cos, got_sin, cos, sin
+            )));
+        }
+    }
+    Ok(None)
+}
+
+// The angle sum identity against a direct rotation
+#[cfg(feature = "float")]
+fn f64_identities() -> Result<Option<String>, CordicError> {
+    for i in -20..=20 {
+        let (a, b) = (i as f64 * 0.3, 0.7 - i as f64 * 0.11);
+        let pass = |theta: f64| cordic(FixedPoint::new(theta), MAX_ITERS);
+        let sum = sin_cos_sum(pass(a)?, pass(b)?);
+        let direct = pass(a + b)?;
+        if (0..2).any(|k| (sum[k] - direct[k]).abs() > FixedPoint::new(1e-14)) {
+            return Ok(Some(format!("sum identity off at {} + {}", a, b)));
+        }
+    }
+    Ok(None)
+}
+
+// Both backends to within the f64 backend's rounding
+#[cfg(feature = "float")]
+fn backend_agreement() -> Result<Option<String>, CordicError> {
+    let run = |backend, theta| {
+        CordicConfig::new(MAX_ITERS)
+            .backend(backend)
+            .rotate(FixedPoint::new(theta))
+            .map(|ret| [ret.x.to_f64(), ret.y.to_f64()])
+    };
+    for i in -64..=64 {
+        let theta = i as f64 / 10.0;
+        let integer = run(Backend::Integer, theta)?;
+        let float = run(Backend::Float, theta)?;
+        if (0..2).any(|k| (integer[k] - float[k]).abs() > 1e-14) {
+            return Ok(Some(format!(
+                "backends differ at {}: {:?} and {:?}",
+                theta, integer, float
+            )));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_here() {
+        let checks = selftest();
+        #[cfg(feature = "float")]
+        assert_eq![checks.len(), 7];
+        for check in &checks {
+            assert![check.passed, "{}: {}", check.name, check.detail];
+            assert![check.detail.is_empty()];
+        }
+    }
+}
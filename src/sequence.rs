@@ -0,0 +1,144 @@
+use crate::cordic::{checked_iters, gain};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::integer;
+
+// The directions CORDIC takes for one angle, worked out ahead of time.
+// When the angle is a constant (an FFT twiddle factor, say) the
+// comparisons on z are the same every call, so they can be done once and
+// stored as a bit per iteration, and rotating a vector is then nothing
+// but shifts and adds:
+//
+//   let twiddle = precompute_sigmas(theta, 24)?;
+//   let rotated = twiddle.apply(v);
+//
+// The directions are the integer backend's, so apply() gives the same
+// bits as CordicConfig::new(iters).rotate() would
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigmaSequence {
+    // Bit i is set where iteration i goes counterclockwise
+    bits: u64,
+    iters: usize,
+    // Whether the quadrant fold rotates by pi first
+    negate: bool,
+}
+
+// Iterations past MAX_ITERS are clamped as usual, so the sequence always
+// fits in the 64 bits
+pub fn precompute_sigmas(theta: FixedPoint, iters: usize) -> Result<SigmaSequence, CordicError> {
+    let iters = checked_iters(iters)?;
+    let (bits, negate) =
+        integer::directions(theta, iters).ok_or(CordicError::InvalidAngle("precompute_sigmas"))?;
+    Ok(SigmaSequence {
+        bits,
+        iters,
+        negate,
+    })
+}
+
+impl SigmaSequence {
+    // A sequence stored earlier, from bits(), iters() and negate()
+    pub fn from_bits(bits: u64, iters: usize, negate: bool) -> Result<Self, CordicError> {
+        let iters = checked_iters(iters)?;
+        Ok(Self {
+            bits: bits & (u64::MAX >> (64 - iters)),
+            iters,
+            negate,
+        })
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    pub fn iters(&self) -> usize {
+        self.iters
+    }
+
+    pub fn negate(&self) -> bool {
+        self.negate
+    }
+
+    // Rotates `v` by the stored directions and undoes the gain
+    pub fn apply(&self, v: [FixedPoint; 2]) -> [FixedPoint; 2] {
+        let [x, y] = self.apply_raw(v);
+        // The clamped iteration count is always valid
+        let k = gain(self.iters).unwrap_or(FixedPoint::new(1.0));
+        [x * k, y * k]
+    }
+
+    // The same without the multiply, so the vector comes back 1/K too
+    // long, for when the gain is folded in elsewhere
+    pub fn apply_raw(&self, v: [FixedPoint; 2]) -> [FixedPoint; 2] {
+        integer::replay(v, self.bits, self.iters, self.negate)
+    }
+
+    // apply() to every point in place
+    pub fn apply_all(&self, points: &mut [(FixedPoint, FixedPoint)]) {
+        for point in points.iter_mut() {
+            let [x, y] = self.apply([point.0, point.1]);
+            *point = (x, y);
+        }
+    }
+}
+
+// Rotates a whole point cloud by theta, working out the directions once
+// and then spending only shifts and adds (and the gain multiply) on each
+// point, as fixed point graphics or lidar preprocessing would
+pub fn rotate_points(
+    points: &mut [(FixedPoint, FixedPoint)],
+    theta: FixedPoint,
+    iters: usize,
+) -> Result<(), CordicError> {
+    precompute_sigmas(theta, iters)?.apply_all(points);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cordic::CordicConfig;
+
+    #[test]
+    fn matches_rotate() {
+        let v = [FixedPoint::new(0.75), FixedPoint::new(-2.5)];
+        for i in -40..=40 {
+            let theta = FixedPoint::new(i as f64 / 10.0);
+            for &iters in &[1, 16, 53, 1000] {
+                let sigmas = precompute_sigmas(theta, iters).unwrap();
+                let ret = CordicConfig::new(iters).compose(v, &[theta]).unwrap();
+                assert_eq![sigmas.apply(v), [ret.x, ret.y]];
+            }
+        }
+    }
+
+    #[test]
+    fn stored() {
+        let sigmas = precompute_sigmas(FixedPoint::new(2.0), 24).unwrap();
+        assert![sigmas.negate()];
+        let stored = SigmaSequence::from_bits(sigmas.bits(), 24, true).unwrap();
+        assert_eq![stored, sigmas];
+        assert![SigmaSequence::from_bits(0, 0, false).is_err()];
+        assert_eq![
+            precompute_sigmas(FixedPoint::new(f64::NAN), 24),
+            Err(CordicError::InvalidAngle("precompute_sigmas"))
+        ];
+    }
+
+    #[test]
+    fn point_cloud() {
+        let theta = FixedPoint::new(0.4);
+        let mut points = (0..1000)
+            .map(|i| (FixedPoint::new(i as f64), FixedPoint::new(-0.5 * i as f64)))
+            .collect::<Vec<_>>();
+        let original = points.clone();
+        rotate_points(&mut points, theta, 32).unwrap();
+
+        let config = CordicConfig::new(32);
+        for (rotated, &(x, y)) in points.iter().zip(&original) {
+            let ret = config.compose([x, y], &[theta]).unwrap();
+            assert_eq![*rotated, (ret.x, ret.y)];
+        }
+        assert![rotate_points(&mut points, theta, 0).is_err()];
+    }
+}
@@ -0,0 +1,168 @@
+// A tiny JSON-over-HTTP front end so a web page can drive the model
+// without any glue, started with
+//
+//   cargo run --features serve -- serve --port 8080
+//
+// Endpoints:
+//   GET /sincos?theta=0.5&iters=20  {"theta":0.5,"iters":20,"cos":...,"sin":...}
+//   GET /table?iters=16&frac_bits=30  {"frac_bits":30,"angles":[...],"gains":[...]}
+//
+// It's std::net only, handles one connection at a time and closes it
+// after each response, which is plenty for a demo on localhost and
+// nowhere near something to put on the internet
+
+use crate::cordic::{cordic, MAX_ITERS};
+use crate::fixed::FixedPoint;
+use crate::table::Tables;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+// Serves requests on 127.0.0.1:port until the listener fails
+pub fn run(port: u16) -> std::io::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    for stream in listener.incoming() {
+        // A client hanging up mid-request shouldn't take the server down
+        if let Err(e) = stream.and_then(respond) {
+            eprintln!("warning: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers, nothing in them matters here
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let (status, body) = handle(&request_line);
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+// Maps a request line like "GET /sincos?theta=1 HTTP/1.1" to a status
+// line and JSON body, kept apart from the socket so it can be tested
+pub fn handle(request_line: &str) -> (&'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next(), parts.next().unwrap_or(""));
+    if method != Some("GET") {
+        return ("405 Method Not Allowed", error("only GET is supported"));
+    }
+    let (path, query) = match target.find('?') {
+        Some(i) => (&target[..i], &target[i + 1..]),
+        None => (target, ""),
+    };
+    let result = match path {
+        "/sincos" => sincos(query),
+        "/table" => table(query),
+        _ => return ("404 Not Found", error("unknown endpoint")),
+    };
+    match result {
+        Ok(body) => ("200 OK", body),
+        Err(message) => ("400 Bad Request", error(&message)),
+    }
+}
+
+fn sincos(query: &str) -> Result<String, String> {
+    let theta = param(query, "theta")?.unwrap_or(0.0);
+    let iters = param(query, "iters")?.unwrap_or(MAX_ITERS);
+    let [cos, sin] = cordic(FixedPoint::new(theta), iters).map_err(|e| e.to_string())?;
+    Ok(format!(
+        "{{\"theta\":{},\"iters\":{},\"cos\":{},\"sin\":{}}}",
+        number(theta),
+        iters,
+        number(cos.to_f64()),
+        number(sin.to_f64())
+    ))
+}
+
+fn table(query: &str) -> Result<String, String> {
+    let iters = param(query, "iters")?.unwrap_or(MAX_ITERS);
+    let frac_bits = param(query, "frac_bits")?.unwrap_or(30);
+    let tables = Tables::generate(iters, frac_bits).map_err(|e| e.to_string())?;
+    let list = |values: &[i32]| {
+        values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    };
+    Ok(format!(
+        "{{\"frac_bits\":{},\"angles\":[{}],\"gains\":[{}]}}",
+        frac_bits,
+        list(&tables.angles),
+        list(&tables.gains)
+    ))
+}
+
+// Looks up `name=value` in the query string. Values are only ever
+// numbers, so there's no percent-decoding
+fn param<T: std::str::FromStr>(query: &str, name: &str) -> Result<Option<T>, String> {
+    for pair in query.split('&') {
+        let mut kv = pair.splitn(2, '=');
+        if kv.next() == Some(name) {
+            let value = kv.next().unwrap_or("");
+            return value
+                .parse::<T>()
+                .map(Some)
+                .map_err(|_| format!("bad value for {}: {}", name, value));
+        }
+    }
+    Ok(None)
+}
+
+// JSON has no NaN or infinity
+fn number(x: f64) -> String {
+    if x.is_finite() {
+        format!("{:?}", x)
+    } else {
+        "null".to_string()
+    }
+}
+
+fn error(message: &str) -> String {
+    format!("{{\"error\":\"{}\"}}", message.replace('"', "'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sincos_endpoint() {
+        let (status, body) = handle("GET /sincos?theta=0&iters=20 HTTP/1.1\r\n");
+        assert_eq![status, "200 OK"];
+        assert![body.starts_with("{\"theta\":0.0,\"iters\":20,\"cos\":0.99")];
+    }
+
+    #[test]
+    fn table_endpoint() {
+        let (status, body) = handle("GET /table?iters=2&frac_bits=4 HTTP/1.1\r\n");
+        assert_eq![status, "200 OK"];
+        assert_eq![
+            body,
+            "{\"frac_bits\":4,\"angles\":[13,7],\"gains\":[11,10]}"
+        ];
+    }
+
+    #[test]
+    fn bad_requests() {
+        assert_eq![
+            handle("GET /sincos?theta=abc HTTP/1.1").0,
+            "400 Bad Request"
+        ];
+        assert_eq![handle("GET /sincos?iters=0 HTTP/1.1").0, "400 Bad Request"];
+        assert_eq![handle("GET /nope HTTP/1.1").0, "404 Not Found"];
+        assert_eq![handle("POST /sincos HTTP/1.1").0, "405 Method Not Allowed"];
+    }
+}
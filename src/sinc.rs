@@ -0,0 +1,73 @@
+use crate::cordic::sincos;
+use crate::fixed::FixedPoint;
+
+// Below this, sin(x) / x is computed from its Taylor series instead.
+// sin(x) itself is only accurate to an absolute error, so dividing by a
+// tiny x blows that up into a large relative error (and x == 0 is 0/0).
+// The first term the series drops is x^6 / 5040, which is below 2^-54 for
+// anything under this threshold
+const SERIES_THRESHOLD: f64 = 1.0 / 128.0;
+
+// sin(x) / x, with the removable singularity at 0 filled in with 1
+pub fn sinc(x: FixedPoint) -> FixedPoint {
+    if x.abs() < FixedPoint::new(SERIES_THRESHOLD) {
+        // 1 - x^2 / 6 + x^4 / 120
+        let x2 = x * x;
+        FixedPoint::new(1.0) - x2 / FixedPoint::new(6.0) + x2 * x2 / FixedPoint::new(120.0)
+    } else {
+        let [_, sin] = sincos(x);
+        sin / x
+    }
+}
+
+// sin(pi x) / (pi x), the version used in signal processing (and by
+// numpy), which is zero at every nonzero integer
+pub fn sinc_normalized(x: FixedPoint) -> FixedPoint {
+    sinc(FixedPoint::new(std::f64::consts::PI) * x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_enough(a: FixedPoint, b: FixedPoint) -> bool {
+        let diff = a - b;
+        diff < FixedPoint::new(0.01) && -diff < FixedPoint::new(0.01)
+    }
+
+    #[test]
+    fn basic() {
+        for i in -1000..=1000 {
+            let x = i as f64 / 100.0;
+            let expected = if i == 0 { 1.0 } else { x.sin() / x };
+
+            assert![close_enough(
+                sinc(FixedPoint::new(x)),
+                FixedPoint::new(expected)
+            )];
+        }
+    }
+
+    #[test]
+    fn near_zero() {
+        // Right around the threshold both sides have to agree closely,
+        // otherwise there's a visible step in the function
+        let below = sinc(FixedPoint::new(SERIES_THRESHOLD * 0.999_999));
+        let above = sinc(FixedPoint::new(SERIES_THRESHOLD * 1.000_001));
+        assert![(below - above).abs() < FixedPoint::new(1e-9)];
+
+        assert![sinc(FixedPoint::new(0.0)) == FixedPoint::new(1.0)];
+        assert![sinc(FixedPoint::new(-1e-300)) == FixedPoint::new(1.0)];
+    }
+
+    #[test]
+    fn normalized_zeros() {
+        for n in 1..=10 {
+            let zero = sinc_normalized(FixedPoint::new(n as f64));
+            assert![zero.abs() < FixedPoint::new(1e-12)];
+            let zero = sinc_normalized(FixedPoint::new(-n as f64));
+            assert![zero.abs() < FixedPoint::new(1e-12)];
+        }
+        assert![sinc_normalized(FixedPoint::new(0.0)) == FixedPoint::new(1.0)];
+    }
+}
@@ -0,0 +1,473 @@
+// Sine lookup tables, for when what's wanted in the end is a table in
+// ROM rather than an algorithm. Entry i is sin(2 pi i / entries), worked
+// out on the Q60 integer backend and rounded once into the requested Q
+// format, and exports to a binary blob, a Rust const or a C array.
+//
+// The binary layout is, in the byte order given by the endianness flag:
+//
+//   offset  size  field
+//   0       4     magic, "SINT"
+//   4       1     format version (1)
+//   5       1     endianness (0 = little, 1 = big)
+//   6       1     integer bits of the Q format
+//   7       1     fractional bits of the Q format
+//   8       4     entry count n
+//   12      wn    entries, each w = 1, 2, 4 or 8 bytes, whichever is the
+//                 smallest to hold the Q format
+//   12+wn   4     CRC-32 (IEEE) of everything before it
+
+use crate::algorithm::{Cost, TrigAlgorithm, REDUCTION_ADDS};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::integer::{sincos_q60, Q60, TWO_PI};
+use crate::math;
+use crate::qformat::{convert, Overflow, QFormat, Rounding};
+use crate::table::{crc32, fnv1a, Endian};
+
+const MAGIC: &[u8; 4] = b"SINT";
+const VERSION: u8 = 1;
+
+// Error of the Q60 kernel itself at 60 iterations, well below anything a
+// table entry can resolve, but counted so max_error() stays a bound
+const KERNEL_ERROR: f64 = 1.0 / 4_503_599_627_370_496.0;
+
+// How to read between the entries of a SinTable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    // The closest entry
+    Nearest,
+    // A straight line between the entries either side
+    Linear,
+    // The parabola through the closest entry and its two neighbours
+    Quadratic,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SinTable {
+    format: QFormat,
+    values: Vec<i64>,
+    // Largest |entry - Q60 sine| in Q60 units
+    rounding: i64,
+}
+
+pub fn generate_table(entries: usize, format: QFormat) -> Result<SinTable, CordicError> {
+    if entries == 0 || entries > u32::MAX as usize {
+        return Err(CordicError::InvalidTable(
+            "entry count must be in 1..=2^32-1",
+        ));
+    }
+    if format.frac_bits() > Q60::FRAC_BITS {
+        return Err(CordicError::InvalidTable(
+            "fractional bits must be at most 60",
+        ));
+    }
+
+    let q60 = QFormat::new(3, Q60::FRAC_BITS)?;
+    let drop = Q60::FRAC_BITS - format.frac_bits();
+    let mut rounding = 0;
+    let values = (0..entries)
+        .map(|i| {
+            // 2 pi i / entries, rounded to Q60
+            let theta = (TWO_PI as i128 * i as i128 * 2 + entries as i128) / (2 * entries as i128);
+            let [_, sin] = sincos_q60(Q60::from_bits(theta as i64), 60)?;
+            let sin = sin.to_bits();
+            let value = convert(sin, q60, format, Rounding::Nearest, Overflow::Saturate)?;
+            rounding = rounding.max(((value << drop) - sin).abs());
+            Ok(value)
+        })
+        .collect::<Result<Vec<i64>, CordicError>>()?;
+
+    Ok(SinTable {
+        format,
+        values,
+        rounding,
+    })
+}
+
+impl SinTable {
+    pub fn format(&self) -> QFormat {
+        self.format
+    }
+
+    pub fn values(&self) -> &[i64] {
+        &self.values
+    }
+
+    pub fn entries(&self) -> usize {
+        self.values.len()
+    }
+
+    // Angle between neighbouring entries, 2 pi / entries
+    pub fn step(&self) -> f64 {
+        2.0 * std::f64::consts::PI / self.entries() as f64
+    }
+
+    // Worst error of any single entry against the true sine: the rounding
+    // into the Q format (a full step where 1.0 saturates), plus the
+    // kernel's own error
+    pub fn entry_error(&self) -> f64 {
+        self.rounding as f64 * math::powi(2.0, -(Q60::FRAC_BITS as i32)) + KERNEL_ERROR
+    }
+
+    // Guaranteed bound on the error of linearly interpolating between
+    // entries (with exact arithmetic) anywhere on the circle
+    pub fn max_error(&self) -> f64 {
+        self.error_bound(Interpolation::Linear)
+    }
+
+    // Guaranteed bound on the error of sin() with `interpolation`, from
+    // the usual interpolation remainder terms (every derivative of sin
+    // is at most 1) for a step of h, plus the error in the entries
+    // weighted by how much of each entry the interpolation uses:
+    //   Nearest:   h / 2
+    //   Linear:    h^2 / 8
+    //   Quadratic: h^3 / 16 (at most half a step from the middle point),
+    //              with up to 1.25 times the entry error
+    pub fn error_bound(&self, interpolation: Interpolation) -> f64 {
+        let h = self.step();
+        match interpolation {
+            Interpolation::Nearest => h / 2.0 + self.entry_error(),
+            Interpolation::Linear => h * h / 8.0 + self.entry_error(),
+            Interpolation::Quadratic => h * h * h / 16.0 + 1.25 * self.entry_error(),
+        }
+    }
+
+    // sin theta read off the table, wrapping around at both ends
+    pub fn sin(&self, theta: FixedPoint, interpolation: Interpolation) -> FixedPoint {
+        let turns = theta.to_f64() / (2.0 * std::f64::consts::PI);
+        self.lookup(turns, interpolation)
+    }
+
+    // cos theta, read a quarter turn further on
+    pub fn cos(&self, theta: FixedPoint, interpolation: Interpolation) -> FixedPoint {
+        let turns = theta.to_f64() / (2.0 * std::f64::consts::PI) + 0.25;
+        self.lookup(turns, interpolation)
+    }
+
+    fn lookup(&self, turns: f64, interpolation: Interpolation) -> FixedPoint {
+        if !turns.is_finite() {
+            return FixedPoint::new(f64::NAN);
+        }
+        let n = self.entries();
+        let position = (turns - turns.floor()) * n as f64;
+        let entry = |i: usize| self.format.to_f64(self.values[i % n]);
+
+        let value = match interpolation {
+            Interpolation::Nearest => entry(math::round(position) as usize),
+            Interpolation::Linear => {
+                let i = position.floor();
+                let t = position - i;
+                let i = i as usize;
+                entry(i) + t * (entry(i + 1) - entry(i))
+            }
+            Interpolation::Quadratic => {
+                let i = math::round(position);
+                let u = position - i;
+                let i = i as usize;
+                let [before, middle, after] = [entry(i + n - 1), entry(i), entry(i + 1)];
+                middle + u * (after - before) / 2.0 + u * u * (after - 2.0 * middle + before) / 2.0
+            }
+        };
+        FixedPoint::new(value)
+    }
+
+    // Bytes per entry in the exports
+    fn width(&self) -> usize {
+        byte_width(self.format)
+    }
+
+    // A hash of the format and every value, the same whatever endianness
+    // or language the table is exported in, see Tables::fingerprint()
+    pub fn fingerprint(&self) -> u64 {
+        let mut bytes = vec![self.format.int_bits() as u8, self.format.frac_bits() as u8];
+        for &value in &self.values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        fnv1a(&bytes)
+    }
+
+    pub fn to_bytes(&self, endian: Endian) -> Vec<u8> {
+        let width = self.width();
+        let mut bytes = Vec::with_capacity(12 + width * self.entries() + 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(match endian {
+            Endian::Little => 0,
+            Endian::Big => 1,
+        });
+        bytes.push(self.format.int_bits() as u8);
+        bytes.push(self.format.frac_bits() as u8);
+        let push = |bytes: &mut Vec<u8>, x: u64, width: usize| match endian {
+            Endian::Little => bytes.extend_from_slice(&x.to_le_bytes()[..width]),
+            Endian::Big => bytes.extend_from_slice(&x.to_be_bytes()[8 - width..]),
+        };
+        push(&mut bytes, self.entries() as u64, 4);
+        for &value in &self.values {
+            push(&mut bytes, value as u64, width);
+        }
+
+        let crc = crc32(&bytes);
+        push(&mut bytes, crc as u64, 4);
+        bytes
+    }
+
+    // `pub const NAME: [iN; entries] = [...];`
+    pub fn to_rust(&self, name: &str) -> String {
+        format!(
+            "// {}\npub const {}: [i{}; {}] = [\n{}];\n",
+            self.describe(),
+            name.to_uppercase(),
+            8 * self.width(),
+            self.entries(),
+            self.rows()
+        )
+    }
+
+    // `static const intN_t name[entries] = {...};`
+    pub fn to_c(&self, name: &str) -> String {
+        format!(
+            "#include <stdint.h>\n\n/* {} */\nstatic const int{}_t {}[{}] = {{\n{}}};\n",
+            self.describe(),
+            8 * self.width(),
+            name,
+            self.entries(),
+            self.rows()
+        )
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "sin(2 pi i / {}) in {}, max linear interpolation error {:e}, generated by cordic-rs",
+            self.entries(),
+            self.format,
+            self.max_error()
+        )
+    }
+
+    fn rows(&self) -> String {
+        array_rows(&self.values)
+    }
+}
+
+// Bytes per entry of the smallest integer type `format` fits in
+pub(crate) fn byte_width(format: QFormat) -> usize {
+    match format.bits() {
+        0..=8 => 1,
+        9..=16 => 2,
+        17..=32 => 4,
+        _ => 8,
+    }
+}
+
+// The entries 8 to a line, indented, each line ending in a comma.
+// i64::MIN can't be written as a literal in C, so it's spelled out
+pub(crate) fn array_rows(values: &[i64]) -> String {
+    values
+        .chunks(8)
+        .map(|row| {
+            let row = row
+                .iter()
+                .map(|&value| {
+                    if value == i64::MIN {
+                        "(-9223372036854775807 - 1)".to_string()
+                    } else {
+                        value.to_string()
+                    }
+                })
+                .collect::<Vec<String>>();
+            format!("    {},\n", row.join(", "))
+        })
+        .collect()
+}
+
+// sin of every angle in `thetas`, written to the same index of `out`
+pub fn sin_lut(
+    table: &SinTable,
+    interpolation: Interpolation,
+    thetas: &[FixedPoint],
+    out: &mut [FixedPoint],
+) -> Result<(), CordicError> {
+    if thetas.len() != out.len() {
+        return Err(CordicError::DomainError("sin_lut"));
+    }
+    for (out, &theta) in out.iter_mut().zip(thetas) {
+        *out = table.sin(theta, interpolation);
+    }
+    Ok(())
+}
+
+// A table and a way of reading it, as something to compare against
+// CORDIC (see Registry)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lut {
+    pub table: SinTable,
+    pub interpolation: Interpolation,
+}
+
+impl TrigAlgorithm for Lut {
+    fn name(&self) -> String {
+        let interpolation = match self.interpolation {
+            Interpolation::Nearest => "nearest",
+            Interpolation::Linear => "linear",
+            Interpolation::Quadratic => "quadratic",
+        };
+        format!(
+            "lut-{}-{}-{}",
+            interpolation,
+            self.table.entries(),
+            self.table.format()
+        )
+    }
+
+    fn sincos(&self, theta: FixedPoint) -> Result<[FixedPoint; 2], CordicError> {
+        Ok([
+            self.table.cos(theta, self.interpolation),
+            self.table.sin(theta, self.interpolation),
+        ])
+    }
+
+    // Scaling theta to a table position is a multiply, then for each of
+    // cos and sin: nothing more for the nearest entry, a subtract,
+    // multiply and add for linear, and twice that for quadratic
+    fn cost(&self) -> Option<Cost> {
+        let (adds, multiplies) = match self.interpolation {
+            Interpolation::Nearest => (0, 0),
+            Interpolation::Linear => (2, 1),
+            Interpolation::Quadratic => (5, 3),
+        };
+        Some(Cost {
+            adds: REDUCTION_ADDS + 2 * adds,
+            shifts: 0,
+            multiplies: 1 + 2 * multiplies,
+            divides: 0,
+            table_entries: self.table.entries(),
+            toggles: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values() {
+        let table = generate_table(256, QFormat::Q15).unwrap();
+        assert_eq![table.entries(), 256];
+        assert_eq![table.values()[0], 0];
+        // sin(pi/2) saturates, sin(pi/6) is exactly half
+        assert_eq![table.values()[64], 32767];
+        assert_eq![table.values()[192], -32768];
+        assert_eq![generate_table(12, QFormat::Q15).unwrap().values()[1], 16384];
+        for (i, &value) in table.values().iter().enumerate() {
+            let exact = (table.step() * i as f64).sin();
+            assert![(QFormat::Q15.to_f64(value) - exact).abs() <= table.entry_error()];
+        }
+    }
+
+    #[test]
+    fn pinned_fingerprints() {
+        let table = generate_table(256, QFormat::Q15).unwrap();
+        assert_eq![table.fingerprint(), 0xe999_1be9_db35_2790];
+        let table = generate_table(1024, QFormat::Q31).unwrap();
+        assert_eq![table.fingerprint(), 0xac13_4bc5_7046_e720];
+    }
+
+    #[test]
+    fn error_bound() {
+        // Sample between the entries and check linear interpolation never
+        // strays past max_error()
+        for &(entries, format) in &[(64, QFormat::Q15), (1024, QFormat::Q31)] {
+            let table = generate_table(entries, format).unwrap();
+            let value = |i: usize| format.to_f64(table.values()[i % entries]);
+            let mut worst = 0.0f64;
+            for i in 0..entries {
+                for j in 0..16 {
+                    let t = j as f64 / 16.0;
+                    let theta = table.step() * (i as f64 + t);
+                    let interpolated = value(i) + t * (value(i + 1) - value(i));
+                    worst = worst.max((interpolated - theta.sin()).abs());
+                }
+            }
+            assert![worst <= table.max_error()];
+            assert![worst > table.max_error() / 2.0];
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        for &(entries, format) in &[(64, QFormat::Q15), (256, QFormat::Q31)] {
+            let table = generate_table(entries, format).unwrap();
+            for interpolation in [
+                Interpolation::Nearest,
+                Interpolation::Linear,
+                Interpolation::Quadratic,
+            ] {
+                let bound = table.error_bound(interpolation);
+                for i in -1000..=1000 {
+                    let theta = i as f64 / 150.0;
+                    let sin = table.sin(FixedPoint::new(theta), interpolation).to_f64();
+                    let cos = table.cos(FixedPoint::new(theta), interpolation).to_f64();
+                    assert![(sin - theta.sin()).abs() <= bound];
+                    assert![(cos - theta.cos()).abs() <= bound];
+                }
+            }
+            assert![
+                table.error_bound(Interpolation::Quadratic)
+                    < table.error_bound(Interpolation::Linear)
+            ];
+        }
+
+        let table = generate_table(256, QFormat::Q31).unwrap();
+        let thetas = [0.5, -2.0, 7.0].map(FixedPoint::new);
+        let mut out = [FixedPoint::new(0.0); 3];
+        sin_lut(&table, Interpolation::Linear, &thetas, &mut out).unwrap();
+        assert_eq![out[1], table.sin(thetas[1], Interpolation::Linear)];
+        assert![sin_lut(&table, Interpolation::Linear, &thetas, &mut out[..2]).is_err()];
+        assert![table
+            .sin(FixedPoint::new(f64::NAN), Interpolation::Nearest)
+            .to_f64()
+            .is_nan()];
+    }
+
+    #[test]
+    fn compared() {
+        let lut = Lut {
+            table: generate_table(1024, QFormat::Q31).unwrap(),
+            interpolation: Interpolation::Linear,
+        };
+        assert_eq![lut.name(), "lut-linear-1024-Q31"];
+        let thetas = (-314..=314).map(|i| i as f64 / 50.0).collect::<Vec<f64>>();
+        let result = crate::algorithm::accuracy(&lut, &thetas).unwrap();
+        assert![result.max_error() <= lut.table.max_error()];
+        assert_eq![result.cost.unwrap().table_entries, 1024];
+    }
+
+    #[test]
+    fn exports() {
+        let table = generate_table(4, QFormat::Q15).unwrap();
+        let bytes = table.to_bytes(Endian::Big);
+        assert_eq![&bytes[..12], b"SINT\x01\x01\x00\x0f\x00\x00\x00\x04"];
+        assert_eq![
+            &bytes[12..20],
+            &[0x00, 0x00, 0x7f, 0xff, 0x00, 0x00, 0x80, 0x00]
+        ];
+        assert_eq![bytes.len(), 24];
+        assert_eq![&bytes[20..], &crc32(&bytes[..20]).to_be_bytes()];
+
+        let rust = table.to_rust("sin_table");
+        assert![rust.contains("pub const SIN_TABLE: [i16; 4] = [\n    0, 32767, 0, -32768,\n];\n")];
+        let c = table.to_c("sin_table");
+        assert![c.contains("static const int16_t sin_table[4] = {\n    0, 32767, 0, -32768,\n};\n")];
+
+        let wide = generate_table(4, QFormat::new(3, 60).unwrap()).unwrap();
+        assert_eq![wide.to_bytes(Endian::Little).len(), 12 + 32 + 4];
+        assert![wide.to_rust("t").contains("[i64; 4]")];
+    }
+
+    #[test]
+    fn invalid() {
+        assert![generate_table(0, QFormat::Q15).is_err()];
+        assert![generate_table(16, QFormat::new(0, 61).unwrap()).is_err()];
+    }
+}
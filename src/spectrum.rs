@@ -0,0 +1,220 @@
+use crate::complex::Complex;
+use crate::error::CordicError;
+use crate::window::Window;
+
+// Spectrum analysis for judging what the oscillators produce, without
+// reaching for an external tool. This is measurement, not something
+// that would ship on the target, so unlike the rest of the crate it
+// works in plain f64 and gets its twiddles from the standard library
+
+// In place radix-2 decimation in time FFT. The length has to be a power
+// of two
+pub fn fft(data: &mut [Complex<f64>]) -> Result<(), CordicError> {
+    let n = data.len();
+    if !n.is_power_of_two() {
+        return Err(CordicError::DomainError("fft"));
+    }
+
+    // Bit reversed order first, so every butterfly pass works in place
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i
+            .reverse_bits()
+            .checked_shr(usize::BITS - bits)
+            .unwrap_or(0);
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let mut size = 2;
+    while size <= n {
+        let step = -std::f64::consts::PI * 2.0 / size as f64;
+        for start in (0..n).step_by(size) {
+            for k in 0..size / 2 {
+                let twiddle = Complex::new((step * k as f64).cos(), (step * k as f64).sin());
+                let even = data[start + k];
+                let odd = twiddle * data[start + k + size / 2];
+                data[start + k] = even + odd;
+                data[start + k + size / 2] = even - odd;
+            }
+        }
+        size *= 2;
+    }
+    Ok(())
+}
+
+// One sided power spectrum of a real signal, bins 0 to n / 2 - 1, scaled
+// so a full scale sine sitting exactly on a bin reads 0.5 there (its
+// mean square). A window spreads a tone that falls between bins into a
+// few neighbours instead of across the whole spectrum, at the price of a
+// wider peak
+pub fn power_spectrum(signal: &[f64], window: Option<Window>) -> Result<Vec<f64>, CordicError> {
+    let n = signal.len();
+    let shape = match window {
+        Some(window) => window.generate(n).iter().map(|w| w.to_f64()).collect(),
+        None => vec![1.0; n],
+    };
+    // Normalize by the window's coherent gain so the peak keeps its height
+    let gain = shape.iter().sum::<f64>() / n as f64;
+    let mut data = signal
+        .iter()
+        .zip(&shape)
+        .map(|(x, w)| Complex::new(x * w / gain, 0.0))
+        .collect::<Vec<_>>();
+    fft(&mut data)?;
+    Ok(data[..n / 2]
+        .iter()
+        .map(|z| 2.0 * (z.re * z.re + z.im * z.im) / (n * n) as f64)
+        .collect())
+}
+
+// Spur free dynamic range: how far the strongest component other than
+// the tone itself sits below the tone
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sfdr {
+    pub carrier_bin: usize,
+    pub spur_bin: usize,
+    pub dbc: f64,
+}
+
+// Bins either side of a peak that still belong to it, for each window
+fn main_lobe(window: Option<Window>) -> usize {
+    match window {
+        None => 0,
+        Some(Window::Hann) | Some(Window::Hamming) => 2,
+        Some(Window::Blackman) => 3,
+    }
+}
+
+// Finds the SFDR of a real tone. The carrier's main lobe and DC (with
+// its own lobe) are left out of the search for spurs. The windows' own
+// sidelobes limit what can be measured (Blackman's are near -58 dB), so
+// for anything better the tone has to sit exactly on a bin, which
+// leaves no leakage at all with `None`
+pub fn sfdr(signal: &[f64], window: Option<Window>) -> Result<Sfdr, CordicError> {
+    let spectrum = power_spectrum(signal, window)?;
+    let lobe = main_lobe(window);
+    let peak = |bins: &mut dyn Iterator<Item = usize>| {
+        bins.max_by(|&a, &b| spectrum[a].total_cmp(&spectrum[b]))
+    };
+    let carrier_bin =
+        peak(&mut (lobe + 1..spectrum.len())).ok_or(CordicError::DomainError("sfdr"))?;
+    let spur_bin =
+        peak(&mut (lobe + 1..spectrum.len()).filter(|bin| bin.abs_diff(carrier_bin) > lobe))
+            .ok_or(CordicError::DomainError("sfdr"))?;
+    Ok(Sfdr {
+        carrier_bin,
+        spur_bin,
+        dbc: 10.0 * (spectrum[carrier_bin] / spectrum[spur_bin]).log10(),
+    })
+}
+
+// How clean a generated tone is: its SFDR (dBc) and SNR (dB), every
+// departure from the ideal tone counting as noise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneQuality {
+    pub sfdr: f64,
+    pub snr: f64,
+}
+
+// Judges `output` against `ideal`, the same tone computed exactly, by
+// looking at the spectrum of the difference. Unlike sfdr() this needs
+// no exact bin: the error has no big carrier to leak, so a Blackman
+// window on it keeps everything but the strongest spurs out of the way.
+// Whatever lands within the carrier's own lobe is amplitude or phase
+// error of the tone itself rather than a spur, so it's left out of the
+// SFDR but not of the SNR
+pub fn tone_quality(output: &[f64], ideal: &[f64]) -> Result<ToneQuality, CordicError> {
+    if output.len() != ideal.len() {
+        return Err(CordicError::DomainError("tone_quality"));
+    }
+    let window = Some(Window::Blackman);
+    let lobe = main_lobe(window);
+    let carrier = power_spectrum(ideal, window)?;
+    let error = output
+        .iter()
+        .zip(ideal)
+        .map(|(a, b)| a - b)
+        .collect::<Vec<f64>>();
+    let spurs = power_spectrum(&error, window)?;
+
+    let carrier_bin = (lobe + 1..carrier.len())
+        .max_by(|&a, &b| carrier[a].total_cmp(&carrier[b]))
+        .ok_or(CordicError::DomainError("tone_quality"))?;
+    let worst = (0..spurs.len())
+        .filter(|bin| bin.abs_diff(carrier_bin) > lobe)
+        .map(|bin| spurs[bin])
+        .fold(0.0, f64::max);
+    let mean_square = |x: &[f64]| x.iter().map(|x| x * x).sum::<f64>() / x.len() as f64;
+    Ok(ToneQuality {
+        sfdr: 10.0 * (carrier[carrier_bin] / worst).log10(),
+        snr: 10.0 * (mean_square(ideal) / mean_square(&error)).log10(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn against_dft() {
+        let n = 64;
+        let signal = (0..n)
+            .map(|i| Complex::new((i as f64 * 0.37).sin(), (i as f64 * 1.3).cos() * 0.5))
+            .collect::<Vec<_>>();
+        let mut fast = signal.clone();
+        fft(&mut fast).unwrap();
+        for (k, z) in fast.iter().enumerate() {
+            let mut sum = Complex::new(0.0, 0.0);
+            for (i, x) in signal.iter().enumerate() {
+                let theta = -std::f64::consts::PI * 2.0 * (k * i) as f64 / n as f64;
+                sum = sum + *x * Complex::new(theta.cos(), theta.sin());
+            }
+            assert![(z.re - sum.re).abs() < 1e-9 && (z.im - sum.im).abs() < 1e-9];
+        }
+        assert![fft(&mut [Complex::new(0.0, 0.0); 12]).is_err()];
+    }
+
+    #[test]
+    fn two_tones() {
+        // A full scale tone on bin 100 reads 0.5, and a second one 60 dB
+        // down, between bins, sets the SFDR
+        let n = 1024;
+        let signal = (0..n)
+            .map(|i| {
+                let t = std::f64::consts::PI * 2.0 * i as f64 / n as f64;
+                (100.0 * t).sin() + 1e-3 * (300.5 * t).sin()
+            })
+            .collect::<Vec<f64>>();
+        let spectrum = power_spectrum(&signal, None).unwrap();
+        assert![(spectrum[100] - 0.5).abs() < 1e-9];
+
+        let windowed = sfdr(&signal, Some(Window::Blackman)).unwrap();
+        assert_eq![windowed.carrier_bin, 100];
+        assert![windowed.spur_bin == 300 || windowed.spur_bin == 301];
+        // Half a bin off costs the Blackman window about 1.1 dB
+        assert![(windowed.dbc - 61.1).abs() < 0.5, "{}", windowed.dbc];
+
+        // Both on bins and no window reads the 60 dB exactly
+        let on_bins = (0..n)
+            .map(|i| {
+                let t = std::f64::consts::PI * 2.0 * i as f64 / n as f64;
+                (101.0 * t).sin() + 1e-3 * (303.0 * t).sin()
+            })
+            .collect::<Vec<f64>>();
+        let exact = sfdr(&on_bins, None).unwrap();
+        assert_eq![(exact.carrier_bin, exact.spur_bin), (101, 303)];
+        assert![(exact.dbc - 60.0).abs() < 1e-6];
+
+        // Against the first tone alone the second one is the error, at
+        // 60 dB down both ways, bar the window's scalloping
+        let ideal = (0..n)
+            .map(|i| (100.0 * std::f64::consts::PI * 2.0 * i as f64 / n as f64).sin())
+            .collect::<Vec<f64>>();
+        let quality = tone_quality(&signal, &ideal).unwrap();
+        assert![(quality.sfdr - 61.1).abs() < 0.5, "{}", quality.sfdr];
+        assert![(quality.snr - 60.0).abs() < 0.1, "{}", quality.snr];
+        assert![tone_quality(&signal[1..], &ideal).is_err()];
+    }
+}
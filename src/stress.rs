@@ -0,0 +1,295 @@
+use crate::cordic::{reduce, CordicConfig, SmallAngle};
+use crate::error::CordicError;
+use crate::fault::SplitMix64;
+use crate::fixed::FixedPoint;
+
+// Random angle stress testing with shrinking, for turning "some angle
+// somewhere is off" into a bug report. stress() runs a config over
+// `count` pseudo-random angles from a seed (the same ones on every
+// machine) and keeps those where cos or sin is further from f64's than
+// bound(iters) allows. The angles are a mix of
+//
+//   uniform in [-pi, pi]
+//   just off a multiple of pi/4, by 2^-k for k up to 52, where the
+//     folds and the first few micro-rotations make their decisions
+//   uniform in [-1000, 1000], for the reduction
+//
+// minimize() then shrinks a failure the way property testing tools do:
+// down to the fewest iterations that still fail, then to the smallest
+// theta (0, theta reduced into [-pi, pi], theta / 2^j) and the coarsest
+// binary fraction of it (0.5 rather than 0.50000000000000011) that
+// still fail, and around again until none of that gets anywhere.
+// Failure::regression_test() writes the result out as a #[test]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Failure {
+    pub theta: f64,
+    pub iters: usize,
+    // The worse of |cos error| and |sin error|, NaN counting as a failure
+    pub error: f64,
+    // What bound(iters) allowed
+    pub bound: f64,
+}
+
+// Rounds of shrinking minimize() gives up after
+const MAX_PASSES: usize = 64;
+
+// Checks theta at `iters` iterations, Some(Failure) if it's out of bound
+fn check(
+    config: &CordicConfig,
+    theta: f64,
+    iters: usize,
+    bound: &impl Fn(usize) -> f64,
+) -> Result<Option<Failure>, CordicError> {
+    let ret = config.iters(iters).rotate(FixedPoint::new(theta))?;
+    let error = (ret.x.to_f64() - theta.cos())
+        .abs()
+        .max((ret.y.to_f64() - theta.sin()).abs());
+    let bound = bound(iters);
+    // NaN fails this too
+    if error <= bound {
+        return Ok(None);
+    }
+    Ok(Some(Failure {
+        theta,
+        iters,
+        error,
+        bound,
+    }))
+}
+
+// The failures among `count` angles from `seed`, in the order they were
+// drawn. Errors from the config itself (a bad iteration count, say) are
+// passed on
+pub fn stress(
+    config: &CordicConfig,
+    seed: u64,
+    count: usize,
+    bound: impl Fn(usize) -> f64,
+) -> Result<Vec<Failure>, CordicError> {
+    let mut rng = SplitMix64(seed);
+    let pi = std::f64::consts::PI;
+    let mut failures = Vec::new();
+    for _ in 0..count {
+        let theta = match rng.below(4) {
+            0 | 1 => pi * (2.0 * rng.unit() - 1.0),
+            2 => {
+                let k = rng.below(33) as f64 - 16.0;
+                let offset = 0.5f64.powi(rng.below(53) as i32);
+                let sign = if rng.below(2) == 0 { -1.0 } else { 1.0 };
+                k * pi / 4.0 + sign * offset
+            }
+            _ => 1000.0 * (2.0 * rng.unit() - 1.0),
+        };
+        if let Some(failure) = check(config, theta, config.iters, &bound)? {
+            failures.push(failure);
+        }
+    }
+    Ok(failures)
+}
+
+// The smallest case `failure` shrinks to that still fails. With a custom
+// schedule the iteration count doesn't change anything, so only theta
+// shrinks
+pub fn minimize(
+    config: &CordicConfig,
+    failure: &Failure,
+    bound: impl Fn(usize) -> f64,
+) -> Result<Failure, CordicError> {
+    let mut best = *failure;
+    // Rounding can make theta a little bigger again, so the passes are
+    // bounded rather than trusting the steps to always settle
+    for _ in 0..MAX_PASSES {
+        let mut shrunk = false;
+        if config.custom_shifts()?.is_none() {
+            for iters in 1..best.iters {
+                if let Some(smaller) = check(config, best.theta, iters, &bound)? {
+                    best = smaller;
+                    shrunk = true;
+                    break;
+                }
+            }
+        }
+        let mut smaller = vec![0.0, reduce(FixedPoint::new(best.theta)).to_f64()];
+        smaller.extend((1..=60).rev().map(|j| best.theta * 0.5f64.powi(j)));
+        for theta in smaller {
+            if theta.abs() >= best.theta.abs() {
+                continue;
+            }
+            if let Some(smaller) = check(config, theta, best.iters, &bound)? {
+                best = smaller;
+                shrunk = true;
+                break;
+            }
+        }
+        // theta rounded to k fractional bits for k = 0, 1, ..., up to the
+        // k where rounding doesn't change it any more
+        for k in 0..=1074 {
+            let scale = 2f64.powi(k);
+            let coarser = (best.theta * scale).round() / scale;
+            if coarser == best.theta || !coarser.is_finite() {
+                break;
+            }
+            if let Some(simpler) = check(config, coarser, best.iters, &bound)? {
+                best = simpler;
+                shrunk = true;
+                break;
+            }
+        }
+        if !shrunk {
+            break;
+        }
+    }
+    Ok(best)
+}
+
+impl Failure {
+    // A #[test] named `name` that fails the way this does, for pasting
+    // into the crate's tests
+    pub fn regression_test(&self, config: &CordicConfig, name: &str) -> String {
+        format!(
+            "#[test]\n\
+             fn {name}() {{\n\
+             \x20   // error {error:e} against a bound of {bound:e}\n\
+             \x20   let theta = {theta:?};\n\
+             \x20   let ret = {config}\n\
+             \x20       .rotate(FixedPoint::new(theta))\n\
+             \x20       .unwrap();\n\
+             \x20   assert![(ret.x.to_f64() - theta.cos()).abs() <= {bound:e}];\n\
+             \x20   assert![(ret.y.to_f64() - theta.sin()).abs() <= {bound:e}];\n\
+             }}\n",
+            name = name,
+            error = self.error,
+            bound = self.bound,
+            theta = self.theta,
+            config = builder(&config.iters(self.iters)),
+        )
+    }
+}
+
+// The CordicConfig builder calls that make `config`
+fn builder(config: &CordicConfig) -> String {
+    let default = CordicConfig::new(config.iters);
+    let mut calls = format!("CordicConfig::new({})", config.iters);
+    if config.backend != default.backend {
+        calls += &format!(".backend(Backend::{:?})", config.backend);
+    }
+    if config.compensation != default.compensation {
+        calls += &format!(
+            ".gain_compensation(GainCompensation::{:?})",
+            config.compensation
+        );
+    }
+    if let Some(bits) = config.io_bits {
+        calls += &format!(".io_width({})", bits);
+    }
+    if config.double_word {
+        calls += ".double_word(true)";
+    }
+    match config.small_angle {
+        SmallAngle::Off => {}
+        SmallAngle::Backend => calls += ".small_angle(true)",
+        SmallAngle::Below(bits) => calls += &format!(".small_angle_below({})", bits),
+    }
+    if config.special_angles {
+        calls += ".special_angles(true)";
+    }
+    if config.octant {
+        calls += ".octant_reduction(true)";
+    }
+    if config.monotone {
+        calls += ".monotone(true)";
+    }
+    if config.correctly_rounded {
+        calls += ".correctly_rounded(true)";
+    }
+    if let Ok(Some(shifts)) = config.custom_shifts() {
+        calls += &format!(".schedule(&{:?})", shifts.as_slice());
+    }
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cordic::{Backend, GainCompensation, MAX_ITERS};
+
+    // The error after n iterations is at most the last micro-angle,
+    // about 2^(1 - n), plus rounding
+    fn bound(iters: usize) -> f64 {
+        2f64.powi(1 - iters as i32) + 1e-14
+    }
+
+    #[test]
+    fn clean() {
+        for backend in [Backend::Integer, Backend::Float] {
+            for iters in [8, 24, 40] {
+                let config = CordicConfig::new(iters).backend(backend);
+                assert_eq![stress(&config, 7, 2000, bound), Ok(vec![])];
+            }
+        }
+    }
+
+    #[test]
+    fn shrinks() {
+        // A bound 100 times too tight fails nearly everywhere, and every
+        // failure shrinks to one of the few simplest
+        let tight = |iters| bound(iters) / 100.0;
+        let config = CordicConfig::new(24).backend(Backend::Float);
+        let failures = stress(&config, 1, 50, tight).unwrap();
+        assert![failures.len() > 40];
+        assert_eq![stress(&config, 1, 50, tight).unwrap(), failures];
+        assert_ne![stress(&config, 2, 50, tight).unwrap(), failures];
+
+        for failure in &failures {
+            let minimal = minimize(&config, failure, tight).unwrap();
+            assert![minimal.iters <= failure.iters];
+            assert![minimal.error > minimal.bound];
+            // Small, and a binary fraction with only a few bits
+            assert![minimal.iters <= 2, "{:?}", minimal];
+            assert![minimal.theta.abs() <= std::f64::consts::PI, "{:?}", minimal];
+            assert_eq![(minimal.theta * 8.0).fract(), 0.0, "{:?}", minimal];
+            assert_eq![minimize(&config, &minimal, tight).unwrap(), minimal];
+        }
+
+        // A schedule has no count to shrink
+        let scheduled = config.schedule(&[0, 1, 2, 3, 4, 5]);
+        let failure = stress(&scheduled, 1, 1, tight).unwrap()[0];
+        assert_eq![minimize(&scheduled, &failure, tight).unwrap().iters, 24];
+    }
+
+    #[test]
+    fn regression_tests() {
+        let failure = Failure {
+            theta: -0.5,
+            iters: 3,
+            error: 0.125,
+            bound: 0.0625,
+        };
+        let config = CordicConfig::new(MAX_ITERS)
+            .backend(Backend::Float)
+            .gain_compensation(GainCompensation::Prescale)
+            .octant_reduction(true);
+        assert_eq![
+            failure.regression_test(&config, "stress_1"),
+            "#[test]\n\
+             fn stress_1() {\n\
+             \x20   // error 1.25e-1 against a bound of 6.25e-2\n\
+             \x20   let theta = -0.5;\n\
+             \x20   let ret = CordicConfig::new(3).backend(Backend::Float)\
+             .gain_compensation(GainCompensation::Prescale).octant_reduction(true)\n\
+             \x20       .rotate(FixedPoint::new(theta))\n\
+             \x20       .unwrap();\n\
+             \x20   assert![(ret.x.to_f64() - theta.cos()).abs() <= 6.25e-2];\n\
+             \x20   assert![(ret.y.to_f64() - theta.sin()).abs() <= 6.25e-2];\n\
+             }\n"
+        ];
+        assert_eq![
+            builder(&CordicConfig::new(5).io_width(16).small_angle_below(9)),
+            "CordicConfig::new(5).io_width(16).small_angle_below(9)"
+        ];
+        assert_eq![
+            builder(&CordicConfig::new(5).schedule(&[0, 1, 1])),
+            "CordicConfig::new(5).schedule(&[0, 1, 1])"
+        ];
+    }
+}
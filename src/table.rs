@@ -0,0 +1,310 @@
+// Angle and gain tables as fixed point integers, plus a small binary
+// format for shipping them to a device (e.g. flashing them into ROM next
+// to firmware that runs the iterations).
+//
+// The format is, in the byte order given by the endianness flag:
+//
+//   offset  size  field
+//   0       4     magic, "CRDC"
+//   4       1     format version (1)
+//   5       1     endianness (0 = little, 1 = big)
+//   6       1     fractional bits of the Q format
+//   7       1     reserved, 0
+//   8       2     iteration count n
+//   10      2     reserved, 0
+//   12      4n    angles, atan(2^-i) for i in 0..n, as i32
+//   12+4n   4n    gains, K for 1..=n iterations, as i32
+//   12+8n   4     CRC-32 (IEEE) of everything before it
+
+use crate::cordic::{angles, gain, MAX_ITERS};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::math;
+
+const MAGIC: &[u8; 4] = b"CRDC";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+// Tables for `iters` iterations, quantized to Q(31 - frac_bits).frac_bits
+// in an i32. Every entry is below 1, so frac_bits can go all the way up
+// to 31
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tables {
+    pub frac_bits: u32,
+    pub angles: Vec<i32>,
+    pub gains: Vec<i32>,
+}
+
+impl Tables {
+    pub fn generate(iters: usize, frac_bits: u32) -> Result<Self, CordicError> {
+        if iters == 0 || iters > MAX_ITERS {
+            return Err(CordicError::IterationOutOfRange(iters));
+        }
+        if frac_bits == 0 || frac_bits > 31 {
+            return Err(CordicError::InvalidTable(
+                "fractional bits must be in 1..=31",
+            ));
+        }
+
+        let mut tables = Self {
+            frac_bits,
+            angles: Vec::with_capacity(iters),
+            gains: Vec::with_capacity(iters),
+        };
+        tables.extend(iters)?;
+        Ok(tables)
+    }
+
+    pub fn iters(&self) -> usize {
+        self.angles.len()
+    }
+
+    // Grows the tables to `iters` iterations, computing only the new
+    // entries, for when something built for fewer turns out to need
+    // more. Asking for no more than there already are does nothing
+    pub fn extend(&mut self, iters: usize) -> Result<(), CordicError> {
+        if iters == 0 || iters > MAX_ITERS {
+            return Err(CordicError::IterationOutOfRange(iters));
+        }
+        let scale = FixedPoint::new(math::powi(2.0, self.frac_bits as i32));
+        let quantize = |x: FixedPoint| math::round((x * scale).to_f64()) as i32;
+        let angles = angles(iters);
+        for n in self.iters() + 1..=iters {
+            self.angles.push(quantize(angles[n - 1]));
+            self.gains.push(quantize(gain(n)?));
+        }
+        Ok(())
+    }
+
+    // atan(2^-i), for i below iters()
+    pub fn angle(&self, i: usize) -> Result<FixedPoint, CordicError> {
+        match self.angles.get(i) {
+            Some(&bits) => Ok(self.dequantize(bits)),
+            None => Err(CordicError::IterationOutOfRange(i)),
+        }
+    }
+
+    // K for 1..=iters() iterations
+    pub fn gain(&self, iters: usize) -> Result<FixedPoint, CordicError> {
+        match iters.checked_sub(1).and_then(|i| self.gains.get(i)) {
+            Some(&bits) => Ok(self.dequantize(bits)),
+            None => Err(CordicError::IterationOutOfRange(iters)),
+        }
+    }
+
+    fn dequantize(&self, bits: i32) -> FixedPoint {
+        FixedPoint::new(bits as f64 / math::powi(2.0, self.frac_bits as i32))
+    }
+
+    // A hash of the fractional bits and every entry, the same whatever
+    // endianness the tables are written in. Tables generated by different
+    // versions of the crate should only differ in fingerprint when the
+    // generation changed on purpose
+    pub fn fingerprint(&self) -> u64 {
+        let mut bytes = vec![self.frac_bits as u8];
+        for &x in self.angles.iter().chain(self.gains.iter()) {
+            bytes.extend_from_slice(&x.to_le_bytes());
+        }
+        fnv1a(&bytes)
+    }
+
+    pub fn to_bytes(&self, endian: Endian) -> Vec<u8> {
+        let u16_bytes = |x: u16| match endian {
+            Endian::Little => x.to_le_bytes(),
+            Endian::Big => x.to_be_bytes(),
+        };
+        let u32_bytes = |x: u32| match endian {
+            Endian::Little => x.to_le_bytes(),
+            Endian::Big => x.to_be_bytes(),
+        };
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + 8 * self.iters() + 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(match endian {
+            Endian::Little => 0,
+            Endian::Big => 1,
+        });
+        bytes.push(self.frac_bits as u8);
+        bytes.push(0);
+        bytes.extend_from_slice(&u16_bytes(self.iters() as u16));
+        bytes.extend_from_slice(&u16_bytes(0));
+        for &x in self.angles.iter().chain(self.gains.iter()) {
+            bytes.extend_from_slice(&u32_bytes(x as u32));
+        }
+
+        let crc = crc32(&bytes);
+        bytes.extend_from_slice(&u32_bytes(crc));
+        bytes
+    }
+
+    // Loads tables written by to_bytes(), checking the header and CRC
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CordicError> {
+        if bytes.len() < HEADER_LEN + 4 || &bytes[0..4] != MAGIC {
+            return Err(CordicError::InvalidTable("not a table file"));
+        }
+        if bytes[4] != VERSION {
+            return Err(CordicError::InvalidTable("unsupported format version"));
+        }
+        let endian = match bytes[5] {
+            0 => Endian::Little,
+            1 => Endian::Big,
+            _ => return Err(CordicError::InvalidTable("invalid endianness")),
+        };
+        let read_u16 = |at: usize| {
+            let b = [bytes[at], bytes[at + 1]];
+            match endian {
+                Endian::Little => u16::from_le_bytes(b),
+                Endian::Big => u16::from_be_bytes(b),
+            }
+        };
+        let read_u32 = |at: usize| {
+            let b = [bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]];
+            match endian {
+                Endian::Little => u32::from_le_bytes(b),
+                Endian::Big => u32::from_be_bytes(b),
+            }
+        };
+
+        let frac_bits = bytes[6] as u32;
+        let iters = read_u16(8) as usize;
+        let crc_at = HEADER_LEN + 8 * iters;
+        if bytes.len() != crc_at + 4 {
+            return Err(CordicError::InvalidTable(
+                "length doesn't match iteration count",
+            ));
+        }
+        if read_u32(crc_at) != crc32(&bytes[..crc_at]) {
+            return Err(CordicError::InvalidTable("CRC mismatch"));
+        }
+
+        let entry = |i: usize| read_u32(HEADER_LEN + 4 * i) as i32;
+        Ok(Self {
+            frac_bits,
+            angles: (0..iters).map(entry).collect(),
+            gains: (iters..2 * iters).map(entry).collect(),
+        })
+    }
+}
+
+// Plain bitwise CRC-32 (the zlib/Ethernet one), slow but tiny, which is
+// also what a bootloader checking the table would likely use
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFF_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+// 64 bit FNV-1a, for fingerprints. Unlike the CRC it isn't for
+// catching corruption, just a short stable name for some exact bits
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc_check_value() {
+        // The standard check value for CRC-32
+        assert_eq![crc32(b"123456789"), 0xCBF4_3926];
+    }
+
+    #[test]
+    fn pinned_fingerprints() {
+        // FNV-1a's test vectors
+        assert_eq![fnv1a(b""), 0xcbf2_9ce4_8422_2325];
+        assert_eq![fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c];
+        // Generation is pinned to these bits, see manifest.rs
+        let tables = Tables::generate(MAX_ITERS, 30).unwrap();
+        assert_eq![tables.fingerprint(), 0x98c0_f061_f233_9d82];
+        let tables = Tables::generate(24, 31).unwrap();
+        assert_eq![tables.fingerprint(), 0x01fc_9d6e_133f_4d53];
+        let loaded = Tables::from_bytes(&tables.to_bytes(Endian::Big)).unwrap();
+        assert_eq![loaded.fingerprint(), tables.fingerprint()];
+    }
+
+    #[test]
+    fn extend() {
+        let mut tables = Tables::generate(24, 30).unwrap();
+        tables.extend(48).unwrap();
+        assert_eq![tables, Tables::generate(48, 30).unwrap()];
+        tables.extend(10).unwrap();
+        assert_eq![tables.iters(), 48];
+        assert![tables.extend(MAX_ITERS + 1).is_err()];
+    }
+
+    #[test]
+    fn round_trip() {
+        let tables = Tables::generate(24, 30).unwrap();
+        for endian in [Endian::Little, Endian::Big] {
+            let bytes = tables.to_bytes(endian);
+            assert_eq![bytes.len(), HEADER_LEN + 8 * 24 + 4];
+            assert_eq![Tables::from_bytes(&bytes).unwrap(), tables];
+        }
+    }
+
+    #[test]
+    fn quantized_values() {
+        let tables = Tables::generate(32, 30).unwrap();
+        // atan(1) == pi/4
+        assert_eq![
+            tables.angles[0],
+            (std::f64::consts::FRAC_PI_4 * 2_f64.powi(30)).round() as i32
+        ];
+        let diff = tables.gain(32).unwrap() - gain(32).unwrap();
+        assert_eq![
+            tables.angle(0).unwrap().to_f64(),
+            tables.angles[0] as f64 * 2f64.powi(-30)
+        ];
+        assert![tables.angle(32).is_err()];
+        assert![tables.gain(0).is_err()];
+        assert![tables.gain(33).is_err()];
+        assert![diff.abs() <= FixedPoint::new(2_f64.powi(-31))];
+    }
+
+    #[test]
+    fn header_layout() {
+        let bytes = Tables::generate(3, 16).unwrap().to_bytes(Endian::Big);
+        assert_eq![
+            &bytes[0..12],
+            &[b'C', b'R', b'D', b'C', 1, 1, 16, 0, 0, 3, 0, 0]
+        ];
+        // atan(1) in Q15.16 is 0x0000C910
+        assert_eq![&bytes[12..16], &[0x00, 0x00, 0xC9, 0x10]];
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let mut bytes = Tables::generate(8, 30).unwrap().to_bytes(Endian::Little);
+        bytes[20] ^= 1;
+        assert_eq![
+            Tables::from_bytes(&bytes),
+            Err(CordicError::InvalidTable("CRC mismatch"))
+        ];
+        assert![Tables::from_bytes(&bytes[..10]).is_err()];
+        assert![Tables::from_bytes(b"not a table at all").is_err()];
+
+        assert![Tables::generate(0, 30).is_err()];
+        assert![Tables::generate(MAX_ITERS + 1, 30).is_err()];
+        assert![Tables::generate(8, 32).is_err()];
+    }
+}
@@ -0,0 +1,56 @@
+use crate::cordic::{fold_quadrant, reduce};
+use crate::fixed::FixedPoint;
+
+// Taylor series for comparison against CORDIC, using `iters` terms of
+//   cos x = sum (-1)^n x^(2n) / (2n)!
+//   sin x = sum (-1)^n x^(2n+1) / (2n+1)!
+//
+// The series converges for any theta, but the terms get huge before
+// they get small, so theta goes through the same reduction as CORDIC
+pub fn taylor(theta: FixedPoint, iters: usize) -> [FixedPoint; 2] {
+    let (theta, negate) = fold_quadrant(reduce(theta));
+
+    let mut cos = FixedPoint::new(0.0);
+    let mut sin = FixedPoint::new(0.0);
+    // Current term of each series, starting with x^0 / 0! and x^1 / 1!
+    let mut cos_term = FixedPoint::new(1.0);
+    let mut sin_term = theta;
+    for n in 0..iters {
+        cos += cos_term;
+        sin += sin_term;
+
+        // Each term is the previous one times -x^2 / ((2n+1)(2n+2)) for
+        // cosine, and -x^2 / ((2n+2)(2n+3)) for sine
+        let two_n = 2.0 * n as f64;
+        cos_term = -(cos_term * theta * theta) / FixedPoint::new((two_n + 1.0) * (two_n + 2.0));
+        sin_term = -(sin_term * theta * theta) / FixedPoint::new((two_n + 2.0) * (two_n + 3.0));
+    }
+
+    if negate {
+        [-cos, -sin]
+    } else {
+        [cos, sin]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_enough(a: FixedPoint, b: FixedPoint) -> bool {
+        let diff = a - b;
+        diff < FixedPoint::new(0.01) && -diff < FixedPoint::new(0.01)
+    }
+
+    #[test]
+    fn basic() {
+        for i in -628..=628 {
+            let ret = taylor(FixedPoint::new(i as f64 / 100.0), 20);
+            let cos = FixedPoint::new((i as f64 / 100.0).cos());
+            let sin = FixedPoint::new((i as f64 / 100.0).sin());
+
+            assert![close_enough(ret[0], cos)];
+            assert![close_enough(ret[1], sin)];
+        }
+    }
+}
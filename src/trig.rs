@@ -0,0 +1,111 @@
+use crate::cordic::{atan2, CordicConfig, MAX_ITERS};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+
+// f64-style trig methods on FixedPoint, so moving code over from f64 is
+// mostly a matter of changing types:
+//
+//   use cordic_rs::FixedTrig;
+//   let (sin, cos) = theta.sin_cos();
+//   let y = x.sin_cordic(24)?;
+//
+// The plain methods use the default engine (CordicConfig::default(),
+// i.e. MAX_ITERS iterations on the integer backend) and can't fail. The
+// _cordic ones take an iteration count, which can be out of range
+pub trait FixedTrig: Sized {
+    fn sin_cos_cordic(self, iters: usize) -> Result<(Self, Self), CordicError>;
+
+    fn sin_cordic(self, iters: usize) -> Result<Self, CordicError> {
+        self.sin_cos_cordic(iters).map(|(sin, _)| sin)
+    }
+
+    fn cos_cordic(self, iters: usize) -> Result<Self, CordicError> {
+        self.sin_cos_cordic(iters).map(|(_, cos)| cos)
+    }
+
+    fn tan_cordic(self, iters: usize) -> Result<Self, CordicError>;
+
+    // (sin, cos), in the same order as f64::sin_cos
+    fn sin_cos(self) -> (Self, Self);
+
+    fn sin(self) -> Self {
+        self.sin_cos().0
+    }
+
+    fn cos(self) -> Self {
+        self.sin_cos().1
+    }
+
+    fn tan(self) -> Self;
+
+    // The angle of (other, self), like f64::atan2
+    fn atan2(self, other: Self) -> Self;
+}
+
+impl FixedTrig for FixedPoint {
+    fn sin_cos_cordic(self, iters: usize) -> Result<(Self, Self), CordicError> {
+        let ret = CordicConfig::new(iters).rotate(self)?;
+        Ok((ret.y, ret.x))
+    }
+
+    fn tan_cordic(self, iters: usize) -> Result<Self, CordicError> {
+        let (sin, cos) = self.sin_cos_cordic(iters)?;
+        Ok(sin / cos)
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        match self.sin_cos_cordic(MAX_ITERS) {
+            Ok(sin_cos) => sin_cos,
+            // MAX_ITERS is always a valid iteration count
+            Err(_) => unreachable!(),
+        }
+    }
+
+    fn tan(self) -> Self {
+        let (sin, cos) = self.sin_cos();
+        sin / cos
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        atan2(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_enough(a: FixedPoint, b: f64) -> bool {
+        let diff = a - FixedPoint::new(b);
+        diff < FixedPoint::new(0.01) && -diff < FixedPoint::new(0.01)
+    }
+
+    #[test]
+    fn basic() {
+        for i in -628..=628 {
+            let x = i as f64 / 100.0;
+            let theta = FixedPoint::new(x);
+
+            assert![close_enough(theta.sin(), x.sin())];
+            assert![close_enough(theta.cos(), x.cos())];
+            assert![close_enough(theta.sin_cordic(20).unwrap(), x.sin())];
+            assert![close_enough(theta.cos_cordic(20).unwrap(), x.cos())];
+            assert![close_enough(
+                theta.atan2(FixedPoint::new(1.0)),
+                x.atan2(1.0)
+            )];
+            if x.cos().abs() > 0.1 {
+                assert![close_enough(theta.tan(), x.tan())];
+                assert![close_enough(theta.tan_cordic(30).unwrap(), x.tan())];
+            }
+        }
+    }
+
+    #[test]
+    fn matches_engine() {
+        let theta = FixedPoint::new(1.0);
+        let ret = CordicConfig::default().rotate(theta).unwrap();
+        assert_eq![theta.sin_cos(), (ret.y, ret.x)];
+        assert![theta.sin_cordic(0).is_err()];
+    }
+}
@@ -0,0 +1,174 @@
+// wasm-bindgen bindings for running the demonstration in a browser,
+// built with
+//
+//   cargo build --release --target wasm32-unknown-unknown --features wasm
+//   wasm-bindgen --target web target/wasm32-unknown-unknown/release/cordic_rs.wasm --out-dir pkg
+//
+// and imported from the generated pkg/cordic_rs.js. Everything runs the
+// default engine (the integer backend, see CordicConfig::new), so the
+// page gets the same bits as the native build. Bad arguments give
+// `undefined` rather than throwing, since an exception can't be built
+// outside a browser and the tests run natively.
+//
+// From JS:
+//
+//   const { cos, sin } = sincos(1.0, 32);
+//   const trace = trace(1.0, 24);          // trace.cos(i), .sin(i), .z(i)
+//   const bytes = tables(24, 30);          // Uint8Array, see src/table.rs
+
+use crate::cordic::{checked_iters, gain, CordicConfig};
+use crate::fixed::FixedPoint;
+use crate::integer::{angle_table_q60, from_q60};
+use crate::table::{Endian, Tables};
+use wasm_bindgen::prelude::*;
+
+// cos and sin of one angle, as plain numbers on the JS side
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SinCos {
+    pub cos: f64,
+    pub sin: f64,
+}
+
+// cos and sin of theta with `iters` iterations, or undefined if `iters`
+// is out of range
+#[wasm_bindgen]
+pub fn sincos(theta: f64, iters: u32) -> Option<SinCos> {
+    let ret = CordicConfig::new(iters as usize)
+        .rotate(FixedPoint::new(theta))
+        .ok()?;
+    Some(SinCos {
+        cos: ret.x.to_f64(),
+        sin: ret.y.to_f64(),
+    })
+}
+
+// The gain K for `iters` iterations, or undefined if `iters` is out of
+// range
+#[wasm_bindgen]
+pub fn cordic_gain(iters: u32) -> Option<f64> {
+    gain(iters as usize).ok().map(FixedPoint::to_f64)
+}
+
+// The i-th micro-rotation angle, atan(2^-i), from the integer backend's
+// table, or undefined past its end
+#[wasm_bindgen]
+pub fn cordic_angle(i: u32) -> Option<f64> {
+    let table = angle_table_q60(usize::MAX).ok()?;
+    let angle = table.get(i as usize)?;
+    Some(from_q60(angle.to_bits()).to_f64())
+}
+
+// The steps of one rotation (see src/iterations.rs), with the gain so
+// far undone so cos and sin are the estimates after each step
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    // [cos, sin, z] after each step
+    steps: Vec<[f64; 3]>,
+}
+
+#[wasm_bindgen]
+impl Trace {
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.steps.len()
+    }
+
+    // The estimates after step `i`, NaN past the end
+    pub fn cos(&self, i: usize) -> f64 {
+        self.value(i, 0)
+    }
+
+    pub fn sin(&self, i: usize) -> f64 {
+        self.value(i, 1)
+    }
+
+    // The angle still left to rotate by after step `i`
+    pub fn z(&self, i: usize) -> f64 {
+        self.value(i, 2)
+    }
+}
+
+impl Trace {
+    fn value(&self, i: usize, which: usize) -> f64 {
+        self.steps.get(i).map_or(f64::NAN, |step| step[which])
+    }
+}
+
+// The first `iters` steps of the rotation by theta, or undefined if theta
+// isn't finite or `iters` is out of range
+#[wasm_bindgen]
+pub fn trace(theta: f64, iters: u32) -> Option<Trace> {
+    let iters = checked_iters(iters as usize).ok()?;
+    let steps = CordicConfig::new(iters)
+        .iterations(FixedPoint::new(theta))
+        .ok()?
+        .take(iters)
+        .map(|step| {
+            [
+                (step.x * step.gain).to_f64(),
+                (step.y * step.gain).to_f64(),
+                step.z.to_f64(),
+            ]
+        })
+        .collect();
+    Some(Trace { steps })
+}
+
+// Angle and gain tables for `iters` iterations with `frac_bits`
+// fractional bits, in the little endian binary format of src/table.rs,
+// or undefined if either is out of range
+#[wasm_bindgen]
+pub fn tables(iters: u32, frac_bits: u32) -> Option<Vec<u8>> {
+    let tables = Tables::generate(iters as usize, frac_bits).ok()?;
+    Some(tables.to_bytes(Endian::Little))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports() {
+        let ret = sincos(1.0, 32).unwrap();
+        assert![(ret.cos - 1.0_f64.cos()).abs() < 1e-9];
+        assert![(ret.sin - 1.0_f64.sin()).abs() < 1e-9];
+        assert_eq![sincos(2.0, 0), None];
+
+        assert_eq![cordic_gain(0), None];
+        assert![(cordic_gain(32).unwrap() - 0.6072529350088812).abs() < 1e-15];
+
+        // The integer backend's angles, which are atan(2^-i) to 60 bits
+        assert_eq![cordic_angle(0), Some(std::f64::consts::FRAC_PI_4)];
+        for i in 0..61 {
+            let angle = cordic_angle(i).unwrap();
+            assert![(angle - 2f64.powi(-(i as i32)).atan()).abs() < 1e-16];
+        }
+        assert_eq![cordic_angle(61), None];
+        assert_eq![cordic_angle(u32::MAX), None];
+    }
+
+    #[test]
+    fn trace_steps() {
+        let trace = trace(1.0, 24).unwrap();
+        assert_eq![trace.length(), 24];
+        let ret = CordicConfig::new(24).rotate(FixedPoint::new(1.0)).unwrap();
+        assert![(trace.cos(23) - ret.x.to_f64()).abs() < 1e-15];
+        assert![(trace.sin(23) - ret.y.to_f64()).abs() < 1e-15];
+        // z shrinks towards 0 as the steps go
+        assert![trace.z(23).abs() < trace.z(0).abs()];
+        assert![trace.cos(24).is_nan()];
+
+        assert_eq![super::trace(f64::NAN, 24), None];
+        assert_eq![super::trace(1.0, 0), None];
+    }
+
+    #[test]
+    fn table_bytes() {
+        let bytes = tables(24, 30).unwrap();
+        assert_eq![Tables::from_bytes(&bytes), Tables::generate(24, 30)];
+        assert_eq![tables(0, 30), None];
+        assert_eq![tables(24, 32), None];
+    }
+}
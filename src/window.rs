@@ -0,0 +1,193 @@
+use crate::cordic::sincos;
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::qformat::QFormat;
+use crate::sintable::{array_rows, byte_width};
+use std::fmt;
+use std::str::FromStr;
+
+// Window functions for FFTs and FIR design, the other table that always
+// ends up next to the twiddle factors. All three are sums of cosines of
+// 2 pi k / (n - 1), which come from the CORDIC engine:
+//
+//   hann:     0.5 - 0.5 cos
+//   hamming:  0.54 - 0.46 cos
+//   blackman: 0.42 - 0.5 cos + 0.08 cos(2x)
+//
+// They're the symmetric versions (first and last entries equal), as
+// used for filter design, and a single point window is just 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Window {
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl Window {
+    fn coefficients(self) -> &'static [f64] {
+        match self {
+            Window::Hann => &[0.5, 0.5],
+            Window::Hamming => &[0.54, 0.46],
+            Window::Blackman => &[0.42, 0.5, 0.08],
+        }
+    }
+
+    pub fn generate(self, n: usize) -> Vec<FixedPoint> {
+        if n == 1 {
+            return vec![FixedPoint::new(1.0)];
+        }
+        let step = 2.0 * std::f64::consts::PI / (n as f64 - 1.0);
+        (0..n)
+            .map(|k| {
+                let mut sum = FixedPoint::new(0.0);
+                for (j, &a) in self.coefficients().iter().enumerate() {
+                    let [cos, _] = sincos(FixedPoint::new(step * (j * k) as f64));
+                    let term = FixedPoint::new(a) * cos;
+                    sum = if j % 2 == 0 { sum + term } else { sum - term };
+                }
+                sum
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Window::Hann => "hann",
+            Window::Hamming => "hamming",
+            Window::Blackman => "blackman",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Window {
+    type Err = CordicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hann" => Ok(Window::Hann),
+            "hamming" => Ok(Window::Hamming),
+            "blackman" => Ok(Window::Blackman),
+            _ => Err(CordicError::ParseError(
+                "window must be hann, hamming or blackman",
+            )),
+        }
+    }
+}
+
+pub fn hann(n: usize) -> Vec<FixedPoint> {
+    Window::Hann.generate(n)
+}
+
+pub fn hamming(n: usize) -> Vec<FixedPoint> {
+    Window::Hamming.generate(n)
+}
+
+pub fn blackman(n: usize) -> Vec<FixedPoint> {
+    Window::Blackman.generate(n)
+}
+
+// A window rounded to a Q format, ready to paste into firmware. Windows
+// never go above 1, but Q15 and friends stop just short of it, so a
+// centre tap of 1 saturates to the largest value
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowTable {
+    window: Window,
+    format: QFormat,
+    values: Vec<i64>,
+}
+
+impl WindowTable {
+    pub fn new(window: Window, n: usize, format: QFormat) -> Self {
+        let values = window
+            .generate(n)
+            .into_iter()
+            .map(|x| format.fixed_to_bits(x))
+            .collect();
+        Self {
+            window,
+            format,
+            values,
+        }
+    }
+
+    pub fn values(&self) -> &[i64] {
+        &self.values
+    }
+
+    // `pub const NAME: [iN; n] = [...];`
+    pub fn to_rust(&self, name: &str) -> String {
+        format!(
+            "// {}\npub const {}: [i{}; {}] = [\n{}];\n",
+            self.describe(),
+            name.to_uppercase(),
+            8 * byte_width(self.format),
+            self.values.len(),
+            array_rows(&self.values)
+        )
+    }
+
+    // `static const intN_t name[n] = {...};`
+    pub fn to_c(&self, name: &str) -> String {
+        format!(
+            "#include <stdint.h>\n\n/* {} */\nstatic const int{}_t {}[{}] = {{\n{}}};\n",
+            self.describe(),
+            8 * byte_width(self.format),
+            name,
+            self.values.len(),
+            array_rows(&self.values)
+        )
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} point {} window in {}, generated by cordic-rs",
+            self.values.len(),
+            self.window,
+            self.format
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_formulas() {
+        let n = 64;
+        let step = 2.0 * std::f64::consts::PI / (n as f64 - 1.0);
+        let windows = [hann(n), hamming(n), blackman(n)];
+        for k in 0..n {
+            let x = step * k as f64;
+            let expected = [
+                0.5 - 0.5 * x.cos(),
+                0.54 - 0.46 * x.cos(),
+                0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+            ];
+            for (window, expected) in windows.iter().zip(&expected) {
+                assert![(window[k].to_f64() - expected).abs() < 1e-12];
+            }
+        }
+        // Symmetric, zero (or nearly) at the ends
+        assert_eq![hann(n)[0], FixedPoint::new(0.0)];
+        assert![(hann(n)[5] - hann(n)[n - 6]).abs() < FixedPoint::new(1e-14)];
+        assert![hann(0).is_empty()];
+        assert_eq![blackman(1), vec![FixedPoint::new(1.0)]];
+    }
+
+    #[test]
+    fn export() {
+        let table = WindowTable::new(Window::Hann, 5, QFormat::Q15);
+        // 0, 0.5, 1, 0.5, 0 with 1 saturating
+        assert_eq![table.values(), &[0, 16384, 32767, 16384, 0]];
+        let rust = table.to_rust("hann");
+        assert![rust.contains("pub const HANN: [i16; 5]")];
+        assert![table.to_c("hann").contains("static const int16_t hann[5]")];
+        assert_eq!["blackman".parse::<Window>().unwrap(), Window::Blackman];
+        assert![rust.contains("hann window in Q15")];
+        assert!["kaiser".parse::<Window>().is_err()];
+    }
+}
@@ -0,0 +1,273 @@
+use crate::cordic::{checked_iters, reduce};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::integer::{from_q60, sincos_narrow, to_q60, Q60};
+
+// How wide a CORDIC datapath has to be. Every shift drops bits off the
+// bottom of x and y, and over n iterations those truncations add up to
+// about log2(n) bits of error, so the registers need that many guard
+// bits past the accuracy wanted at the output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WordLength {
+    pub frac_bits: u32,
+    // Fractional bits past the target accuracy
+    pub guard_bits: u32,
+    // frac_bits plus a sign bit and an integer bit, which is enough for
+    // z up to pi/2 and for x and y with the gain prescaled
+    pub total_bits: u32,
+    pub worst_error: f64,
+}
+
+// Worst error of either output over `thetas` when the integer kernel
+// runs `iters` iterations with `frac_bits` fractional bits
+pub fn word_error(iters: usize, frac_bits: u32, thetas: &[f64]) -> Result<f64, CordicError> {
+    let iters = checked_iters(iters)?;
+    if frac_bits == 0 || frac_bits > Q60::FRAC_BITS {
+        return Err(CordicError::DomainError("word_error"));
+    }
+
+    if !thetas.iter().all(|theta| theta.is_finite()) {
+        return Err(CordicError::InvalidAngle("word_error"));
+    }
+    let widths = [frac_bits; 3];
+    Ok(worst(thetas, |theta| narrow(theta, iters, widths), exact))
+}
+
+// The narrow kernel's [cos, sin] for widths [registers, angles, gain],
+// see integer::sincos_narrow
+fn narrow(theta: f64, iters: usize, widths: [u32; 3]) -> [f64; 2] {
+    let q60 = Q60::from_bits(to_q60(reduce(FixedPoint::new(theta))));
+    let [registers, angles, gain] = widths;
+    let [cos, sin] = sincos_narrow(q60, iters, registers, angles, gain);
+    [
+        from_q60(cos.to_bits()).to_f64(),
+        from_q60(sin.to_bits()).to_f64(),
+    ]
+}
+
+fn exact(theta: f64) -> [f64; 2] {
+    [theta.cos(), theta.sin()]
+}
+
+// Largest difference between `a` and `b` in either output over `thetas`
+fn worst(thetas: &[f64], a: impl Fn(f64) -> [f64; 2], b: impl Fn(f64) -> [f64; 2]) -> f64 {
+    thetas
+        .iter()
+        .map(|&theta| {
+            let (a, b) = (a(theta), b(theta));
+            (a[0] - b[0]).abs().max((a[1] - b[1]).abs())
+        })
+        .fold(0.0, f64::max)
+}
+
+// The fewest fractional bits for which `iters` iterations still get the
+// worst error over `thetas` down to 2^-target_bits. It's an error if no
+// width up to Q60 gets there, which usually means `iters` is too small
+// for the target
+pub fn min_word_length(
+    iters: usize,
+    target_bits: u32,
+    thetas: &[f64],
+) -> Result<WordLength, CordicError> {
+    let tolerance = 0.5f64.powi(target_bits as i32);
+    for frac_bits in target_bits.max(1)..=Q60::FRAC_BITS {
+        let worst_error = word_error(iters, frac_bits, thetas)?;
+        if worst_error <= tolerance {
+            return Ok(WordLength {
+                frac_bits,
+                guard_bits: frac_bits - target_bits,
+                total_bits: frac_bits + 2,
+                worst_error,
+            });
+        }
+    }
+    Err(CordicError::DomainError("min_word_length"))
+}
+
+// Where the error of a `frac_bits` wide datapath comes from, each as the
+// worst case over a sweep:
+//   iterations:  full width arithmetic with only `iters` micro-rotations,
+//                i.e. the part of the angle they can't reach
+//   angle_table: just theta and the angle table rounded to `frac_bits`
+//   gain:        just K rounded to `frac_bits`
+//   rounding:    just x and y truncated to `frac_bits`
+// The last three are measured against the full width run with the same
+// iterations, so they're what each narrowing adds on its own. They don't
+// simply add up to the total, since errors of different signs cancel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErrorSources {
+    pub iterations: f64,
+    pub angle_table: f64,
+    pub gain: f64,
+    pub rounding: f64,
+    pub total: f64,
+}
+
+pub fn attribute(
+    iters: usize,
+    frac_bits: u32,
+    thetas: &[f64],
+) -> Result<ErrorSources, CordicError> {
+    let iters = checked_iters(iters)?;
+    if frac_bits == 0 || frac_bits > Q60::FRAC_BITS {
+        return Err(CordicError::DomainError("attribute"));
+    }
+    if !thetas.iter().all(|theta| theta.is_finite()) {
+        return Err(CordicError::InvalidAngle("attribute"));
+    }
+
+    let full = Q60::FRAC_BITS;
+    let ideal = |theta| narrow(theta, iters, [full; 3]);
+    let only = |widths| worst(thetas, |theta| narrow(theta, iters, widths), ideal);
+    Ok(ErrorSources {
+        iterations: worst(thetas, ideal, exact),
+        angle_table: only([full, frac_bits, full]),
+        gain: only([full, full, frac_bits]),
+        rounding: only([frac_bits, full, full]),
+        total: worst(thetas, |theta| narrow(theta, iters, [frac_bits; 3]), exact),
+    })
+}
+
+// How often each fractional bit of the outputs matches the exactly
+// rounded cos and sin, over a sweep. agreement[0] is the 2^-1 bit and
+// agreement[frac_bits - 1] the last one; bits that are pure noise agree
+// about half the time
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitAgreement {
+    pub iters: usize,
+    pub frac_bits: u32,
+    pub agreement: Vec<f64>,
+}
+
+impl BitAgreement {
+    // Trailing bits that can't be trusted: everything after the first
+    // bit (from the top) that agrees less than `threshold` of the time
+    pub fn noisy_bits(&self, threshold: f64) -> u32 {
+        let reliable = self
+            .agreement
+            .iter()
+            .take_while(|&&agreement| agreement >= threshold)
+            .count();
+        self.frac_bits - reliable as u32
+    }
+}
+
+pub fn bit_agreement(
+    iters: usize,
+    frac_bits: u32,
+    thetas: &[f64],
+) -> Result<BitAgreement, CordicError> {
+    let iters = checked_iters(iters)?;
+    if frac_bits == 0 || frac_bits > Q60::FRAC_BITS || thetas.is_empty() {
+        return Err(CordicError::DomainError("bit_agreement"));
+    }
+
+    let drop = Q60::FRAC_BITS - frac_bits;
+    let scale = 2.0f64.powi(frac_bits as i32);
+    let mut matches = vec![0usize; frac_bits as usize];
+    for &theta in thetas {
+        if !theta.is_finite() {
+            return Err(CordicError::InvalidAngle("bit_agreement"));
+        }
+        let q60 = Q60::from_bits(to_q60(reduce(FixedPoint::new(theta))));
+        let outputs = sincos_narrow(q60, iters, frac_bits, frac_bits, frac_bits);
+        let exact = [theta.cos(), theta.sin()];
+        for (output, exact) in outputs.iter().zip(&exact) {
+            let output = output.to_bits() >> drop;
+            let exact = (exact * scale).round() as i64;
+            for (k, count) in matches.iter_mut().enumerate() {
+                let bit = frac_bits as usize - 1 - k;
+                if (output >> bit) & 1 == (exact >> bit) & 1 {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    let total = (2 * thetas.len()) as f64;
+    Ok(BitAgreement {
+        iters,
+        frac_bits,
+        agreement: matches.iter().map(|&count| count as f64 / total).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sweep() -> Vec<f64> {
+        (-314..=314).map(|i| i as f64 / 100.0).collect()
+    }
+
+    #[test]
+    fn guard_bits() {
+        // 24 iterations are good for about 23 bits, and 16 for 15, each
+        // needing a few guard bits on top
+        let thetas = sweep();
+        let wide = min_word_length(24, 20, &thetas).unwrap();
+        assert![wide.worst_error <= 0.5f64.powi(20)];
+        assert![(2..=6).contains(&wide.guard_bits)];
+        assert_eq![wide.total_bits, wide.frac_bits + 2];
+        assert![word_error(24, wide.frac_bits - 1, &thetas).unwrap() > 0.5f64.powi(20)];
+
+        let narrow = min_word_length(16, 12, &thetas).unwrap();
+        assert![narrow.frac_bits < wide.frac_bits];
+    }
+
+    #[test]
+    fn wider_is_better() {
+        let thetas = sweep();
+        let errors = [12, 20, 28, 36]
+            .iter()
+            .map(|&bits| word_error(40, bits, &thetas).unwrap())
+            .collect::<Vec<f64>>();
+        assert![errors.windows(2).all(|pair| pair[1] < pair[0])];
+    }
+
+    #[test]
+    fn bits() {
+        let thetas = sweep();
+        let few = bit_agreement(8, 24, &thetas).unwrap();
+        let many = bit_agreement(28, 24, &thetas).unwrap();
+        assert_eq![many.agreement.len(), 24];
+
+        // Top bits always right, bottom ones coin flips, and more
+        // iterations push the noise further down
+        assert![few.agreement[0] > 0.99 && many.agreement[0] > 0.99];
+        assert![few.agreement[23] < 0.75];
+        assert![few.noisy_bits(0.99) > many.noisy_bits(0.99)];
+        assert![many.noisy_bits(0.99) <= 8];
+        assert_eq![few.noisy_bits(0.0), 0];
+        assert![bit_agreement(8, 24, &[]).is_err()];
+    }
+
+    #[test]
+    fn sources() {
+        let thetas = sweep();
+
+        // Plenty of bits: nearly everything is down to the iterations
+        let wide = attribute(16, 40, &thetas).unwrap();
+        assert![wide.iterations > 1e-6];
+        assert![wide.angle_table < 1e-10 && wide.gain < 1e-10 && wide.rounding < 1e-10];
+        assert![(wide.total - wide.iterations).abs() < 1e-9];
+
+        // Plenty of iterations: it's the arithmetic
+        let narrow = attribute(40, 16, &thetas).unwrap();
+        assert![narrow.iterations < 1e-11];
+        assert![narrow.rounding > narrow.angle_table && narrow.angle_table > narrow.gain];
+        assert![narrow.total > 0.5f64.powi(16)];
+    }
+
+    #[test]
+    fn unreachable() {
+        // 8 iterations can't get anywhere near 30 bits however wide
+        assert![min_word_length(8, 30, &sweep()).is_err()];
+        assert![word_error(16, 0, &[0.0]).is_err()];
+        assert![word_error(16, 61, &[0.0]).is_err()];
+        assert_eq![
+            word_error(16, 20, &[f64::NAN]),
+            Err(CordicError::InvalidAngle("word_error"))
+        ];
+    }
+}
@@ -0,0 +1,405 @@
+use crate::algorithm::{Cost, Tradeoff};
+use crate::error::CordicError;
+use crate::fixed::FixedPoint;
+use crate::remez::{minimax, Minimax, QuantizedPolynomial};
+use crate::sintable::Interpolation;
+
+// The comparison Registry does for cos and sin, for any f: Fn(f64) ->
+// f64 on an interval [a, b]. Approximations of it (minimax polynomials,
+// tables, compositions of the crate's CORDIC functions, or your own)
+// are measured against f on an even grid, and pareto() picks out the
+// cheapest way to each level of accuracy as it does for sincos:
+//
+//   let mut bench = Workbench::with_builtins(f64::exp, -1.0, 1.0)?;
+//   bench.register(Composed::new("cordic-exp", |x| Ok(cordic_rs::exp(x))));
+//   let frontier = pareto(&bench.accuracy(f64::exp, 1001)?);
+pub trait Approximation {
+    // Short name used to label results, e.g. "minimax-5"
+    fn name(&self) -> String;
+
+    fn eval(&self, x: f64) -> Result<f64, CordicError>;
+
+    // What one evaluation costs, if there's a model for it. As with
+    // TrigAlgorithm, approximations without one are left out of pareto()
+    fn cost(&self) -> Option<Cost> {
+        None
+    }
+}
+
+// Horner's rule: an add and a multiply per degree, and the coefficients
+// to store
+fn horner_cost(coeffs: usize) -> Cost {
+    let degree = coeffs.saturating_sub(1);
+    Cost {
+        adds: degree,
+        multiplies: degree,
+        table_entries: coeffs,
+        ..Cost::default()
+    }
+}
+
+impl Approximation for Minimax {
+    fn name(&self) -> String {
+        format!("minimax-{}", self.coeffs.len() - 1)
+    }
+
+    fn eval(&self, x: f64) -> Result<f64, CordicError> {
+        Ok(Minimax::eval(self, x))
+    }
+
+    fn cost(&self) -> Option<Cost> {
+        Some(horner_cost(self.coeffs.len()))
+    }
+}
+
+impl Approximation for QuantizedPolynomial {
+    fn name(&self) -> String {
+        format!("minimax-{}-{}", self.coeffs.len() - 1, self.format)
+    }
+
+    fn eval(&self, x: f64) -> Result<f64, CordicError> {
+        Ok(QuantizedPolynomial::eval(self, x))
+    }
+
+    fn cost(&self) -> Option<Cost> {
+        Some(horner_cost(self.coeffs.len()))
+    }
+}
+
+// f sampled at `entries` evenly spaced points from a to b, both ends
+// included, and read back with interpolation like a SinTable. Reading
+// outside [a, b] is a DomainError
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionTable {
+    pub interval: (f64, f64),
+    pub values: Vec<f64>,
+    pub interpolation: Interpolation,
+}
+
+impl FunctionTable {
+    // At least 2 entries, or 3 for Interpolation::Quadratic
+    pub fn new(
+        f: impl Fn(f64) -> f64,
+        a: f64,
+        b: f64,
+        entries: usize,
+        interpolation: Interpolation,
+    ) -> Result<Self, CordicError> {
+        let least = match interpolation {
+            Interpolation::Quadratic => 3,
+            _ => 2,
+        };
+        if !(a.is_finite() && b.is_finite() && a < b) || entries < least {
+            return Err(CordicError::DomainError("FunctionTable"));
+        }
+        let step = (b - a) / (entries - 1) as f64;
+        Ok(FunctionTable {
+            interval: (a, b),
+            values: (0..entries).map(|i| f(a + step * i as f64)).collect(),
+            interpolation,
+        })
+    }
+}
+
+impl Approximation for FunctionTable {
+    fn name(&self) -> String {
+        let interpolation = match self.interpolation {
+            Interpolation::Nearest => "nearest",
+            Interpolation::Linear => "linear",
+            Interpolation::Quadratic => "quadratic",
+        };
+        format!("table-{}-{}", interpolation, self.values.len())
+    }
+
+    fn eval(&self, x: f64) -> Result<f64, CordicError> {
+        let (a, b) = self.interval;
+        if !(a..=b).contains(&x) {
+            return Err(CordicError::DomainError("FunctionTable"));
+        }
+        let last = self.values.len() - 1;
+        let position = (x - a) / (b - a) * last as f64;
+        let value = |i: usize| self.values[i];
+        Ok(match self.interpolation {
+            Interpolation::Nearest => value(position.round() as usize),
+            Interpolation::Linear => {
+                let i = (position.floor() as usize).min(last - 1);
+                let t = position - i as f64;
+                value(i) + t * (value(i + 1) - value(i))
+            }
+            // Through the closest entry and its neighbours, moved in
+            // from the ends so there are always two
+            Interpolation::Quadratic => {
+                let i = (position.round() as usize).clamp(1, last - 1);
+                let t = position - i as f64;
+                let (before, here, after) = (value(i - 1), value(i), value(i + 1));
+                here + 0.5 * t * (after - before) + 0.5 * t * t * (after - 2.0 * here + before)
+            }
+        })
+    }
+
+    // The same model as Lut: a multiply to find the position, then a
+    // subtract, multiply and add for linear and twice that for quadratic
+    fn cost(&self) -> Option<Cost> {
+        let (adds, multiplies) = match self.interpolation {
+            Interpolation::Nearest => (0, 0),
+            Interpolation::Linear => (2, 1),
+            Interpolation::Quadratic => (5, 3),
+        };
+        Some(Cost {
+            adds: 1 + adds,
+            multiplies: 1 + multiplies,
+            table_entries: self.values.len(),
+            ..Cost::default()
+        })
+    }
+}
+
+// Any function of FixedPoint, usually built from the crate's CORDIC
+// primitives (exp, ln, sqrt, atan2, ...), under a name and with a cost
+// if you have one
+pub struct Composed<F> {
+    name: String,
+    f: F,
+    cost: Option<Cost>,
+}
+
+impl<F: Fn(FixedPoint) -> Result<FixedPoint, CordicError>> Composed<F> {
+    pub fn new(name: &str, f: F) -> Self {
+        Composed {
+            name: name.to_string(),
+            f,
+            cost: None,
+        }
+    }
+
+    pub fn cost(mut self, cost: Cost) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+}
+
+impl<F: Fn(FixedPoint) -> Result<FixedPoint, CordicError>> Approximation for Composed<F> {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn eval(&self, x: f64) -> Result<f64, CordicError> {
+        (self.f)(FixedPoint::new(x)).map(FixedPoint::to_f64)
+    }
+
+    fn cost(&self) -> Option<Cost> {
+        self.cost
+    }
+}
+
+// How far one approximation strayed from f over the grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionAccuracy {
+    pub name: String,
+    pub max_error: f64,
+    pub rms_error: f64,
+    pub cost: Option<Cost>,
+}
+
+impl FunctionAccuracy {
+    // Correct fractional bits, i.e. -log2 of max_error
+    pub fn bits(&self) -> f64 {
+        -self.max_error.log2()
+    }
+}
+
+impl Tradeoff for FunctionAccuracy {
+    fn cost(&self) -> Option<Cost> {
+        self.cost
+    }
+
+    fn max_error(&self) -> f64 {
+        self.max_error
+    }
+}
+
+// The approximations to compare over one interval
+pub struct Workbench {
+    interval: (f64, f64),
+    approximations: Vec<Box<dyn Approximation>>,
+}
+
+impl Workbench {
+    pub fn new(a: f64, b: f64) -> Result<Self, CordicError> {
+        if !(a.is_finite() && b.is_finite() && a < b) {
+            return Err(CordicError::DomainError("workbench"));
+        }
+        Ok(Workbench {
+            interval: (a, b),
+            approximations: Vec::new(),
+        })
+    }
+
+    // Minimax polynomials of degree 1 to 12 and tables of f with 16 to
+    // 4096 entries read each way. Degrees the exchange can't fit are
+    // left out rather than failing the lot
+    pub fn with_builtins(f: impl Fn(f64) -> f64, a: f64, b: f64) -> Result<Self, CordicError> {
+        let mut bench = Self::new(a, b)?;
+        for degree in 1..=12 {
+            if let Ok(poly) = minimax(&f, a, b, degree) {
+                bench.register(poly);
+            }
+        }
+        for log2 in (4..=12).step_by(2) {
+            for interpolation in [
+                Interpolation::Nearest,
+                Interpolation::Linear,
+                Interpolation::Quadratic,
+            ] {
+                bench.register(FunctionTable::new(&f, a, b, 1 << log2, interpolation)?);
+            }
+        }
+        Ok(bench)
+    }
+
+    pub fn interval(&self) -> (f64, f64) {
+        self.interval
+    }
+
+    pub fn register<A: Approximation + 'static>(&mut self, approximation: A) -> &mut Self {
+        self.approximations.push(Box::new(approximation));
+        self
+    }
+
+    pub fn approximations(&self) -> impl Iterator<Item = &dyn Approximation> {
+        self.approximations
+            .iter()
+            .map(|approximation| approximation.as_ref())
+    }
+
+    // Every approximation against f at `points` evenly spaced points of
+    // the interval, ends included, in registration order. Fewer than 2
+    // points is a DomainError, and the first error an approximation
+    // returns is passed on
+    pub fn accuracy(
+        &self,
+        f: impl Fn(f64) -> f64,
+        points: usize,
+    ) -> Result<Vec<FunctionAccuracy>, CordicError> {
+        if points < 2 {
+            return Err(CordicError::DomainError("workbench"));
+        }
+        let (a, b) = self.interval;
+        let step = (b - a) / (points - 1) as f64;
+        // The last point is b exactly, not whatever the steps add up to
+        let xs = (0..points)
+            .map(|i| {
+                if i == points - 1 {
+                    b
+                } else {
+                    a + step * i as f64
+                }
+            })
+            .collect::<Vec<_>>();
+        let reference = xs.iter().map(|&x| f(x)).collect::<Vec<_>>();
+        self.approximations()
+            .map(|approximation| {
+                let mut max = 0.0f64;
+                let mut sum_squares = 0.0;
+                for (&x, &expected) in xs.iter().zip(&reference) {
+                    let error = (approximation.eval(x)? - expected).abs();
+                    max = max.max(error);
+                    sum_squares += error * error;
+                }
+                Ok(FunctionAccuracy {
+                    name: approximation.name(),
+                    max_error: max,
+                    rms_error: (sum_squares / points as f64).sqrt(),
+                    cost: approximation.cost(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::pareto;
+
+    #[test]
+    fn workbench() {
+        let mut bench = Workbench::with_builtins(f64::exp, -1.0, 1.0).unwrap();
+        bench.register(Composed::new("cordic-exp", |x| {
+            Ok(crate::hyperbolic::exp(x))
+        }));
+        let results = bench.accuracy(f64::exp, 1001).unwrap();
+        assert_eq![results.len(), 12 + 5 * 3 + 1];
+        let find = |name: &str| results.iter().find(|r| r.name == name).unwrap();
+
+        // More degree, less error: 1 / (2^12 13!) ~= 4e-14 at degree 12
+        assert![find("minimax-3").max_error > find("minimax-6").max_error];
+        assert![find("minimax-12").max_error < 1e-13];
+        // and the grid finds about the error the exchange levelled to
+        let quintic = minimax(f64::exp, -1.0, 1.0, 5).unwrap();
+        assert![find("minimax-5").max_error <= quintic.max_error];
+        assert![find("minimax-5").max_error > 0.99 * quintic.max_error];
+
+        // Linear interpolation error is h^2 / 8 times the largest f''
+        let h = 2.0 / 255.0;
+        let linear = find("table-linear-256").max_error;
+        assert![linear <= h * h / 8.0 * 1f64.exp() && linear > 0.9 * h * h / 8.0 * 1f64.exp()];
+        assert![find("table-quadratic-256").max_error < linear];
+        assert![find("table-nearest-256").max_error > linear];
+
+        let cordic = find("cordic-exp");
+        assert![cordic.max_error < 1e-12 && cordic.cost.is_none()];
+
+        // Along the frontier cost goes up as error comes down, and
+        // nothing on it is without a cost. A big table read to the nearest
+        // entry is as cheap as a line and beats it
+        let frontier = pareto(&results);
+        assert_eq![frontier[0].name, "table-nearest-4096"];
+        let cycles = |r: &FunctionAccuracy| r.cost.unwrap().cycles();
+        assert![frontier.windows(2).all(|w| cycles(&w[1]) > cycles(&w[0]))];
+        assert![frontier.windows(2).all(|w| w[1].max_error < w[0].max_error)];
+        assert![frontier.iter().all(|r| r.name != "cordic-exp")];
+    }
+
+    #[test]
+    fn tables() {
+        let line = |x: f64| 3.0 * x - 1.0;
+        for interpolation in [
+            Interpolation::Nearest,
+            Interpolation::Linear,
+            Interpolation::Quadratic,
+        ] {
+            let table = FunctionTable::new(line, 0.0, 2.0, 5, interpolation).unwrap();
+            // The entries themselves come back from every reading, and
+            // the ends are in the table
+            assert_eq![table.eval(0.5), Ok(0.5)];
+            assert_eq![table.eval(2.0), Ok(5.0)];
+            assert_eq![
+                table.eval(2.5),
+                Err(CordicError::DomainError("FunctionTable"))
+            ];
+        }
+        let linear = FunctionTable::new(line, 0.0, 2.0, 5, Interpolation::Linear).unwrap();
+        assert![(linear.eval(0.3).unwrap() - line(0.3)).abs() < 1e-15];
+        let square = |x: f64| x * x;
+        let quadratic = FunctionTable::new(square, 0.0, 2.0, 5, Interpolation::Quadratic).unwrap();
+        assert![(quadratic.eval(1.9).unwrap() - square(1.9)).abs() < 1e-15];
+        assert_eq![linear.name(), "table-linear-5"];
+
+        let domain = Err(CordicError::DomainError("FunctionTable"));
+        assert_eq![
+            FunctionTable::new(line, 0.0, 2.0, 2, Interpolation::Quadratic),
+            domain
+        ];
+        assert_eq![
+            FunctionTable::new(line, 2.0, 2.0, 8, Interpolation::Linear),
+            domain
+        ];
+        assert![Workbench::new(0.0, f64::NAN).is_err()];
+        let bench = Workbench::new(0.0, 1.0).unwrap();
+        assert_eq![
+            bench.accuracy(line, 1),
+            Err(CordicError::DomainError("workbench"))
+        ];
+    }
+}
@@ -0,0 +1,52 @@
+// Nothing on the rotation path allocates once the tables are built (see
+// src/cache.rs). Counting that takes a global allocator, which would
+// replace the allocator for every test sharing the binary, so this one
+// gets a binary of its own
+#![cfg(feature = "float")]
+
+use cordic_rs::{exp, warm_tables, Backend, CordicConfig, FixedPoint, MAX_ITERS};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Counts allocations per thread, so the harness's own threads don't get
+// counted
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static COUNTING: Counting = Counting;
+
+#[test]
+fn no_allocations() {
+    // Everything on the rotation path, both backends, once the tables
+    // are built
+    warm_tables(Backend::Float, MAX_ITERS).unwrap();
+    warm_tables(Backend::Integer, MAX_ITERS).unwrap();
+    let theta = FixedPoint::new(0.7);
+    let v = [FixedPoint::new(3.0), FixedPoint::new(4.0)];
+    let thetas = [theta; 8];
+    let (mut sin, mut cos) = ([theta; 8], [theta; 8]);
+    let before = ALLOCATIONS.with(Cell::get);
+    for &backend in &[Backend::Float, Backend::Integer] {
+        let config = CordicConfig::new(MAX_ITERS).backend(backend);
+        config.rotate(theta).unwrap();
+        config.vector(v).unwrap();
+        config.sincos_into(&thetas, &mut sin, &mut cos).unwrap();
+    }
+    exp(FixedPoint::new(0.5));
+    assert_eq![ALLOCATIONS.with(Cell::get), before];
+}
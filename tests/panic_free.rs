@@ -0,0 +1,649 @@
+// The library promises not to panic on any input to any public entry
+// point: bad arguments come back as a CordicError, or as NaN where the
+// function returns plain numbers the way f64's do. This throws the
+// awkward values at everything (NaN, infinities, subnormals, the ends of
+// every integer range, empty and mismatched slices, fields set by hand
+// to things the constructors would refuse) and fails on the first panic.
+// Where there's an obvious right answer it checks that too: NaN in gives
+// NaN out, and a sine or cosine that did come back is at most the
+// uncompensated gain (~1.65) in size.
+//
+// Two things are outside the promise, and so aren't tried here: sizes
+// big enough that allocating the result fails (a Vec of usize::MAX
+// window points aborts the process, it doesn't panic), and the `checks`
+// feature, whose whole point is to panic when a rotation is wrong
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+// Runs `body` for every value, reporting which call panicked and on what
+fn probe<T: std::fmt::Debug + Copy>(name: &str, values: &[T], mut body: impl FnMut(T)) {
+    for &value in values {
+        if catch_unwind(AssertUnwindSafe(|| body(value))).is_err() {
+            panic!("{} panicked on {:?}", name, value);
+        }
+    }
+}
+
+const ITERS: [usize; 12] = [0, 1, 2, 15, 16, 24, 32, 53, 60, 61, 64, usize::MAX];
+const BITS: [u32; 14] = [0, 1, 2, 15, 16, 31, 32, 52, 53, 60, 61, 63, 64, u32::MAX];
+const RAW: [i64; 7] = [0, 1, -1, 1 << 60, -(1 << 60), i64::MAX, i64::MIN];
+
+#[cfg(feature = "float")]
+fn floats() -> Vec<f64> {
+    vec![
+        0.0,
+        -0.0,
+        f64::MIN_POSITIVE,
+        5e-324,
+        1e-9,
+        0.5,
+        1.0,
+        -1.0,
+        std::f64::consts::FRAC_PI_2,
+        std::f64::consts::PI,
+        -std::f64::consts::PI,
+        1e15,
+        1e300,
+        -1e300,
+        f64::MAX,
+        f64::MIN,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NAN,
+    ]
+}
+
+// Sines and cosines come out no bigger than the uncompensated gain
+#[cfg(feature = "float")]
+const BOUND: f64 = 1.7;
+
+// The answer for an angle that isn't finite (NaN), or a sine or cosine
+// within `bound` for one that is
+#[cfg(feature = "float")]
+fn check_trig(name: &str, theta: f64, values: &[f64], bound: f64) {
+    for &value in values {
+        if theta.is_finite() {
+            assert![value.abs() <= bound, "{}({}) gave {}", name, theta, value];
+        } else {
+            assert![value.is_nan(), "{}({}) gave {} instead of NaN", name, theta, value];
+        }
+    }
+}
+
+// Same answer as f64, NaN matching NaN
+#[cfg(feature = "float")]
+fn check_same(name: &str, x: f64, ret: f64, expected: f64) {
+    let same = (ret.is_nan() && expected.is_nan()) || ret == expected;
+    assert![same, "{}({}) gave {} instead of {}", name, x, ret, expected];
+}
+
+#[test]
+fn integer_backend() {
+    use cordic_rs::*;
+
+    probe("Q60::from_str", &["", "-", "1e999", "nan", "0x10", "1.5", "-8.1", "9".repeat(400).as_str()], |s| {
+        let _ = s.parse::<Q60>();
+    });
+    probe("Q60 tables", &ITERS, |iters| {
+        let _ = (gain_q60(iters), angle_table_q60(iters));
+    });
+    probe("chebyshev::eval_q60", &RAW, |bits| {
+        let coeffs = RAW.map(Q60::from_bits);
+        for n in 0..=coeffs.len() {
+            let _ = chebyshev::eval_q60(&coeffs[..n], Q60::from_bits(bits));
+        }
+    });
+    probe("sincos_q60", &RAW, |bits| {
+        for iters in ITERS {
+            let _ = sincos_q60(Q60::from_bits(bits), iters);
+            let _ = sincos_recoded(Q60::from_bits(bits), iters);
+            for window in BITS {
+                let _ = sincos_carry_save(Q60::from_bits(bits), iters, window);
+                let _ = sincos_sigma(Q60::from_bits(bits), iters, &MsbWindow(window));
+                let _ = sincos_sigma(Q60::from_bits(bits), iters, &LookAhead(window));
+            }
+            let _ = sincos_sigma(Q60::from_bits(bits), iters, &ExactSign);
+        }
+        let _ = sincos_q60_fixed::<1>(Q60::from_bits(bits));
+        let _ = sincos_q60_fixed::<60>(Q60::from_bits(bits));
+        let _ = Q60::from_bits(bits).to_string();
+    });
+    probe("set_max_level", &[None, Some(LogLevel::Debug)], |level| {
+        set_max_level(level);
+        let _ = sincos_q60(Q60::from_bits(1 << 58), 4);
+        set_max_level(None);
+    });
+}
+
+#[cfg(feature = "float")]
+mod float {
+    use super::*;
+    use cordic_rs::window::{Window, WindowTable};
+    use cordic_rs::*;
+
+    // Each with the size its sines and cosines stay within. Registers of
+    // only a few bits can wrap (see integer::sincos_narrow), so narrow
+    // ones are only checked for NaN
+    fn configs() -> Vec<(CordicConfig, f64)> {
+        let mut configs = Vec::new();
+        for iters in ITERS {
+            for backend in [Backend::Integer, Backend::Float] {
+                for compensation in [
+                    GainCompensation::Post,
+                    GainCompensation::Prescale,
+                    GainCompensation::Csd,
+                    GainCompensation::None,
+                ] {
+                    let config = CordicConfig::new(iters)
+                        .backend(backend)
+                        .gain_compensation(compensation);
+                    configs.push((config, BOUND));
+                    configs.push((config.small_angle(true).special_angles(true), BOUND));
+                    configs.push((config.small_angle_below(u32::MAX).octant_reduction(true), BOUND));
+                    configs.push((config.small_angle_below(0), BOUND));
+                    configs.push((config.schedule(&[0, 52, 52, 1]).octant_reduction(true), BOUND));
+                    configs.push((config.schedule(&[usize::MAX]), BOUND));
+                    configs.push((config.schedule(&[]), BOUND));
+                    configs.push((config.correctly_rounded(true), BOUND));
+                    configs.push((config.correctly_rounded(true).io_width(31), BOUND));
+                    configs.push((config.monotone(true), BOUND));
+                    configs.push((config.monotone(true).io_width(31), BOUND));
+                    for io_bits in [0, 1, 31, 60, 61, u32::MAX] {
+                        let bound = if io_bits < 31 { f64::INFINITY } else { BOUND };
+                        configs.push((config.io_width(io_bits), bound));
+                        configs.push((config.io_width(io_bits).double_word(true), bound));
+                    }
+                }
+            }
+        }
+        configs
+    }
+
+    #[test]
+    fn cordic_config() {
+        let floats = floats();
+        probe("CordicConfig", &configs(), |(config, bound)| {
+            for &theta in &floats {
+                let raw = theta;
+                let theta = FixedPoint::new(theta);
+                if let Ok(ret) = config.rotate(theta) {
+                    check_trig("rotate", raw, &[ret.x.to_f64(), ret.y.to_f64()], bound);
+                }
+                if let Ok(ret) = TrigAlgorithm::sincos(&config, theta) {
+                    check_trig("sincos", raw, &[ret[0].to_f64(), ret[1].to_f64()], bound);
+                }
+                if let Ok(ret) = config.vector([theta, FixedPoint::new(1.0)]) {
+                    if raw.is_nan() {
+                        assert![ret.magnitude.to_f64().is_nan() && ret.angle.to_f64().is_nan()];
+                    }
+                }
+                let _ = config.vector([FixedPoint::new(0.0), theta]);
+                let _ = config.compose([theta, theta], &[theta, -theta]);
+                let _ = config.compose([theta, theta], &[]);
+                let _ = config.cost();
+                let _ = config.name();
+            }
+            let _ = edge_cases(&config);
+            let thetas = floats.iter().map(|&x| FixedPoint::new(x)).collect::<Vec<_>>();
+            let mut sin = vec![FixedPoint::new(0.0); thetas.len()];
+            let mut cos = vec![FixedPoint::new(0.0); thetas.len()];
+            let _ = config.sincos_into(&thetas, &mut sin, &mut cos);
+            let _ = config.sincos_into(&thetas, &mut sin[1..], &mut cos);
+            for threads in [0, 1, 3, 1000] {
+                let _ = config.sincos_parallel(&thetas, &mut sin, &mut cos, threads);
+            }
+        });
+    }
+
+    #[test]
+    fn free_functions() {
+        let floats = floats();
+        probe("free functions", &floats, |raw| {
+            let x = FixedPoint::new(raw);
+            let z = cis(x);
+            check_trig("cis", raw, &[z.re.to_f64(), z.im.to_f64()], BOUND);
+            check_trig("sincos", raw, &cordic_rs::sincos(raw), BOUND);
+            let rotated = Complex::new(FixedPoint::new(1.0), FixedPoint::new(0.0)).rotate(x);
+            check_trig("Complex::rotate", raw, &[rotated.re.to_f64(), rotated.im.to_f64()], BOUND);
+            let (sin, cos) = x.sin_cos();
+            check_trig("sin_cos", raw, &[sin.to_f64(), cos.to_f64()], BOUND);
+            if !raw.is_finite() {
+                check_same("exp", raw, exp(x).to_f64(), raw.exp());
+                check_same("tanh", raw, tanh(x).to_f64(), raw.tanh());
+                check_same("sinh", raw, sinh(x).to_f64(), raw.sinh());
+                if let Ok(ret) = recip(x) {
+                    check_same("recip", raw, ret.to_f64(), 1.0 / raw);
+                }
+                if let Ok(ret) = ln(x) {
+                    check_same("ln", raw, ret.to_f64(), raw.ln());
+                }
+            }
+            let _ = (sin_cos_half(x), sin_cos_multiples(x, 3), harmonics(x, 3));
+            for &y in &floats {
+                let _ = (ulp_distance(x.to_f64(), y), relative_error(x.to_f64(), y));
+            }
+            let _ = (chebyshev::eval(&[x, x, x], x), chebyshev::fit(|y| y * x, 3));
+            for &y in &floats {
+                if !(raw.is_finite() && y.is_finite()) {
+                    let ret = atan2(FixedPoint::new(y), x).to_f64();
+                    check_same("atan2", y, ret, y.atan2(raw));
+                    if let Ok(ret) = powf(x, FixedPoint::new(y)) {
+                        check_same("powf", y, ret.to_f64(), raw.powf(y));
+                    }
+                }
+                let y = FixedPoint::new(y);
+                let _ = log(x, y);
+                let _ = fold_octant(x, [y, y]);
+                let _ = Complex::from_polar(x, y).to_polar();
+                let _ = sin_cos_difference(sin_cos_sum([x, y], [y, x]), sin_cos_double([x, x]));
+                let _ = Complex::new(x, y).rotate(y);
+                let _ = (x + y, x - y, x * y, x / y, x % y);
+                let _ = taylor(x, 64);
+                let _ = (lambert(x, 12), lambert_tan(x, usize::MAX));
+            }
+            let _ = reduce(x);
+            let _ = fold_quadrant(x);
+            let _ = (cis(x), expi(x), sinc(x), sinc_normalized(x));
+            let _ = (cosh(x), sinh(x), tanh(x), exp(x), exp2(x));
+            let _ = (ln(x), log2(x), sqrt(x), atanh(x), asinh(x), acosh(x));
+            let _ = (recip(x), rsqrt(x));
+            let _ = (x.sin(), x.cos(), x.tan(), x.sin_cos());
+            let _ = (x.to_q31(), x.to_q15(), x.to_i64(), x.signum(), x.raw_bits());
+            let _ = (x.to_string(), format!("{:?}", x));
+            let _ = cordic_rs::sincos(x.to_f64());
+            for iters in ITERS {
+                if let Ok(ret) = cordic(x, iters) {
+                    check_trig("cordic", raw, &[ret[0].to_f64(), ret[1].to_f64()], BOUND);
+                }
+                let _ = x.sin_cos_cordic(iters);
+                let _ = x.tan_cordic(iters);
+                let _ = taylor(x, iters.min(1 << 16));
+                if let Ok(sigmas) = precompute_sigmas(x, iters) {
+                    let _ = sigmas.apply([x, x]);
+                    let _ = sigmas.apply_raw([x, x]);
+                }
+                let _ = rotate_points(&mut [(x, x), (x, -x)], x, iters);
+                let _ = rotate_points(&mut [], x, iters);
+            }
+        });
+        probe("gain", &ITERS, |iters| {
+            let _ = (gain(iters), angle_table(iters));
+            let _ = checked_iters(iters);
+            for bits in [0, 1, u64::MAX] {
+                if let Ok(sigmas) = SigmaSequence::from_bits(bits, iters, true) {
+                    let _ = sigmas.apply([FixedPoint::new(1.0), FixedPoint::new(0.0)]);
+                }
+            }
+        });
+        probe("FixedPoint from bits", &RAW, |bits| {
+            let _ = FixedPoint::from_raw_bits(bits);
+            let _ = FixedPoint::from_i64(bits);
+            let _ = FixedPoint::from_q31(bits as i32);
+            let _ = FixedPoint::from_q15(bits as i16);
+        });
+    }
+
+    #[test]
+    fn formats_and_tables() {
+        let floats = floats();
+        let formats = [(0, 1), (0, 15), (1, 30), (0, 63), (63, 0), (64, 0), (0, 64), (1, 63), (u32::MAX, u32::MAX)];
+        probe("QFormat", &formats, |(int_bits, frac_bits)| {
+            if let Ok(format) = QFormat::new(int_bits, frac_bits) {
+                let _ = (format.resolution(), format.max(), format.min(), format.dynamic_range_db());
+                let _ = format.to_string().parse::<QFormat>();
+                for &x in &floats {
+                    let _ = format.quantize(x);
+                    let _ = format.fixed_to_bits(FixedPoint::new(x));
+                }
+                for bits in RAW {
+                    let _ = (format.to_f64(bits), format.fixed_from_bits(bits));
+                    for to in [QFormat::Q15, QFormat::Q31, format] {
+                        for rounding in [Rounding::Floor, Rounding::TowardZero, Rounding::Nearest, Rounding::NearestEven] {
+                            for overflow in [Overflow::Saturate, Overflow::Wrap, Overflow::Error] {
+                                let _ = convert(bits, format, to, rounding, overflow);
+                            }
+                        }
+                    }
+                }
+                for entries in [0, 1, 2, 3, 1000] {
+                    if let Ok(table) = generate_table(entries, format) {
+                        let _ = (table.to_bytes(Endian::Big), table.to_rust("t"), table.to_c("t"));
+                        let _ = (table.entry_error(), table.max_error(), table.step());
+                        let thetas = floats.iter().map(|&x| FixedPoint::new(x)).collect::<Vec<_>>();
+                        let mut out = vec![FixedPoint::new(0.0); thetas.len()];
+                        for interpolation in [Interpolation::Nearest, Interpolation::Linear, Interpolation::Quadratic] {
+                            let _ = table.error_bound(interpolation);
+                            let _ = sin_lut(&table, interpolation, &thetas, &mut out);
+                            let _ = sin_lut(&table, interpolation, &thetas, &mut out[1..]);
+                            for &theta in &thetas {
+                                let _ = (table.sin(theta, interpolation), table.cos(theta, interpolation));
+                            }
+                        }
+                    }
+                    for window in [Window::Hann, Window::Hamming, Window::Blackman] {
+                        let table = WindowTable::new(window, entries, format);
+                        let _ = (table.to_rust("w"), table.to_c("w"));
+                    }
+                }
+            }
+        });
+        probe("QFormat::from_str", &["", "Q", "Q0", "Q0.0", "Q99.99", "Q1.-1", "Q4294967296", "UQ1.15"], |text| {
+            let _ = text.parse::<QFormat>();
+            let _ = text.parse::<Window>();
+        });
+
+        probe("Tables", &ITERS, |iters| {
+            for frac_bits in BITS {
+                if let Ok(mut tables) = Tables::generate(iters, frac_bits) {
+                    let _ = tables.extend(iters.wrapping_mul(2));
+                }
+                // Fields are public, so anything can be in them
+                let tables = Tables {
+                    frac_bits,
+                    angles: vec![i32::MIN; iters.min(70)],
+                    gains: vec![i32::MAX; iters.min(3)],
+                };
+                for i in ITERS {
+                    let _ = (tables.angle(i), tables.gain(i));
+                }
+                for endian in [Endian::Little, Endian::Big] {
+                    let bytes = tables.to_bytes(endian);
+                    let _ = Tables::from_bytes(&bytes);
+                    for cut in 0..bytes.len().min(40) {
+                        let _ = Tables::from_bytes(&bytes[..cut]);
+                    }
+                }
+            }
+        });
+        probe("Tables::from_bytes", &[0u8, 1, 0xff], |fill| {
+            let mut bytes = b"CRDC\x01\x00\x1f\x00\xff\xff\x00\x00".to_vec();
+            bytes.resize(64, fill);
+            let _ = Tables::from_bytes(&bytes);
+        });
+    }
+
+    #[test]
+    fn signals() {
+        let floats = floats();
+        probe("Nco", &floats, |freq| {
+            for &rate in &floats {
+                for iters in [0, 1, 24, usize::MAX] {
+                    let nco = || Nco::new(freq, rate, iters);
+                    if let Ok(nco) = nco() {
+                        let _ = nco.take(4).count();
+                    }
+                    for bits in BITS {
+                        let _ = nco().and_then(|nco| nco.accumulator_bits(bits)).map(|nco| nco.take(4).count());
+                        let _ = nco().and_then(|nco| nco.phase_bits(bits)).map(|nco| nco.take(4).count());
+                        for shape in [false, true] {
+                            let _ = nco().and_then(|nco| nco.amplitude_bits(bits, shape)).map(|nco| nco.take(4).count());
+                        }
+                    }
+                    for samples in [0, 1, 5] {
+                        if let Ok(chirp) = Chirp::new(freq, -freq, rate, samples, iters) {
+                            let _ = chirp.take(8).count();
+                        }
+                    }
+                    if let Ok(pll) = Pll::new(freq, rate, iters) {
+                        let mut pll = pll.gains(freq, rate);
+                        for &x in &floats {
+                            let _ = pll.process([FixedPoint::new(x), FixedPoint::new(freq)]);
+                        }
+                        let _ = (pll.phase(), pll.frequency());
+                    }
+                }
+            }
+        });
+
+        let lengths = [0, 1, 2, 3, 8, 64];
+        probe("spectrum", &lengths, |n| {
+            for &x in &floats {
+                let signal = vec![x; n];
+                let mut data = vec![Complex::new(x, -x); n];
+                let _ = fft(&mut data);
+                for window in [None, Some(Window::Hann), Some(Window::Hamming), Some(Window::Blackman)] {
+                    let _ = power_spectrum(&signal, window);
+                    let _ = sfdr(&signal, window);
+                }
+                let _ = tone_quality(&signal, &signal);
+                let _ = tone_quality(&signal, &vec![0.0; n]);
+                let _ = tone_quality(&signal, &[x]);
+                let _ = (npy(&signal), npz(&[("a", &signal)]), matlab_arrays(&[("a", &signal)]));
+            }
+            for window in [Window::Hann, Window::Hamming, Window::Blackman] {
+                let _ = window.generate(n);
+            }
+            let samples = vec![i16::MIN; n];
+            for rate in [0, 1, u32::MAX] {
+                let _ = wav_pcm16(&samples, rate);
+            }
+            for (up, down) in [(0, 1), (1, 0), (3, 2), (1 << 10, 1), (1, usize::MAX), (7, 7)] {
+                for taps in [0, 1, 2, 64] {
+                    if let Ok(mut resampler) = Resampler::new(up, down, taps, Window::Hann) {
+                        let _ = resampler.process(&samples);
+                        let _ = resampler.ratio();
+                    }
+                }
+            }
+            // up * taps overflows
+            let _ = Resampler::new(usize::MAX / 2, 1, 3, Window::Blackman);
+        });
+    }
+
+    #[test]
+    fn analysis() {
+        let floats = floats();
+        let sweeps: [&[f64]; 4] = [&[], &[0.5], &[1.0, -2.0, 3.0], &floats];
+        probe("analysis", &sweeps, |thetas| {
+            for iters in [0, 1, 16, 60, usize::MAX] {
+                for bits in [0, 1, 16, 60, 61, u32::MAX] {
+                    let _ = word_error(iters, bits, thetas);
+                    let _ = attribute(iters, bits, thetas);
+                    if let Ok(agreement) = bit_agreement(iters, bits, thetas) {
+                        for &threshold in &floats {
+                            let _ = agreement.noisy_bits(threshold);
+                        }
+                    }
+                }
+                for target in [0, 1, 16, 61, u32::MAX] {
+                    let _ = min_word_length(iters.min(24), target, thetas);
+                }
+                for &tolerance in &[None, Some(0.0), Some(-1.0), Some(f64::NAN), Some(1e-3)] {
+                    if let Ok((schedule, _)) = prune(thetas, iters.min(24), tolerance) {
+                        let _ = schedule.worst_error(thetas);
+                    }
+                }
+                let registry = Registry::with_builtins(iters.min(64));
+                let _ = registry.accuracy(thetas).map(|results| pareto(&results));
+            }
+            for shifts in [vec![], vec![0], vec![0, 0, 1], vec![64], vec![usize::MAX]] {
+                if let Ok(schedule) = Schedule::new(shifts) {
+                    let _ = (schedule.gain(), schedule.worst_error(thetas));
+                    for &theta in &floats {
+                        let _ = schedule.sincos(FixedPoint::new(theta));
+                    }
+                }
+            }
+            for iters in ITERS {
+                let _ = Schedule::full(iters);
+            }
+        });
+
+        probe("jacobi", &floats, |a| {
+            for &b in &floats {
+                let matrix = [[FixedPoint::new(a), FixedPoint::new(b)], [FixedPoint::new(b), FixedPoint::new(-a)]];
+                for iters in [0, 1, 32, usize::MAX] {
+                    if let Ok(schur) = jacobi_rotation(matrix, iters) {
+                        let _ = schur.apply(matrix);
+                    }
+                    if let Ok(svd) = svd2(matrix, iters) {
+                        let _ = svd.reconstruct();
+                    }
+                }
+            }
+        });
+
+        probe("Pipeline", &[0, 1, 16, 64, 100, usize::MAX], |iters| {
+            for width in BITS {
+                for architecture in [Architecture::Unrolled, Architecture::Iterative] {
+                    for angle_width in BITS {
+                        let pipeline = Pipeline::new(iters, width)
+                            .angle_width(angle_width)
+                            .growth(angle_width)
+                            .stage_delay(iters)
+                            .architecture(architecture)
+                            .gain_compensation(GainCompensation::Prescale);
+                        let _ = pipeline.report();
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn images() {
+        let headers: [&[u8]; 10] = [
+            b"",
+            b"P5",
+            b"P5 0 0 255\n",
+            b"P5 1 1 255\n",
+            b"P5 2 2 0\n\x00\x00\x00\x00",
+            b"P6 99999999999999999999 1 255\n",
+            b"P6 4294967296 4294967296 255\n",
+            b"P5 1 1 65535\n\x00\x00",
+            b"P5 #\n1 1 255\n\x00",
+            b"P3 1 1 255\n0 0 0",
+        ];
+        probe("Image::from_pnm", &headers, |bytes| {
+            if let Ok(image) = Image::from_pnm(bytes) {
+                let _ = image.to_pnm();
+                for &theta in &floats() {
+                    let _ = image.rotate(FixedPoint::new(theta));
+                }
+            }
+        });
+        let shapes = [(0, 0, 1), (1, 1, 1), (2, 3, 3), (3, 2, 0), (2, 2, 5)];
+        probe("Image fields", &shapes, |(width, height, channels)| {
+            // Built by hand, with a pixel buffer that doesn't match
+            for len in [0, width * height * channels, 1] {
+                let image = Image {
+                    width,
+                    height,
+                    channels,
+                    maxval: 0,
+                    pixels: vec![7; len],
+                };
+                let _ = image.to_pnm();
+                let _ = image.rotate(FixedPoint::new(1.0));
+            }
+        });
+    }
+
+    #[cfg(feature = "serve")]
+    #[test]
+    fn serve() {
+        let requests = [
+            "",
+            "GET",
+            "GET / HTTP/1.1",
+            "GET /sincos HTTP/1.1",
+            "GET /sincos?theta=nan&iters=0 HTTP/1.1",
+            "GET /sincos?theta=1e308&iters=18446744073709551616 HTTP/1.1",
+            "GET /sincos?theta=&&&= HTTP/1.1",
+            "GET /table?iters=0&frac_bits=99 HTTP/1.1",
+            "GET /table?iters=9999&frac_bits=0 HTTP/1.1",
+            "POST /sincos?theta=1 HTTP/1.1",
+        ];
+        probe("serve::handle", &requests, |request| {
+            let _ = cordic_rs::serve::handle(request);
+        });
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn ffi() {
+        use cordic_rs::ffi::*;
+        probe("ffi", &floats(), |theta| {
+            let (mut sin, mut cos) = (0.0, 0.0);
+            let (mut sin_q31, mut cos_q31, mut angle) = (0, 0, 0);
+            for iters in [0, 1, 32, u32::MAX] {
+                unsafe {
+                    if cordic_sincos_f64(theta, iters, &mut sin, &mut cos) == CordicStatus::CordicOk {
+                        check_trig("cordic_sincos_f64", theta, &[sin, cos], BOUND);
+                    }
+                    cordic_sincos_f64(theta, iters, std::ptr::null_mut(), &mut cos);
+                    let thetas = [theta, -theta];
+                    let (mut sins, mut coss) = ([0.0; 2], [0.0; 2]);
+                    cordic_sincos_batch_f64(thetas.as_ptr(), 2, iters, sins.as_mut_ptr(), coss.as_mut_ptr());
+                    cordic_sincos_batch_f64(std::ptr::null(), 0, iters, sins.as_mut_ptr(), coss.as_mut_ptr());
+                }
+            }
+            for bits in [0, 1, -1, i32::MAX, i32::MIN] {
+                unsafe {
+                    cordic_sincos_q31(bits, &mut sin_q31, &mut cos_q31);
+                    cordic_sincos_q31(bits, std::ptr::null_mut(), &mut cos_q31);
+                    cordic_atan2_q31(bits, theta as i32, &mut angle);
+                    cordic_atan2_q31(bits, i32::MIN, std::ptr::null_mut());
+                }
+            }
+        });
+    }
+
+    #[cfg(feature = "cmsis")]
+    #[test]
+    fn cmsis() {
+        use cordic_rs::cmsis::*;
+        probe("cmsis", &[0, 1, -1, i32::MAX, i32::MIN], |x| {
+            let (mut sin, mut cos, mut out) = (0, 0, 0);
+            let (mut sin_f, mut cos_f, mut out_f) = (0.0, 0.0, 0.0);
+            let mut out_q15 = 0;
+            let _ = (arm_sin_q31(x), arm_cos_q31(x), arm_sin_q15(x as i16), arm_cos_q15(x as i16));
+            let _ = (arm_sin_f32(x as f32), arm_cos_f32(f32::NAN), arm_sin_f32(f32::INFINITY));
+            unsafe {
+                arm_sin_cos_q31(x, &mut sin, &mut cos);
+                arm_sin_cos_f32(x as f32, &mut sin_f, &mut cos_f);
+                arm_sin_cos_f32(f32::NAN, &mut sin_f, &mut cos_f);
+                arm_atan2_q31(x, i32::MIN, &mut out);
+                arm_atan2_f32(f32::NAN, x as f32, &mut out_f);
+                arm_sqrt_q31(x, &mut out);
+                arm_sqrt_q15(x as i16, &mut out_q15);
+            }
+        });
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn python() {
+        use cordic_rs::python::*;
+        probe("python", &floats(), |theta| {
+            for iters in [0, 1, 32, usize::MAX] {
+                if let Ok((cos, sin)) = sincos(theta, iters) {
+                    check_trig("sincos", theta, &[cos, sin], BOUND);
+                }
+                if let Ok((cos, sin)) = sincos_batch(vec![theta, -theta], iters) {
+                    check_trig("sincos_batch", theta, &[cos[0], sin[0]], BOUND);
+                }
+                let _ = trace(theta, iters);
+                let _ = error_stats(vec![theta], iters);
+                let _ = error_stats(Vec::new(), iters);
+            }
+        });
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn wasm() {
+        use cordic_rs::wasm::*;
+        probe("wasm", &floats(), |theta| {
+            for iters in [0, 1, 32, u32::MAX] {
+                if let Some(ret) = sincos(theta, iters) {
+                    check_trig("sincos", theta, &[ret.cos, ret.sin], BOUND);
+                }
+                if let Some(trace) = trace(theta, iters) {
+                    let _ = (trace.cos(usize::MAX), trace.sin(0), trace.z(trace.length()));
+                }
+                let _ = (cordic_gain(iters), cordic_angle(iters), tables(iters, iters));
+            }
+        });
+    }
+}